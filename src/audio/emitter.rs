@@ -0,0 +1,53 @@
+use bevy::audio::{SpatialAudioSink, Volume};
+use bevy::prelude::*;
+
+/// A looping sound attached to an entity (a humming saw, a waterfall), whose volume falls
+/// off with distance from the [SpatialListener], and which is paused entirely when the
+/// listener is beyond the `audible_radius`. Left/right panning is handled by Bevy's spatial
+/// audio, based on the emitter's position relative to the listener.
+#[derive(Component, Debug)]
+pub struct SoundEmitter {
+	pub sound: Handle<AudioSource>,
+	/// Volume when the listener is right on top of the emitter
+	pub volume: f32,
+	/// Distance beyond which the sound is inaudible (and paused, to save on mixing)
+	pub audible_radius: f32,
+}
+
+/// Starts playback for newly-added [SoundEmitter]s. Each starts silent, and gets its
+/// actual volume from [attenuate_sound_emitters] once the sink exists.
+pub fn start_sound_emitters(mut commands: Commands, emitters: Query<(Entity, &SoundEmitter), Added<SoundEmitter>>) {
+	for (entity, emitter) in &emitters {
+		commands.entity(entity).insert((
+			AudioPlayer::<AudioSource>(emitter.sound.clone()),
+			PlaybackSettings::LOOP.with_spatial(true).with_volume(Volume::new(0.0)),
+		));
+	}
+}
+
+/// Updates each emitter's volume based on its distance to the listener
+pub fn attenuate_sound_emitters(
+	listeners: Query<&GlobalTransform, With<SpatialListener>>,
+	emitters: Query<(&SoundEmitter, &GlobalTransform, &SpatialAudioSink)>,
+) {
+	let Ok(listener) = listeners.get_single() else {
+		return;
+	};
+	let listener_pos = listener.translation().truncate();
+
+	for (emitter, transform, sink) in &emitters {
+		let distance = transform.translation().truncate().distance(listener_pos);
+		if distance >= emitter.audible_radius {
+			if !sink.is_paused() {
+				sink.pause();
+			}
+			continue;
+		}
+		if sink.is_paused() {
+			sink.play();
+		}
+		// quadratic falloff sounds more natural than linear, and still reaches 0 at the radius
+		let closeness = 1.0 - distance / emitter.audible_radius;
+		sink.set_volume(emitter.volume * closeness * closeness);
+	}
+}
@@ -0,0 +1,5 @@
+mod emitter;
+mod music;
+
+pub use emitter::*;
+pub use music::*;
@@ -0,0 +1,157 @@
+use crate::level::{CurrentLevel, LevelAsset, MusicDef};
+use bevy::audio::Volume;
+use bevy::prelude::*;
+
+/// One of the stems a piece of music is split into. Every stem plays all the time (so they stay
+/// in sync), and the [MusicIntensity] decides how loud each one is.
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MusicLayer {
+	/// Always audible
+	Base,
+	/// Fades in as things start getting tense
+	Percussion,
+	/// Fades in when the player is in real danger
+	Danger,
+}
+
+/// The stems of a piece of music. Only the base layer is required; missing layers stay silent.
+pub struct MusicTrack {
+	pub base: Handle<AudioSource>,
+	pub percussion: Option<Handle<AudioSource>>,
+	pub danger: Option<Handle<AudioSource>>,
+}
+
+impl MusicTrack {
+	/// Starts all of the track's stems looping together, each starting out silent
+	/// until [crossfade_music_layers] brings it up to volume
+	pub fn spawn(self, commands: &mut Commands) {
+		let MusicTrack {
+			base,
+			percussion,
+			danger,
+		} = self;
+		let stems = [
+			(MusicLayer::Base, Some(base)),
+			(MusicLayer::Percussion, percussion),
+			(MusicLayer::Danger, danger),
+		];
+		for (layer, sound) in stems {
+			if let Some(sound) = sound {
+				commands.spawn((
+					layer,
+					AudioPlayer::<AudioSource>(sound),
+					PlaybackSettings::LOOP.with_volume(Volume::new(0.0)),
+				));
+			}
+		}
+	}
+}
+
+/// Starts the current level's music once the level loads, replacing whatever was playing.
+/// Moving on to a level with the same music (or none) leaves the music playing, rather than
+/// starting it over.
+pub fn start_level_music(
+	mut commands: Commands,
+	mut events: EventReader<AssetEvent<LevelAsset>>,
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	asset_server: Res<AssetServer>,
+	stems: Query<Entity, With<MusicLayer>>,
+	mut playing: Local<Option<MusicDef>>,
+) {
+	let Some(current_level) = current_level else {
+		return;
+	};
+	let current_id = current_level.0.id();
+	let loaded = events
+		.read()
+		.any(|event| matches!(event, AssetEvent::LoadedWithDependencies { id } if *id == current_id));
+	let Some(music) = levels.get(current_id).and_then(|level| level.music.as_ref()) else {
+		return;
+	};
+	if !loaded || playing.as_ref() == Some(music) {
+		return;
+	}
+	for entity in &stems {
+		commands.entity(entity).despawn();
+	}
+	MusicTrack {
+		base: asset_server.load(&music.base),
+		percussion: music.percussion.as_ref().map(|path| asset_server.load(path)),
+		danger: music.danger.as_ref().map(|path| asset_server.load(path)),
+	}
+	.spawn(&mut commands);
+	*playing = Some(music.clone());
+}
+
+/// How intense the gameplay currently is, from `0.0` (calm) to `1.0` (as tense as it gets).
+/// Each source is kept up to date by the gameplay systems that know about it,
+/// and the music follows whichever is highest.
+#[derive(Resource, Debug, Default)]
+pub struct MusicIntensity {
+	/// How close the nearest alerted enemy is
+	pub enemy_proximity: f32,
+	/// How close to death the player is
+	pub low_health: f32,
+}
+
+impl MusicIntensity {
+	pub fn overall(&self) -> f32 {
+		self.enemy_proximity.max(self.low_health).clamp(0.0, 1.0)
+	}
+}
+
+/// How the [MusicLayer]s respond to the [MusicIntensity]
+#[derive(Resource, Debug)]
+pub struct MusicMix {
+	/// Overall music volume
+	pub volume: f32,
+	/// Intensity range over which the percussion fades in
+	pub percussion_fade_in: (f32, f32),
+	/// Intensity range over which the danger layer fades in
+	pub danger_fade_in: (f32, f32),
+	/// How quickly a layer's volume can change, in (full) volume per second
+	pub crossfade_speed: f32,
+}
+
+impl Default for MusicMix {
+	fn default() -> Self {
+		MusicMix {
+			volume: 0.6,
+			percussion_fade_in: (0.2, 0.5),
+			danger_fade_in: (0.6, 0.9),
+			crossfade_speed: 0.5,
+		}
+	}
+}
+
+impl MusicMix {
+	/// Volume that the given layer should settle at, for the given intensity
+	fn target_volume(&self, layer: MusicLayer, intensity: f32) -> f32 {
+		let fade_in =
+			|(start, end): (f32, f32)| ((intensity - start) / (end - start).max(f32::EPSILON)).clamp(0.0, 1.0);
+		let level = match layer {
+			MusicLayer::Base => 1.0,
+			MusicLayer::Percussion => fade_in(self.percussion_fade_in),
+			MusicLayer::Danger => fade_in(self.danger_fade_in),
+		};
+		level * self.volume
+	}
+}
+
+/// Gradually moves each layer's volume towards the level called for by the current [MusicIntensity].
+/// Runs in real time, so the music keeps responding while the game is paused.
+pub fn crossfade_music_layers(
+	intensity: Res<MusicIntensity>,
+	mix: Res<MusicMix>,
+	real_time: Res<Time<Real>>,
+	stems: Query<(&MusicLayer, &AudioSink)>,
+) {
+	let intensity = intensity.overall();
+	let max_step = mix.crossfade_speed * real_time.delta_secs();
+	for (layer, sink) in &stems {
+		let target = mix.target_volume(*layer, intensity);
+		let volume = sink.volume();
+		sink.set_volume(volume + (target - volume).clamp(-max_step, max_step));
+	}
+}
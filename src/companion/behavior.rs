@@ -0,0 +1,97 @@
+use crate::companion::{Companion, CompanionInterest};
+use crate::player::PlayerJumped;
+use crate::util::FrameCount;
+use bevy::prelude::*;
+
+/// How long a [Companion] celebrates for
+const CELEBRATION_DURATION: FrameCount = FrameCount(30);
+
+/// Number of hops a [Companion] does while celebrating
+const CELEBRATION_HOPS: f32 = 2.0;
+
+/// What a [Companion] is currently up to.
+///
+/// New activities (like fetching collectibles) can be added as variants, with
+/// [companion_behavior_system] deciding when to start them, and `companion_follow_system`
+/// deciding where the companion goes while doing them.
+#[derive(Component, Debug, Default, Copy, Clone, PartialEq)]
+pub enum CompanionBehavior {
+	/// Trailing along after the player
+	#[default]
+	Following,
+	/// Hopping around happily after the player jumps
+	Celebrating { remaining: FrameCount },
+	/// Pointing out a nearby [CompanionInterest]
+	Pointing { at: Entity },
+}
+
+impl CompanionBehavior {
+	/// How high the companion is in its current hop, from 0 to 1.
+	/// Always 0 unless celebrating.
+	pub fn hop(&self) -> f32 {
+		match self {
+			CompanionBehavior::Celebrating { remaining } => {
+				let t = remaining.0 as f32 / CELEBRATION_DURATION.0 as f32;
+				(t * CELEBRATION_HOPS * std::f32::consts::PI).sin().abs()
+			}
+			_ => 0.0,
+		}
+	}
+}
+
+/// Decides what each [Companion] should be doing. Celebrating a jump takes priority, then pointing out
+/// the closest [CompanionInterest] in range, and otherwise the companion just follows along.
+/// Should run after `player_system`, so it hears about the player's jumps on the same frame.
+pub fn companion_behavior_system(
+	mut jump_events: EventReader<PlayerJumped>,
+	interests: Query<(Entity, &CompanionInterest, &Transform), Without<Companion>>,
+	mut companions: Query<(&Companion, &Transform, &mut CompanionBehavior)>,
+) {
+	let jumpers: Vec<Entity> = jump_events.read().map(|e| e.player).collect();
+	for (companion, transform, mut behavior) in &mut companions {
+		if jumpers.contains(&companion.leader) {
+			*behavior = CompanionBehavior::Celebrating {
+				remaining: CELEBRATION_DURATION,
+			};
+			continue;
+		}
+		if let CompanionBehavior::Celebrating { remaining } = &mut *behavior {
+			remaining.decrement();
+			if remaining.0 > 0 {
+				continue;
+			}
+		}
+
+		let position = transform.translation.truncate();
+		let distance_to = |t: &Transform| t.translation.truncate().distance(position);
+		let nearest_interest = interests
+			.iter()
+			.filter(|(_, interest, t)| distance_to(t) <= interest.radius)
+			.min_by(|(.., a), (.., b)| distance_to(a).total_cmp(&distance_to(b)))
+			.map(|(entity, ..)| entity);
+		let next = nearest_interest.map_or(CompanionBehavior::Following, |at| CompanionBehavior::Pointing { at });
+		if *behavior != next {
+			debug!("companion is now {:?}", next);
+			*behavior = next;
+		}
+	}
+}
+
+/// Draws an arrow from each pointing [Companion] towards whatever it's pointing at
+pub fn draw_companion_pointing(
+	companions: Query<(&Transform, &CompanionBehavior)>,
+	targets: Query<&Transform>,
+	mut gizmos: Gizmos,
+) {
+	for (transform, behavior) in &companions {
+		let CompanionBehavior::Pointing { at } = *behavior else {
+			continue;
+		};
+		let Ok(target) = targets.get(at) else {
+			continue;
+		};
+		let start = transform.translation.truncate();
+		let direction = (target.translation.truncate() - start).normalize_or_zero();
+		gizmos.arrow_2d(start, start + direction * 2.5, Color::srgb(1.0, 0.9, 0.3));
+	}
+}
@@ -0,0 +1,49 @@
+use crate::companion::{Companion, CompanionBehavior};
+use crate::player::{Player, PlayerControlState, PlayerHistory, TutorialGhost};
+use crate::util::{GameClock, PhysicsPosition};
+use bevy::prelude::*;
+use bevy_rapier2d::control::KinematicCharacterController;
+
+/// Moves each [Companion] towards a spot above where its leader was a moment ago, so it follows
+/// the same path the player took. Should run after `player_system`.
+pub fn companion_follow_system(
+	clock: Res<GameClock>,
+	leaders: Query<
+		(&Transform, &PlayerHistory, &PlayerControlState),
+		(With<Player>, Without<Companion>, Without<TutorialGhost>),
+	>,
+	mut companions: Query<(
+		&Companion,
+		&CompanionBehavior,
+		&mut KinematicCharacterController,
+		&mut Transform,
+		Option<&mut PhysicsPosition>,
+	)>,
+) {
+	for (companion, behavior, mut controller, mut transform, position) in &mut companions {
+		let Ok((leader_transform, history, leader_state)) = leaders.get(companion.leader) else {
+			continue;
+		};
+		let leader_position = leader_transform.translation.truncate();
+		let current = transform.translation.truncate();
+
+		// rather than trying to find a way around whatever it's stuck behind, just catch up
+		if current.distance(leader_position) > companion.teleport_distance {
+			debug!("companion fell too far behind, teleporting to {:?}", leader_position);
+			transform.translation = leader_position.extend(transform.translation.z);
+			if let Some(mut position) = position {
+				position.snap(&transform);
+			}
+			controller.translation = None;
+			continue;
+		}
+
+		let trail = history
+			.frames_ago(companion.follow_delay)
+			.map_or(leader_position, |t| t.truncate());
+		let height = companion.hover_height * (1.0 + 0.5 * behavior.hop());
+		let target = trail + leader_state.gravity.orient(Vec2::Y * height);
+		let velocity = ((target - current) * companion.responsiveness).clamp_length_max(companion.max_speed);
+		controller.translation = Some(velocity * clock.delta_secs());
+	}
+}
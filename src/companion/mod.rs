@@ -0,0 +1,33 @@
+mod behavior;
+mod follow;
+
+use bevy::prelude::{Component, Entity};
+pub use behavior::*;
+pub use follow::*;
+
+/// A small critter that floats along after a player. It moves with its own
+/// `KinematicCharacterController`, so it goes around obstacles instead of through them,
+/// and teleports back to the player if it ever gets stuck too far behind.
+#[derive(Component, Debug)]
+#[require(CompanionBehavior)]
+pub struct Companion {
+	/// The player being followed
+	pub leader: Entity,
+	/// How many fixed updates behind the player the companion trails, along the player's own path
+	pub follow_delay: usize,
+	/// How far above the player's head (relative to their gravity) the companion hovers
+	pub hover_height: f32,
+	/// Fraction of the distance to where it wants to be that the companion tries to cover per second
+	pub responsiveness: f32,
+	/// Top speed, per second
+	pub max_speed: f32,
+	/// Falling further behind the player than this makes the companion teleport to them
+	pub teleport_distance: f32,
+}
+
+/// Something a [Companion] points out when it gets close, like a secret
+#[derive(Component, Debug)]
+pub struct CompanionInterest {
+	/// How close the companion has to be to notice it
+	pub radius: f32,
+}
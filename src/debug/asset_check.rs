@@ -0,0 +1,496 @@
+use crate::debug::InputScript;
+use crate::enemies::EnemyArchetype;
+use crate::level::{BreakableDef, CollectibleKind, LevelAsset, Prefab};
+use crate::player::{HorizontalControlParams, InputAction, InputMap, PlayerControlParams, PlayerSlot};
+use crate::ui::FloatingTextStyle;
+use crate::util::FrameCount;
+use bevy::utils::HashSet;
+use serde::de::DeserializeOwned;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+/// Something wrong with an asset file
+struct AssetProblem {
+	/// 1-based line number, if the problem could be pinned down to one
+	line: Option<usize>,
+	message: String,
+}
+
+/// Collects the [AssetProblem]s found while validating a parsed asset
+struct AssetChecker<'a> {
+	text: &'a str,
+	problems: Vec<AssetProblem>,
+}
+
+impl AssetChecker<'_> {
+	/// Records a problem unless `ok`. The problem is reported at the first line containing `near`
+	/// (e.g. the field's name, or the id of the thing that's wrong), or without a line if there is none.
+	fn require(&mut self, ok: bool, near: &str, message: impl Display) {
+		if !ok {
+			let line = self.text.lines().position(|line| line.contains(near)).map(|i| i + 1);
+			self.problems.push(AssetProblem {
+				line,
+				message: message.to_string(),
+			});
+		}
+	}
+
+	fn require_positive(&mut self, value: f32, field: &str) {
+		self.require(
+			value > 0.0,
+			&field_line(field),
+			format!("`{}` must be positive, but is {}", field, value),
+		);
+	}
+
+	fn require_frames(&mut self, value: FrameCount, field: &str) {
+		self.require(
+			value.0 > 0,
+			&field_line(field),
+			format!("`{}` must be at least one frame", field),
+		);
+	}
+
+	fn require_ratio(&mut self, value: f32, field: &str) {
+		self.require(
+			(0.0..=1.0).contains(&value),
+			&field_line(field),
+			format!("`{}` must be between 0 and 1, but is {}", field, value),
+		);
+	}
+}
+
+/// What to look for to find the line of a (possibly nested, like `run.max_speed`) field.
+/// Nested fields are reported where their outermost field starts, since field names like
+/// `max_speed` show up in more than one place.
+fn field_line(field: &str) -> String {
+	format!("{}:", field.split('.').next().unwrap_or(field))
+}
+
+/// Loads every asset file under `root` the way the game would, then checks the loaded values for
+/// mistakes that parse just fine but make no sense (negative speeds, duplicate ids, and so on).
+/// Each problem is printed as `path:line: message`. Returns the number of problems found.
+///
+/// Since every asset type denies unknown fields, typos in field names show up as parse errors.
+pub fn check_assets(root: &Path) -> usize {
+	let mut files = Vec::new();
+	if let Err(err) = find_asset_files(root, &mut files) {
+		eprintln!("{}: could not read asset directory: {}", root.display(), err);
+		return 1;
+	}
+	files.sort();
+
+	let mut problem_count = 0;
+	let mut checked_count = 0;
+	for path in &files {
+		// images and sounds aren't text, let alone RON
+		if path.extension().is_none_or(|extension| extension != "ron") {
+			continue;
+		}
+		let text = match std::fs::read_to_string(path) {
+			Ok(text) => text,
+			Err(err) => {
+				eprintln!("{}: could not read file: {}", path.display(), err);
+				problem_count += 1;
+				continue;
+			}
+		};
+		let Some(problems) = check_asset_file(path, &text) else {
+			continue;
+		};
+		checked_count += 1;
+		for problem in &problems {
+			match problem.line {
+				Some(line) => eprintln!("{}:{}: {}", path.display(), line, problem.message),
+				None => eprintln!("{}: {}", path.display(), problem.message),
+			}
+		}
+		problem_count += problems.len();
+	}
+	println!(
+		"checked {} asset files, found {} problems",
+		checked_count, problem_count
+	);
+	problem_count
+}
+
+fn find_asset_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+	for entry in std::fs::read_dir(dir)? {
+		let path = entry?.path();
+		if path.is_dir() {
+			find_asset_files(&path, files)?;
+		} else {
+			files.push(path);
+		}
+	}
+	Ok(())
+}
+
+/// Picks the asset type by file name, the same way the asset loaders do.
+/// Returns `None` for files that aren't RON assets (e.g. images).
+fn check_asset_file(path: &Path, text: &str) -> Option<Vec<AssetProblem>> {
+	let name = path.file_name()?.to_string_lossy();
+	let problems = if name.ends_with(".level.ron") {
+		parse_and_validate(text, validate_level)
+	} else if name.ends_with(".enemy.ron") {
+		parse_and_validate(text, validate_enemy)
+	} else if name.ends_with(".style.ron") {
+		parse_and_validate(text, validate_floating_text_style)
+	} else if name.ends_with(".prefab.ron") {
+		parse_and_validate(text, validate_prefab)
+	} else if name.ends_with(".input.ron") {
+		parse_and_validate(text, validate_input_map)
+	} else if name.ends_with(".demo.ron") {
+		parse_and_validate(text, validate_demo)
+	} else if name.ends_with(".ghost.ron") {
+		parse_and_validate(text, validate_ghost)
+	} else if name.ends_with(".ron") {
+		parse_and_validate(text, validate_player_params)
+	} else {
+		return None;
+	};
+	Some(problems)
+}
+
+fn parse_and_validate<T: DeserializeOwned>(text: &str, validate: fn(&T, &mut AssetChecker)) -> Vec<AssetProblem> {
+	match ron::de::from_str::<T>(text) {
+		Ok(asset) => {
+			let mut checker = AssetChecker {
+				text,
+				problems: Vec::new(),
+			};
+			validate(&asset, &mut checker);
+			checker.problems
+		}
+		Err(err) => vec![AssetProblem {
+			line: Some(err.span.start.line),
+			message: err.code.to_string(),
+		}],
+	}
+}
+
+fn validate_input_map(map: &InputMap, checker: &mut AssetChecker) {
+	for action in InputAction::REQUIRED {
+		let bound = map
+			.bindings
+			.get(&action)
+			.is_some_and(|binding| !binding.keys.is_empty() || !binding.buttons.is_empty());
+		checker.require(
+			bound,
+			&format!("{:?}:", action),
+			format!("`{:?}` needs at least one key or button", action),
+		);
+	}
+}
+
+fn validate_horizontal(params: &HorizontalControlParams, name: &str, checker: &mut AssetChecker) {
+	checker.require_positive(params.max_speed, &format!("{}.max_speed", name));
+	checker.require_positive(params.acceleration, &format!("{}.acceleration", name));
+	checker.require_positive(params.deceleration, &format!("{}.deceleration", name));
+	checker.require_positive(params.turn_deceleration, &format!("{}.turn_deceleration", name));
+}
+
+fn validate_player_params(params: &PlayerControlParams, checker: &mut AssetChecker) {
+	validate_horizontal(&params.run, "run", checker);
+	validate_horizontal(&params.sprint, "sprint", checker);
+	validate_horizontal(&params.float, "float", checker);
+	validate_horizontal(&params.crouch, "crouch", checker);
+	validate_horizontal(&params.swim.horizontal, "swim.horizontal", checker);
+	checker.require(
+		params.crouch_height_ratio > 0.0 && params.crouch_height_ratio <= 1.0,
+		"crouch_height_ratio:",
+		"`crouch_height_ratio` must be more than 0 and at most 1",
+	);
+	checker.require(
+		params.gravity < 0.0,
+		"gravity:",
+		"`gravity` must be negative (pulling downward)",
+	);
+	checker.require_positive(params.max_fall_speed, "max_fall_speed");
+	if let Some(fast_fall_speed) = params.fast_fall_speed {
+		checker.require(
+			fast_fall_speed >= params.max_fall_speed,
+			"fast_fall_speed:",
+			"`fast_fall_speed` is slower than `max_fall_speed`, so fast falling would slow the player down",
+		);
+	}
+	checker.require_ratio(params.swim.damping, "swim.damping");
+	checker.require_positive(params.zipline.grab_distance, "zipline.grab_distance");
+	checker.require_positive(params.zipline.max_speed, "zipline.max_speed");
+	checker.require_ratio(params.zipline.drag, "zipline.drag");
+	checker.require(params.max_jumps > 0, "max_jumps:", "`max_jumps` must be at least 1");
+	checker.require(
+		params.jump_profiles.len() <= params.max_jumps as usize,
+		"jump_profiles:",
+		format!(
+			"there are {} `jump_profiles`, but only {} jumps, so some profiles are never used",
+			params.jump_profiles.len(),
+			params.max_jumps
+		),
+	);
+	if let Some(charge) = params.charge_jump {
+		checker.require_frames(charge.charge_time, "charge_jump.charge_time");
+		checker.require(
+			charge.min_ratio <= charge.max_ratio,
+			"charge_jump:",
+			"`charge_jump.min_ratio` is more than `max_ratio`, so charging would weaken the jump",
+		);
+	}
+	if let Some(stomp) = params.stomp {
+		checker.require_positive(stomp.bounce_speed, "stomp.bounce_speed");
+	}
+
+	let wall = &params.wall_control_params;
+	checker.require_positive(wall.detection_length, "detection_length");
+	checker.require_positive(wall.stamina_max, "stamina_max");
+	checker.require(
+		wall.slippery_slide_max_speed >= wall.slide_max_speed,
+		"slippery_slide_max_speed:",
+		"`slippery_slide_max_speed` is slower than `slide_max_speed`, so slippery walls would be easier to hold",
+	);
+	checker.require(
+		wall.wall_snap_distance <= wall.detection_length,
+		"wall_snap_distance:",
+		"`wall_snap_distance` is longer than `detection_length`, so the extra distance is never detected",
+	);
+}
+
+fn validate_level(level: &LevelAsset, checker: &mut AssetChecker) {
+	// ids are how hot reloads find each platform's entity, so they must identify exactly one
+	let mut platform_ids = HashSet::new();
+	for platform in &level.platforms {
+		let near = format!("\"{}\"", platform.id);
+		checker.require(!platform.id.is_empty(), "id: \"\"", "platform has an empty `id`");
+		checker.require(
+			platform_ids.insert(platform.id.as_str()),
+			&near,
+			format!("platform id {:?} is used more than once", platform.id),
+		);
+		checker.require(
+			platform.size.x > 0.0 && platform.size.y > 0.0,
+			&near,
+			format!(
+				"platform {:?} has a size of {:?}, which isn't positive",
+				platform.id, platform.size
+			),
+		);
+		if let Some(breakable) = &platform.breakable {
+			validate_breakable(breakable, &format!("platform {:?}", platform.id), &near, checker);
+		}
+	}
+	// prefabs become platforms when the level loads, so their ids can't clash with the platforms' either
+	for prefab in &level.prefabs {
+		checker.require(!prefab.id.is_empty(), "id: \"\"", "prefab has an empty `id`");
+		checker.require(
+			platform_ids.insert(prefab.id.as_str()),
+			&format!("\"{}\"", prefab.id),
+			format!("platform id {:?} is used more than once", prefab.id),
+		);
+	}
+
+	if let Err(message) = level.validate_groups() {
+		checker.require(false, "groups:", message);
+	}
+
+	let mut room_ids = HashSet::new();
+	for room in &level.rooms {
+		let near = format!("\"{}\"", room.id);
+		checker.require(
+			room_ids.insert(room.id.as_str()),
+			&near,
+			format!("room id {:?} is used more than once", room.id),
+		);
+		checker.require(
+			room.min.x < room.max.x && room.min.y < room.max.y,
+			&near,
+			format!(
+				"room {:?} has its `min` corner above or to the right of its `max` corner",
+				room.id
+			),
+		);
+	}
+
+	for sound in level.zones.iter().filter_map(|zone| zone.sound.as_ref()) {
+		let near = format!("\"{}\"", sound.sound);
+		checker.require(
+			sound.volume >= 0.0,
+			&near,
+			format!("sound {:?} has a negative `volume`", sound.sound),
+		);
+		checker.require(
+			sound.audible_radius > 0.0,
+			&near,
+			format!("sound {:?} has an `audible_radius` that isn't positive", sound.sound),
+		);
+	}
+
+	let mut secret_ids = HashSet::new();
+	for secret in &level.secrets {
+		let near = format!("\"{}\"", secret.id);
+		checker.require(
+			secret_ids.insert(secret.id.as_str()),
+			&near,
+			format!("secret id {:?} is used more than once", secret.id),
+		);
+		checker.require(
+			secret.min.x < secret.max.x && secret.min.y < secret.max.y,
+			&near,
+			format!(
+				"secret {:?} has its `min` corner above or to the right of its `max` corner",
+				secret.id
+			),
+		);
+	}
+
+	// ids are how picked up collectibles stay picked up across hot reloads
+	let mut collectible_ids = HashSet::new();
+	for collectible in &level.collectibles {
+		checker.require(
+			collectible_ids.insert(collectible.id.as_str()),
+			&format!("\"{}\"", collectible.id),
+			format!("collectible id {:?} is used more than once", collectible.id),
+		);
+	}
+
+	let key_ids: HashSet<&str> = level
+		.collectibles
+		.iter()
+		.filter(|collectible| collectible.kind == CollectibleKind::Key)
+		.map(|collectible| collectible.id.as_str())
+		.collect();
+	for platform in &level.platforms {
+		if let Some(key) = &platform.lock {
+			checker.require(
+				key_ids.contains(key.as_str()),
+				&format!("\"{}\"", platform.id),
+				format!(
+					"door {:?} is locked with key {:?}, which isn't in the level",
+					platform.id, key
+				),
+			);
+		}
+	}
+
+	if let Some(intro_pan) = &level.intro_pan {
+		checker.require_positive(intro_pan.duration, "intro_pan.duration");
+		checker.require_positive(intro_pan.zoom, "intro_pan.zoom");
+	}
+
+	if let Some(ranks) = &level.ranks {
+		let tiers = [("S", &ranks.s), ("A", &ranks.a), ("B", &ranks.b)];
+		for (name, requirement) in tiers {
+			checker.require(
+				requirement.max_time.is_none_or(|max_time| max_time > 0.0),
+				"ranks:",
+				format!("rank {}'s `max_time` must be positive", name),
+			);
+		}
+		// a better rank that's easier to earn than a worse one would mean the worse one is never given
+		for pair in tiers.windows(2) {
+			let [(better, stricter), (worse, looser)] = pair else {
+				continue;
+			};
+			checker.require(
+				at_most(stricter.max_time, looser.max_time)
+					&& at_most(stricter.max_deaths, looser.max_deaths)
+					&& stricter.min_collectibles >= looser.min_collectibles,
+				"ranks:",
+				format!("rank {} is easier to earn than rank {}", better, worse),
+			);
+		}
+	}
+}
+
+/// Checks a breakable platform's timings, where `what` names the platform in messages (e.g. `platform "ledge"`)
+fn validate_breakable(breakable: &BreakableDef, what: &str, near: &str, checker: &mut AssetChecker) {
+	checker.require(
+		breakable.crack_time.0 > 0,
+		near,
+		format!("breakable {} must take at least one frame to crack", what),
+	);
+	checker.require(
+		breakable.respawn_time.is_none_or(|time| time.0 > 0),
+		near,
+		format!("breakable {} must take at least one frame to respawn", what),
+	);
+}
+
+/// Whether an optional limit is at least as strict as another, where no limit is the loosest of all
+fn at_most<T: PartialOrd>(limit: Option<T>, other: Option<T>) -> bool {
+	match (limit, other) {
+		(_, None) => true,
+		(None, Some(_)) => false,
+		(Some(limit), Some(other)) => limit <= other,
+	}
+}
+
+fn validate_prefab(prefab: &Prefab, checker: &mut AssetChecker) {
+	checker.require(
+		prefab.size.x > 0.0 && prefab.size.y > 0.0,
+		"size:",
+		format!("`size` must be positive, but is {:?}", prefab.size),
+	);
+	if let Some(breakable) = &prefab.breakable {
+		validate_breakable(breakable, "prefab", "breakable:", checker);
+	}
+}
+
+fn validate_enemy(archetype: &EnemyArchetype, checker: &mut AssetChecker) {
+	let perception = &archetype.perception;
+	checker.require_positive(perception.view_distance, "view_distance");
+	checker.require(
+		perception.view_half_angle > 0.0 && perception.view_half_angle <= 180.0,
+		"view_half_angle:",
+		"`view_half_angle` must be more than 0 and at most 180 degrees",
+	);
+	checker.require(perception.view_rays > 0, "view_rays:", "`view_rays` must be at least 1");
+	checker.require(
+		perception.hearing_radius >= 0.0,
+		"hearing_radius:",
+		"`hearing_radius` can't be negative",
+	);
+	if let Some(attack) = &archetype.attack {
+		checker.require(
+			attack.half_extents.x > 0.0 && attack.half_extents.y > 0.0,
+			"half_extents:",
+			format!("`half_extents` must be positive, but is {:?}", attack.half_extents),
+		);
+		checker.require_frames(attack.windup, "windup");
+		checker.require_frames(attack.active_duration, "active_duration");
+	}
+}
+
+fn validate_demo(script: &InputScript, checker: &mut AssetChecker) {
+	checker.require(
+		!script.ticks.is_empty(),
+		"ticks:",
+		"a demo needs at least one tick of input",
+	);
+}
+
+fn validate_ghost(script: &InputScript, checker: &mut AssetChecker) {
+	// the ghost starts out where the lead player was after the first tick, and plays the rest
+	let starts = script
+		.ticks
+		.first()
+		.is_some_and(|tick| tick.positions.iter().any(|(slot, _)| *slot == PlayerSlot::LEAD.0));
+	checker.require(
+		starts && script.ticks.len() > 1,
+		"ticks:",
+		"a tutorial ghost needs at least two ticks, starting with the lead player's position",
+	);
+}
+
+fn validate_floating_text_style(style: &FloatingTextStyle, checker: &mut AssetChecker) {
+	checker.require_frames(style.lifetime, "lifetime");
+	checker.require_positive(style.font_size, "font_size");
+	checker.require_positive(style.world_scale, "world_scale");
+	checker.require(style.max_active > 0, "max_active:", "`max_active` must be at least 1");
+	for (field, (r, g, b)) in [("damage_color", style.damage_color), ("score_color", style.score_color)] {
+		checker.require(
+			[r, g, b].iter().all(|c| (0.0..=1.0).contains(c)),
+			&format!("{}:", field),
+			format!("`{}` components must be between 0 and 1", field),
+		);
+	}
+}
@@ -0,0 +1,234 @@
+use crate::level::{CurrentLevel, LevelAsset, PlatformDef};
+use crate::player::{Character, PlayerInput, PlayerSlot};
+use crate::player_bundle;
+use crate::util::GameClock;
+use bevy::app::{PluginGroupBuilder, ScheduleRunnerPlugin};
+use bevy::prelude::*;
+use bevy::render::RenderPlugin;
+use bevy::render::settings::WgpuSettings;
+use bevy::window::ExitCondition;
+use bevy::winit::WinitPlugin;
+use std::time::{Duration, Instant};
+
+/// Fixed updates to time, unless given on the command line
+const DEFAULT_FRAMES: usize = 600;
+
+/// Size of the generated grid of platforms
+const GRID_COLUMNS: usize = 80;
+const GRID_ROWS: usize = 40;
+
+/// Platforms are spaced out this far, leaving room for the bots to run and jump between them
+const GRID_SPACING: Vec2 = Vec2::new(10.0, 12.0);
+
+/// Every this many columns, the grid has a tall pillar instead of a ledge, so the bots run into
+/// walls and keep their wall sensors busy
+const PILLAR_INTERVAL: usize = 4;
+
+const BOT_COUNT: usize = 48;
+
+/// Runs the game headless on a generated stress level, crowded with thousands of platforms and dozens
+/// of bot-controlled characters, and reports how long each fixed update takes before exiting.
+/// Meant for measuring physics and sensor optimizations before and after.
+///
+/// Enabled by running the game with `--benchmark`, optionally followed by the number of fixed updates
+/// to time.
+#[derive(Resource, Debug, Default)]
+pub struct Benchmark {
+	frames: Option<usize>,
+	samples: Vec<Duration>,
+	started: Option<Instant>,
+}
+
+impl Benchmark {
+	/// Reads whether to benchmark (and for how long) from the command line
+	pub fn from_args() -> Self {
+		let mut args = std::env::args().skip_while(|arg| arg != "--benchmark");
+		let frames = args.next().map(|_| {
+			args.next()
+				.and_then(|arg| arg.parse().ok())
+				.filter(|frames| *frames > 0)
+				.unwrap_or(DEFAULT_FRAMES)
+		});
+		if let Some(frames) = frames {
+			info!("benchmarking {} fixed updates", frames);
+		}
+		Benchmark { frames, ..default() }
+	}
+
+	pub fn is_enabled(&self) -> bool {
+		self.frames.is_some()
+	}
+
+	/// Swaps out the window and renderer, so the benchmark only measures the game itself
+	/// (and can run on machines without a display), and runs the app loop as fast as it can
+	pub fn headless(plugins: PluginGroupBuilder) -> PluginGroupBuilder {
+		plugins
+			.set(WindowPlugin {
+				primary_window: None,
+				exit_condition: ExitCondition::DontExit,
+				close_when_requested: false,
+			})
+			.set(RenderPlugin {
+				render_creation: WgpuSettings {
+					backends: None,
+					..default()
+				}
+				.into(),
+				..default()
+			})
+			.disable::<WinitPlugin>()
+			.add(ScheduleRunnerPlugin::run_loop(Duration::ZERO))
+	}
+}
+
+/// Controls one of the characters crowding the stress level
+#[derive(Component, Debug)]
+pub struct BenchmarkBot;
+
+/// A grid of ledges and pillars, with a floor underneath it all
+fn stress_level() -> LevelAsset {
+	let platform = |id: String, pos: Vec2, size: Vec2| PlatformDef {
+		id,
+		pos,
+		size,
+		color: (0.5, 0.5, 0.55),
+		one_way: false,
+		surface: None,
+		wall: None,
+		wall_material: None,
+		decorate: false,
+		spring: None,
+		breakable: None,
+		block: None,
+		group: None,
+		path: None,
+		sticky: None,
+		hazard: None,
+		lock: None,
+	};
+	let grid_size = GRID_SPACING * Vec2::new(GRID_COLUMNS as f32, GRID_ROWS as f32);
+	let mut platforms = vec![platform(
+		"floor".into(),
+		Vec2::new(grid_size.x * 0.5, -2.0),
+		Vec2::new(grid_size.x + 20.0, 4.0),
+	)];
+	for row in 0..GRID_ROWS {
+		for column in 0..GRID_COLUMNS {
+			let pos = GRID_SPACING * Vec2::new(column as f32 + 0.5, row as f32 + 1.0);
+			let size = if column % PILLAR_INTERVAL == 0 {
+				Vec2::new(2.0, 8.0)
+			} else {
+				Vec2::new(5.0, 1.0)
+			};
+			platforms.push(platform(format!("grid_{}_{}", column, row), pos, size));
+		}
+	}
+	LevelAsset {
+		platforms,
+		prefabs: Vec::new(),
+		rooms: Vec::new(),
+		groups: Vec::new(),
+		secrets: Vec::new(),
+		crush_response: default(),
+		intro_pan: None,
+		ranks: None,
+		player_start: None,
+		tiles: Vec::new(),
+		zones: Vec::new(),
+		ziplines: Vec::new(),
+		props: Vec::new(),
+		collectibles: Vec::new(),
+		switches: Vec::new(),
+		music: None,
+	}
+}
+
+/// Replaces the current level with the generated stress level, and fills it with bots.
+/// Should run after the normal level and player setup.
+pub fn setup_benchmark(
+	mut commands: Commands,
+	benchmark: Res<Benchmark>,
+	asset_server: Res<AssetServer>,
+	mut levels: ResMut<Assets<LevelAsset>>,
+	mut level_events: EventWriter<AssetEvent<LevelAsset>>,
+) {
+	if !benchmark.is_enabled() {
+		return;
+	}
+	let level = levels.add(stress_level());
+	// levels are synced once they've loaded; a generated one is ready right away
+	level_events.send(AssetEvent::LoadedWithDependencies { id: level.id() });
+	commands.insert_resource(CurrentLevel(level));
+
+	let character = Character {
+		name: "bot".into(),
+		params: asset_server.load("player.ron"),
+		half_extents: Vec2::new(1.5, 2.5),
+		color: Color::srgb(0.6, 0.6, 1.0),
+	};
+	for index in 0..BOT_COUNT {
+		// spread out over the grid, each in the gap above a ledge (rather than inside a pillar)
+		let column = match (index * 7) % GRID_COLUMNS {
+			column if column % PILLAR_INTERVAL == 0 => column + 1,
+			column => column,
+		};
+		let row = (index * 5) % GRID_ROWS;
+		let position = GRID_SPACING * Vec2::new(column as f32 + 0.5, row as f32 + 1.5);
+		commands.spawn((
+			// past the keyboard and any connected gamepads
+			player_bundle(&character, PlayerSlot(1000 + index), position),
+			BenchmarkBot,
+		));
+	}
+}
+
+/// Runs each bot back and forth, jumping every so often, with the timing varied from bot to bot
+/// so they don't all do the same thing at once. Should run before `player_system`.
+pub fn drive_benchmark_bots(
+	clock: Res<GameClock>,
+	mut bots: Query<(&PlayerSlot, &mut PlayerInput), With<BenchmarkBot>>,
+) {
+	let frame = clock.frame().0;
+	for (slot, mut input) in &mut bots {
+		let t = frame + slot.0 * 17;
+		input.horizontal = if (t / 90) % 2 == 0 { 1.0 } else { -1.0 };
+		input.jump = t % 40 < 10;
+		input.jump_pressed = t % 40 == 0;
+		input.sprint = (t / 200) % 2 == 0;
+	}
+}
+
+/// Notes when each fixed update starts. Runs in `FixedFirst`.
+pub fn start_fixed_update_timing(mut benchmark: ResMut<Benchmark>) {
+	if benchmark.is_enabled() {
+		benchmark.started = Some(Instant::now());
+	}
+}
+
+/// Records how long each fixed update took, and once enough have been timed, reports the timings
+/// and exits. Runs in `FixedLast`.
+pub fn finish_fixed_update_timing(mut benchmark: ResMut<Benchmark>, mut exit_events: EventWriter<AppExit>) {
+	let (Some(frames), Some(started)) = (benchmark.frames, benchmark.started.take()) else {
+		return;
+	};
+	benchmark.samples.push(started.elapsed());
+	if benchmark.samples.len() != frames {
+		return;
+	}
+
+	let mut samples = benchmark.samples.clone();
+	samples.sort();
+	let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+	let percentile = |p: f64| millis(samples[((samples.len() - 1) as f64 * p).round() as usize]);
+	let mean = millis(samples.iter().sum::<Duration>()) / samples.len() as f64;
+	info!(
+		"fixed update timings over {} frames: mean {:.3}ms, median {:.3}ms, p95 {:.3}ms, p99 {:.3}ms, max {:.3}ms",
+		samples.len(),
+		mean,
+		percentile(0.5),
+		percentile(0.95),
+		percentile(0.99),
+		percentile(1.0),
+	);
+	exit_events.send(AppExit::Success);
+}
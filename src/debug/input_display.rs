@@ -0,0 +1,138 @@
+use crate::player::{Health, Player, PlayerControlParams, PlayerControlState, PlayerInput, PlayerSlot};
+use crate::util::{CapacitiveFlag, Cooldown, FrameCount, YSide};
+use bevy::prelude::*;
+use std::fmt::Write;
+
+/// Toggles the [InputDisplay]
+const TOGGLE_KEY: KeyCode = KeyCode::F7;
+
+/// A HUD widget showing what the lead player is pressing, and the state of the buffers and cooldowns
+/// that decide what those presses do, as of the latest fixed update. Handy when tuning the jump buffer
+/// and coyote time (seeing exactly how late a jump was pressed), and for showing inputs on stream.
+///
+/// Hidden until toggled on with F7.
+#[derive(Component, Debug)]
+pub struct InputDisplay;
+
+/// Spawns the (hidden) [InputDisplay] in the bottom left corner
+pub fn setup_input_display(mut commands: Commands) {
+	commands.spawn((
+		InputDisplay,
+		Text::default(),
+		TextFont::from_font_size(14.0),
+		Node {
+			position_type: PositionType::Absolute,
+			bottom: Val::Px(20.0),
+			left: Val::Px(20.0),
+			padding: UiRect::all(Val::Px(8.0)),
+			..default()
+		},
+		BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+		Visibility::Hidden,
+	));
+}
+
+/// Shows or hides the [InputDisplay] on F7, and keeps it up to date while it's shown
+pub fn input_display_system(
+	kb: Res<ButtonInput<KeyCode>>,
+	control_params: Res<Assets<PlayerControlParams>>,
+	players: Query<(&Player, &PlayerSlot, &PlayerInput, &PlayerControlState, &Health)>,
+	mut displays: Query<(&mut Text, &mut Visibility), With<InputDisplay>>,
+) {
+	for (mut text, mut visibility) in &mut displays {
+		if kb.just_pressed(TOGGLE_KEY) {
+			visibility.toggle_visible_hidden();
+		}
+		if *visibility == Visibility::Hidden {
+			continue;
+		}
+
+		let Some((player, _, input, state, health)) = players.iter().find(|(_, slot, ..)| **slot == PlayerSlot::LEAD)
+		else {
+			continue;
+		};
+		let Some(params) = control_params.get(player.0.id()) else {
+			continue;
+		};
+		let mut display = String::new();
+		let _ = writeln!(display, "input    {}", describe_input(input));
+		let _ = writeln!(
+			display,
+			"jump buffer  {}",
+			describe_flag(&state.jump_requested, params.jump_input_buffer)
+		);
+		let _ = writeln!(
+			display,
+			"coyote  {}",
+			describe_flag(&state.grounded, params.coyote_time)
+		);
+		let _ = writeln!(display, "jumps left  {}", state.jumps_remaining);
+		let _ = writeln!(display, "health  {}/{}", health.current, health.max);
+		let cooldowns = [
+			("jump", &state.jump_cooldown),
+			("drop through", &state.drop_through_cooldown),
+			("wall jump input", &state.wall_jump_input_cooldown),
+			("knockback", &state.knockback_control_loss),
+			("invulnerability", &state.invulnerability),
+			("zipline", &state.zipline_cooldown),
+			("sticky platform", &state.stick_cooldown),
+		];
+		let _ = write!(display, "cooldowns");
+		for (name, cooldown) in cooldowns {
+			let _ = write!(display, "\n  {}  {}", name, describe_cooldown(cooldown));
+		}
+		text.0 = display;
+	}
+}
+
+/// Arrows for the directions being pressed (with the analog amount, when it isn't all the way),
+/// followed by the held buttons
+fn describe_input(input: &PlayerInput) -> String {
+	let mut description = match input.horizontal {
+		h if h < 0.0 => format!("\u{2190} {:.2}", -h),
+		h if h > 0.0 => format!("\u{2192} {:.2}", h),
+		_ => "\u{00b7}".to_string(),
+	};
+	if let Some(direction) = input.vertical_direction() {
+		let arrow = match direction {
+			YSide::Up => "\u{2191}",
+			YSide::Down => "\u{2193}",
+		};
+		let _ = write!(description, " {}", arrow);
+	}
+	for (held, name) in [
+		(input.jump, "JUMP"),
+		(input.jump_pressed, "(pressed)"),
+		(input.sprint, "SPRINT"),
+	] {
+		if held {
+			let _ = write!(description, " {}", name);
+		}
+	}
+	description
+}
+
+/// Whether the flag is set, or how many frames ago it was, and whether that's still within the `window`
+fn describe_flag(flag: &CapacitiveFlag, window: FrameCount) -> String {
+	if flag.is_set() {
+		return "set".to_string();
+	}
+	let FrameCount(frames) = flag.time_since_released();
+	if frames == usize::MAX {
+		return "-".to_string();
+	}
+	let verdict = if flag.was_set_within(window) {
+		"within"
+	} else {
+		"outside"
+	};
+	format!("{}f ago ({} {}f window)", frames, verdict, window.0)
+}
+
+fn describe_cooldown(cooldown: &Cooldown) -> String {
+	if cooldown.is_ready() {
+		"ready".to_string()
+	} else {
+		format!("{}f", cooldown.remaining().0)
+	}
+}
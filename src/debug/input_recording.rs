@@ -0,0 +1,508 @@
+use crate::level::{CurrentLevel, LevelAsset};
+use crate::player::{Player, PlayerControlParams, PlayerInput, PlayerSlot};
+use bevy::prelude::*;
+use bevy::window::WindowCloseRequested;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// How far a replayed player can end up from where they were recorded before it counts as a difference.
+/// Replays are deterministic, so this only has to absorb the rounding of positions written to the file.
+const POSITION_TOLERANCE: f32 = 0.001;
+
+/// One fixed update of a recorded run
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecordedTick {
+	/// Each player's input, by [PlayerSlot]
+	pub inputs: Vec<(usize, PlayerInput)>,
+	/// Where each player was at the end of the update, by [PlayerSlot]
+	pub positions: Vec<(usize, Vec2)>,
+}
+
+/// A recorded run: every player's input for every fixed update, from the moment the level and
+/// the players' params had loaded, along with where it got them
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InputScript {
+	pub ticks: Vec<RecordedTick>,
+}
+
+#[derive(Debug, Error)]
+pub enum InputScriptError {
+	#[error("Could not access input script: {0}")]
+	Io(#[from] std::io::Error),
+
+	#[error("Could not serialize input script: {0}")]
+	Ron(#[from] ron::Error),
+
+	#[error("Could not parse input script: {0}")]
+	Parse(#[from] ron::de::SpannedError),
+}
+
+impl InputScript {
+	pub fn read(path: &Path) -> Result<Self, InputScriptError> {
+		Ok(ron::de::from_str(&std::fs::read_to_string(path)?)?)
+	}
+
+	fn write(&self, path: &Path) -> Result<(), InputScriptError> {
+		std::fs::write(path, ron::ser::to_string(self)?)?;
+		Ok(())
+	}
+}
+
+#[derive(Debug, Default, PartialEq)]
+enum RecordingMode {
+	#[default]
+	Off,
+	Record,
+	/// Replays the script in place of the players' own input
+	Replay,
+	/// Replays the script without a window, and exits once it's done, with an error if any player
+	/// ended up somewhere other than where they were recorded
+	Verify,
+}
+
+/// Records the [PlayerInput] stream to a file, or plays one back in place of the players' own input.
+///
+/// Gameplay only depends on the input it's given each fixed update, so replaying a recorded run
+/// with the same level and params moves the players exactly as before. Each replayed update is
+/// checked against the positions recorded for it, and the first difference is reported; after
+/// a change to the params, that shows where (and whether) the change made a difference.
+///
+/// Enabled by running the game with one of:
+/// - `--record-input <path>` to record until the game closes
+/// - `--replay-input <path>` to watch a recorded run
+/// - `--verify-input <path>` to replay a recorded run headless, exiting with an error if it
+///   turned out differently, e.g. to catch physics regressions
+#[derive(Resource, Debug, Default)]
+pub struct InputRecording {
+	mode: RecordingMode,
+	path: PathBuf,
+	script: InputScript,
+	/// Whether the level and params have loaded, so the run has started
+	started: bool,
+	tick: usize,
+	/// The first tick where a replay turned out differently than recorded,
+	/// along with which player slot differed and by how much
+	divergence: Option<(usize, usize, f32)>,
+}
+
+impl InputRecording {
+	/// Reads what to record or replay (if anything) from the command line
+	pub fn from_args() -> Self {
+		let arg_after = |flag: &str| std::env::args().skip_while(|arg| arg != flag).nth(1).map(PathBuf::from);
+		let (mode, path) = if let Some(path) = arg_after("--record-input") {
+			(RecordingMode::Record, path)
+		} else if let Some(path) = arg_after("--replay-input") {
+			(RecordingMode::Replay, path)
+		} else if let Some(path) = arg_after("--verify-input") {
+			(RecordingMode::Verify, path)
+		} else {
+			return InputRecording::default();
+		};
+
+		let mut script = InputScript::default();
+		match mode {
+			RecordingMode::Record => info!("recording input to {:?}", path),
+			_ => match InputScript::read(&path) {
+				Ok(read) => {
+					info!("replaying {} ticks of input from {:?}", read.ticks.len(), path);
+					script = read;
+				}
+				Err(err) => {
+					error!("failed to read input script from {:?}: {}", path, err);
+					if mode == RecordingMode::Verify {
+						std::process::exit(1);
+					}
+					return InputRecording::default();
+				}
+			},
+		}
+		InputRecording {
+			mode,
+			path,
+			script,
+			..default()
+		}
+	}
+
+	/// Whether a run is being recorded or replayed
+	pub fn is_active(&self) -> bool {
+		self.mode != RecordingMode::Off
+	}
+
+	/// Replays the given script in place of the players' own input, starting with the next fixed update
+	/// (or once the level and params have loaded)
+	pub fn replay(&mut self, script: InputScript) {
+		*self = InputRecording {
+			mode: RecordingMode::Replay,
+			script,
+			..default()
+		};
+	}
+
+	/// Whether a replay has played all of its script, leaving the players standing still
+	pub fn is_replay_over(&self) -> bool {
+		self.mode == RecordingMode::Replay && self.started && self.tick >= self.script.ticks.len()
+	}
+
+	/// Stops replaying, handing control back to the players
+	pub fn stop_replay(&mut self) {
+		if self.mode == RecordingMode::Replay {
+			*self = InputRecording::default();
+		}
+	}
+
+	/// Whether to run without a window, like the [Benchmark](crate::debug::Benchmark)
+	pub fn is_headless(&self) -> bool {
+		self.mode == RecordingMode::Verify
+	}
+}
+
+/// Run condition for the systems that restore and autosave the player's progress. A recorded run starts
+/// from the level's start rather than the save, so that it replays the same on any machine, and it
+/// leaves the save alone.
+pub fn not_recording_input(recording: Res<InputRecording>) -> bool {
+	!recording.is_active()
+}
+
+/// Starts the run once the level and every player's params have loaded, so that it doesn't depend on how
+/// long loading took. Then records each player's input, or replaces it with the replayed input.
+/// Should run before `player_system`, after anything else that sets the players' input.
+pub fn record_or_replay_input(
+	mut recording: ResMut<InputRecording>,
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	control_params: Res<Assets<PlayerControlParams>>,
+	mut players: Query<(&Player, &PlayerSlot, &mut PlayerInput)>,
+) {
+	if recording.mode == RecordingMode::Off {
+		return;
+	}
+	if !recording.started {
+		let level_loaded = current_level.is_some_and(|level| levels.contains(level.0.id()));
+		let params_loaded = players
+			.iter()
+			.all(|(player, _, _)| control_params.contains(player.0.id()));
+		recording.started = level_loaded && params_loaded;
+	}
+
+	if recording.mode == RecordingMode::Record {
+		if recording.started {
+			let inputs = players.iter().map(|(_, slot, input)| (slot.0, input.clone())).collect();
+			recording.script.ticks.push(RecordedTick { inputs, ..default() });
+		}
+		return;
+	}
+
+	// the players stand still until the replay starts, and after it's over
+	let tick = recording
+		.started
+		.then(|| recording.script.ticks.get(recording.tick))
+		.flatten();
+	for (_, slot, mut input) in &mut players {
+		*input = tick
+			.and_then(|tick| tick.inputs.iter().find(|(recorded_slot, _)| *recorded_slot == slot.0))
+			.map_or_else(PlayerInput::default, |(_, recorded)| recorded.clone());
+	}
+}
+
+/// Records where each player ended up after the fixed update, or checks that the replay got them to
+/// the same place as the recording. Once a replay is over, reports how it went (and exits, when
+/// verifying). Runs in `FixedLast`.
+pub fn check_recorded_positions(
+	mut recording: ResMut<InputRecording>,
+	players: Query<(&PlayerSlot, &Transform), With<Player>>,
+	mut exit_events: EventWriter<AppExit>,
+) {
+	if recording.mode == RecordingMode::Off || !recording.started {
+		return;
+	}
+	let positions = players
+		.iter()
+		.map(|(slot, transform)| (slot.0, transform.translation.truncate()));
+
+	if recording.mode == RecordingMode::Record {
+		if let Some(tick) = recording.script.ticks.last_mut() {
+			tick.positions = positions.collect();
+		}
+		return;
+	}
+
+	let index = recording.tick;
+	let Some(tick) = recording.script.ticks.get(index) else {
+		return;
+	};
+	let divergence = positions
+		.filter_map(|(slot, position)| {
+			let (_, recorded) = tick
+				.positions
+				.iter()
+				.find(|(recorded_slot, _)| *recorded_slot == slot)?;
+			let distance = position.distance(*recorded);
+			(distance > POSITION_TOLERANCE).then_some((index, slot, distance))
+		})
+		.next();
+	if recording.divergence.is_none() {
+		recording.divergence = divergence;
+	}
+	recording.tick += 1;
+	if recording.tick < recording.script.ticks.len() {
+		return;
+	}
+
+	match recording.divergence {
+		None => info!("replay of {} ticks matched the recording", recording.tick),
+		Some((tick, slot, distance)) => warn!(
+			"replay diverged from the recording at tick {} of {}: player {} was {:.3} away",
+			tick, recording.tick, slot, distance
+		),
+	}
+	if recording.mode == RecordingMode::Verify {
+		exit_events.send(match recording.divergence {
+			None => AppExit::Success,
+			Some(_) => AppExit::error(),
+		});
+	}
+}
+
+/// Writes the recording out when the game is closing.
+/// Runs in `Last`, so that it sees the `AppExit` sent when the window is closed before the app stops.
+pub fn save_input_recording(
+	recording: Res<InputRecording>,
+	mut close_events: EventReader<WindowCloseRequested>,
+	mut exit_events: EventReader<AppExit>,
+) {
+	let closing = close_events.read().count() > 0 || exit_events.read().count() > 0;
+	if !closing || recording.mode != RecordingMode::Record {
+		return;
+	}
+	match recording.script.write(&recording.path) {
+		Ok(()) => info!(
+			"recorded {} ticks of input to {:?}",
+			recording.script.ticks.len(),
+			recording.path
+		),
+		Err(err) => error!("failed to write input recording to {:?}: {}", recording.path, err),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::platforms::OneWayPlatformHooks;
+	use crate::player::{
+		Character, KnockbackEvent, PlayerJumped, PlayerSkidded, PlayerStomped, clear_player_input_presses,
+		player_system,
+	};
+	use crate::save::AssistOptions;
+	use crate::util::{GameClock, tick_game_clock};
+	use crate::{WallArgs, player_bundle};
+	use bevy::gizmos::LineGizmo;
+	use bevy::time::TimeUpdateStrategy;
+	use bevy_rapier2d::prelude::{RapierPhysicsPlugin, TimestepMode};
+	use std::time::Duration;
+
+	/// A short run of one player walking right, hopping up, and walking back down, recorded against
+	/// [move_players] rather than the real player movement
+	const RECORDING: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/recordings/walk_and_hop.ron");
+
+	/// Stands in for `player_system`, moving each player one unit per tick in the direction of their input
+	fn move_players(mut players: Query<(&PlayerInput, &mut Transform)>) {
+		for (input, mut transform) in &mut players {
+			transform.translation += Vec3::new(input.horizontal, input.vertical, 0.0);
+		}
+	}
+
+	/// Replays the script, one fixed update per app update, and returns how the app was told to exit
+	fn replay(mode: RecordingMode, script: InputScript) -> Option<AppExit> {
+		let ticks = script.ticks.len();
+		let mut app = App::new();
+		app.init_resource::<Assets<LevelAsset>>()
+			.init_resource::<Assets<PlayerControlParams>>()
+			.insert_resource(InputRecording {
+				mode,
+				script,
+				// there's no level or params to wait for
+				started: true,
+				..default()
+			})
+			.add_systems(
+				Update,
+				(record_or_replay_input, move_players, check_recorded_positions).chain(),
+			);
+		app.world_mut()
+			.spawn((Player(Handle::default()), PlayerSlot(0), Transform::default()));
+		for _ in 0..ticks {
+			app.update();
+		}
+		app.should_exit()
+	}
+
+	fn recording() -> InputScript {
+		InputScript::read(Path::new(RECORDING)).expect("the recording should parse")
+	}
+
+	#[test]
+	fn verify_succeeds_when_the_replay_matches() {
+		assert_eq!(replay(RecordingMode::Verify, recording()), Some(AppExit::Success));
+	}
+
+	#[test]
+	fn verify_fails_when_the_replay_diverges() {
+		let mut script = recording();
+		script.ticks[3].positions[0].1.x += 0.5;
+		assert_eq!(replay(RecordingMode::Verify, script), Some(AppExit::error()));
+	}
+
+	#[test]
+	fn verify_tolerates_rounding() {
+		let mut script = recording();
+		script.ticks[3].positions[0].1.x += POSITION_TOLERANCE / 2.0;
+		assert_eq!(replay(RecordingMode::Verify, script), Some(AppExit::Success));
+	}
+
+	#[test]
+	fn replay_keeps_running_afterwards() {
+		assert_eq!(replay(RecordingMode::Replay, recording()), None);
+	}
+
+	/// How many fixed updates the run through the real player pipeline lasts
+	const PIPELINE_TICKS: usize = 180;
+
+	/// Plays the part of the player during a recording: walks right into a ledge, jumps up onto it,
+	/// then walks back off of it
+	fn drive_player(mut players: Query<&mut PlayerInput>, mut frame: Local<usize>) {
+		for mut input in &mut players {
+			*input = PlayerInput {
+				horizontal: match *frame {
+					0..60 => 1.0,
+					60..120 => -1.0,
+					_ => 0.0,
+				},
+				jump: (20..40).contains(&*frame),
+				jump_pressed: *frame == 20,
+				..default()
+			};
+		}
+		*frame += 1;
+	}
+
+	/// A headless app running the players' real fixed update, from their input through `player_system`
+	/// and Rapier's character controller, on a floor with a ledge, with every app update advancing the
+	/// game by one fixed update
+	fn pipeline_app(recording: InputRecording, params: PlayerControlParams) -> App {
+		let mut app = App::new();
+		app.add_plugins(MinimalPlugins)
+			.insert_resource(Time::<Fixed>::from_hz(60.))
+			.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1. / 60.)))
+			.insert_resource(TimestepMode::Fixed {
+				dt: 1. / 60.,
+				substeps: 1,
+			})
+			.add_plugins(RapierPhysicsPlugin::<OneWayPlatformHooks>::pixels_per_meter(10.0).in_fixed_schedule())
+			// `player_system` draws its sensors, with nothing to render them
+			.init_resource::<Assets<LineGizmo>>()
+			.init_gizmo_group::<DefaultGizmoConfigGroup>()
+			.init_resource::<Assets<LevelAsset>>()
+			.init_resource::<Assets<PlayerControlParams>>()
+			.init_resource::<GameClock>()
+			.init_resource::<AssistOptions>()
+			.add_event::<PlayerJumped>()
+			.add_event::<PlayerStomped>()
+			.add_event::<PlayerSkidded>()
+			.add_event::<KnockbackEvent>()
+			.insert_resource(InputRecording {
+				// the level is spawned below rather than loaded
+				started: true,
+				..recording
+			})
+			.add_systems(FixedFirst, tick_game_clock)
+			.add_systems(
+				FixedUpdate,
+				(drive_player, record_or_replay_input, player_system).chain(),
+			)
+			.add_systems(FixedLast, (check_recorded_positions, clear_player_input_presses));
+
+		let floor = WallArgs {
+			color: Color::WHITE,
+			pos: Vec2::new(50., 0.),
+			size: Vec2::new(100., 2.),
+		};
+		let ledge = WallArgs {
+			color: Color::WHITE,
+			pos: Vec2::new(40., 3.),
+			size: Vec2::new(20., 4.),
+		};
+		app.world_mut().spawn(floor.bundle());
+		app.world_mut().spawn(ledge.bundle());
+		let character = Character {
+			name: "runner".into(),
+			params: app
+				.world_mut()
+				.resource_mut::<Assets<PlayerControlParams>>()
+				.add(params),
+			half_extents: Vec2::new(1.5, 2.5),
+			color: Color::WHITE,
+		};
+		app.world_mut()
+			.spawn(player_bundle(&character, PlayerSlot(0), Vec2::new(10., 3.5)));
+		app
+	}
+
+	fn player_params() -> PlayerControlParams {
+		let path = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/player.ron");
+		ron::de::from_str(&std::fs::read_to_string(path).unwrap()).expect("the player params should parse")
+	}
+
+	/// Records [drive_player]'s run through the real player pipeline
+	fn record_pipeline() -> InputScript {
+		let recording = InputRecording {
+			mode: RecordingMode::Record,
+			..default()
+		};
+		let mut app = pipeline_app(recording, player_params());
+		// the first update only starts the clock, without running a fixed update
+		for _ in 0..=PIPELINE_TICKS {
+			app.update();
+		}
+		app.world().resource::<InputRecording>().script.clone()
+	}
+
+	/// Verifies the script against the real player pipeline, and returns how the app was told to exit
+	fn verify_pipeline(script: InputScript, params: PlayerControlParams) -> Option<AppExit> {
+		let recording = InputRecording {
+			mode: RecordingMode::Verify,
+			script,
+			..default()
+		};
+		let mut app = pipeline_app(recording, params);
+		for _ in 0..=PIPELINE_TICKS {
+			app.update();
+		}
+		app.should_exit()
+	}
+
+	#[test]
+	fn real_pipeline_replays_its_own_recording() {
+		let script = record_pipeline();
+		assert_eq!(script.ticks.len(), PIPELINE_TICKS);
+		let (start, end) = (&script.ticks[0].positions[0].1, &script.ticks[59].positions[0].1);
+		assert!(
+			end.x - start.x > 10.,
+			"the player should have walked right, from {} to {}",
+			start,
+			end
+		);
+		assert_eq!(verify_pipeline(script, player_params()), Some(AppExit::Success));
+	}
+
+	#[test]
+	fn real_pipeline_diverges_when_the_params_change() {
+		let script = record_pipeline();
+		let mut params = player_params();
+		params.run.max_speed *= 0.5;
+		assert_eq!(verify_pipeline(script, params), Some(AppExit::error()));
+	}
+}
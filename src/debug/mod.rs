@@ -0,0 +1,17 @@
+mod asset_check;
+mod benchmark;
+mod complete_level;
+mod input_display;
+mod input_recording;
+mod spawn_palette;
+mod tuning_diff;
+mod wall_frame_export;
+
+pub use asset_check::*;
+pub use benchmark::*;
+pub use complete_level::*;
+pub use input_display::*;
+pub use input_recording::*;
+pub use spawn_palette::*;
+pub use tuning_diff::*;
+pub use wall_frame_export::*;
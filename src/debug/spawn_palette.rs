@@ -0,0 +1,131 @@
+use crate::ui::WorldCamera;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_rapier2d::prelude::*;
+
+/// Marker for dynamic bodies spawned from the sandbox palette, so they can be cleared out
+#[derive(Component)]
+pub struct SandboxObject;
+
+/// An entry in the sandbox spawn palette, bound to a number key
+#[derive(Debug, Copy, Clone)]
+enum PaletteItem {
+	Ball { radius: f32, restitution: f32 },
+	Crate { size: f32, density: f32 },
+	CrateStack { size: f32, count: usize },
+}
+
+const PALETTE: [(KeyCode, PaletteItem); 5] = [
+	(
+		KeyCode::Digit1,
+		PaletteItem::Ball {
+			radius: 2.0,
+			restitution: 0.9,
+		},
+	),
+	(
+		KeyCode::Digit2,
+		PaletteItem::Ball {
+			radius: 1.5,
+			restitution: 0.1,
+		},
+	),
+	(
+		KeyCode::Digit3,
+		PaletteItem::Crate {
+			size: 3.0,
+			density: 0.5,
+		},
+	),
+	(
+		KeyCode::Digit4,
+		PaletteItem::Crate {
+			size: 4.0,
+			density: 5.0,
+		},
+	),
+	(KeyCode::Digit5, PaletteItem::CrateStack { size: 3.0, count: 4 }),
+];
+
+/// Debug palette for stress-testing interactions between the player and dynamic bodies.
+/// Number keys 1-5 spawn objects at the mouse cursor; 0 removes everything spawned this way.
+pub fn spawn_palette_system(
+	mut commands: Commands,
+	kb: Res<ButtonInput<KeyCode>>,
+	windows: Query<&Window, With<PrimaryWindow>>,
+	cameras: Query<(&Camera, &GlobalTransform), With<WorldCamera>>,
+	spawned: Query<Entity, With<SandboxObject>>,
+) {
+	if kb.just_pressed(KeyCode::Digit0) {
+		for entity in &spawned {
+			commands.entity(entity).despawn_recursive();
+		}
+		return;
+	}
+
+	let Some(item) = PALETTE
+		.iter()
+		.find(|(key, _)| kb.just_pressed(*key))
+		.map(|(_, item)| *item)
+	else {
+		return;
+	};
+	let (Ok(window), Ok((camera, camera_transform))) = (windows.get_single(), cameras.get_single()) else {
+		return;
+	};
+	let Some(cursor) = window.cursor_position() else {
+		return;
+	};
+	let Ok(pos) = camera.viewport_to_world_2d(camera_transform, cursor) else {
+		return;
+	};
+
+	match item {
+		PaletteItem::Ball { radius, restitution } => {
+			commands.spawn((
+				dynamic_body(pos),
+				Sprite::from_color(
+					Color::srgb(0.2, 0.6 + 0.4 * restitution, 0.2),
+					Vec2::splat(radius * 2.0),
+				),
+				Collider::ball(radius),
+				Restitution::coefficient(restitution),
+			));
+		}
+		PaletteItem::Crate { size, density } => {
+			spawn_crate(&mut commands, pos, size, density);
+		}
+		PaletteItem::CrateStack { size, count } => {
+			for i in 0..count {
+				spawn_crate(&mut commands, pos + Vec2::Y * size * i as f32, size, 1.0);
+			}
+		}
+	}
+}
+
+fn spawn_crate(commands: &mut Commands, pos: Vec2, size: f32, density: f32) {
+	// heavier crates are drawn darker
+	let shade = (1.0 / (1.0 + density)).clamp(0.2, 0.8);
+	commands.spawn((
+		dynamic_body(pos),
+		Sprite::from_color(
+			Color::srgb(0.4 + shade * 0.5, 0.3 + shade * 0.4, 0.1),
+			Vec2::splat(size),
+		),
+		Collider::cuboid(size * 0.5, size * 0.5),
+		ColliderMassProperties::Density(density),
+	));
+}
+
+/// Components shared by everything in the palette, including what the player needs in order to push them
+fn dynamic_body(pos: Vec2) -> impl Bundle {
+	(
+		SandboxObject,
+		RigidBody::Dynamic,
+		Transform::from_xyz(pos.x, pos.y, 0.0),
+		ExternalImpulse::default(),
+		ExternalForce::default(),
+		ReadMassProperties::default(),
+		Velocity::default(),
+	)
+}
@@ -0,0 +1,228 @@
+use crate::player::PlayerControlParams;
+use crate::util::{FrameCount, GameClock, Lifetime};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::Write;
+
+/// Where F8 exports the session's tuning history
+const HISTORY_PATH: &str = "tuning_history.csv";
+
+const CSV_HEADER: &str = "elapsed_secs,frame,asset,param,old,new";
+
+/// How long the overlay listing a reload's changes stays up
+const OVERLAY_LIFETIME: FrameCount = FrameCount(300);
+
+/// Stands in for the value of a parameter that only exists on one side of a change
+const MISSING: &str = "(none)";
+
+/// One parameter that changed when a params file was hot-reloaded
+#[derive(Clone, Debug)]
+pub struct TuningChange {
+	/// Gameplay seconds into the session when the change was reloaded
+	pub elapsed_secs: f32,
+	pub frame: FrameCount,
+	/// Asset path of the params file that changed
+	pub asset: String,
+	/// Where the parameter is within the params, e.g. `run.max_speed`
+	pub param: String,
+	pub old: String,
+	pub new: String,
+}
+
+/// Every change made to the players' params during this session, in the order they were reloaded.
+/// Exported with F8, so playtest feedback can be matched up with exactly what the params were at the time.
+#[derive(Resource, Debug, Default)]
+pub struct TuningHistory {
+	/// Each params asset's values as of its latest load, to diff the next reload against
+	snapshots: HashMap<AssetId<PlayerControlParams>, Vec<(String, String)>>,
+	pub changes: Vec<TuningChange>,
+}
+
+impl TuningHistory {
+	fn export(&self) -> std::io::Result<()> {
+		let mut file = File::create(HISTORY_PATH)?;
+		writeln!(file, "{}", CSV_HEADER)?;
+		for change in &self.changes {
+			writeln!(
+				file,
+				"{},{},{},{},{},{}",
+				change.elapsed_secs,
+				change.frame.0,
+				csv_field(&change.asset),
+				csv_field(&change.param),
+				csv_field(&change.old),
+				csv_field(&change.new),
+			)?;
+		}
+		Ok(())
+	}
+}
+
+/// Quotes a value if it would otherwise break up the row, e.g. a list of values with commas
+fn csv_field(value: &str) -> String {
+	if value.contains([',', '"']) {
+		format!("\"{}\"", value.replace('"', "\"\""))
+	} else {
+		value.to_string()
+	}
+}
+
+/// Marks the overlay listing the changes from the latest reload
+#[derive(Component, Debug)]
+pub struct TuningDiffOverlay;
+
+/// Flattens a value into `(path, value)` pairs, one per field that doesn't have fields of its own,
+/// e.g. `("run.max_speed", "30.0")`. Works from the pretty-printed `Debug` output, so that none of
+/// the params' many types need to do anything special to be diffed.
+fn flatten_params(value: &impl Debug) -> Vec<(String, String)> {
+	// the segments of the path to the current line, each with the bracket that opened it,
+	// and how many unnamed items (e.g. of a list) it has had so far
+	let mut stack: Vec<(String, char, usize)> = Vec::new();
+	let mut fields = Vec::new();
+	for line in format!("{:#?}", value).lines() {
+		let line = line.trim().trim_end_matches(',');
+		if matches!(line, "}" | ")" | "]") {
+			stack.pop();
+			continue;
+		}
+		let (name, value) = match line.split_once(": ") {
+			Some((name, value)) => (name.to_string(), value),
+			// list items are named by their index, while the contents of e.g. `Some(...)` take the name
+			// of whatever they're in
+			None => match stack.last_mut() {
+				Some((_, '[', count)) => {
+					*count += 1;
+					(format!("[{}]", *count - 1), line)
+				}
+				_ => (String::new(), line),
+			},
+		};
+		match value.chars().last() {
+			Some(bracket @ ('{' | '(' | '[')) => stack.push((name, bracket, 0)),
+			_ => {
+				let segments = stack
+					.iter()
+					.map(|(segment, ..)| segment.as_str())
+					.chain([name.as_str()]);
+				fields.push((join_path(segments), value.to_string()));
+			}
+		}
+	}
+	fields
+}
+
+/// Joins field names into a path like `jump_profiles[0].speed`, skipping unnamed segments
+fn join_path<'a>(segments: impl Iterator<Item = &'a str>) -> String {
+	let mut path = String::new();
+	for segment in segments.filter(|segment| !segment.is_empty()) {
+		if !path.is_empty() && !segment.starts_with('[') {
+			path.push('.');
+		}
+		path.push_str(segment);
+	}
+	path
+}
+
+/// The fields whose values differ between two flattened versions of the same params, as
+/// `(path, old, new)`, including any that were added or removed (e.g. an optional param being set)
+fn diff_params(old: &[(String, String)], new: &[(String, String)]) -> Vec<(String, String, String)> {
+	let old_values: HashMap<&str, &str> = old
+		.iter()
+		.map(|(path, value)| (path.as_str(), value.as_str()))
+		.collect();
+	let new_values: HashMap<&str, &str> = new
+		.iter()
+		.map(|(path, value)| (path.as_str(), value.as_str()))
+		.collect();
+	let changed = new.iter().filter_map(|(path, value)| {
+		let old_value = old_values.get(path.as_str()).copied().unwrap_or(MISSING);
+		(old_value != value).then(|| (path.clone(), old_value.to_string(), value.clone()))
+	});
+	let removed = old
+		.iter()
+		.filter(|(path, _)| !new_values.contains_key(path.as_str()))
+		.map(|(path, value)| (path.clone(), value.clone(), MISSING.to_string()));
+	changed.chain(removed).collect()
+}
+
+/// Diffs each params file against its previous version when it hot-reloads, showing what changed
+/// in an overlay for a few seconds, and recording the changes in the [TuningHistory]
+pub fn track_tuning_changes(
+	mut commands: Commands,
+	mut events: EventReader<AssetEvent<PlayerControlParams>>,
+	params: Res<Assets<PlayerControlParams>>,
+	asset_server: Res<AssetServer>,
+	clock: Res<GameClock>,
+	mut history: ResMut<TuningHistory>,
+	overlays: Query<Entity, With<TuningDiffOverlay>>,
+) {
+	for event in events.read() {
+		let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = *event else {
+			continue;
+		};
+		let Some(asset) = params.get(id) else {
+			continue;
+		};
+		let fields = flatten_params(asset);
+		// nothing to compare the first load against
+		let Some(previous) = history.snapshots.insert(id, fields.clone()) else {
+			continue;
+		};
+		let changes = diff_params(&previous, &fields);
+		if changes.is_empty() {
+			continue;
+		}
+
+		let asset_path = asset_server
+			.get_path(id)
+			.map_or_else(|| format!("{:?}", id), |path| path.to_string());
+		let mut summary = format!("{} reloaded", asset_path);
+		for (param, old, new) in changes {
+			info!("{}: {} {} -> {}", asset_path, param, old, new);
+			summary.push_str(&format!("\n{} {} \u{2192} {}", param, old, new));
+			history.changes.push(TuningChange {
+				elapsed_secs: clock.elapsed_secs(),
+				frame: clock.frame(),
+				asset: asset_path.clone(),
+				param,
+				old,
+				new,
+			});
+		}
+
+		// only the latest reload is shown
+		for overlay in &overlays {
+			commands.entity(overlay).despawn_recursive();
+		}
+		commands.spawn((
+			TuningDiffOverlay,
+			Text::new(summary),
+			TextFont {
+				font_size: 14.0,
+				..default()
+			},
+			Node {
+				position_type: PositionType::Absolute,
+				bottom: Val::Px(20.0),
+				right: Val::Px(20.0),
+				padding: UiRect::all(Val::Px(8.0)),
+				..default()
+			},
+			BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+			Lifetime(OVERLAY_LIFETIME),
+		));
+	}
+}
+
+/// Debug shortcut that exports the [TuningHistory] to a CSV file on F8
+pub fn export_tuning_history(kb: Res<ButtonInput<KeyCode>>, history: Res<TuningHistory>) {
+	if !kb.just_pressed(KeyCode::F8) {
+		return;
+	}
+	match history.export() {
+		Ok(()) => info!("exported {} tuning changes to {}", history.changes.len(), HISTORY_PATH),
+		Err(err) => warn!("failed to export tuning history: {}", err),
+	}
+}
@@ -0,0 +1,134 @@
+use crate::player::{Player, PlayerControlParams, PlayerControlState, PlayerSlot};
+use crate::util::{GameClock, Side, WallFrame};
+use bevy::prelude::*;
+use std::fmt::Debug;
+use std::fs::OpenOptions;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::Write;
+use std::path::PathBuf;
+
+const CSV_HEADER: &str =
+	"params_id,episode,frame,game_frame,left_hits,right_hits,horizontal_input,vertical_input,airborne,vx,vy,wall_state";
+
+/// Logs every frame of each wall interaction "episode" (from first touching a wall until letting go)
+/// to a CSV file, so the wall tuning parameters can be analyzed offline.
+///
+/// Enabled by running the game with `--export-wall-frames <path>`. Rows are appended to the file,
+/// each tagged with an id derived from the player's params, so sessions played with different
+/// revisions of `player.ron` can be collected in one file and told apart.
+#[derive(Resource, Debug, Default)]
+pub struct WallFrameExport {
+	path: Option<PathBuf>,
+	/// Rows of the episode in progress, written out once it ends
+	episode: Vec<String>,
+	episode_count: usize,
+}
+
+impl WallFrameExport {
+	/// Reads the export path (if any) from the command line
+	pub fn from_args() -> Self {
+		let path = std::env::args()
+			.skip_while(|arg| arg != "--export-wall-frames")
+			.nth(1)
+			.map(PathBuf::from);
+		if let Some(path) = &path {
+			info!("exporting wall frame data to {:?}", path);
+		}
+		WallFrameExport { path, ..default() }
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> {
+		let Some(path) = &self.path else {
+			return Ok(());
+		};
+		let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+		if file.metadata()?.len() == 0 {
+			writeln!(file, "{}", CSV_HEADER)?;
+		}
+		for row in self.episode.drain(..) {
+			writeln!(file, "{}", row)?;
+		}
+		Ok(())
+	}
+}
+
+/// Identifies a revision of the player's params. Not stable across builds, but good enough to
+/// group the rows from one play session against those from another.
+fn params_id(params: &PlayerControlParams) -> String {
+	let mut hasher = DefaultHasher::new();
+	format!("{:?}", params).hash(&mut hasher);
+	format!("{:016x}", hasher.finish())
+}
+
+/// Missing values are written as empty columns
+fn optional<T: Debug>(value: Option<T>) -> String {
+	value.map_or(String::new(), |v| format!("{:?}", v))
+}
+
+fn csv_row(
+	params_id: &str,
+	episode: usize,
+	frame: usize,
+	game_frame: usize,
+	wall: &WallFrame,
+	velocity: Vec2,
+) -> String {
+	format!(
+		"{},{},{},{},{:04b},{:04b},{},{},{},{},{},{}",
+		params_id,
+		episode,
+		frame,
+		game_frame,
+		wall.hit_patterns[Side::Left],
+		wall.hit_patterns[Side::Right],
+		optional(wall.horizontal_input),
+		optional(wall.vertical_input),
+		wall.airborne,
+		velocity.x,
+		velocity.y,
+		optional(wall.state),
+	)
+}
+
+/// Records the latest [WallFrame] of the player while they're interacting with a wall, and writes out
+/// the episode once they let go. Should run after `player_system`.
+pub fn export_wall_frames(
+	mut export: ResMut<WallFrameExport>,
+	players: Query<(&Player, &PlayerSlot, &PlayerControlState)>,
+	control_params: Res<Assets<PlayerControlParams>>,
+	clock: Res<GameClock>,
+) {
+	if export.path.is_none() {
+		return;
+	}
+	// only the lead player's wall interactions are logged, so episodes from different players don't mix
+	let Some((params, player)) = players
+		.iter()
+		.find(|(_, slot, _)| **slot == PlayerSlot::LEAD)
+		.and_then(|(p, _, player)| control_params.get(p.0.id()).map(|params| (params, player)))
+	else {
+		return;
+	};
+
+	let wall = &player.wall_frame;
+	// the frame where the player lets go ends the episode, but is still worth recording
+	if wall.state.is_none() && export.episode.is_empty() {
+		return;
+	}
+	let row = csv_row(
+		&params_id(params),
+		export.episode_count,
+		export.episode.len(),
+		clock.frame().0,
+		wall,
+		player.previous_total_velocity,
+	);
+	export.episode.push(row);
+
+	if wall.state.is_none() {
+		if let Err(err) = export.flush() {
+			warn!("failed to export wall frame data: {}", err);
+		}
+		export.episode_count += 1;
+	}
+}
@@ -0,0 +1,249 @@
+use crate::platforms::PLAYER_GROUP;
+use crate::player::{Player, PlayerControlState};
+use crate::util::{FrameCount, GameClock, ImpulseBroadcast, Kickable, Lifetime, SeededRng};
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
+
+/// Lightweight physics props that exist purely for juice. They get kicked around when the
+/// player runs past, and rattle (sound and particles) when they hit something hard enough.
+///
+/// Props don't collide with the player, so they can never get in the way.
+#[derive(Component, Debug)]
+pub struct AmbientProp {
+	pub kind: AmbientPropKind,
+	/// Played (spatially) when the prop hits something at or above `impact_speed`
+	pub impact_sound: Option<Handle<AudioSource>>,
+	pub impact_speed: f32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+pub enum AmbientPropKind {
+	Can,
+	Leaf,
+	/// One link of a chain hanging from an anchor point
+	ChainLink,
+}
+
+impl AmbientPropKind {
+	fn half_extents(self) -> Vec2 {
+		match self {
+			AmbientPropKind::Can => Vec2::new(0.4, 0.6),
+			AmbientPropKind::Leaf => Vec2::new(0.5, 0.08),
+			AmbientPropKind::ChainLink => Vec2::new(0.15, 0.5),
+		}
+	}
+
+	fn color(self) -> Color {
+		match self {
+			AmbientPropKind::Can => Color::srgb(0.75, 0.2, 0.2),
+			AmbientPropKind::Leaf => Color::srgb(0.85, 0.55, 0.15),
+			AmbientPropKind::ChainLink => Color::srgb(0.45, 0.45, 0.5),
+		}
+	}
+
+	/// Slowest impact that still makes a rattle
+	fn impact_speed(self) -> f32 {
+		match self {
+			AmbientPropKind::Can => 8.0,
+			AmbientPropKind::Leaf => f32::INFINITY,
+			AmbientPropKind::ChainLink => 12.0,
+		}
+	}
+}
+
+/// Describes a single ambient prop; see [AmbientPropArgs::spawn_chain] for chains
+pub struct AmbientPropArgs {
+	pub kind: AmbientPropKind,
+	pub pos: Vec2,
+	pub impact_sound: Option<Handle<AudioSource>>,
+}
+
+impl AmbientPropArgs {
+	fn prop_bundle(kind: AmbientPropKind, pos: Vec2, impact_sound: Option<Handle<AudioSource>>) -> impl Bundle {
+		let half_extents = kind.half_extents();
+		(
+			AmbientProp {
+				kind,
+				impact_sound,
+				impact_speed: kind.impact_speed(),
+			},
+			Kickable,
+			RigidBody::Dynamic,
+			Sprite::from_color(kind.color(), half_extents * 2.0),
+			Collider::cuboid(half_extents.x, half_extents.y),
+			CollisionGroups::new(Group::GROUP_2, Group::ALL - PLAYER_GROUP),
+			ActiveEvents::COLLISION_EVENTS,
+			Transform::from_xyz(pos.x, pos.y, 0.1),
+			Velocity::default(),
+			ExternalImpulse::default(),
+			ExternalForce::default(),
+			ReadMassProperties::default(),
+		)
+	}
+
+	pub fn spawn(self, commands: &mut Commands) -> Entity {
+		let AmbientPropArgs {
+			kind,
+			pos,
+			impact_sound,
+		} = self;
+		match kind {
+			AmbientPropKind::Can => commands
+				.spawn((
+					Self::prop_bundle(kind, pos, impact_sound),
+					ColliderMassProperties::Density(0.5),
+					Restitution::coefficient(0.3),
+				))
+				.id(),
+			AmbientPropKind::Leaf => commands
+				.spawn((
+					Self::prop_bundle(kind, pos, impact_sound),
+					ColliderMassProperties::Density(0.1),
+					GravityScale(0.2),
+					Damping {
+						linear_damping: 3.0,
+						angular_damping: 2.0,
+					},
+				))
+				.id(),
+			AmbientPropKind::ChainLink => commands.spawn(Self::prop_bundle(kind, pos, impact_sound)).id(),
+		}
+	}
+
+	/// Spawns a chain of `links` hanging down from a fixed `anchor` point
+	pub fn spawn_chain(commands: &mut Commands, anchor: Vec2, links: usize, impact_sound: Option<Handle<AudioSource>>) {
+		let kind = AmbientPropKind::ChainLink;
+		let link_length = kind.half_extents().y * 2.0;
+		let mut parent = commands
+			.spawn((RigidBody::Fixed, Transform::from_xyz(anchor.x, anchor.y, 0.1)))
+			.id();
+		let mut parent_anchor = Vec2::ZERO;
+		for i in 0..links {
+			let link_pos = anchor - Vec2::Y * link_length * (i as f32 + 0.5);
+			let joint = RevoluteJointBuilder::new()
+				.local_anchor1(parent_anchor)
+				.local_anchor2(Vec2::Y * link_length * 0.5)
+				// neighboring links overlap at the joint, and shouldn't rattle against each other
+				.contacts_enabled(false);
+			parent = commands
+				.spawn((
+					Self::prop_bundle(kind, link_pos, impact_sound.clone()),
+					ImpulseJoint::new(parent, joint),
+				))
+				.id();
+			parent_anchor = -Vec2::Y * link_length * 0.5;
+		}
+	}
+}
+
+/// How much props get kicked around by the player running past them
+#[derive(Resource, Debug)]
+pub struct AmbientPropKick {
+	pub radius: f32,
+	/// Change in velocity given to a prop right at the player's feet, at `full_speed`
+	pub strength: f32,
+	pub upward_bias: f32,
+	/// The player has to be running at least this fast to kick anything
+	pub min_speed: f32,
+	/// Speed at which the kick reaches its full `strength`
+	pub full_speed: f32,
+}
+
+impl Default for AmbientPropKick {
+	fn default() -> Self {
+		AmbientPropKick {
+			radius: 3.0,
+			strength: 1.5,
+			upward_bias: 1.0,
+			min_speed: 15.0,
+			full_speed: 40.0,
+		}
+	}
+}
+
+/// Broadcasts a small impulse from the feet of each player running along the ground
+pub fn kick_ambient_props(
+	kick: Res<AmbientPropKick>,
+	players: Query<(&PlayerControlState, &Transform, &Collider), With<Player>>,
+	mut broadcasts: EventWriter<ImpulseBroadcast>,
+) {
+	for (player, transform, collider) in &players {
+		let speed = player.previous_total_velocity.x.abs();
+		if !player.grounded.is_set() || speed < kick.min_speed {
+			continue;
+		}
+		let half_height = collider.as_cuboid().map_or(0.0, |c| c.half_extents().y);
+		let intensity = (speed / kick.full_speed).min(1.0);
+		broadcasts.send(ImpulseBroadcast {
+			origin: transform.translation.truncate() - Vec2::Y * half_height,
+			radius: kick.radius,
+			strength: kick.strength * intensity,
+			upward_bias: kick.upward_bias,
+		});
+	}
+}
+
+/// A speck thrown off by a prop's impact. Falls under gravity until its [Lifetime] runs out.
+#[derive(Component, Debug)]
+pub struct ImpactParticle {
+	velocity: Vec2,
+}
+
+impl ImpactParticle {
+	pub fn new(velocity: Vec2) -> Self {
+		ImpactParticle { velocity }
+	}
+}
+
+/// Plays a rattle and throws off some particles whenever a prop hits something fast enough
+pub fn ambient_prop_impacts(
+	mut commands: Commands,
+	mut collisions: EventReader<CollisionEvent>,
+	props: Query<(&AmbientProp, &Velocity, &Transform, &Sprite)>,
+) {
+	for event in collisions.read() {
+		let CollisionEvent::Started(a, b, _) = event else {
+			continue;
+		};
+		for entity in [*a, *b] {
+			let Ok((prop, velocity, transform, sprite)) = props.get(entity) else {
+				continue;
+			};
+			let speed = velocity.linvel.length();
+			if speed < prop.impact_speed {
+				continue;
+			}
+			let position = transform.translation.truncate();
+
+			if let Some(sound) = &prop.impact_sound {
+				commands.spawn((
+					AudioPlayer::<AudioSource>(sound.clone()),
+					PlaybackSettings::DESPAWN
+						.with_spatial(true)
+						.with_volume(Volume::new((speed / 40.0).min(1.0))),
+					Transform::from_translation(position.extend(0.0)),
+				));
+			}
+
+			let mut rng = SeededRng::new(entity.to_bits() ^ speed.to_bits() as u64);
+			for _ in 0..4 {
+				let velocity = Vec2::new(rng.range(-8.0, 8.0), rng.range(4.0, 12.0));
+				commands.spawn((
+					ImpactParticle { velocity },
+					Lifetime(FrameCount(20)),
+					Sprite::from_color(sprite.color, Vec2::splat(0.25)),
+					Transform::from_translation(position.extend(0.2)),
+				));
+			}
+		}
+	}
+}
+
+pub fn move_impact_particles(mut particles: Query<(&mut ImpactParticle, &mut Transform)>, clock: Res<GameClock>) {
+	for (mut particle, mut transform) in &mut particles {
+		particle.velocity.y -= 40.0 * clock.delta_secs();
+		transform.translation += (particle.velocity * clock.delta_secs()).extend(0.0);
+	}
+}
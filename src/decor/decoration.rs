@@ -0,0 +1,161 @@
+use crate::Platform;
+use crate::util::SeededRng;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{Collider, RigidBody};
+
+/// Purely visual props. Decorations never get colliders, so physics ignores them entirely.
+#[derive(Component, Debug, Copy, Clone)]
+pub struct Decoration(pub DecorationKind);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DecorationKind {
+	GrassTuft,
+	Rock,
+	Pipe,
+}
+
+/// Which side of the gameplay layer (z = 0) a decoration is drawn on
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DecorationLayer {
+	Background,
+	Foreground,
+}
+
+impl DecorationLayer {
+	fn z(self) -> f32 {
+		match self {
+			DecorationLayer::Background => -0.5,
+			DecorationLayer::Foreground => 0.8,
+		}
+	}
+}
+
+impl DecorationKind {
+	fn size(self) -> Vec2 {
+		match self {
+			DecorationKind::GrassTuft => Vec2::new(1.5, 1.0),
+			DecorationKind::Rock => Vec2::new(2.0, 1.2),
+			DecorationKind::Pipe => Vec2::new(1.5, 8.0),
+		}
+	}
+
+	fn color(self) -> Color {
+		match self {
+			DecorationKind::GrassTuft => Color::srgb(0.3, 0.9, 0.35),
+			DecorationKind::Rock => Color::srgb(0.5, 0.5, 0.55),
+			DecorationKind::Pipe => Color::srgb(0.35, 0.4, 0.45),
+		}
+	}
+}
+
+/// Marks a platform that shouldn't get any scattered decorations, e.g. the ceiling
+#[derive(Component)]
+pub struct NoDecorations;
+
+/// Controls the procedural scattering of decorations along the tops of platforms
+#[derive(Resource, Debug)]
+pub struct DecorationScatter {
+	pub seed: u64,
+	/// Average number of decorations per unit of platform width
+	pub density: f32,
+	/// Which kinds of decoration to choose from
+	pub kinds: Vec<DecorationKind>,
+}
+
+impl Default for DecorationScatter {
+	fn default() -> Self {
+		DecorationScatter {
+			seed: 1,
+			density: 0.15,
+			kinds: vec![
+				DecorationKind::GrassTuft,
+				DecorationKind::GrassTuft,
+				DecorationKind::Rock,
+			],
+		}
+	}
+}
+
+/// Describes a decoration placed explicitly at a specific position, where `pos` is
+/// the bottom-center of the decoration (i.e. the point it "sits" on)
+pub struct DecorationArgs {
+	pub kind: DecorationKind,
+	pub pos: Vec2,
+	pub layer: DecorationLayer,
+}
+
+impl DecorationArgs {
+	pub fn bundle(self) -> impl Bundle {
+		let DecorationArgs { kind, pos, layer } = self;
+		let size = kind.size();
+		(
+			Decoration(kind),
+			Sprite::from_color(kind.color(), size),
+			Transform::from_xyz(pos.x, pos.y + size.y * 0.5, layer.z()),
+		)
+	}
+
+	pub fn spawn(self, commands: &mut Commands) {
+		commands.spawn(self.bundle());
+	}
+}
+
+/// Scatters decorations along the top surface of each (fixed, horizontal) platform, whenever
+/// a platform is spawned or resized. Decorations are spawned as children of their platform,
+/// so they disappear along with it.
+///
+/// Each platform's decorations are seeded from both the global seed and the platform's
+/// position, so that adding a new platform doesn't reshuffle the decorations on others.
+pub fn scatter_decorations(
+	mut commands: Commands,
+	scatter: Res<DecorationScatter>,
+	platforms: Query<
+		(Entity, &Transform, &Collider, &RigidBody),
+		(
+			With<Platform>,
+			Without<NoDecorations>,
+			Or<(Added<Platform>, Changed<Collider>)>,
+		),
+	>,
+) {
+	if scatter.kinds.is_empty() || scatter.density <= 0.0 {
+		return;
+	}
+	for (entity, transform, collider, body) in &platforms {
+		let Some(half_extents) = collider.as_cuboid().map(|c| c.half_extents()) else {
+			continue;
+		};
+		// only decorate static floors, not walls or moving platforms
+		if *body != RigidBody::Fixed || half_extents.x <= half_extents.y {
+			continue;
+		}
+
+		let center = transform.translation.truncate();
+		let position_hash = (center.x.to_bits() as u64) << 32 | center.y.to_bits() as u64;
+		let mut rng = SeededRng::new(scatter.seed ^ position_hash);
+
+		let width = half_extents.x * 2.0;
+		let count = (width * scatter.density * rng.range(0.5, 1.5)).round() as usize;
+		commands.entity(entity).with_children(|parent| {
+			for _ in 0..count {
+				let kind = *rng.pick(&scatter.kinds);
+				let margin = kind.size().x * 0.5;
+				// positions are relative to the platform's center
+				let x = rng.range(-half_extents.x + margin, half_extents.x - margin);
+				let layer = if rng.next_f32() < 0.25 {
+					DecorationLayer::Foreground
+				} else {
+					DecorationLayer::Background
+				};
+				parent.spawn(
+					DecorationArgs {
+						kind,
+						pos: Vec2::new(x, half_extents.y),
+						layer,
+					}
+					.bundle(),
+				);
+			}
+		});
+	}
+}
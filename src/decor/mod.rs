@@ -0,0 +1,5 @@
+mod ambient_props;
+mod decoration;
+
+pub use ambient_props::*;
+pub use decoration::*;
@@ -0,0 +1,50 @@
+use crate::enemies::{AttackParams, PerceptionParams};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Tuning shared by every enemy of a kind, loaded from a `.enemy.ron` file
+#[derive(Asset, Clone, Debug, Deserialize, TypePath)]
+#[serde(deny_unknown_fields)]
+pub struct EnemyArchetype {
+	pub perception: PerceptionParams,
+	/// How the enemy attacks players it's chasing. Enemies without one only keep watch.
+	#[serde(default)]
+	pub attack: Option<AttackParams>,
+}
+
+#[derive(Default)]
+pub struct EnemyArchetypeLoader;
+
+#[derive(Debug, Error)]
+pub enum EnemyArchetypeLoaderError {
+	#[error("Could not load asset: {0}")]
+	Io(#[from] std::io::Error),
+
+	#[error("Could not parse RON: {0}")]
+	Ron(#[from] ron::de::SpannedError),
+}
+impl AssetLoader for EnemyArchetypeLoader {
+	type Asset = EnemyArchetype;
+	type Settings = ();
+	type Error = EnemyArchetypeLoaderError;
+
+	async fn load(
+		&self,
+		reader: &mut dyn Reader,
+		_settings: &Self::Settings,
+		_load_context: &mut LoadContext<'_>,
+	) -> Result<Self::Asset, Self::Error> {
+		let mut bytes = Vec::new();
+		reader.read_to_end(&mut bytes).await?;
+		let archetype = ron::de::from_bytes::<EnemyArchetype>(&bytes)?;
+		Ok(archetype)
+	}
+
+	fn extensions(&self) -> &[&str] {
+		// more specific than the "ron" claimed by the player loader
+		&["enemy.ron"]
+	}
+}
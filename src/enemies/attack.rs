@@ -0,0 +1,106 @@
+use crate::enemies::{AlertState, Enemy, EnemyArchetype};
+use crate::hazards::{PlayerDamagedEvent, Telegraph, TelegraphShape, collider_area};
+use crate::level::RoomInactive;
+use crate::player::{KnockbackEvent, Player, PlayerControlState, TutorialGhost};
+use crate::util::{FrameCount, Side};
+use bevy::prelude::*;
+use bevy_rapier2d::geometry::Collider;
+use serde::Deserialize;
+
+/// How an enemy attacks a player it's chasing: by striking an area in front of it,
+/// after a wind-up that gives the player a chance to get out of the way
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AttackParams {
+	/// Offset from the enemy's center to the center of the area it strikes, with `x` mirrored when facing left
+	pub offset: Vec2,
+	pub half_extents: Vec2,
+	/// How long the attack is telegraphed before it strikes
+	pub windup: FrameCount,
+	/// How long the attack keeps hurting players who are in its area
+	pub active_duration: FrameCount,
+	pub damage: u32,
+	/// Initial speed of the knockback
+	pub knockback: f32,
+}
+
+impl AttackParams {
+	fn shape(&self, facing: Side) -> TelegraphShape {
+		TelegraphShape::Rect {
+			offset: Vec2::new(self.offset.x * facing, self.offset.y),
+			half_extents: self.half_extents,
+		}
+	}
+}
+
+const ATTACK_COLOR: Color = Color::srgb(1.0, 0.3, 0.2);
+
+/// Has chasing enemies wind up an attack when a player is within its area, and hurts the players
+/// caught in the area once it strikes. The attack is [Telegraph]ed, so it's only dangerous once
+/// the wind-up is over; a player who gets out of the area in time is safe.
+///
+/// Should run after `perception_system`, so it acts on the enemies' latest [AlertState].
+pub fn enemy_attack_system(
+	mut commands: Commands,
+	mut enemies: Query<(Entity, &Enemy, &AlertState, &Transform, Option<&mut Telegraph>), Without<RoomInactive>>,
+	players: Query<(Entity, &PlayerControlState, &Transform, &Collider), (With<Player>, Without<TutorialGhost>)>,
+	archetypes: Res<Assets<EnemyArchetype>>,
+	mut damaged_events: EventWriter<PlayerDamagedEvent>,
+	mut knockback_events: EventWriter<KnockbackEvent>,
+) {
+	for (enemy_entity, enemy, alert_state, transform, telegraph) in &mut enemies {
+		let Some(attack) = archetypes
+			.get(enemy.archetype.id())
+			.and_then(|archetype| archetype.attack)
+		else {
+			continue;
+		};
+		let Some(mut telegraph) = telegraph else {
+			// the attack's timing comes from the archetype, which has only just loaded
+			commands.entity(enemy_entity).insert(Telegraph::new(
+				attack.shape(enemy.facing),
+				attack.windup,
+				attack.active_duration,
+				ATTACK_COLOR,
+			));
+			continue;
+		};
+		telegraph.shape = attack.shape(enemy.facing);
+		telegraph.windup = attack.windup;
+		telegraph.active_duration = attack.active_duration;
+
+		let enemy_position = transform.translation.truncate();
+		let offset = Vec2::new(attack.offset.x * enemy.facing, attack.offset.y);
+		let area = Rect::from_center_half_size(enemy_position + offset, attack.half_extents);
+		let in_area = players.iter().filter(|(_, _, transform, collider)| {
+			collider_area(transform, collider).is_some_and(|player_area| !player_area.intersect(area).is_empty())
+		});
+
+		if !telegraph.is_active() {
+			if matches!(alert_state, AlertState::Chasing { .. }) && in_area.count() > 0 {
+				telegraph.trigger();
+			}
+			continue;
+		}
+		for (player_entity, state, player_transform, _) in in_area {
+			if !state.invulnerability.is_ready() {
+				continue;
+			}
+			// away from the enemy
+			let direction = (player_transform.translation.truncate() - enemy_position).normalize_or(Vec2::Y);
+			damaged_events.send(PlayerDamagedEvent {
+				player: player_entity,
+				source: enemy_entity,
+				damage: attack.damage,
+				direction,
+			});
+			if attack.knockback > 0.0 {
+				knockback_events.send(KnockbackEvent {
+					player: player_entity,
+					direction,
+					strength: attack.knockback,
+				});
+			}
+		}
+	}
+}
@@ -0,0 +1,18 @@
+mod archetype;
+mod attack;
+mod perception;
+
+use crate::util::Side;
+use bevy::asset::Handle;
+use bevy::prelude::Component;
+pub use archetype::*;
+pub use attack::*;
+pub use perception::*;
+
+#[derive(Component, Debug)]
+#[require(AlertState)]
+pub struct Enemy {
+	pub archetype: Handle<EnemyArchetype>,
+	/// The direction the enemy is looking in
+	pub facing: Side,
+}
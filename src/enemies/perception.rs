@@ -0,0 +1,209 @@
+use crate::audio::MusicIntensity;
+use crate::enemies::{Enemy, EnemyArchetype};
+use crate::level::RoomInactive;
+use crate::physics::{Physics, PhysicsWorld, QueryScope};
+use crate::player::{Player, PlayerControlState, TutorialGhost};
+use crate::util::FrameCount;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// How an enemy notices the player
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PerceptionParams {
+	/// Offset from the enemy's center to where it looks from, with `x` mirrored when facing left
+	pub eye_offset: Vec2,
+	/// How far the vision cone reaches
+	pub view_distance: f32,
+	/// Half of the vision cone's angle, in degrees
+	pub view_half_angle: f32,
+	/// Number of rays spread across the vision cone. More rays means fewer gaps for the player to hide in.
+	pub view_rays: usize,
+	/// The player can be heard (regardless of walls) within this distance, if moving fast enough
+	pub hearing_radius: f32,
+	/// The player is only heard while moving at least this fast
+	pub hearing_min_speed: f32,
+	/// How long the player has to stay in view before a suspicious enemy starts chasing
+	pub notice_time: FrameCount,
+	/// How long an enemy goes without seeing or hearing the player before it calms down a level
+	pub calm_down_time: FrameCount,
+}
+
+/// What an enemy thinks is going on
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum AlertState {
+	#[default]
+	Idle,
+	/// Saw or heard something. Becomes `Chasing` if the player stays in view long enough.
+	Suspicious {
+		seen_for: FrameCount,
+		unnoticed_for: FrameCount,
+	},
+	/// Knows where the player is
+	Chasing { unseen_for: FrameCount },
+}
+
+impl AlertState {
+	/// Advance the state by one frame, given whether the player was seen or heard during it
+	fn tick(&mut self, seen: bool, heard: bool, params: &PerceptionParams) {
+		*self = match *self {
+			AlertState::Idle if seen || heard => AlertState::Suspicious {
+				seen_for: FrameCount(0),
+				unnoticed_for: FrameCount(0),
+			},
+			AlertState::Idle => AlertState::Idle,
+			AlertState::Suspicious {
+				mut seen_for,
+				mut unnoticed_for,
+			} => {
+				if seen {
+					seen_for.increment();
+				}
+				if seen || heard {
+					unnoticed_for.reset();
+				} else {
+					unnoticed_for.increment();
+				}
+				if seen_for >= params.notice_time {
+					AlertState::Chasing {
+						unseen_for: FrameCount(0),
+					}
+				} else if unnoticed_for >= params.calm_down_time {
+					AlertState::Idle
+				} else {
+					AlertState::Suspicious {
+						seen_for,
+						unnoticed_for,
+					}
+				}
+			}
+			AlertState::Chasing { mut unseen_for } => {
+				// hearing the player is enough to keep chasing, but not to start
+				if seen || heard {
+					unseen_for.reset();
+				} else {
+					unseen_for.increment();
+				}
+				if unseen_for >= params.calm_down_time {
+					AlertState::Suspicious {
+						seen_for: FrameCount(0),
+						unnoticed_for: FrameCount(0),
+					}
+				} else {
+					AlertState::Chasing { unseen_for }
+				}
+			}
+		};
+	}
+}
+
+/// Marks the text shown above an enemy to indicate its [AlertState]
+#[derive(Component, Debug)]
+pub struct AlertIndicator;
+
+/// Updates each enemy's [AlertState] by checking whether any player is within its vision cone
+/// (and not hidden behind anything), or close enough to be heard
+pub fn perception_system(
+	mut enemies: Query<(Entity, &Enemy, &mut AlertState, &Transform), Without<RoomInactive>>,
+	players: Query<(&PlayerControlState, &Transform), (With<Player>, Without<TutorialGhost>)>,
+	archetypes: Res<Assets<EnemyArchetype>>,
+	physics: Physics,
+	mut gizmos: Gizmos,
+) {
+	let physics = physics.world();
+	if players.is_empty() {
+		return;
+	}
+
+	for (enemy_entity, enemy, mut alert_state, transform) in &mut enemies {
+		let Some(params) = archetypes.get(enemy.archetype.id()).map(|a| a.perception) else {
+			continue;
+		};
+		let eye = transform.translation.truncate() + Vec2::new(params.eye_offset.x * enemy.facing, params.eye_offset.y);
+		let forward = Vec2::X * enemy.facing;
+
+		// cast a fan of rays; a player is seen if any ray hits them before hitting anything else
+		let half_angle = params.view_half_angle.to_radians();
+		let seen = (0..params.view_rays).any(|i| {
+			let t = if params.view_rays > 1 {
+				i as f32 / (params.view_rays - 1) as f32
+			} else {
+				0.5
+			};
+			let direction = Vec2::from_angle((t * 2.0 - 1.0) * half_angle).rotate(forward);
+			let hit = physics.cast_ray(
+				eye,
+				direction,
+				params.view_distance,
+				QueryScope::solid().excluding(enemy_entity),
+			);
+			let sees_player = hit.is_some_and(|hit| players.contains(hit.entity));
+			let ray_length = hit.map_or(params.view_distance, |hit| hit.distance);
+			let color = if sees_player {
+				Color::srgba(1.0, 0.2, 0.2, 0.5)
+			} else {
+				Color::srgba(1.0, 1.0, 0.5, 0.15)
+			};
+			gizmos.ray_2d(eye, direction * ray_length, color);
+			sees_player
+		});
+
+		let heard = players.iter().any(|(player, player_transform)| {
+			eye.distance(player_transform.translation.truncate()) <= params.hearing_radius
+				&& player.previous_total_velocity.length() >= params.hearing_min_speed
+		});
+
+		let previous = *alert_state;
+		alert_state.tick(seen, heard, &params);
+		if std::mem::discriminant(&previous) != std::mem::discriminant(&*alert_state) {
+			debug!("enemy {:?} is now {:?}", enemy_entity, *alert_state);
+		}
+	}
+}
+
+/// Shows `?` above suspicious enemies and `!` above chasing ones
+pub fn alert_indicator_system(
+	enemies: Query<(&AlertState, &Children), Changed<AlertState>>,
+	mut indicators: Query<(&mut Text2d, &mut TextColor), With<AlertIndicator>>,
+) {
+	for (alert_state, children) in &enemies {
+		let (text, color) = match alert_state {
+			AlertState::Idle => ("", Color::NONE),
+			AlertState::Suspicious { .. } => ("?", Color::srgb(1.0, 0.9, 0.2)),
+			AlertState::Chasing { .. } => ("!", Color::srgb(1.0, 0.2, 0.2)),
+		};
+		let mut children = indicators.iter_many_mut(children);
+		while let Some((mut indicator_text, mut indicator_color)) = children.fetch_next() {
+			indicator_text.0 = text.to_string();
+			indicator_color.0 = color;
+		}
+	}
+}
+
+/// Alerted enemies within this distance of the player raise the [MusicIntensity]
+const ALERT_MUSIC_RADIUS: f32 = 40.0;
+
+/// Raises the music's intensity as alerted enemies get closer to the nearest player.
+/// Chasing enemies count fully, and suspicious ones count for half.
+pub fn alert_music_intensity(
+	enemies: Query<(&AlertState, &Transform), (With<Enemy>, Without<RoomInactive>)>,
+	players: Query<&Transform, (With<Player>, Without<TutorialGhost>)>,
+	mut intensity: ResMut<MusicIntensity>,
+) {
+	intensity.enemy_proximity = enemies
+		.iter()
+		.map(|(alert_state, transform)| {
+			let weight = match alert_state {
+				AlertState::Idle => 0.0,
+				AlertState::Suspicious { .. } => 0.5,
+				AlertState::Chasing { .. } => 1.0,
+			};
+			let position = transform.translation.truncate();
+			let distance = players
+				.iter()
+				.map(|player_transform| position.distance(player_transform.translation.truncate()))
+				.fold(f32::INFINITY, f32::min);
+			weight * (1.0 - distance / ALERT_MUSIC_RADIUS).max(0.0)
+		})
+		.fold(0.0, f32::max);
+}
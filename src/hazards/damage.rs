@@ -0,0 +1,95 @@
+use crate::level::RoomInactive;
+use crate::player::{KnockbackEvent, Player, PlayerControlState, TutorialGhost};
+use bevy::prelude::*;
+use bevy_rapier2d::geometry::Collider;
+use serde::Deserialize;
+
+/// How close the player has to come to a hazard for it to hurt them. Solid hazards (like spikes)
+/// only ever touch the player's collider, rather than overlapping it.
+const CONTACT_MARGIN: f32 = 0.1;
+
+/// Something that hurts the player when they touch it, and throws them back away from it.
+/// Hazards can be solid, like a bed of spikes, or sensors, like a pool of lava.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Hazard {
+	pub damage: u32,
+	/// Initial speed of the knockback
+	pub knockback: f32,
+}
+
+impl Default for Hazard {
+	fn default() -> Self {
+		Hazard {
+			damage: 1,
+			knockback: 40.0,
+		}
+	}
+}
+
+/// Sent when the player gets hurt, which costs them [Health](crate::player::Health)
+#[derive(Event, Debug, Copy, Clone)]
+pub struct PlayerDamagedEvent {
+	pub player: Entity,
+	/// Whatever hurt the player
+	pub source: Entity,
+	pub damage: u32,
+	/// Which way the player was knocked back
+	pub direction: Vec2,
+}
+
+/// The area covered by a box- or ball-shaped collider with the given `transform`
+pub(crate) fn collider_area(transform: &Transform, collider: &Collider) -> Option<Rect> {
+	let half_extents = collider
+		.as_cuboid()
+		.map(|cuboid| cuboid.half_extents())
+		.or_else(|| collider.as_ball().map(|ball| Vec2::splat(ball.radius())))?;
+	Some(Rect::from_center_half_size(
+		transform.translation.truncate(),
+		half_extents,
+	))
+}
+
+/// Hurts players touching a [Hazard], sending a [PlayerDamagedEvent] and knocking them back away from
+/// the hazard. Players can't be hurt again until their `invulnerability` from the last hit wears off.
+///
+/// Should run after `player_system`, so that the players have been moved into (or out of) contact.
+pub fn hazard_contact_system(
+	hazards: Query<(Entity, &Hazard, &Transform, &Collider), (Without<Player>, Without<RoomInactive>)>,
+	players: Query<(Entity, &PlayerControlState, &Transform, &Collider), (With<Player>, Without<TutorialGhost>)>,
+	mut damaged_events: EventWriter<PlayerDamagedEvent>,
+	mut knockback_events: EventWriter<KnockbackEvent>,
+) {
+	for (player_entity, state, transform, collider) in &players {
+		if !state.invulnerability.is_ready() {
+			continue;
+		}
+		let Some(player_area) = collider_area(transform, collider).map(|area| area.inflate(CONTACT_MARGIN)) else {
+			continue;
+		};
+		let touching = hazards.iter().find_map(|(entity, hazard, transform, collider)| {
+			let area = collider_area(transform, collider)?;
+			(!area.intersect(player_area).is_empty()).then_some((entity, *hazard, area))
+		});
+		let Some((source, hazard, area)) = touching else {
+			continue;
+		};
+
+		// away from the nearest part of the hazard, e.g. straight up off the top of a bed of spikes
+		let center = player_area.center();
+		let direction = (center - center.clamp(area.min, area.max)).normalize_or(Vec2::Y);
+		damaged_events.send(PlayerDamagedEvent {
+			player: player_entity,
+			source,
+			damage: hazard.damage,
+			direction,
+		});
+		if hazard.knockback > 0.0 {
+			knockback_events.send(KnockbackEvent {
+				player: player_entity,
+				direction,
+				strength: hazard.knockback,
+			});
+		}
+	}
+}
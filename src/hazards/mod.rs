@@ -0,0 +1,5 @@
+mod damage;
+mod telegraph;
+
+pub use damage::*;
+pub use telegraph::*;
@@ -0,0 +1,130 @@
+use crate::level::RoomInactive;
+use crate::util::FrameCount;
+use bevy::prelude::*;
+
+/// The area affected by a [Telegraph]ed attack, relative to the attacker's position
+#[derive(Debug, Copy, Clone)]
+pub enum TelegraphShape {
+	Rect { offset: Vec2, half_extents: Vec2 },
+	Circle { offset: Vec2, radius: f32 },
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum TelegraphPhase {
+	#[default]
+	Idle,
+	/// The attack area is being flashed as a warning; the attack isn't dangerous yet
+	WindingUp(FrameCount),
+	/// The attack is happening
+	Active(FrameCount),
+}
+
+/// Warns the player about an upcoming attack (a crusher slam, a turret shot) by outlining
+/// its area for a wind-up period before it becomes active.
+///
+/// The attacker's behavior decides *when* to attack by calling [Telegraph::trigger], and
+/// checks [Telegraph::is_active] to decide whether the attack should actually deal damage.
+#[derive(Component, Debug)]
+pub struct Telegraph {
+	pub shape: TelegraphShape,
+	pub windup: FrameCount,
+	pub active_duration: FrameCount,
+	pub color: Color,
+	phase: TelegraphPhase,
+}
+
+/// Sent when a [Telegraph]'s wind-up finishes and its attack becomes active
+#[derive(Event, Debug)]
+pub struct TelegraphActivated(pub Entity);
+
+impl Telegraph {
+	pub fn new(shape: TelegraphShape, windup: FrameCount, active_duration: FrameCount, color: Color) -> Self {
+		Telegraph {
+			shape,
+			windup,
+			active_duration,
+			color,
+			phase: TelegraphPhase::Idle,
+		}
+	}
+
+	/// Start winding up the attack, unless it is already in progress
+	pub fn trigger(&mut self) {
+		if self.phase == TelegraphPhase::Idle {
+			self.phase = TelegraphPhase::WindingUp(FrameCount(0));
+		}
+	}
+
+	pub fn phase(&self) -> TelegraphPhase {
+		self.phase
+	}
+
+	pub fn is_active(&self) -> bool {
+		matches!(self.phase, TelegraphPhase::Active(_))
+	}
+
+	/// Advance the telegraph by one frame, returning `true` if the attack just became active
+	fn tick(&mut self) -> bool {
+		match &mut self.phase {
+			TelegraphPhase::Idle => false,
+			TelegraphPhase::WindingUp(elapsed) => {
+				elapsed.increment();
+				if *elapsed >= self.windup {
+					self.phase = TelegraphPhase::Active(FrameCount(0));
+					true
+				} else {
+					false
+				}
+			}
+			TelegraphPhase::Active(elapsed) => {
+				elapsed.increment();
+				if *elapsed >= self.active_duration {
+					self.phase = TelegraphPhase::Idle;
+				}
+				false
+			}
+		}
+	}
+}
+
+pub fn telegraph_system(
+	mut telegraphs: Query<(Entity, &mut Telegraph), Without<RoomInactive>>,
+	mut events: EventWriter<TelegraphActivated>,
+) {
+	for (entity, mut telegraph) in &mut telegraphs {
+		if telegraph.tick() {
+			events.send(TelegraphActivated(entity));
+		}
+	}
+}
+
+/// Outlines each telegraphed attack area. During the wind-up, the outline flashes faster
+/// as the attack gets closer; while the attack is active, the outline is drawn solid.
+pub fn draw_telegraphs(telegraphs: Query<(&Telegraph, &GlobalTransform)>, mut gizmos: Gizmos) {
+	for (telegraph, transform) in &telegraphs {
+		let color = match telegraph.phase {
+			TelegraphPhase::Idle => continue,
+			TelegraphPhase::WindingUp(elapsed) => {
+				let remaining = telegraph.windup.0.saturating_sub(elapsed.0);
+				// flash every 8 frames at first, down to every 2 frames right before the attack
+				let flash_period = (remaining / 4).clamp(2, 8);
+				if (elapsed.0 / flash_period) % 2 == 1 {
+					continue;
+				}
+				let ratio = elapsed.0 as f32 / telegraph.windup.0.max(1) as f32;
+				telegraph.color.with_alpha(0.3 + 0.7 * ratio)
+			}
+			TelegraphPhase::Active(_) => telegraph.color,
+		};
+
+		let center = transform.translation().truncate();
+		match telegraph.shape {
+			TelegraphShape::Rect { offset, half_extents } => {
+				gizmos.rect_2d(Isometry2d::from_translation(center + offset), half_extents * 2.0, color);
+			}
+			TelegraphShape::Circle { offset, radius } => {
+				gizmos.circle_2d(Isometry2d::from_translation(center + offset), radius, color);
+			}
+		}
+	}
+}
@@ -0,0 +1,267 @@
+use crate::decor::AmbientPropKind;
+use crate::hazards::Hazard;
+use crate::level::{CollectibleDef, GroupDef, LevelExit, PrefabDef, RankThresholds, RoomDef, SecretDef, SwitchDef};
+use crate::platforms::{
+	BreakableBlock, PathMode, Spring, StickyPlatform, SurfaceMaterial, WallMaterial, WallSurface, Zipline,
+};
+use crate::player::CrushResponse;
+use crate::util::FrameCount;
+use crate::zones::{GravityZone, TriggerZone, WaterVolume, WindZone};
+use bevy::math::{Rect, Vec2};
+use bevy::prelude::{Asset, EaseFunction, TypePath};
+use bevy::utils::HashSet;
+use serde::Deserialize;
+
+/// A level's geometry, loaded from a `.level.ron` file
+#[derive(Asset, Clone, Debug, Deserialize, TypePath)]
+#[serde(deny_unknown_fields)]
+pub struct LevelAsset {
+	pub platforms: Vec<PlatformDef>,
+	/// Copies of [Prefab](crate::level::Prefab)s, which are added to the `platforms` as the level loads
+	#[serde(default)]
+	pub prefabs: Vec<PrefabDef>,
+	#[serde(default)]
+	pub rooms: Vec<RoomDef>,
+	/// Sets of platforms that can be switched on and off, moved, or recolored together
+	#[serde(default)]
+	pub groups: Vec<GroupDef>,
+	/// Hidden areas, covered up until the player finds them
+	#[serde(default)]
+	pub secrets: Vec<SecretDef>,
+	/// What happens when the player gets squeezed between obstacles
+	#[serde(default)]
+	pub crush_response: CrushResponse,
+	/// If set, the camera pans around the level when it loads, before the player gets control
+	#[serde(default)]
+	pub intro_pan: Option<IntroPanDef>,
+	/// What it takes to earn each rank on the results screen. Levels without thresholds aren't ranked.
+	#[serde(default)]
+	pub ranks: Option<RankThresholds>,
+	/// Where the players start the level, if not where they were spawned
+	#[serde(default)]
+	pub player_start: Option<Vec2>,
+	/// Images drawn behind the level's platforms, e.g. the tile layers of an LDtk level
+	#[serde(default)]
+	pub tiles: Vec<TileDef>,
+	/// Areas that affect whatever is inside them, like water and checkpoints
+	#[serde(default)]
+	pub zones: Vec<ZoneDef>,
+	#[serde(default)]
+	pub ziplines: Vec<Zipline>,
+	/// Loose things lying around the level for the player to kick about
+	#[serde(default)]
+	pub props: Vec<PropDef>,
+	/// Coins and gems for the player to pick up
+	#[serde(default)]
+	pub collectibles: Vec<CollectibleDef>,
+	/// Pressure plates and the like, which switch groups of platforms on and off
+	#[serde(default)]
+	pub switches: Vec<SwitchDef>,
+	/// What plays while the level is being played. Levels without music keep playing the previous level's.
+	#[serde(default)]
+	pub music: Option<MusicDef>,
+}
+
+impl LevelAsset {
+	/// The smallest rectangle containing every platform in the level
+	pub fn bounds(&self) -> Option<Rect> {
+		self.platforms
+			.iter()
+			.map(|def| Rect::from_center_size(def.pos, def.size))
+			.reduce(|a, b| a.union(b))
+	}
+
+	/// The first platform id that's used by more than one platform, if any.
+	/// Prefabs only count once they've been turned into platforms.
+	pub fn duplicate_platform_id(&self) -> Option<&str> {
+		let mut ids = HashSet::new();
+		self.platforms
+			.iter()
+			.map(|platform| platform.id.as_str())
+			.find(|id| !ids.insert(*id))
+	}
+}
+
+/// The stems of a level's [MusicTrack](crate::audio::MusicTrack), as asset paths
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MusicDef {
+	pub base: String,
+	#[serde(default)]
+	pub percussion: Option<String>,
+	#[serde(default)]
+	pub danger: Option<String>,
+}
+
+/// A camera pan shown when a [LevelAsset] loads, to show off the layout of the level
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IntroPanDef {
+	/// Points for the camera to pass through. If empty, the camera sweeps along the
+	/// level's bounds instead.
+	#[serde(default)]
+	pub path: Vec<Vec2>,
+	/// Seconds to travel the whole path
+	pub duration: f32,
+	pub easing: EaseFunction,
+	/// Camera projection scale during the pan; smaller values zoom in further
+	pub zoom: f32,
+}
+
+/// A static platform in a [LevelAsset]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlatformDef {
+	/// Identifies the platform across hot reloads, so that edits can be applied to the existing
+	/// entity rather than despawning and respawning everything. Must be unique within the level.
+	pub id: String,
+	pub pos: Vec2,
+	pub size: Vec2,
+	/// sRGB color components
+	pub color: (f32, f32, f32),
+	#[serde(default)]
+	pub one_way: bool,
+	#[serde(default)]
+	pub surface: Option<SurfaceMaterial>,
+	/// Restricts how the player can hold onto the sides of this platform
+	#[serde(default)]
+	pub wall: Option<WallSurface>,
+	/// Makes the sides of this platform easier or harder to hold onto
+	#[serde(default)]
+	pub wall_material: Option<WallMaterial>,
+	/// Whether decorations may be scattered along the top of this platform
+	#[serde(default = "default_true")]
+	pub decorate: bool,
+	/// If set, the platform launches whatever lands on it
+	#[serde(default)]
+	pub spring: Option<Spring>,
+	/// If set, the platform collapses after being stood on
+	#[serde(default)]
+	pub breakable: Option<BreakableDef>,
+	/// If set, the platform shatters when the player hits it the right way
+	#[serde(default)]
+	pub block: Option<BreakableBlock>,
+	/// Id of the [GroupDef] this platform belongs to, if any
+	#[serde(default)]
+	pub group: Option<String>,
+	/// If set, the platform travels along this path rather than staying put
+	#[serde(default)]
+	pub path: Option<PathDef>,
+	/// If set, the platform keeps carrying the player for a moment after they step off its edge
+	#[serde(default)]
+	pub sticky: Option<StickyPlatform>,
+	/// If set, the platform hurts the player when they touch it, like a bed of spikes
+	#[serde(default)]
+	pub hazard: Option<Hazard>,
+	/// If set, the platform is a door that opens for a player carrying the key with this id
+	/// (a [CollectibleDef] of the `Key` kind)
+	#[serde(default)]
+	pub lock: Option<String>,
+}
+
+/// One tile of a tileset image, drawn as part of a [LevelAsset]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TileDef {
+	/// Asset path of the tileset image
+	pub tileset: String,
+	/// Pixel rectangle of the tile within the tileset
+	pub src: Rect,
+	/// Where the center of the tile goes in the level, and how big it's drawn
+	pub pos: Vec2,
+	pub size: Vec2,
+	#[serde(default)]
+	pub flip_x: bool,
+	#[serde(default)]
+	pub flip_y: bool,
+	/// Draw order among the tiles, where lower values are further back. Tiles are always drawn
+	/// behind the platforms.
+	#[serde(default)]
+	pub depth: f32,
+}
+
+/// The route of a [PlatformDef] that moves, which starts out at the platform's `pos`
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PathDef {
+	/// Where the platform travels to after its `pos`, relative to its `pos`
+	pub waypoints: Vec<Vec2>,
+	/// Average speed along each leg of the path, in units per second
+	pub speed: f32,
+	#[serde(default = "default_easing")]
+	pub easing: EaseFunction,
+	#[serde(default = "default_path_mode")]
+	pub mode: PathMode,
+}
+
+/// An area of a [LevelAsset] that affects whatever is inside it, without blocking anything
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ZoneDef {
+	pub kind: ZoneKind,
+	pub pos: Vec2,
+	pub size: Vec2,
+	/// sRGBA color components; zones are usually mostly see-through
+	pub color: (f32, f32, f32, f32),
+	/// A sound that loops from the middle of the zone, like the lapping of a pool of water
+	#[serde(default)]
+	pub sound: Option<SoundDef>,
+}
+
+/// A looping sound played by something in a [LevelAsset], as a [SoundEmitter](crate::audio::SoundEmitter)
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SoundDef {
+	/// Asset path of the sound
+	pub sound: String,
+	pub volume: f32,
+	pub audible_radius: f32,
+}
+
+/// What a [ZoneDef] does to whatever is inside it
+#[derive(Clone, Debug, Deserialize)]
+pub enum ZoneKind {
+	Water(WaterVolume),
+	Wind(WindZone),
+	Gravity(GravityZone),
+	/// Hurts the player while they're inside it, like a pool of lava
+	Hazard(Hazard),
+	/// A [Checkpoint](crate::player::Checkpoint), which the player respawns at after reaching it
+	Checkpoint,
+	/// Takes the players to another level
+	Exit(LevelExit),
+	/// Tells the rest of the game when players go in and out of it, e.g. to start a cutscene
+	Trigger(TriggerZone),
+}
+
+/// A loose prop in a [LevelAsset]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PropDef {
+	pub kind: AmbientPropKind,
+	pub pos: Vec2,
+}
+
+/// Settings for a [PlatformDef] that collapses after being stood on
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BreakableDef {
+	/// How long the platform shows warning cracks (and shakes) before it gives way
+	pub crack_time: FrameCount,
+	/// If set, the platform comes back this long after giving way. Otherwise it only comes back
+	/// once the player leaves the room.
+	#[serde(default)]
+	pub respawn_time: Option<FrameCount>,
+}
+
+pub(crate) fn default_true() -> bool {
+	true
+}
+
+fn default_easing() -> EaseFunction {
+	EaseFunction::Linear
+}
+
+fn default_path_mode() -> PathMode {
+	PathMode::PingPong
+}
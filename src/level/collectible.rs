@@ -0,0 +1,185 @@
+use crate::level::{CurrentLevel, LevelAsset, PlayStats, RestartLevel, RoomInactive};
+use crate::player::{Player, PlayerInventory, TutorialGhost};
+use crate::ui::{FloatingTextKind, SpawnFloatingText};
+use crate::util::{FrameCount, Tween, TweenRepeat, TweenTarget};
+use bevy::color::Luminance;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{Collider, Sensor};
+use serde::Deserialize;
+
+/// Draw order of collectibles: just behind the player
+const COLLECTIBLES_Z: f32 = 0.3;
+
+/// How long collectibles take to brighten (and then dim again) as they glint
+const GLINT_DURATION: FrameCount = FrameCount(45);
+
+/// What a [CollectibleDef] is, which decides how it looks and how much it's worth
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum CollectibleKind {
+	/// Small change, usually laid out in lines that show the way
+	Coin,
+	/// Worth a handful of coins, and usually tucked away somewhere out of the way
+	Gem,
+	/// Opens the [LockedDoor](crate::platforms::LockedDoor)s locked with the key's `id`. Worth nothing
+	/// by itself, so it doesn't count towards the [PlayStats].
+	Key,
+}
+
+impl CollectibleKind {
+	/// How much picking one up adds to the [PlayStats]' `score`
+	pub fn value(self) -> u32 {
+		match self {
+			CollectibleKind::Coin => 1,
+			CollectibleKind::Gem => 5,
+			CollectibleKind::Key => 0,
+		}
+	}
+
+	/// Radius of the sensor that picks it up, which is also how big it's drawn
+	pub fn radius(self) -> f32 {
+		match self {
+			CollectibleKind::Coin => 0.75,
+			CollectibleKind::Gem => 1.0,
+			CollectibleKind::Key => 1.0,
+		}
+	}
+
+	fn color(self) -> Color {
+		match self {
+			CollectibleKind::Coin => Color::srgb(1.0, 0.8, 0.2),
+			CollectibleKind::Gem => Color::srgb(0.3, 0.9, 0.9),
+			CollectibleKind::Key => Color::srgb(0.9, 0.9, 0.95),
+		}
+	}
+}
+
+/// Something for the player to pick up in a [LevelAsset]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CollectibleDef {
+	/// Identifies the collectible in the [PlayStats]. Must be unique within the level.
+	pub id: String,
+	pub kind: CollectibleKind,
+	pub pos: Vec2,
+}
+
+/// A collectible lying in the level, waiting to be picked up by a player touching its sensor
+#[derive(Component, Debug)]
+#[require(Sensor)]
+pub struct Collectible {
+	pub id: String,
+	pub kind: CollectibleKind,
+}
+
+/// Sent when a player picks up a collectible, e.g. for playing a sound or bursting into sparkles
+#[derive(Event, Debug)]
+pub struct CollectiblePickedUp {
+	pub player: Entity,
+	pub kind: CollectibleKind,
+	pub position: Vec2,
+}
+
+/// Respawns the current level's collectibles whenever it loads, is modified, or restarts, leaving out
+/// the ones picked up during the current attempt. Should run after `track_play_stats`, so that
+/// restarting the level has started a new attempt (with nothing picked up) by the time this runs.
+pub fn sync_collectibles_system(
+	mut commands: Commands,
+	mut events: EventReader<AssetEvent<LevelAsset>>,
+	mut restart_events: EventReader<RestartLevel>,
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	stats: Res<PlayStats>,
+	existing: Query<Entity, With<Collectible>>,
+) {
+	let Some(current_level) = current_level else {
+		return;
+	};
+	let current_id = current_level.0.id();
+	let changed = events.read().any(|event| match event {
+		AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => *id == current_id,
+		_ => false,
+	});
+	let restarted = restart_events.read().count() > 0;
+	if !changed && !restarted {
+		return;
+	}
+	let Some(level) = levels.get(current_id) else {
+		return;
+	};
+
+	for entity in &existing {
+		commands.entity(entity).despawn();
+	}
+	for def in &level.collectibles {
+		if stats.collected.contains(&def.id) {
+			continue;
+		}
+		let radius = def.kind.radius();
+		let mut transform = Transform::from_translation(def.pos.extend(COLLECTIBLES_Z));
+		if def.kind == CollectibleKind::Gem {
+			transform.rotate_z(std::f32::consts::FRAC_PI_4);
+		}
+		commands.spawn((
+			Collectible {
+				id: def.id.clone(),
+				kind: def.kind,
+			},
+			Collider::ball(radius),
+			Sprite::from_color(def.kind.color(), Vec2::splat(radius * 1.4)),
+			transform,
+			// glint, to catch the player's eye
+			Tween::new(
+				TweenTarget::SpriteColor {
+					from: def.kind.color(),
+					to: def.kind.color().lighter(0.15),
+				},
+				GLINT_DURATION,
+				EaseFunction::SineInOut,
+			)
+			.with_repeat(TweenRepeat::PingPong),
+		));
+	}
+}
+
+/// Picks up the collectibles that the players are touching, adding them to the [PlayStats], or to the
+/// [PlayerInventory] of whoever picked them up in the case of keys
+pub fn pick_up_collectibles(
+	mut commands: Commands,
+	mut players: Query<(Entity, &mut PlayerInventory, &Transform, &Collider), (With<Player>, Without<TutorialGhost>)>,
+	collectibles: Query<(Entity, &Collectible, &Transform), (Without<Player>, Without<RoomInactive>)>,
+	mut stats: ResMut<PlayStats>,
+	mut picked_up: EventWriter<CollectiblePickedUp>,
+	mut floating_text: EventWriter<SpawnFloatingText>,
+) {
+	for (player, mut inventory, player_transform, player_collider) in &mut players {
+		let half_extents = player_collider.as_cuboid().map_or(Vec2::ZERO, |c| c.half_extents());
+		let area = Rect::from_center_half_size(player_transform.translation.truncate(), half_extents);
+		for (entity, collectible, transform) in &collectibles {
+			let position = transform.translation.truncate();
+			let touching = position.distance(position.clamp(area.min, area.max)) <= collectible.kind.radius();
+			// two players can touch the same collectible at once, but only one of them gets it
+			if !touching || !stats.collected.insert(collectible.id.clone()) {
+				continue;
+			}
+			info!("picked up {:?} {:?}", collectible.kind, collectible.id);
+			commands.entity(entity).despawn();
+			picked_up.send(CollectiblePickedUp {
+				player,
+				kind: collectible.kind,
+				position,
+			});
+			if collectible.kind == CollectibleKind::Key {
+				inventory.keys.insert(collectible.id.clone());
+				continue;
+			}
+			let value = collectible.kind.value();
+			stats.collectibles += 1;
+			stats.score += value;
+			floating_text.send(SpawnFloatingText {
+				kind: FloatingTextKind::Score,
+				value: value as i32,
+				position,
+			});
+		}
+	}
+}
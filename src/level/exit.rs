@@ -0,0 +1,113 @@
+use crate::level::{CurrentLevel, LevelAsset, PLAYER_START_SPACING, PlayStats, RoomInactive};
+use crate::player::{Player, PlayerSlot, PlayerSpawnPoint, ReturnToCheckpoint, TutorialGhost};
+use crate::util::{GameClock, PauseReason};
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{Collider, Sensor};
+use serde::Deserialize;
+
+/// An area that takes the players to another level once one of them steps inside, like a door
+/// at the end of a corridor
+#[derive(Component, Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[require(Sensor)]
+pub struct LevelExit {
+	/// Asset path of the level to go to
+	pub level: String,
+	/// Where the players arrive in the other level. Defaults to the level's `player_start`.
+	#[serde(default)]
+	pub spawn: Option<Vec2>,
+}
+
+/// Present while the players are on their way to another level. Gameplay stays paused until the
+/// level has loaded and its entities have been spawned, so nothing falls through the floor mid-swap.
+#[derive(Resource, Debug)]
+pub struct LevelTransition {
+	/// Where the players arrive, if the exit said
+	spawn: Option<Vec2>,
+}
+
+/// Sent once the players have arrived in a new level through a [LevelExit]
+#[derive(Event, Debug)]
+pub struct LevelEntered {
+	pub level: Handle<LevelAsset>,
+}
+
+/// Starts loading the level behind the first [LevelExit] that a player's center is in, pausing the game
+/// until it's ready. The new level starts a new attempt in the [PlayStats].
+///
+/// Should run before the systems that spawn the level's entities, so they see the new [CurrentLevel]
+/// in the same frame as the event saying it's loaded.
+pub fn enter_level_exits(
+	mut commands: Commands,
+	transition: Option<Res<LevelTransition>>,
+	mut current_level: ResMut<CurrentLevel>,
+	exits: Query<(&LevelExit, &Transform, &Collider), (Without<Player>, Without<RoomInactive>)>,
+	players: Query<&Transform, (With<Player>, Without<TutorialGhost>)>,
+	asset_server: Res<AssetServer>,
+	levels: Res<Assets<LevelAsset>>,
+	mut level_events: EventWriter<AssetEvent<LevelAsset>>,
+	mut stats: ResMut<PlayStats>,
+	mut clock: ResMut<GameClock>,
+) {
+	if transition.is_some() {
+		return;
+	}
+	let entered = exits.iter().find(|(_, transform, collider)| {
+		let half_extents = collider.as_cuboid().map_or(Vec2::ZERO, |c| c.half_extents());
+		let area = Rect::from_center_half_size(transform.translation.truncate(), half_extents);
+		players
+			.iter()
+			.any(|player| area.contains(player.translation.truncate()))
+	});
+	let Some((exit, ..)) = entered else {
+		return;
+	};
+
+	info!("leaving for level {:?}", exit.level);
+	let level: Handle<LevelAsset> = asset_server.load(&exit.level);
+	// levels that were loaded before (e.g. when going back the way the players came) won't be loaded
+	// again, so the systems spawning their entities have to be told about them instead
+	if levels.contains(level.id()) {
+		level_events.send(AssetEvent::LoadedWithDependencies { id: level.id() });
+	}
+	current_level.0 = level;
+	commands.insert_resource(LevelTransition { spawn: exit.spawn });
+	stats.start_attempt(&clock);
+	clock.pause(PauseReason::LevelTransition);
+}
+
+/// Finishes a [LevelTransition] once the new level is loaded, moving the players to where they
+/// arrive and unpausing the game. Should run after the systems that spawn the level's entities.
+pub fn finish_level_transition(
+	mut commands: Commands,
+	transition: Option<Res<LevelTransition>>,
+	current_level: Res<CurrentLevel>,
+	levels: Res<Assets<LevelAsset>>,
+	mut players: Query<(Entity, &PlayerSlot, &mut PlayerSpawnPoint), With<Player>>,
+	mut returns: EventWriter<ReturnToCheckpoint>,
+	mut entered_events: EventWriter<LevelEntered>,
+	mut clock: ResMut<GameClock>,
+) {
+	let Some(transition) = transition else {
+		return;
+	};
+	let Some(level) = levels.get(current_level.0.id()) else {
+		return;
+	};
+
+	match transition.spawn.or(level.player_start) {
+		Some(arrival) => {
+			for (player, slot, mut spawn_point) in &mut players {
+				spawn_point.0 = arrival + Vec2::X * PLAYER_START_SPACING * slot.0 as f32;
+				returns.send(ReturnToCheckpoint(player));
+			}
+		}
+		None => warn!("the new level has no player_start, so the players stay where they were"),
+	}
+	info!("entered level");
+	commands.remove_resource::<LevelTransition>();
+	entered_events.send(LevelEntered {
+		level: current_level.0.clone(),
+	});
+	clock.unpause(PauseReason::LevelTransition);
+}
@@ -0,0 +1,213 @@
+use crate::level::{CurrentLevel, LevelAsset, LevelPlatform, RestartLevel};
+use crate::platforms::{BreakState, BreakableFloor};
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_rapier2d::prelude::ColliderDisabled;
+use serde::Deserialize;
+
+/// A named set of platforms in a [LevelAsset] (e.g. `"spikes_room2"`) that can be switched on and off,
+/// moved, or recolored together by switches, scripts, or the editor. Platforms join a group by naming
+/// it in their `group` field.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GroupDef {
+	/// Must be unique within the level
+	pub id: String,
+	/// Whether the group's platforms start out switched off, to be switched on later
+	#[serde(default)]
+	pub starts_disabled: bool,
+}
+
+impl LevelAsset {
+	/// Checks that every group is only defined once, and that every platform's (and switch target's)
+	/// group is defined
+	pub fn validate_groups(&self) -> Result<(), String> {
+		let mut ids = HashSet::new();
+		for group in &self.groups {
+			if !ids.insert(group.id.as_str()) {
+				return Err(format!("group id {:?} is used more than once", group.id));
+			}
+		}
+		for platform in &self.platforms {
+			if let Some(group) = platform.group.as_ref().filter(|group| !ids.contains(group.as_str())) {
+				return Err(format!(
+					"platform {:?} is in group {:?}, which isn't defined in `groups`",
+					platform.id, group
+				));
+			}
+		}
+		for switch in &self.switches {
+			if let Some(target) = switch
+				.targets
+				.iter()
+				.find(|target| !ids.contains(target.group.as_str()))
+			{
+				return Err(format!(
+					"switch {:?} targets group {:?}, which isn't defined in `groups`",
+					switch.id, target.group
+				));
+			}
+		}
+		Ok(())
+	}
+}
+
+/// The current state of one of the level's groups
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupState {
+	pub enabled: bool,
+	/// How far the group's platforms have been moved from where the level puts them
+	pub offset: Vec2,
+	/// Replaces the color of each of the group's platforms, if set
+	pub color: Option<Color>,
+}
+
+impl GroupState {
+	fn new(def: &GroupDef) -> Self {
+		GroupState {
+			enabled: !def.starts_disabled,
+			offset: Vec2::ZERO,
+			color: None,
+		}
+	}
+}
+
+/// The state of each group in the current level, by id
+#[derive(Resource, Debug, Default)]
+pub struct LevelGroups(HashMap<String, GroupState>);
+
+impl LevelGroups {
+	pub fn get(&self, id: &str) -> Option<&GroupState> {
+		self.0.get(id)
+	}
+}
+
+/// Something to do to every platform in a group at once
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GroupAction {
+	Enable,
+	Disable,
+	Toggle,
+	/// Moves the group's platforms this far from where the level puts them
+	MoveTo(Vec2),
+	/// Moves the group's platforms this far from where they currently are
+	MoveBy(Vec2),
+	/// Replaces the color of the group's platforms, or with `None`, goes back to their own colors
+	Recolor(Option<Color>),
+}
+
+/// Sent to apply a [GroupAction] to the group with the given id
+#[derive(Event, Debug, Clone)]
+pub struct LevelGroupCommand {
+	pub group: String,
+	pub action: GroupAction,
+}
+
+/// Marks a platform that is switched off because its group is disabled, as opposed to some other
+/// reason (like a broken floor), so that only the group's own changes are undone when it's re-enabled
+#[derive(Component, Debug)]
+pub struct DisabledByGroup;
+
+/// Resets every group to its initial state when the level loads or restarts. Hot reloads keep the
+/// state of groups that were already there, so switching something on and then tweaking the level
+/// doesn't switch it back off.
+pub fn reset_level_groups(
+	mut level_events: EventReader<AssetEvent<LevelAsset>>,
+	mut restart_events: EventReader<RestartLevel>,
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	mut groups: ResMut<LevelGroups>,
+) {
+	let restarted = restart_events.read().count() > 0;
+	let Some(current_level) = current_level else {
+		return;
+	};
+	let current_id = current_level.0.id();
+	let (mut loaded, mut modified) = (false, false);
+	for event in level_events.read() {
+		match event {
+			AssetEvent::LoadedWithDependencies { id } if *id == current_id => loaded = true,
+			AssetEvent::Modified { id } if *id == current_id => modified = true,
+			_ => (),
+		}
+	}
+	if !restarted && !loaded && !modified {
+		return;
+	}
+	let Some(level) = levels.get(current_id) else {
+		return;
+	};
+	let mut previous = if restarted || loaded {
+		HashMap::new()
+	} else {
+		std::mem::take(&mut groups.0)
+	};
+	groups.0 = level
+		.groups
+		.iter()
+		.map(|def| {
+			let state = previous.remove(&def.id).unwrap_or_else(|| GroupState::new(def));
+			(def.id.clone(), state)
+		})
+		.collect();
+}
+
+/// Applies each [LevelGroupCommand] to the [LevelGroups]
+pub fn level_group_commands(mut commands: EventReader<LevelGroupCommand>, mut groups: ResMut<LevelGroups>) {
+	for LevelGroupCommand { group, action } in commands.read() {
+		let Some(state) = groups.0.get_mut(group) else {
+			warn!("no group {:?} in the current level", group);
+			continue;
+		};
+		debug!("{:?} group {:?}", action, group);
+		match *action {
+			GroupAction::Enable => state.enabled = true,
+			GroupAction::Disable => state.enabled = false,
+			GroupAction::Toggle => state.enabled = !state.enabled,
+			GroupAction::MoveTo(offset) => state.offset = offset,
+			GroupAction::MoveBy(delta) => state.offset += delta,
+			GroupAction::Recolor(color) => state.color = color,
+		}
+	}
+}
+
+/// Makes each grouped platform match its group's state, whenever the state changes or the platform
+/// is (re)built from its definition
+pub fn apply_level_groups(
+	mut commands: Commands,
+	groups: Res<LevelGroups>,
+	mut platforms: Query<(
+		Entity,
+		Ref<LevelPlatform>,
+		&mut Transform,
+		&mut Sprite,
+		&mut Visibility,
+		Has<DisabledByGroup>,
+		Option<&BreakableFloor>,
+	)>,
+) {
+	for (entity, platform, mut transform, mut sprite, mut visibility, was_disabled, breakable) in &mut platforms {
+		if !groups.is_changed() && !platform.is_changed() {
+			continue;
+		}
+		let def = &platform.0;
+		let Some(state) = def.group.as_deref().and_then(|group| groups.get(group)) else {
+			continue;
+		};
+		transform.translation = (def.pos + state.offset).extend(transform.translation.z);
+		let (r, g, b) = def.color;
+		sprite.color = state.color.unwrap_or(Color::srgb(r, g, b));
+
+		if !state.enabled {
+			commands.entity(entity).insert((DisabledByGroup, ColliderDisabled));
+			*visibility = Visibility::Hidden;
+		} else if was_disabled {
+			commands.entity(entity).remove::<DisabledByGroup>();
+			// a floor that broke before its group was switched off stays broken
+			if breakable.is_none_or(|floor| !matches!(floor.state(), BreakState::Broken(_))) {
+				commands.entity(entity).remove::<ColliderDisabled>();
+				*visibility = Visibility::Inherited;
+			}
+		}
+	}
+}
@@ -0,0 +1,87 @@
+use bevy::math::Vec2;
+
+/// sRGBA color of hazard zones from levels made in other editors
+pub(crate) const HAZARD_ZONE_COLOR: (f32, f32, f32, f32) = (0.9, 0.25, 0.1, 0.5);
+
+/// Converts positions in a level made in another editor, measured in pixels with Y pointing down,
+/// into the game's units with Y pointing up
+pub(crate) struct PixelSpace {
+	pub units_per_px: f32,
+	/// Height of the whole level, in pixels
+	pub level_height: f32,
+}
+
+impl PixelSpace {
+	/// Where the given pixel position is in the level
+	pub fn point(&self, px: Vec2) -> Vec2 {
+		Vec2::new(px.x, self.level_height - px.y) * self.units_per_px
+	}
+
+	/// The center and size of a rectangle with the given top-left corner and size in pixels
+	pub fn rect(&self, top_left: Vec2, size: Vec2) -> (Vec2, Vec2) {
+		(self.point(top_left + size * 0.5), size * self.units_per_px)
+	}
+}
+
+/// Parses a color like `#FF8800` into sRGB color components
+pub(crate) fn parse_hex_color(color: &str) -> Option<(f32, f32, f32)> {
+	let hex = color.strip_prefix('#')?;
+	let channel = |i: usize| {
+		let byte = u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()?;
+		Some(byte as f32 / 255.0)
+	};
+	Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// A rectangle of cells in a grid, which all have the same value
+pub(crate) struct CellRect<T> {
+	pub value: T,
+	/// Column and row of the rectangle's top-left cell
+	pub x: usize,
+	pub y: usize,
+	pub width: usize,
+	pub height: usize,
+}
+
+/// Covers the non-empty (i.e. not `default`) cells of a grid, listed row by row, with as few rectangles
+/// of matching cells as it can: each run of matching cells along a row is extended down over the rows
+/// below it for as long as they have the same run.
+///
+/// Tile-based level geometry gets one collider per rectangle this way, rather than one per cell, which
+/// leaves no seams between neighboring cells for the player to catch on, and is far less for the physics
+/// to keep track of.
+pub(crate) fn merge_cells<T: Copy + PartialEq + Default>(cells: &[T], width: usize) -> Vec<CellRect<T>> {
+	let height = cells.len() / width.max(1);
+	let cell = |x: usize, y: usize| cells[y * width + x];
+	let mut covered = vec![false; cells.len()];
+	let mut rects = Vec::new();
+
+	for y in 0..height {
+		let mut x = 0;
+		while x < width {
+			let value = cell(x, y);
+			if value == T::default() || covered[y * width + x] {
+				x += 1;
+				continue;
+			}
+			let run = (x..width)
+				.take_while(|&end| cell(end, y) == value && !covered[y * width + end])
+				.count();
+			let rows = (y..height)
+				.take_while(|&row| (x..x + run).all(|col| cell(col, row) == value && !covered[row * width + col]))
+				.count();
+			for row in y..y + rows {
+				covered[row * width + x..row * width + x + run].fill(true);
+			}
+			rects.push(CellRect {
+				value,
+				x,
+				y,
+				width: run,
+				height: rows,
+			});
+			x += run;
+		}
+	}
+	rects
+}
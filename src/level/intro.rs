@@ -0,0 +1,63 @@
+use crate::level::{CurrentLevel, LevelAsset};
+use crate::ui::{CameraPath, CinematicCamera};
+use bevy::prelude::*;
+
+/// Half the size of the area the camera shows at a projection scale of 1
+const HALF_VIEW: f32 = 50.0;
+
+/// Plays the current level's intro pan (if it has one) once the level first loads.
+/// Hot reloads don't replay it.
+pub fn start_level_intro(
+	mut events: EventReader<AssetEvent<LevelAsset>>,
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	mut cinematic: ResMut<CinematicCamera>,
+) {
+	let Some(current_level) = current_level else {
+		return;
+	};
+	let current_id = current_level.0.id();
+	let loaded = events
+		.read()
+		.any(|event| matches!(event, AssetEvent::LoadedWithDependencies { id } if *id == current_id));
+	if !loaded {
+		return;
+	}
+	let Some(level) = levels.get(current_id) else {
+		return;
+	};
+	let Some(intro) = &level.intro_pan else {
+		return;
+	};
+
+	let points = if intro.path.is_empty() {
+		bounds_sweep(level, intro.zoom)
+	} else {
+		intro.path.clone()
+	};
+	info!("playing level intro pan through {} points", points.len());
+	cinematic.play(CameraPath {
+		points,
+		duration: intro.duration,
+		easing: intro.easing,
+		zoom: intro.zoom,
+		skippable: true,
+	});
+}
+
+/// A path around the level's bounds (top-left, top-right, bottom-right, bottom-left), inset so the
+/// camera doesn't show much beyond the edges of the level at the given `zoom`
+fn bounds_sweep(level: &LevelAsset, zoom: f32) -> Vec<Vec2> {
+	let Some(bounds) = level.bounds() else {
+		return Vec::new();
+	};
+	// if the view is bigger than the level, there's nowhere to go but the middle
+	let inset = Vec2::splat(HALF_VIEW * zoom).min(bounds.half_size());
+	let inner = Rect::from_corners(bounds.min + inset, bounds.max - inset);
+	vec![
+		Vec2::new(inner.min.x, inner.max.y),
+		inner.max,
+		Vec2::new(inner.max.x, inner.min.y),
+		inner.min,
+	]
+}
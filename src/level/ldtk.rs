@@ -0,0 +1,323 @@
+use crate::hazards::Hazard;
+use crate::level::{
+	CollectibleDef, CollectibleKind, HAZARD_ZONE_COLOR, LevelAsset, PixelSpace, PlatformDef, TileDef, ZoneDef,
+	ZoneKind, merge_cells, parse_hex_color,
+};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::log::warn;
+use bevy::math::{Rect, Vec2};
+use serde::Deserialize;
+use thiserror::Error;
+
+/// How big one cell of the project's default grid is in the game's units
+const UNITS_PER_CELL: f32 = 2.0;
+
+/// The layer whose IntGrid cells become the level's platforms
+const COLLISIONS_LAYER: &str = "Collisions";
+
+/// The entity marking where the players start
+const PLAYER_START_ENTITY: &str = "PlayerStart";
+
+/// Entities by this name become hazard zones, covering the entity
+const HAZARD_ENTITY: &str = "Hazard";
+
+/// Entities by these names become collectibles, at the entity's position
+const COIN_ENTITY: &str = "Coin";
+const GEM_ENTITY: &str = "Gem";
+
+/// IntGrid cells with a value by this name become one-way platforms, rather than solid ones
+const ONE_WAY_VALUE: &str = "OneWay";
+
+/// Color of platforms whose IntGrid value doesn't have a (readable) color
+const DEFAULT_COLOR: (f32, f32, f32) = (0.5, 0.5, 0.55);
+
+/// The parts of an LDtk project file that the [LdtkLevelLoader] reads.
+/// Project files have many more fields than these, which are ignored.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkProject {
+	default_grid_size: f32,
+	external_levels: bool,
+	defs: LdtkDefs,
+	levels: Vec<LdtkLevel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LdtkDefs {
+	layers: Vec<LdtkLayerDef>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkLayerDef {
+	uid: i64,
+	#[serde(default)]
+	int_grid_values: Vec<LdtkIntGridValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LdtkIntGridValue {
+	value: i32,
+	identifier: Option<String>,
+	color: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkLevel {
+	identifier: String,
+	px_hei: f32,
+	layer_instances: Option<Vec<LdtkLayer>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkLayer {
+	#[serde(rename = "__identifier")]
+	identifier: String,
+	#[serde(rename = "__cWid")]
+	c_wid: usize,
+	#[serde(rename = "__gridSize")]
+	grid_size: f32,
+	#[serde(rename = "__pxTotalOffsetX")]
+	px_total_offset_x: f32,
+	#[serde(rename = "__pxTotalOffsetY")]
+	px_total_offset_y: f32,
+	#[serde(rename = "__tilesetRelPath")]
+	tileset_rel_path: Option<String>,
+	layer_def_uid: i64,
+	#[serde(default)]
+	int_grid_csv: Vec<i32>,
+	#[serde(default)]
+	grid_tiles: Vec<LdtkTile>,
+	#[serde(default)]
+	auto_layer_tiles: Vec<LdtkTile>,
+	#[serde(default)]
+	entity_instances: Vec<LdtkEntity>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LdtkTile {
+	/// Top-left corner of the tile in the layer, in pixels
+	px: [f32; 2],
+	/// Top-left corner of the tile in the tileset, in pixels
+	src: [f32; 2],
+	/// Bit 0 flips the tile horizontally, and bit 1 vertically
+	f: u8,
+}
+
+#[derive(Debug, Deserialize)]
+struct LdtkEntity {
+	#[serde(rename = "__identifier")]
+	identifier: String,
+	#[serde(rename = "__pivot")]
+	pivot: [f32; 2],
+	/// Unique id of the entity within the project
+	iid: String,
+	/// Position of the entity's pivot in the layer, in pixels
+	px: [f32; 2],
+	width: f32,
+	height: f32,
+}
+
+/// Loads the first level of an LDtk project (`.ldtk`) as a [LevelAsset], so levels can be drawn in LDtk
+/// rather than written out by hand. Like `.level.ron` levels, they're hot-reloaded whenever the project is saved.
+///
+/// - Cells of the IntGrid layer named `Collisions` become platforms, merged into as few rectangles as possible
+///   and colored like their IntGrid value. Cells with the value named `OneWay` become one-way platforms.
+/// - Tiles from every layer (including auto-layers) are drawn behind the platforms.
+/// - A `PlayerStart` entity marks where the players start.
+/// - `Hazard` entities become zones that hurt the player.
+/// - `Coin` and `Gem` entities become collectibles.
+///
+/// Other entities are skipped with a warning, as there isn't anything to spawn them as yet.
+#[derive(Default)]
+pub struct LdtkLevelLoader;
+
+#[derive(Debug, Error)]
+pub enum LdtkLevelLoaderError {
+	#[error("Could not load asset: {0}")]
+	Io(#[from] std::io::Error),
+
+	#[error("Could not parse LDtk project: {0}")]
+	Json(#[from] serde_json::Error),
+
+	#[error("Invalid LDtk project: {0}")]
+	Invalid(String),
+
+	#[error("Platform id {0:?} is used more than once")]
+	DuplicatePlatformId(String),
+}
+
+impl AssetLoader for LdtkLevelLoader {
+	type Asset = LevelAsset;
+	type Settings = ();
+	type Error = LdtkLevelLoaderError;
+
+	async fn load(
+		&self,
+		reader: &mut dyn Reader,
+		_settings: &Self::Settings,
+		load_context: &mut LoadContext<'_>,
+	) -> Result<Self::Asset, Self::Error> {
+		let mut bytes = Vec::new();
+		reader.read_to_end(&mut bytes).await?;
+		let project = serde_json::from_slice::<LdtkProject>(&bytes)?;
+		if project.external_levels {
+			return Err(LdtkLevelLoaderError::Invalid(
+				"levels saved in separate files aren't supported".into(),
+			));
+		}
+		let level = project
+			.levels
+			.first()
+			.ok_or_else(|| LdtkLevelLoaderError::Invalid("the project has no levels".into()))?;
+		let space = PixelSpace {
+			units_per_px: UNITS_PER_CELL / project.default_grid_size,
+			level_height: level.px_hei,
+		};
+
+		let mut platforms = Vec::new();
+		let mut tiles = Vec::new();
+		let mut player_start = None;
+		let mut zones = Vec::new();
+		let mut collectibles = Vec::new();
+		let layers = level.layer_instances.as_deref().unwrap_or_default();
+		// layers are listed from the front to the back
+		for (index, layer) in layers.iter().enumerate() {
+			let offset = Vec2::new(layer.px_total_offset_x, layer.px_total_offset_y);
+
+			if layer.identifier == COLLISIONS_LAYER {
+				let values = project
+					.defs
+					.layers
+					.iter()
+					.find(|def| def.uid == layer.layer_def_uid)
+					.map_or(&[][..], |def| &def.int_grid_values);
+				platforms.extend(collision_platforms(layer, values, offset, &space));
+			}
+
+			if let Some(tileset) = &layer.tileset_rel_path {
+				let tileset = load_context
+					.asset_path()
+					.resolve_embed(tileset)
+					.map_err(|err| LdtkLevelLoaderError::Invalid(format!("bad tileset path {:?}: {}", tileset, err)))?
+					.to_string();
+				let tile_size = Vec2::splat(layer.grid_size);
+				for tile in layer.grid_tiles.iter().chain(&layer.auto_layer_tiles) {
+					let (pos, size) = space.rect(Vec2::from(tile.px) + offset, tile_size);
+					let src = Vec2::from(tile.src);
+					tiles.push(TileDef {
+						tileset: tileset.clone(),
+						src: Rect::from_corners(src, src + tile_size),
+						pos,
+						size,
+						flip_x: tile.f & 1 != 0,
+						flip_y: tile.f & 2 != 0,
+						depth: -(index as f32),
+					});
+				}
+			}
+
+			for entity in &layer.entity_instances {
+				let size = Vec2::new(entity.width, entity.height);
+				let top_left = Vec2::from(entity.px) + offset - Vec2::from(entity.pivot) * size;
+				let (pos, size) = space.rect(top_left, size);
+				match entity.identifier.as_str() {
+					PLAYER_START_ENTITY => player_start = Some(pos),
+					HAZARD_ENTITY => zones.push(ZoneDef {
+						kind: ZoneKind::Hazard(Hazard::default()),
+						pos,
+						size,
+						color: HAZARD_ZONE_COLOR,
+						sound: None,
+					}),
+					COIN_ENTITY | GEM_ENTITY => collectibles.push(CollectibleDef {
+						id: entity.iid.clone(),
+						kind: if entity.identifier == GEM_ENTITY {
+							CollectibleKind::Gem
+						} else {
+							CollectibleKind::Coin
+						},
+						pos,
+					}),
+					other => warn!(
+						"skipping unsupported LDtk entity {:?} in level {:?}",
+						other, level.identifier
+					),
+				}
+			}
+		}
+
+		let level = LevelAsset {
+			platforms,
+			prefabs: Vec::new(),
+			rooms: Vec::new(),
+			groups: Vec::new(),
+			secrets: Vec::new(),
+			crush_response: Default::default(),
+			intro_pan: None,
+			ranks: None,
+			player_start,
+			tiles,
+			zones,
+			ziplines: Vec::new(),
+			props: Vec::new(),
+			collectibles,
+			switches: Vec::new(),
+			music: None,
+		};
+		// the platforms' ids are made from the layers' identifiers, which LDtk keeps unique,
+		// but a hand-edited project might not
+		if let Some(id) = level.duplicate_platform_id() {
+			return Err(LdtkLevelLoaderError::DuplicatePlatformId(id.to_string()));
+		}
+		Ok(level)
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["ldtk"]
+	}
+}
+
+/// Covers the layer's non-empty IntGrid cells with as few platforms as it can
+fn collision_platforms(
+	layer: &LdtkLayer,
+	values: &[LdtkIntGridValue],
+	offset: Vec2,
+	space: &PixelSpace,
+) -> Vec<PlatformDef> {
+	merge_cells(&layer.int_grid_csv, layer.c_wid)
+		.into_iter()
+		.map(|rect| {
+			let value_def = values.iter().find(|def| def.value == rect.value);
+			let top_left = Vec2::new(rect.x as f32, rect.y as f32) * layer.grid_size + offset;
+			let (pos, size) = space.rect(
+				top_left,
+				Vec2::new(rect.width as f32, rect.height as f32) * layer.grid_size,
+			);
+			PlatformDef {
+				id: format!("{}_{}_{}", layer.identifier, rect.x, rect.y),
+				pos,
+				size,
+				color: value_def
+					.and_then(|def| parse_hex_color(&def.color))
+					.unwrap_or(DEFAULT_COLOR),
+				one_way: value_def.is_some_and(|def| def.identifier.as_deref() == Some(ONE_WAY_VALUE)),
+				surface: None,
+				wall: None,
+				wall_material: None,
+				decorate: true,
+				spring: None,
+				breakable: None,
+				block: None,
+				group: None,
+				path: None,
+				sticky: None,
+				hazard: None,
+				lock: None,
+			}
+		})
+		.collect()
+}
@@ -0,0 +1,61 @@
+use crate::level::{LevelAsset, Prefab};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext, LoadDirectError};
+use thiserror::Error;
+
+#[derive(Default)]
+pub struct LevelAssetLoader;
+
+#[derive(Debug, Error)]
+pub enum LevelAssetLoaderError {
+	#[error("Could not load asset: {0}")]
+	Io(#[from] std::io::Error),
+
+	#[error("Could not parse RON: {0}")]
+	Ron(#[from] ron::de::SpannedError),
+
+	#[error("Could not load prefab: {0}")]
+	Prefab(#[from] LoadDirectError),
+
+	#[error("Invalid level: {0}")]
+	Invalid(String),
+
+	#[error("Platform id {0:?} is used more than once")]
+	DuplicatePlatformId(String),
+}
+impl AssetLoader for LevelAssetLoader {
+	type Asset = LevelAsset;
+	type Settings = ();
+	type Error = LevelAssetLoaderError;
+
+	async fn load(
+		&self,
+		reader: &mut dyn Reader,
+		_settings: &Self::Settings,
+		load_context: &mut LoadContext<'_>,
+	) -> Result<Self::Asset, Self::Error> {
+		let mut bytes = Vec::new();
+		reader.read_to_end(&mut bytes).await?;
+		let mut level = ron::de::from_bytes::<LevelAsset>(&bytes)?;
+		// loading the prefabs as part of the level means editing one reloads the levels that use it
+		for def in &level.prefabs {
+			let prefab = load_context
+				.loader()
+				.immediate()
+				.load::<Prefab>(def.asset_path())
+				.await?;
+			level.platforms.push(prefab.get().platform(def));
+		}
+		// checked after adding the prefabs, whose ids can clash with the platforms' too
+		if let Some(id) = level.duplicate_platform_id() {
+			return Err(LevelAssetLoaderError::DuplicatePlatformId(id.to_string()));
+		}
+		level.validate_groups().map_err(LevelAssetLoaderError::Invalid)?;
+		Ok(level)
+	}
+
+	fn extensions(&self) -> &[&str] {
+		// more specific than the "ron" claimed by the player loader
+		&["level.ron"]
+	}
+}
@@ -0,0 +1,33 @@
+mod asset;
+mod collectible;
+mod exit;
+mod group;
+mod import;
+mod intro;
+mod ldtk;
+mod loader;
+mod prefab;
+mod results;
+mod room;
+mod secret;
+mod stats;
+mod switch;
+mod sync;
+mod tiled;
+
+pub use asset::*;
+pub use collectible::*;
+pub use exit::*;
+pub use group::*;
+pub use import::*;
+pub use intro::*;
+pub use ldtk::*;
+pub use loader::*;
+pub use prefab::*;
+pub use results::*;
+pub use room::*;
+pub use secret::*;
+pub use stats::*;
+pub use switch::*;
+pub use sync::*;
+pub use tiled::*;
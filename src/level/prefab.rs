@@ -0,0 +1,123 @@
+use crate::hazards::Hazard;
+use crate::level::{BreakableDef, PathDef, PlatformDef, default_true};
+use crate::platforms::{BreakableBlock, Spring, StickyPlatform, SurfaceMaterial, WallMaterial, WallSurface};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// A kind of platform that levels can place any number of copies of, loaded from a `.prefab.ron` file
+/// in the `prefabs` folder. Holds everything about the platform except where it goes, so that e.g. every
+/// bed of spikes in the game looks and hurts the same, and can be tweaked in one place.
+#[derive(Asset, Clone, Debug, PartialEq, Deserialize, TypePath)]
+#[serde(deny_unknown_fields)]
+pub struct Prefab {
+	pub size: Vec2,
+	/// sRGB color components
+	pub color: (f32, f32, f32),
+	#[serde(default)]
+	pub one_way: bool,
+	#[serde(default)]
+	pub surface: Option<SurfaceMaterial>,
+	#[serde(default)]
+	pub wall: Option<WallSurface>,
+	#[serde(default)]
+	pub wall_material: Option<WallMaterial>,
+	#[serde(default = "default_true")]
+	pub decorate: bool,
+	#[serde(default)]
+	pub spring: Option<Spring>,
+	#[serde(default)]
+	pub breakable: Option<BreakableDef>,
+	#[serde(default)]
+	pub block: Option<BreakableBlock>,
+	#[serde(default)]
+	pub sticky: Option<StickyPlatform>,
+	#[serde(default)]
+	pub hazard: Option<Hazard>,
+}
+
+impl Prefab {
+	/// The platform that the given copy of the prefab becomes
+	pub fn platform(&self, def: &PrefabDef) -> PlatformDef {
+		PlatformDef {
+			id: def.id.clone(),
+			pos: def.pos,
+			size: self.size,
+			color: self.color,
+			one_way: self.one_way,
+			surface: self.surface,
+			wall: self.wall,
+			wall_material: self.wall_material,
+			decorate: self.decorate,
+			spring: self.spring,
+			breakable: self.breakable.clone(),
+			block: self.block,
+			group: def.group.clone(),
+			path: def.path.clone(),
+			sticky: self.sticky,
+			hazard: self.hazard,
+			lock: def.lock.clone(),
+		}
+	}
+}
+
+/// A copy of a [Prefab] placed in a [LevelAsset](crate::level::LevelAsset), along with the things
+/// that can differ between copies
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PrefabDef {
+	/// Id of the platform it becomes. Must be unique among the level's platforms.
+	pub id: String,
+	/// Name of the prefab, e.g. `"spike_bed"` for `prefabs/spike_bed.prefab.ron`
+	pub prefab: String,
+	pub pos: Vec2,
+	#[serde(default)]
+	pub group: Option<String>,
+	#[serde(default)]
+	pub path: Option<PathDef>,
+	#[serde(default)]
+	pub lock: Option<String>,
+}
+
+impl PrefabDef {
+	/// Asset path of the prefab's file
+	pub fn asset_path(&self) -> String {
+		format!("prefabs/{}.prefab.ron", self.prefab)
+	}
+}
+
+#[derive(Default)]
+pub struct PrefabLoader;
+
+#[derive(Debug, Error)]
+pub enum PrefabLoaderError {
+	#[error("Could not load asset: {0}")]
+	Io(#[from] std::io::Error),
+
+	#[error("Could not parse RON: {0}")]
+	Ron(#[from] ron::de::SpannedError),
+}
+impl AssetLoader for PrefabLoader {
+	type Asset = Prefab;
+	type Settings = ();
+	type Error = PrefabLoaderError;
+
+	async fn load(
+		&self,
+		reader: &mut dyn Reader,
+		_settings: &Self::Settings,
+		_load_context: &mut LoadContext<'_>,
+	) -> Result<Self::Asset, Self::Error> {
+		let mut bytes = Vec::new();
+		reader.read_to_end(&mut bytes).await?;
+		let prefab = ron::de::from_bytes::<Prefab>(&bytes)?;
+		Ok(prefab)
+	}
+
+	fn extensions(&self) -> &[&str] {
+		// more specific than the "ron" claimed by the player loader
+		&["prefab.ron"]
+	}
+}
@@ -0,0 +1,87 @@
+use crate::level::PlayStats;
+use crate::util::GameClock;
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::fmt;
+
+/// Sent when a player reaches the end of the current level, which brings up the results screen
+#[derive(Event, Debug)]
+pub struct LevelCompleteEvent {
+	pub player: Entity,
+}
+
+/// How a run through a level went, as graded on the results screen
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LevelResults {
+	/// Gameplay seconds from the start of the attempt to the end of the level
+	pub time: f32,
+	pub deaths: u32,
+	pub collectibles: u32,
+}
+
+impl LevelResults {
+	/// The results of the attempt tracked by `stats`, ending now
+	pub fn from_stats(stats: &PlayStats, clock: &GameClock) -> Self {
+		LevelResults {
+			time: stats.attempt_secs(clock),
+			deaths: stats.deaths,
+			collectibles: stats.collectibles,
+		}
+	}
+}
+
+/// The grade given to a [LevelResults], from best to worst
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rank {
+	S,
+	A,
+	B,
+	C,
+}
+
+impl fmt::Display for Rank {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(self, f)
+	}
+}
+
+/// What a run has to achieve to earn each rank in a level. Runs that don't meet the
+/// requirement for B are ranked C.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RankThresholds {
+	pub s: RankRequirement,
+	pub a: RankRequirement,
+	pub b: RankRequirement,
+}
+
+impl RankThresholds {
+	/// The best rank whose requirement the `results` meet
+	pub fn grade(&self, results: &LevelResults) -> Rank {
+		[(Rank::S, &self.s), (Rank::A, &self.a), (Rank::B, &self.b)]
+			.into_iter()
+			.find(|(_, requirement)| requirement.is_met(results))
+			.map_or(Rank::C, |(rank, _)| rank)
+	}
+}
+
+/// The requirement for one rank in [RankThresholds]. Limits that are left out don't apply.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RankRequirement {
+	/// Longest the level can take, in seconds
+	#[serde(default)]
+	pub max_time: Option<f32>,
+	#[serde(default)]
+	pub max_deaths: Option<u32>,
+	#[serde(default)]
+	pub min_collectibles: u32,
+}
+
+impl RankRequirement {
+	pub fn is_met(&self, results: &LevelResults) -> bool {
+		self.max_time.is_none_or(|max| results.time <= max)
+			&& self.max_deaths.is_none_or(|max| results.deaths <= max)
+			&& results.collectibles >= self.min_collectibles
+	}
+}
@@ -0,0 +1,160 @@
+use crate::enemies::Enemy;
+use crate::level::{Collectible, CurrentLevel, LevelAsset, LevelFixture, LevelPlatform, LevelProp};
+use crate::player::{Player, PlayerSlot, TutorialGhost};
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::RigidBodyDisabled;
+use serde::Deserialize;
+
+/// A rectangular region of a [LevelAsset]. The camera stays within the room the player is in, and
+/// the level's entities in other rooms are frozen until the player gets there. Some level state
+/// (e.g. broken floors) only resets once the player has left the room it happened in.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoomDef {
+	pub id: String,
+	pub min: Vec2,
+	pub max: Vec2,
+	/// How the camera gets here from the previous room
+	#[serde(default)]
+	pub transition: RoomTransition,
+}
+
+/// How the camera moves into a room when the player crosses into it
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum RoomTransition {
+	/// The camera pans over from the previous room, with the game paused until it arrives
+	#[default]
+	Pan,
+	/// The camera cuts straight to the new room
+	Snap,
+}
+
+impl RoomDef {
+	pub fn contains(&self, point: Vec2) -> bool {
+		Rect::from_corners(self.min, self.max).contains(point)
+	}
+
+	/// Where to center a camera that sees `view_size` of the world, so that it stays as close to
+	/// `focus` as it can without showing anything outside the room. Along axes where the room is
+	/// smaller than the view, the room is centered instead.
+	pub fn camera_center(&self, focus: Vec2, view_size: Vec2) -> Vec2 {
+		let center = (self.min + self.max) * 0.5;
+		// where the room is too small, both bounds collapse onto its center
+		let min = (self.min + view_size * 0.5).min(center);
+		let max = (self.max - view_size * 0.5).max(center);
+		focus.clamp(min, max)
+	}
+}
+
+impl LevelAsset {
+	/// Finds the room containing the given point, if any
+	pub fn room_at(&self, point: Vec2) -> Option<&RoomDef> {
+		self.rooms.iter().find(|room| room.contains(point))
+	}
+}
+
+/// The id of the room the lead player is currently in, which is the only room that's active.
+///
+/// In co-op, the other players follow the lead player around rather than having rooms of their own.
+/// Wandering into another room, they can stand on its (frozen) platforms, but nothing else there
+/// reacts to them until the lead player arrives: no hazards, pickups, springs, zones, or enemies.
+#[derive(Resource, Debug, Default)]
+pub struct CurrentRoom(pub Option<String>);
+
+/// Sent when the player moves from one room to another
+#[derive(Event, Debug)]
+pub struct RoomChanged {
+	pub left: Option<String>,
+	pub entered: Option<String>,
+}
+
+/// Keeps the [CurrentRoom] up to date with the lead player's position
+pub fn track_current_room(
+	players: Query<(&PlayerSlot, &Transform), (With<Player>, Without<TutorialGhost>)>,
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	mut current_room: ResMut<CurrentRoom>,
+	mut events: EventWriter<RoomChanged>,
+) {
+	let Some(level) = current_level.and_then(|level| levels.get(level.0.id())) else {
+		return;
+	};
+	let Some((_, player_transform)) = players.iter().find(|(slot, _)| **slot == PlayerSlot::LEAD) else {
+		return;
+	};
+	let room = level
+		.room_at(player_transform.translation.truncate())
+		.map(|room| room.id.clone());
+	if room != current_room.0 {
+		info!("player moved from room {:?} to {:?}", current_room.0, room);
+		let left = std::mem::replace(&mut current_room.0, room.clone());
+		events.send(RoomChanged { left, entered: room });
+	}
+}
+
+/// Marks one of the level's entities (a platform, prop, zone, collectible, or enemy) that is frozen
+/// because it's in a room other than the [CurrentRoom]. Dynamic props have their rigid body disabled
+/// as well. Frozen platforms still block the players, while everything else ignores them.
+#[derive(Component, Debug)]
+pub struct RoomInactive;
+
+/// Freezes the level's entities outside the [CurrentRoom], and wakes up the ones inside it.
+/// Platforms and props go by the room their definition puts them in, so a moving platform belongs to
+/// the room it starts in, and a prop to the room it was placed in. Zones, collectibles, and enemies
+/// don't move, and go by where they are. Anything outside every room is always active, and so is
+/// everything while the player isn't in any room.
+pub fn activate_current_room(
+	mut commands: Commands,
+	mut room_events: EventReader<RoomChanged>,
+	current_room: Res<CurrentRoom>,
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	spawned: Query<
+		(),
+		Or<(
+			Changed<LevelPlatform>,
+			Changed<LevelProp>,
+			Added<LevelFixture>,
+			Added<Collectible>,
+			Added<Enemy>,
+		)>,
+	>,
+	platforms: Query<(Entity, &LevelPlatform, Has<RoomInactive>)>,
+	props: Query<(Entity, &LevelProp, Has<RoomInactive>)>,
+	fixtures: Query<(Entity, &Transform, Has<RoomInactive>), Or<(With<LevelFixture>, With<Collectible>, With<Enemy>)>>,
+) {
+	let room_changed = room_events.read().count() > 0;
+	if !room_changed && spawned.is_empty() {
+		return;
+	}
+	let Some(level) = current_level.and_then(|level| levels.get(level.0.id())) else {
+		return;
+	};
+	let is_active = |pos: Vec2| match (&current_room.0, level.room_at(pos)) {
+		(Some(current), Some(room)) => room.id == *current,
+		_ => true,
+	};
+
+	let platforms = platforms
+		.iter()
+		.map(|(entity, LevelPlatform(def), inactive)| (entity, def.pos, inactive, false));
+	let props = props
+		.iter()
+		.map(|(entity, LevelProp(def), inactive)| (entity, def.pos, inactive, true));
+	let fixtures = fixtures
+		.iter()
+		.map(|(entity, transform, inactive)| (entity, transform.translation.truncate(), inactive, false));
+	for (entity, pos, inactive, is_prop) in platforms.chain(props).chain(fixtures) {
+		let active = is_active(pos);
+		if active != inactive {
+			continue;
+		}
+		let mut entity = commands.entity(entity);
+		match (active, is_prop) {
+			(true, false) => entity.remove::<RoomInactive>(),
+			(true, true) => entity.remove::<(RoomInactive, RigidBodyDisabled)>(),
+			(false, false) => entity.insert(RoomInactive),
+			(false, true) => entity.insert((RoomInactive, RigidBodyDisabled)),
+		};
+	}
+}
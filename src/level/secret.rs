@@ -0,0 +1,121 @@
+use crate::companion::CompanionInterest;
+use crate::level::{CurrentLevel, LevelAsset, PlayStats};
+use crate::player::{Player, TutorialGhost};
+use crate::util::{FrameCount, Tween, TweenTarget};
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// Draw order of secret covers: in front of the player, so they're hidden while inside
+const SECRET_COVER_Z: f32 = 0.8;
+
+/// How long a secret's cover takes to fade away
+const REVEAL_FADE_DURATION: FrameCount = FrameCount(30);
+
+/// How close the companion has to get to an undiscovered secret to point it out
+const COMPANION_NOTICE_RADIUS: f32 = 15.0;
+
+/// A hidden area of a [LevelAsset], covered by a fake wall that fades away once the player
+/// steps inside. The fake wall has no collider; it only hides whatever is behind it.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SecretDef {
+	/// Identifies the secret in the [PlayStats]. Must be unique within the level.
+	pub id: String,
+	pub min: Vec2,
+	pub max: Vec2,
+	/// sRGB color components of the fake wall, usually matching the real walls around it
+	pub color: (f32, f32, f32),
+}
+
+/// The fake wall covering a secret area, which also acts as the trigger volume for finding it
+#[derive(Component, Debug)]
+pub struct SecretCover {
+	pub id: String,
+	pub area: Rect,
+	/// Set once the player has entered the area, after which the cover fades away
+	pub revealed: bool,
+}
+
+/// Sent the first time the player finds a secret
+#[derive(Event, Debug)]
+pub struct SecretDiscovered {
+	pub id: String,
+}
+
+/// Respawns the current level's secret covers whenever it loads or is modified.
+/// Secrets that were already found stay uncovered.
+pub fn sync_secrets_system(
+	mut commands: Commands,
+	mut events: EventReader<AssetEvent<LevelAsset>>,
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	stats: Res<PlayStats>,
+	existing: Query<Entity, With<SecretCover>>,
+) {
+	let Some(current_level) = current_level else {
+		return;
+	};
+	let current_id = current_level.0.id();
+	let needs_sync = events.read().any(|event| match event {
+		AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => *id == current_id,
+		_ => false,
+	});
+	if !needs_sync {
+		return;
+	}
+	let Some(level) = levels.get(current_id) else {
+		return;
+	};
+
+	for entity in &existing {
+		commands.entity(entity).despawn();
+	}
+	for def in &level.secrets {
+		let area = Rect::from_corners(def.min, def.max);
+		let found = stats.secrets_found.contains(&def.id);
+		let (r, g, b) = def.color;
+		let mut cover = commands.spawn((
+			SecretCover {
+				id: def.id.clone(),
+				area,
+				revealed: found,
+			},
+			Sprite::from_color(Color::srgba(r, g, b, if found { 0.0 } else { 1.0 }), area.size()),
+			Transform::from_translation(area.center().extend(SECRET_COVER_Z)),
+		));
+		if !found {
+			cover.insert(CompanionInterest {
+				radius: COMPANION_NOTICE_RADIUS,
+			});
+		}
+	}
+}
+
+/// Reveals a secret once a player's center enters its area, recording the discovery in the
+/// [PlayStats], then fades its cover away
+pub fn reveal_secrets(
+	mut commands: Commands,
+	players: Query<&Transform, (With<Player>, Without<TutorialGhost>)>,
+	mut covers: Query<(Entity, &mut SecretCover, &Sprite)>,
+	mut stats: ResMut<PlayStats>,
+	mut discovered: EventWriter<SecretDiscovered>,
+) {
+	for (entity, mut cover, sprite) in &mut covers {
+		let entered = players
+			.iter()
+			.any(|transform| cover.area.contains(transform.translation.truncate()));
+		if cover.revealed || !entered {
+			continue;
+		}
+		cover.revealed = true;
+		commands.entity(entity).remove::<CompanionInterest>().insert(Tween::new(
+			TweenTarget::sprite_alpha(sprite.color, 0.0),
+			REVEAL_FADE_DURATION,
+			EaseFunction::Linear,
+		));
+		if stats.secrets_found.insert(cover.id.clone()) {
+			info!("found secret {:?}", cover.id);
+			discovered.send(SecretDiscovered { id: cover.id.clone() });
+		}
+	}
+}
@@ -0,0 +1,55 @@
+use crate::level::RestartLevel;
+use crate::player::PlayerDied;
+use crate::util::GameClock;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+/// Tallies of what the player has done in the current level, to be saved and shown back to them
+#[derive(Resource, Debug, Default)]
+pub struct PlayStats {
+	/// Ids of the [SecretDef](crate::level::SecretDef)s the player has found
+	pub secrets_found: HashSet<String>,
+	/// Deaths since the current attempt at the level started
+	pub deaths: u32,
+	/// Collectibles picked up since the current attempt at the level started
+	pub collectibles: u32,
+	/// Ids of the [CollectibleDef](crate::level::CollectibleDef)s picked up during the current attempt
+	pub collected: HashSet<String>,
+	/// What the collectibles picked up during the current attempt are worth, all together
+	pub score: u32,
+	/// The [GameClock]'s `elapsed_secs` when the current attempt at the level started
+	pub attempt_started_at: f32,
+}
+
+impl PlayStats {
+	/// Starts a new attempt at the level. Secrets stay found, since they're discoveries about
+	/// the level rather than part of any one run through it.
+	pub fn start_attempt(&mut self, clock: &GameClock) {
+		self.deaths = 0;
+		self.collectibles = 0;
+		self.collected.clear();
+		self.score = 0;
+		self.attempt_started_at = clock.elapsed_secs();
+	}
+
+	/// Gameplay seconds spent on the current attempt so far
+	pub fn attempt_secs(&self, clock: &GameClock) -> f32 {
+		(clock.elapsed_secs() - self.attempt_started_at).max(0.0)
+	}
+}
+
+/// Counts deaths towards the current attempt, and starts a new attempt whenever the level restarts
+pub fn track_play_stats(
+	mut died_events: EventReader<PlayerDied>,
+	mut restart_events: EventReader<RestartLevel>,
+	mut stats: ResMut<PlayStats>,
+	clock: Res<GameClock>,
+) {
+	let deaths = died_events.read().count() as u32;
+	if deaths > 0 {
+		stats.deaths += deaths;
+	}
+	if restart_events.read().count() > 0 {
+		stats.start_attempt(&clock);
+	}
+}
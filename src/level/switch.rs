@@ -0,0 +1,184 @@
+use crate::hazards::collider_area;
+use crate::level::{GroupAction, LevelGroupCommand, RestartLevel, RoomInactive};
+use crate::player::Player;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{Collider, RigidBody};
+use serde::Deserialize;
+
+/// How far above a switch something can be and still count as resting on it
+const PRESS_HEIGHT: f32 = 0.25;
+
+/// A switch in a [LevelAsset](crate::level::LevelAsset): a plate that flips its targets when something
+/// rests on it, whether that's a player or a dynamic body (like the ball)
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SwitchDef {
+	/// Identifies the switch in its [SwitchToggledEvent]s. Must be unique within the level.
+	pub id: String,
+	pub pos: Vec2,
+	pub size: Vec2,
+	#[serde(default)]
+	pub kind: SwitchKind,
+	/// What happens to the level when the switch is turned on. Turning it back off undoes it.
+	pub targets: Vec<SwitchTarget>,
+}
+
+/// How a switch responds to things resting on it
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum SwitchKind {
+	/// On for as long as something rests on it
+	#[default]
+	PressurePlate,
+	/// Flips between on and off each time something lands on it
+	Toggle,
+}
+
+/// A group of platforms that responds to a switch, e.g. a door that opens while a pressure plate is held
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SwitchTarget {
+	/// Id of the [GroupDef](crate::level::GroupDef)
+	pub group: String,
+	pub response: SwitchResponse,
+}
+
+/// What a [SwitchTarget]'s group does while its switch is on
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize)]
+pub enum SwitchResponse {
+	/// The group is switched on, e.g. a bridge appearing
+	Appear,
+	/// The group is switched off, e.g. a door opening
+	Disappear,
+	/// The group moves this far from where the level puts it
+	Move(Vec2),
+}
+
+impl SwitchResponse {
+	/// The [GroupAction] that puts the group in the right state for its switch being `on` or off
+	fn action(self, on: bool) -> GroupAction {
+		match (self, on) {
+			(SwitchResponse::Appear, true) | (SwitchResponse::Disappear, false) => GroupAction::Enable,
+			(SwitchResponse::Appear, false) | (SwitchResponse::Disappear, true) => GroupAction::Disable,
+			(SwitchResponse::Move(offset), true) => GroupAction::MoveTo(offset),
+			(SwitchResponse::Move(_), false) => GroupAction::MoveTo(Vec2::ZERO),
+		}
+	}
+}
+
+/// A switch spawned from a [SwitchDef]
+#[derive(Component, Debug)]
+pub struct Switch {
+	pub id: String,
+	pub kind: SwitchKind,
+	pub targets: Vec<SwitchTarget>,
+	/// Whether something was resting on the switch as of the latest fixed update
+	occupied: bool,
+	on: bool,
+}
+
+impl Switch {
+	pub fn new(def: &SwitchDef) -> Self {
+		Switch {
+			id: def.id.clone(),
+			kind: def.kind,
+			targets: def.targets.clone(),
+			occupied: false,
+			on: false,
+		}
+	}
+
+	fn color(&self) -> Color {
+		if self.on {
+			Color::srgb(0.3, 0.8, 0.3)
+		} else {
+			Color::srgb(0.6, 0.3, 0.25)
+		}
+	}
+}
+
+/// Sent when a switch is turned on or off
+#[derive(Event, Debug)]
+pub struct SwitchToggledEvent {
+	pub switch: Entity,
+	pub id: String,
+	pub on: bool,
+}
+
+/// The bundle for a switch spawned from the given definition
+pub fn switch_bundle(def: &SwitchDef) -> impl Bundle {
+	let switch = Switch::new(def);
+	(
+		Sprite::from_color(switch.color(), def.size),
+		switch,
+		Collider::cuboid(def.size.x * 0.5, def.size.y * 0.5),
+		Transform::from_translation(def.pos.extend(0.0)),
+	)
+}
+
+/// Turns switches on and off according to what's resting on them. Should run after `player_system`,
+/// so the players have landed on (or stepped off) the switches.
+pub fn press_switches(
+	bodies: Query<(&Transform, &Collider, Option<&RigidBody>, Has<Player>), Without<Switch>>,
+	mut switches: Query<(Entity, &mut Switch, &Transform, &Collider, &mut Sprite), Without<RoomInactive>>,
+	mut toggled_events: EventWriter<SwitchToggledEvent>,
+) {
+	for (entity, mut switch, transform, collider, mut sprite) in &mut switches {
+		let Some(area) = collider_area(transform, collider) else {
+			continue;
+		};
+		let top = Rect::new(area.min.x, area.max.y, area.max.x, area.max.y + PRESS_HEIGHT);
+		let occupied = bodies
+			.iter()
+			.filter(|(.., rigid_body, is_player)| *is_player || *rigid_body == Some(&RigidBody::Dynamic))
+			.filter_map(|(transform, collider, ..)| collider_area(transform, collider))
+			.any(|body_area| !body_area.intersect(top).is_empty());
+		let landed = occupied && !switch.occupied;
+		switch.occupied = occupied;
+		let on = match switch.kind {
+			SwitchKind::PressurePlate => occupied,
+			SwitchKind::Toggle => switch.on != landed,
+		};
+		if on == switch.on {
+			continue;
+		}
+		switch.on = on;
+		sprite.color = switch.color();
+		debug!("switch {:?} turned {}", switch.id, if on { "on" } else { "off" });
+		toggled_events.send(SwitchToggledEvent {
+			switch: entity,
+			id: switch.id.clone(),
+			on,
+		});
+	}
+}
+
+/// Applies each switch's [SwitchTarget]s whenever it's turned on or off
+pub fn apply_switch_targets(
+	mut toggled_events: EventReader<SwitchToggledEvent>,
+	switches: Query<&Switch>,
+	mut group_commands: EventWriter<LevelGroupCommand>,
+) {
+	for event in toggled_events.read() {
+		let Ok(switch) = switches.get(event.switch) else {
+			continue;
+		};
+		for target in &switch.targets {
+			group_commands.send(LevelGroupCommand {
+				group: target.group.clone(),
+				action: target.response.action(event.on),
+			});
+		}
+	}
+}
+
+/// Turns every switch back off when the level restarts, along with the groups they control
+pub fn reset_switches(mut restart_events: EventReader<RestartLevel>, mut switches: Query<(&mut Switch, &mut Sprite)>) {
+	if restart_events.read().count() == 0 {
+		return;
+	}
+	for (mut switch, mut sprite) in &mut switches {
+		switch.occupied = false;
+		switch.on = false;
+		sprite.color = switch.color();
+	}
+}
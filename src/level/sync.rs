@@ -0,0 +1,352 @@
+use crate::WallArgs;
+use crate::audio::SoundEmitter;
+use crate::decor::{AmbientPropArgs, NoDecorations};
+use crate::hazards::Hazard;
+use crate::level::{LevelAsset, PlatformDef, PropDef, TileDef, ZoneDef, ZoneKind, switch_bundle};
+use crate::platforms::{
+	BreakableBlock, BreakableFloor, LockedDoor, MovingPlatform, OneWayPlatform, PlatformMotion, Spring, StickyPlatform,
+	SurfaceMaterial, WallMaterial, WallSurface, Zipline,
+};
+use crate::player::{Checkpoint, Player, PlayerSlot, PlayerSpawnPoint, ReturnToCheckpoint, TutorialGhost};
+use crate::save::SaveFile;
+use crate::util::{PhysicsPosition, Tween};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier2d::prelude::{
+	ActiveEvents, ActiveHooks, Collider, ColliderDisabled, CollisionGroups, RigidBody, Sensor,
+};
+
+/// The level that is currently being played
+#[derive(Resource, Debug)]
+pub struct CurrentLevel(pub Handle<LevelAsset>);
+
+/// Tiles are drawn this far back, behind the platforms
+const TILES_Z: f32 = -0.5;
+
+/// How much further forward each step of a [TileDef]'s depth moves it
+const TILE_DEPTH_STEP: f32 = 0.01;
+
+/// Zones are drawn in front of everything else, tinting whatever is inside them
+const ZONES_Z: f32 = 0.6;
+
+const ZIPLINES_Z: f32 = 0.4;
+
+/// Thickness of the line drawn for a zipline
+const ZIPLINE_THICKNESS: f32 = 0.25;
+
+/// How far apart the players start, side by side, in co-op
+pub(crate) const PLAYER_START_SPACING: f32 = 6.0;
+
+/// Marks an entity spawned from a [PlatformDef], remembering the definition it was spawned
+/// from so that hot reloads can tell which platforms actually changed
+#[derive(Component, Debug)]
+pub struct LevelPlatform(pub PlatformDef);
+
+/// Marks a sprite spawned from one of the level's [TileDef]s
+#[derive(Component, Debug)]
+pub struct LevelTile;
+
+/// Marks an entity spawned from one of the level's zones, ziplines, or switches
+#[derive(Component, Debug)]
+pub struct LevelFixture;
+
+/// Marks a prop spawned from one of the level's [PropDef]s
+#[derive(Component, Debug)]
+pub struct LevelProp(pub PropDef);
+
+/// Spawns the current level's entities when it loads, and applies a diff whenever the level
+/// file is modified: new platforms are spawned, removed ones are despawned, and changed ones
+/// are updated in place. Props are only respawned when the level's props change, so they stay
+/// wherever they've been kicked to otherwise. Anything not spawned from the level (the player,
+/// other dynamic bodies) is left untouched, so level tweaks can be made while playing.
+pub fn sync_level_system(
+	mut commands: Commands,
+	mut events: EventReader<AssetEvent<LevelAsset>>,
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	asset_server: Res<AssetServer>,
+	existing: Query<(Entity, &LevelPlatform)>,
+	tiles: Query<Entity, With<LevelTile>>,
+	fixtures: Query<Entity, With<LevelFixture>>,
+	props: Query<(Entity, &LevelProp)>,
+) {
+	let Some(current_level) = current_level else {
+		return;
+	};
+	let current_id = current_level.0.id();
+	let needs_sync = events.read().any(|event| match event {
+		AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => *id == current_id,
+		_ => false,
+	});
+	if !needs_sync {
+		return;
+	}
+	let Some(level) = levels.get(current_id) else {
+		return;
+	};
+
+	let mut wanted: HashMap<&str, &PlatformDef> = level.platforms.iter().map(|def| (def.id.as_str(), def)).collect();
+	let (mut added, mut changed, mut removed) = (0, 0, 0);
+
+	for (entity, LevelPlatform(current_def)) in &existing {
+		match wanted.remove(current_def.id.as_str()) {
+			None => {
+				commands.entity(entity).despawn_recursive();
+				removed += 1;
+			}
+			Some(def) if def != current_def => {
+				apply_platform_def(commands.entity(entity), def);
+				changed += 1;
+			}
+			Some(_) => (),
+		}
+	}
+	for def in wanted.into_values() {
+		apply_platform_def(commands.spawn_empty(), def);
+		added += 1;
+	}
+
+	// tiles don't have any state worth keeping, so they're simply rebuilt
+	for tile in &tiles {
+		commands.entity(tile).despawn();
+	}
+	for def in &level.tiles {
+		commands.spawn(tile_bundle(def, &asset_server));
+	}
+
+	// nor do zones, ziplines, and switches
+	for fixture in &fixtures {
+		commands.entity(fixture).despawn();
+	}
+	for def in &level.zones {
+		spawn_zone(&mut commands, def, &asset_server);
+	}
+	for zipline in &level.ziplines {
+		commands.spawn((
+			LevelFixture,
+			*zipline,
+			Sprite::from_color(
+				Color::srgb(0.4, 0.35, 0.3),
+				Vec2::new(zipline.length(), ZIPLINE_THICKNESS),
+			),
+			zipline.transform(ZIPLINES_Z),
+		));
+	}
+	for def in &level.switches {
+		commands.spawn((LevelFixture, switch_bundle(def)));
+	}
+
+	if !props.iter().map(|(_, LevelProp(def))| def).eq(&level.props) {
+		for (prop, _) in &props {
+			commands.entity(prop).despawn();
+		}
+		for def in &level.props {
+			let prop = AmbientPropArgs {
+				kind: def.kind,
+				pos: def.pos,
+				impact_sound: None,
+			}
+			.spawn(&mut commands);
+			commands.entity(prop).insert(LevelProp(def.clone()));
+		}
+	}
+
+	info!(
+		"synced level: {} added, {} changed, {} removed",
+		added, changed, removed
+	);
+}
+
+fn tile_bundle(def: &TileDef, asset_server: &AssetServer) -> impl Bundle {
+	(
+		LevelTile,
+		Sprite {
+			image: asset_server.load(&def.tileset),
+			rect: Some(def.src),
+			custom_size: Some(def.size),
+			flip_x: def.flip_x,
+			flip_y: def.flip_y,
+			..default()
+		},
+		Transform::from_translation(def.pos.extend(TILES_Z + def.depth * TILE_DEPTH_STEP)),
+	)
+}
+
+fn spawn_zone(commands: &mut Commands, def: &ZoneDef, asset_server: &AssetServer) {
+	let (r, g, b, a) = def.color;
+	let mut zone = commands.spawn((
+		LevelFixture,
+		Sensor,
+		Sprite::from_color(Color::srgba(r, g, b, a), def.size),
+		Collider::cuboid(def.size.x * 0.5, def.size.y * 0.5),
+		Transform::from_translation(def.pos.extend(ZONES_Z)),
+	));
+	match &def.kind {
+		ZoneKind::Water(water) => zone.insert(*water),
+		ZoneKind::Wind(wind) => zone.insert(*wind),
+		ZoneKind::Gravity(gravity) => zone.insert(*gravity),
+		ZoneKind::Hazard(hazard) => zone.insert(*hazard),
+		ZoneKind::Checkpoint => zone.insert(Checkpoint),
+		ZoneKind::Exit(exit) => zone.insert(exit.clone()),
+		ZoneKind::Trigger(trigger) => zone.insert(trigger.clone()),
+	};
+	if let Some(sound) = &def.sound {
+		zone.insert(SoundEmitter {
+			sound: asset_server.load(&sound.sound),
+			volume: sound.volume,
+			audible_radius: sound.audible_radius,
+		});
+	}
+}
+
+/// Moves the players to the level's `player_start` (if it has one) when it loads, side by side in co-op.
+/// Players picking up from a checkpoint in the save stay where the save put them.
+pub fn place_players_at_start(
+	mut events: EventReader<AssetEvent<LevelAsset>>,
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	asset_server: Res<AssetServer>,
+	save: Res<SaveFile>,
+	mut players: Query<
+		(
+			&PlayerSlot,
+			&mut PlayerSpawnPoint,
+			&mut Transform,
+			Option<&mut PhysicsPosition>,
+		),
+		(With<Player>, Without<TutorialGhost>),
+	>,
+) {
+	let Some(current_level) = current_level else {
+		return;
+	};
+	let current_id = current_level.0.id();
+	let loaded = events
+		.read()
+		.any(|event| matches!(event, AssetEvent::LoadedWithDependencies { id } if *id == current_id));
+	if !loaded {
+		return;
+	}
+	let Some(start) = levels.get(current_id).and_then(|level| level.player_start) else {
+		return;
+	};
+	let level_path = asset_server.get_path(current_id).map(|path| path.to_string());
+	if save.data.checkpoint.is_some() && save.data.level.is_some() && save.data.level == level_path {
+		return;
+	}
+	for (slot, mut spawn_point, mut transform, physics_position) in &mut players {
+		let position = start + Vec2::X * PLAYER_START_SPACING * slot.0 as f32;
+		spawn_point.0 = position;
+		transform.translation = position.extend(transform.translation.z);
+		// otherwise the next fixed update would put them back where they were
+		if let Some(mut physics_position) = physics_position {
+			physics_position.snap(&transform);
+		}
+	}
+}
+
+/// Sent to put the current level back the way it was when it loaded
+#[derive(Event, Debug)]
+pub struct RestartLevel;
+
+/// Rebuilds every platform from its definition (un-breaking broken floors and so on),
+/// and sends each player back to their spawn point
+pub fn restart_level_system(
+	mut commands: Commands,
+	mut events: EventReader<RestartLevel>,
+	existing: Query<(Entity, &LevelPlatform)>,
+	players: Query<Entity, (With<Player>, Without<TutorialGhost>)>,
+	mut returns: EventWriter<ReturnToCheckpoint>,
+) {
+	if events.read().count() == 0 {
+		return;
+	}
+	info!("restarting level");
+	for (entity, LevelPlatform(def)) in &existing {
+		apply_platform_def(commands.entity(entity), def);
+	}
+	for player in &players {
+		returns.send(ReturnToCheckpoint(player));
+	}
+}
+
+/// Makes the given entity match the platform definition, whether it was just spawned or
+/// was previously spawned from a different version of the definition
+fn apply_platform_def(mut entity: EntityCommands, def: &PlatformDef) {
+	let (r, g, b) = def.color;
+	entity.insert((
+		LevelPlatform(def.clone()),
+		WallArgs {
+			color: Color::srgb(r, g, b),
+			pos: def.pos,
+			size: def.size,
+		}
+		.bundle(),
+	));
+
+	// decorations are re-scattered whenever the collider changes
+	entity.despawn_descendants();
+
+	if def.one_way {
+		entity.insert(OneWayPlatform);
+	} else {
+		entity.remove::<(OneWayPlatform, CollisionGroups, ActiveHooks)>();
+	}
+	match def.surface {
+		Some(material) => entity.insert(material),
+		None => entity.remove::<SurfaceMaterial>(),
+	};
+	match def.wall {
+		Some(wall) => entity.insert(wall),
+		None => entity.remove::<WallSurface>(),
+	};
+	match def.wall_material {
+		Some(material) => entity.insert(material),
+		None => entity.remove::<WallMaterial>(),
+	};
+	match def.spring {
+		Some(spring) => entity.insert((spring, ActiveEvents::COLLISION_EVENTS)),
+		None => entity.remove::<(Spring, ActiveEvents)>(),
+	};
+	if def.decorate {
+		entity.remove::<NoDecorations>();
+	} else {
+		entity.insert(NoDecorations);
+	}
+	// (re)building a breakable floor or block always leaves it intact
+	entity.remove::<ColliderDisabled>().insert(Visibility::Inherited);
+	match &def.breakable {
+		Some(breakable) => entity.insert(BreakableFloor::new(breakable.crack_time, breakable.respawn_time)),
+		None => entity.remove::<BreakableFloor>(),
+	};
+	match def.block {
+		Some(block) => entity.insert(block),
+		None => entity.remove::<BreakableBlock>(),
+	};
+	// the wall bundle makes the platform fixed, which moving ones can't be
+	match def.path.as_ref().filter(|path| !path.waypoints.is_empty()) {
+		Some(path) => {
+			let waypoints = std::iter::once(def.pos)
+				.chain(path.waypoints.iter().map(|&offset| def.pos + offset))
+				.collect();
+			entity.insert((
+				MovingPlatform::new(waypoints, path.speed, path.easing, path.mode),
+				RigidBody::KinematicPositionBased,
+			))
+		}
+		None => entity.remove::<(MovingPlatform, PlatformMotion)>(),
+	};
+	match def.sticky {
+		Some(sticky) => entity.insert(sticky),
+		None => entity.remove::<StickyPlatform>(),
+	};
+	match def.hazard {
+		Some(hazard) => entity.insert(hazard),
+		None => entity.remove::<Hazard>(),
+	};
+	// (re)building a door always leaves it locked, and the reset above has already made it solid again
+	match &def.lock {
+		Some(key) => entity.remove::<Tween>().insert(LockedDoor::new(key.clone())),
+		None => entity.remove::<LockedDoor>(),
+	};
+}
@@ -0,0 +1,700 @@
+use crate::hazards::Hazard;
+use crate::level::{
+	CollectibleDef, CollectibleKind, HAZARD_ZONE_COLOR, LevelAsset, LevelExit, PathDef, PixelSpace, PlatformDef,
+	TileDef, ZoneDef, ZoneKind, merge_cells, parse_hex_color,
+};
+use crate::platforms::PathMode;
+use crate::zones::TriggerZone;
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AssetPath, LoadContext, ReadAssetBytesError};
+use bevy::log::warn;
+use bevy::math::{EaseFunction, Rect, Vec2};
+use bevy::utils::{HashMap, HashSet};
+use roxmltree::{Document, Node};
+use serde::de::DeserializeOwned;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// How big one tile of the map is in the game's units
+const UNITS_PER_TILE: f32 = 2.0;
+
+/// The object (by name or class) marking where the players start
+const PLAYER_START_OBJECT: &str = "PlayerStart";
+
+/// The class of objects that become platforms, which is also what unclassed rectangles become
+const PLATFORM_CLASS: &str = "Platform";
+
+/// The class of rectangle objects that become hazard zones
+const HAZARD_CLASS: &str = "Hazard";
+
+/// The class of rectangle objects that become exits to other levels
+const EXIT_CLASS: &str = "Exit";
+
+/// sRGBA color of exits
+const EXIT_ZONE_COLOR: (f32, f32, f32, f32) = (0.2, 0.4, 0.9, 0.4);
+
+/// The class of rectangle objects that become trigger zones, identified by the object's name
+const TRIGGER_CLASS: &str = "Trigger";
+
+/// The classes of objects that become collectibles, at the object's center
+const COIN_CLASS: &str = "Coin";
+const GEM_CLASS: &str = "Gem";
+const KEY_CLASS: &str = "Key";
+
+/// Color of platforms without a `color` property, on a layer without a color of its own
+const DEFAULT_COLOR: (f32, f32, f32) = (0.5, 0.5, 0.55);
+
+/// Speed of moving platforms without a `speed` property, in units per second
+const DEFAULT_PATH_SPEED: f32 = 8.0;
+
+// the top bits of a tile's global id say how it's flipped
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+const FLIP_FLAGS: u32 = 0xF000_0000;
+
+/// Loads a Tiled map (`.tmx`) as a [LevelAsset], so levels can be drawn in Tiled rather than written out
+/// by hand. Like `.level.ron` levels, they're hot-reloaded whenever the map is saved. Only orthogonal,
+/// finite maps are supported, with tile layers saved as CSV or XML.
+///
+/// - Rectangle objects without a class, or with the `Platform` class, become platforms. Their custom
+///   properties map onto the platform's settings:
+///   - `one_way` and `decorate` (bool), `group` and `lock` (string), and `color`
+///   - `surface`, `wall`, `wall_material`, `spring`, `breakable`, `block`, `sticky` and `hazard` (string),
+///     written as they would be in a `.level.ron` file, e.g. `Ice` or `(crack_time: 30)`
+///   - `path` (object), a polyline the platform travels along, starting from where it's placed, with
+///     `speed` (float), and `easing` and `path_mode` (string), e.g. `SineInOut` and `Loop`
+/// - Tiles from every tile layer are drawn behind the platforms.
+/// - Tile layers with the `collision` property (bool) also become platforms, merged into as few
+///   rectangles as possible and colored by the layer's `color` property. Tiles with the `one_way`
+///   property (bool) in their tileset become one-way platforms.
+/// - Rectangle objects with the `Hazard` class become zones that hurt the player, with the `damage` (int)
+///   and `knockback` (float) properties.
+/// - Rectangle objects with the `Exit` class take the players to the level at their `level` property
+///   (string, an asset path).
+/// - Rectangle objects with the `Trigger` class become invisible trigger zones, identified by their name.
+/// - Objects with the `Coin`, `Gem` or `Key` class (usually points) become collectibles. Keys are
+///   identified by their name, which is what the `lock` property of the doors they open refers to.
+/// - An object named or classed `PlayerStart` marks where the players start.
+///
+/// Other objects are skipped with a warning, as there isn't anything to spawn them as yet.
+#[derive(Default)]
+pub struct TiledLevelLoader;
+
+#[derive(Debug, Error)]
+pub enum TiledLevelLoaderError {
+	#[error("Could not load asset: {0}")]
+	Io(#[from] std::io::Error),
+
+	#[error("Could not load tileset: {0}")]
+	Tileset(#[from] ReadAssetBytesError),
+
+	#[error("Map is not valid UTF-8: {0}")]
+	Utf8(#[from] std::str::Utf8Error),
+
+	#[error("Could not parse Tiled map: {0}")]
+	Xml(#[from] roxmltree::Error),
+
+	#[error("Invalid Tiled map: {0}")]
+	Invalid(String),
+
+	#[error("Platform id {0:?} is used more than once")]
+	DuplicatePlatformId(String),
+}
+
+/// A tileset used by the map, which may have been saved inside the map or in its own `.tsx` file
+struct TiledTileset {
+	/// Global id of the tileset's first tile. Tiles in the map are numbered across all of its tilesets.
+	first_gid: u32,
+	/// Asset path of the tileset image
+	image: String,
+	tile_size: Vec2,
+	columns: u32,
+	spacing: f32,
+	margin: f32,
+	/// Tiles (by their id within the tileset) that make one-way platforms in collision layers
+	one_way_tiles: HashSet<u32>,
+}
+
+impl TiledTileset {
+	fn parse(node: Node, first_gid: u32, path: &AssetPath) -> Result<Self, TiledLevelLoaderError> {
+		let image = child(node, "image").ok_or_else(|| {
+			TiledLevelLoaderError::Invalid("tilesets made from a collection of images aren't supported".into())
+		})?;
+		let source = required_attribute::<String>(image, "source")?;
+		// Tiled saves 0 columns when the image is narrower than one tile
+		let columns = required_attribute(node, "columns")?;
+		if columns == 0 {
+			return Err(TiledLevelLoaderError::Invalid(format!(
+				"tileset image {:?} is narrower than one tile",
+				source
+			)));
+		}
+		Ok(TiledTileset {
+			first_gid,
+			image: path
+				.resolve_embed(&source)
+				.map_err(|err| TiledLevelLoaderError::Invalid(format!("bad tileset image path {:?}: {}", source, err)))?
+				.to_string(),
+			tile_size: Vec2::new(
+				required_attribute(node, "tilewidth")?,
+				required_attribute(node, "tileheight")?,
+			),
+			columns,
+			spacing: attribute_or(node, "spacing", 0.0)?,
+			margin: attribute_or(node, "margin", 0.0)?,
+			one_way_tiles: node
+				.children()
+				.filter(|tile| tile.has_tag_name("tile"))
+				.filter(|tile| {
+					parse_properties(*tile)
+						.get("one_way")
+						.is_some_and(|value| value == "true")
+				})
+				.map(|tile| required_attribute(tile, "id"))
+				.collect::<Result<_, _>>()?,
+		})
+	}
+
+	/// Pixel rectangle of the tile within the tileset image
+	fn src(&self, gid: u32) -> Rect {
+		let index = gid - self.first_gid;
+		let cell = Vec2::new((index % self.columns) as f32, (index / self.columns) as f32);
+		let min = Vec2::splat(self.margin) + cell * (self.tile_size + self.spacing);
+		Rect::from_corners(min, min + self.tile_size)
+	}
+}
+
+/// What a cell of a collision layer makes part of
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+enum CollisionCell {
+	#[default]
+	Empty,
+	Solid,
+	OneWay,
+}
+
+/// What an object in one of the map's object layers is drawn as
+enum ObjectShape {
+	Rectangle,
+	/// A polyline or polygon, with its points relative to the object's position
+	Path(Vec<Vec2>),
+	/// An ellipse, point, text, or tile
+	Other,
+}
+
+/// An object from one of the map's object layers
+struct TiledObject {
+	id: u32,
+	layer: String,
+	name: String,
+	class: String,
+	/// Top-left corner of the object (or its first point), in pixels
+	top_left: Vec2,
+	size: Vec2,
+	shape: ObjectShape,
+	/// Color of the object's layer, if it has one
+	layer_color: Option<(f32, f32, f32)>,
+	properties: HashMap<String, String>,
+}
+
+impl TiledObject {
+	fn parse(
+		node: Node,
+		layer: &str,
+		offset: Vec2,
+		layer_color: Option<(f32, f32, f32)>,
+	) -> Result<Self, TiledLevelLoaderError> {
+		let shape = if node.has_attribute("gid")
+			|| ["ellipse", "point", "text"]
+				.into_iter()
+				.any(|tag_name| child(node, tag_name).is_some())
+		{
+			ObjectShape::Other
+		} else if let Some(points) = child(node, "polyline").or_else(|| child(node, "polygon")) {
+			ObjectShape::Path(parse_points(&required_attribute::<String>(points, "points")?)?)
+		} else {
+			ObjectShape::Rectangle
+		};
+		let object = TiledObject {
+			id: required_attribute(node, "id")?,
+			layer: layer.to_string(),
+			name: node.attribute("name").unwrap_or_default().to_string(),
+			// Tiled calls this the object's "type" in maps saved before version 1.9
+			class: node
+				.attribute("class")
+				.or_else(|| node.attribute("type"))
+				.unwrap_or_default()
+				.to_string(),
+			top_left: Vec2::new(required_attribute(node, "x")?, required_attribute(node, "y")?) + offset,
+			size: Vec2::new(attribute_or(node, "width", 0.0)?, attribute_or(node, "height", 0.0)?),
+			shape,
+			layer_color,
+			properties: parse_properties(node),
+		};
+		if attribute_or(node, "rotation", 0.0)? != 0.0 {
+			warn!("ignoring the rotation of Tiled object {}", object.describe());
+		}
+		Ok(object)
+	}
+
+	fn describe(&self) -> String {
+		format!("{} ({:?} on layer {:?})", self.id, self.name, self.layer)
+	}
+
+	/// Parses the property with the given name, if the object has it
+	fn property<T: FromStr>(&self, name: &str) -> Result<Option<T>, TiledLevelLoaderError> {
+		self.properties
+			.get(name)
+			.map(|value| parse_value(value, &format!("{:?} property of object {}", name, self.describe())))
+			.transpose()
+	}
+
+	/// Reads a property written the same way the setting would be in a `.level.ron` file
+	fn ron_property<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, TiledLevelLoaderError> {
+		self.properties
+			.get(name)
+			.map(|value| {
+				ron::from_str(value).map_err(|err| {
+					TiledLevelLoaderError::Invalid(format!(
+						"object {} has a bad {:?} property: {}",
+						self.describe(),
+						name,
+						err
+					))
+				})
+			})
+			.transpose()
+	}
+
+	/// Converts the object into a platform, with its path (if it has one) looked up among the `paths`
+	fn platform(
+		&self,
+		paths: &HashMap<u32, Vec<Vec2>>,
+		space: &PixelSpace,
+	) -> Result<PlatformDef, TiledLevelLoaderError> {
+		let (pos, size) = space.rect(self.top_left, self.size);
+		// unset object properties are saved as 0
+		let path = match self.property::<u32>("path")?.filter(|&id| id != 0) {
+			Some(id) => {
+				let points = paths.get(&id).ok_or_else(|| {
+					TiledLevelLoaderError::Invalid(format!(
+						"object {} moves along object {}, which isn't a polyline or polygon",
+						self.describe(),
+						id
+					))
+				})?;
+				Some(PathDef {
+					waypoints: points[1..].iter().map(|&point| point - points[0]).collect(),
+					speed: self.property("speed")?.unwrap_or(DEFAULT_PATH_SPEED),
+					easing: self.ron_property("easing")?.unwrap_or(EaseFunction::Linear),
+					mode: self.ron_property("path_mode")?.unwrap_or(PathMode::PingPong),
+				})
+			}
+			None => None,
+		};
+		Ok(PlatformDef {
+			id: format!("{}_{}", self.layer, self.id),
+			pos,
+			size,
+			color: self
+				.properties
+				.get("color")
+				.and_then(|color| parse_tiled_color(color))
+				.or(self.layer_color)
+				.unwrap_or(DEFAULT_COLOR),
+			one_way: self.property("one_way")?.unwrap_or(false),
+			surface: self.ron_property("surface")?,
+			wall: self.ron_property("wall")?,
+			wall_material: self.ron_property("wall_material")?,
+			decorate: self.property("decorate")?.unwrap_or(true),
+			spring: self.ron_property("spring")?,
+			breakable: self.ron_property("breakable")?,
+			block: self.ron_property("block")?,
+			group: self.properties.get("group").cloned(),
+			path,
+			sticky: self.ron_property("sticky")?,
+			hazard: self.ron_property("hazard")?,
+			lock: self.property::<String>("lock")?.filter(|key| !key.is_empty()),
+		})
+	}
+
+	/// Converts the object into a zone that hurts the player
+	fn hazard_zone(&self, space: &PixelSpace) -> Result<ZoneDef, TiledLevelLoaderError> {
+		let (pos, size) = space.rect(self.top_left, self.size);
+		let default = Hazard::default();
+		Ok(ZoneDef {
+			kind: ZoneKind::Hazard(Hazard {
+				damage: self.property("damage")?.unwrap_or(default.damage),
+				knockback: self.property("knockback")?.unwrap_or(default.knockback),
+			}),
+			pos,
+			size,
+			color: HAZARD_ZONE_COLOR,
+			sound: None,
+		})
+	}
+
+	/// Converts the object into an exit to another level
+	fn exit_zone(&self, space: &PixelSpace) -> Result<ZoneDef, TiledLevelLoaderError> {
+		let (pos, size) = space.rect(self.top_left, self.size);
+		let level = self.property("level")?.ok_or_else(|| {
+			TiledLevelLoaderError::Invalid(format!("exit {} has no `level` property", self.describe()))
+		})?;
+		Ok(ZoneDef {
+			kind: ZoneKind::Exit(LevelExit { level, spawn: None }),
+			pos,
+			size,
+			color: EXIT_ZONE_COLOR,
+			sound: None,
+		})
+	}
+
+	/// Converts the object into an invisible trigger zone
+	fn trigger_zone(&self, space: &PixelSpace) -> Result<ZoneDef, TiledLevelLoaderError> {
+		if self.name.is_empty() {
+			return Err(TiledLevelLoaderError::Invalid(format!(
+				"trigger {} needs a name, for the game to refer to",
+				self.describe()
+			)));
+		}
+		let (pos, size) = space.rect(self.top_left, self.size);
+		Ok(ZoneDef {
+			kind: ZoneKind::Trigger(TriggerZone { id: self.name.clone() }),
+			pos,
+			size,
+			color: (0.0, 0.0, 0.0, 0.0),
+			sound: None,
+		})
+	}
+
+	/// Converts the object into a collectible of the given kind
+	fn collectible(&self, kind: CollectibleKind, space: &PixelSpace) -> Result<CollectibleDef, TiledLevelLoaderError> {
+		let id = match kind {
+			CollectibleKind::Key if self.name.is_empty() => {
+				return Err(TiledLevelLoaderError::Invalid(format!(
+					"key {} needs a name, for doors to refer to",
+					self.describe()
+				)));
+			}
+			CollectibleKind::Key => self.name.clone(),
+			_ => format!("{}_{}", self.layer, self.id),
+		};
+		Ok(CollectibleDef {
+			id,
+			kind,
+			pos: space.rect(self.top_left, self.size).0,
+		})
+	}
+}
+
+impl AssetLoader for TiledLevelLoader {
+	type Asset = LevelAsset;
+	type Settings = ();
+	type Error = TiledLevelLoaderError;
+
+	async fn load(
+		&self,
+		reader: &mut dyn Reader,
+		_settings: &Self::Settings,
+		load_context: &mut LoadContext<'_>,
+	) -> Result<Self::Asset, Self::Error> {
+		let mut bytes = Vec::new();
+		reader.read_to_end(&mut bytes).await?;
+		let document = Document::parse(std::str::from_utf8(&bytes)?)?;
+		let map = document.root_element();
+		if map.attribute("orientation") != Some("orthogonal") {
+			return Err(TiledLevelLoaderError::Invalid(
+				"only orthogonal maps are supported".into(),
+			));
+		}
+		if map.attribute("infinite") == Some("1") {
+			return Err(TiledLevelLoaderError::Invalid("infinite maps aren't supported".into()));
+		}
+		let tile_size = Vec2::new(
+			required_attribute(map, "tilewidth")?,
+			required_attribute(map, "tileheight")?,
+		);
+		let space = PixelSpace {
+			units_per_px: UNITS_PER_TILE / tile_size.x,
+			level_height: required_attribute::<f32>(map, "height")? * tile_size.y,
+		};
+
+		let map_path = load_context.asset_path().clone();
+		let mut tilesets = Vec::new();
+		for node in map.children().filter(|node| node.has_tag_name("tileset")) {
+			let first_gid = required_attribute(node, "firstgid")?;
+			match node.attribute("source") {
+				Some(source) => {
+					let path = map_path.resolve_embed(source).map_err(|err| {
+						TiledLevelLoaderError::Invalid(format!("bad tileset path {:?}: {}", source, err))
+					})?;
+					let bytes = load_context.read_asset_bytes(path.clone()).await?;
+					let document = Document::parse(std::str::from_utf8(&bytes)?)?;
+					tilesets.push(TiledTileset::parse(document.root_element(), first_gid, &path)?);
+				}
+				None => tilesets.push(TiledTileset::parse(node, first_gid, &map_path)?),
+			}
+		}
+		tilesets.sort_by_key(|tileset| tileset.first_gid);
+
+		let mut tiles = Vec::new();
+		let mut platforms = Vec::new();
+		let mut objects = Vec::new();
+		// layers are listed from the back to the front
+		let mut layers = Vec::new();
+		collect_layers(map, Vec2::ZERO, &mut layers)?;
+		for (index, (layer, offset)) in layers.into_iter().enumerate() {
+			let name = layer.attribute("name").unwrap_or_default();
+			if layer.has_tag_name("layer") {
+				let width: usize = required_attribute(layer, "width")?;
+				if width == 0 {
+					return Err(TiledLevelLoaderError::Invalid(format!(
+						"tile layer {:?} has a width of 0",
+						name
+					)));
+				}
+				let properties = parse_properties(layer);
+				let collision = properties.get("collision").is_some_and(|value| value == "true");
+				let data = parse_tile_data(layer)?;
+				let mut collision_cells = vec![CollisionCell::Empty; if collision { data.len() } else { 0 }];
+				for (i, raw_gid) in data.into_iter().enumerate() {
+					let gid = raw_gid & !FLIP_FLAGS;
+					if gid == 0 {
+						continue;
+					}
+					let tileset = tilesets
+						.iter()
+						.rev()
+						.find(|tileset| tileset.first_gid <= gid)
+						.ok_or_else(|| {
+							TiledLevelLoaderError::Invalid(format!("tile {} in layer {:?} has no tileset", gid, name))
+						})?;
+					if collision {
+						collision_cells[i] = if tileset.one_way_tiles.contains(&(gid - tileset.first_gid)) {
+							CollisionCell::OneWay
+						} else {
+							CollisionCell::Solid
+						};
+					}
+					// tiles bigger than the map's grid stick up out of the top of their cell
+					let cell = Vec2::new((i % width) as f32, (i / width) as f32);
+					let top_left = (cell + Vec2::Y) * tile_size - Vec2::Y * tileset.tile_size.y + offset;
+					let (pos, size) = space.rect(top_left, tileset.tile_size);
+					tiles.push(TileDef {
+						tileset: tileset.image.clone(),
+						src: tileset.src(gid),
+						pos,
+						size,
+						flip_x: raw_gid & FLIPPED_HORIZONTALLY != 0,
+						flip_y: raw_gid & FLIPPED_VERTICALLY != 0,
+						depth: index as f32,
+					});
+				}
+
+				let color = properties
+					.get("color")
+					.and_then(|color| parse_tiled_color(color))
+					.unwrap_or(DEFAULT_COLOR);
+				for rect in merge_cells(&collision_cells, width) {
+					let top_left = Vec2::new(rect.x as f32, rect.y as f32) * tile_size + offset;
+					let (pos, size) =
+						space.rect(top_left, Vec2::new(rect.width as f32, rect.height as f32) * tile_size);
+					platforms.push(PlatformDef {
+						id: format!("{}_{}_{}", name, rect.x, rect.y),
+						pos,
+						size,
+						color,
+						one_way: rect.value == CollisionCell::OneWay,
+						surface: None,
+						wall: None,
+						wall_material: None,
+						decorate: true,
+						spring: None,
+						breakable: None,
+						block: None,
+						group: None,
+						path: None,
+						sticky: None,
+						hazard: None,
+						lock: None,
+					});
+				}
+			} else if layer.has_tag_name("objectgroup") {
+				let layer_color = layer.attribute("color").and_then(parse_tiled_color);
+				for node in layer.children().filter(|node| node.has_tag_name("object")) {
+					objects.push(TiledObject::parse(node, name, offset, layer_color)?);
+				}
+			}
+		}
+
+		let paths: HashMap<u32, Vec<Vec2>> = objects
+			.iter()
+			.filter_map(|object| match &object.shape {
+				ObjectShape::Path(points) if !points.is_empty() => Some((
+					object.id,
+					points
+						.iter()
+						.map(|&point| space.point(object.top_left + point))
+						.collect(),
+				)),
+				_ => None,
+			})
+			.collect();
+		let mut player_start = None;
+		let mut zones = Vec::new();
+		let mut collectibles = Vec::new();
+		for object in &objects {
+			if object.name == PLAYER_START_OBJECT || object.class == PLAYER_START_OBJECT {
+				player_start = Some(space.rect(object.top_left, object.size).0);
+				continue;
+			}
+			match (&object.shape, object.class.as_str()) {
+				(ObjectShape::Rectangle, "" | PLATFORM_CLASS) => platforms.push(object.platform(&paths, &space)?),
+				(ObjectShape::Rectangle, HAZARD_CLASS) => zones.push(object.hazard_zone(&space)?),
+				(ObjectShape::Rectangle, EXIT_CLASS) => zones.push(object.exit_zone(&space)?),
+				(ObjectShape::Rectangle, TRIGGER_CLASS) => zones.push(object.trigger_zone(&space)?),
+				(_, COIN_CLASS) => collectibles.push(object.collectible(CollectibleKind::Coin, &space)?),
+				(_, GEM_CLASS) => collectibles.push(object.collectible(CollectibleKind::Gem, &space)?),
+				(_, KEY_CLASS) => collectibles.push(object.collectible(CollectibleKind::Key, &space)?),
+				// paths are only there for platforms to follow
+				(ObjectShape::Path(_), "") => {}
+				_ => warn!("skipping unsupported Tiled object {}", object.describe()),
+			}
+		}
+
+		let level = LevelAsset {
+			platforms,
+			prefabs: Vec::new(),
+			rooms: Vec::new(),
+			groups: Vec::new(),
+			secrets: Vec::new(),
+			crush_response: Default::default(),
+			intro_pan: None,
+			ranks: None,
+			player_start,
+			tiles,
+			zones,
+			ziplines: Vec::new(),
+			props: Vec::new(),
+			collectibles,
+			switches: Vec::new(),
+			music: None,
+		};
+		// layer names aren't necessarily unique, and the platforms' ids are made from them
+		if let Some(id) = level.duplicate_platform_id() {
+			return Err(TiledLevelLoaderError::DuplicatePlatformId(id.to_string()));
+		}
+		Ok(level)
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["tmx"]
+	}
+}
+
+/// Lists the visible tile and object layers within `parent` in the order they're drawn, along with
+/// their offsets in pixels, looking inside any group layers
+fn collect_layers<'a, 'input>(
+	parent: Node<'a, 'input>,
+	parent_offset: Vec2,
+	layers: &mut Vec<(Node<'a, 'input>, Vec2)>,
+) -> Result<(), TiledLevelLoaderError> {
+	for node in parent.children().filter(|node| node.is_element()) {
+		if !matches!(node.tag_name().name(), "layer" | "objectgroup" | "group")
+			|| node.attribute("visible") == Some("0")
+		{
+			continue;
+		}
+		let offset =
+			parent_offset + Vec2::new(attribute_or(node, "offsetx", 0.0)?, attribute_or(node, "offsety", 0.0)?);
+		if node.has_tag_name("group") {
+			collect_layers(node, offset, layers)?;
+		} else {
+			layers.push((node, offset));
+		}
+	}
+	Ok(())
+}
+
+/// Reads the global tile ids of each cell of a tile layer, row by row
+fn parse_tile_data(layer: Node) -> Result<Vec<u32>, TiledLevelLoaderError> {
+	let data = child(layer, "data").ok_or_else(|| TiledLevelLoaderError::Invalid("tile layer has no data".into()))?;
+	match data.attribute("encoding") {
+		Some("csv") => data
+			.text()
+			.unwrap_or_default()
+			.split(',')
+			.map(|gid| parse_value(gid.trim(), "tile"))
+			.collect(),
+		None => data
+			.children()
+			.filter(|node| node.has_tag_name("tile"))
+			.map(|tile| attribute_or(tile, "gid", 0))
+			.collect(),
+		Some(encoding) => Err(TiledLevelLoaderError::Invalid(format!(
+			"tile layers encoded as {:?} aren't supported; save them as CSV instead",
+			encoding
+		))),
+	}
+}
+
+/// Reads an object's custom properties, by name
+fn parse_properties(node: Node) -> HashMap<String, String> {
+	let Some(properties) = child(node, "properties") else {
+		return HashMap::new();
+	};
+	properties
+		.children()
+		.filter(|node| node.has_tag_name("property"))
+		.filter_map(|property| {
+			// multi-line strings are saved as the property's text instead
+			let value = property.attribute("value").or_else(|| property.text())?;
+			Some((property.attribute("name")?.to_string(), value.to_string()))
+		})
+		.collect()
+}
+
+/// Parses a list of points like `0,0 16,-8`
+fn parse_points(points: &str) -> Result<Vec<Vec2>, TiledLevelLoaderError> {
+	points
+		.split_whitespace()
+		.map(|point| {
+			let (x, y) = point
+				.split_once(',')
+				.ok_or_else(|| TiledLevelLoaderError::Invalid(format!("bad point {:?}", point)))?;
+			Ok(Vec2::new(parse_value(x, "x")?, parse_value(y, "y")?))
+		})
+		.collect()
+}
+
+/// Parses a Tiled color like `#FF8800`, or `#80FF8800` with the alpha first, into sRGB color components
+fn parse_tiled_color(color: &str) -> Option<(f32, f32, f32)> {
+	match color.len() {
+		9 => parse_hex_color(&format!("#{}", color.get(3..)?)),
+		_ => parse_hex_color(color),
+	}
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag_name: &str) -> Option<Node<'a, 'input>> {
+	node.children().find(|child| child.has_tag_name(tag_name))
+}
+
+/// Parses a value, where `what` describes the value for the error message if it's bad
+fn parse_value<T: FromStr>(value: &str, what: &str) -> Result<T, TiledLevelLoaderError> {
+	value
+		.parse()
+		.map_err(|_| TiledLevelLoaderError::Invalid(format!("bad {} {:?}", what, value)))
+}
+
+fn required_attribute<T: FromStr>(node: Node, name: &str) -> Result<T, TiledLevelLoaderError> {
+	let value = node
+		.attribute(name)
+		.ok_or_else(|| TiledLevelLoaderError::Invalid(format!("<{}> is missing {:?}", node.tag_name().name(), name)))?;
+	parse_value(value, &format!("{:?} of <{}>", name, node.tag_name().name()))
+}
+
+fn attribute_or<T: FromStr>(node: Node, name: &str, default: T) -> Result<T, TiledLevelLoaderError> {
+	node.attribute(name).map_or(Ok(default), |value| {
+		parse_value(value, &format!("{:?} of <{}>", name, node.tag_name().name()))
+	})
+}
@@ -1,31 +1,451 @@
+mod audio;
+mod companion;
+mod debug;
+mod decor;
+mod enemies;
+mod hazards;
+mod level;
+mod physics;
+mod platforms;
 mod player;
+mod save;
+mod settings;
+mod ui;
 mod util;
+mod zones;
 
-use crate::player::{Player, PlayerAssetLoader, PlayerControlParams, player_system};
+use crate::audio::{
+	MusicIntensity, MusicMix, attenuate_sound_emitters, crossfade_music_layers, start_level_music, start_sound_emitters,
+};
+use crate::companion::{Companion, companion_behavior_system, companion_follow_system, draw_companion_pointing};
+use crate::debug::{
+	Benchmark, InputRecording, TuningHistory, WallFrameExport, check_assets, check_recorded_positions,
+	debug_complete_level, drive_benchmark_bots, export_tuning_history, export_wall_frames, finish_fixed_update_timing,
+	input_display_system, not_recording_input, record_or_replay_input, save_input_recording, setup_benchmark,
+	setup_input_display, spawn_palette_system, start_fixed_update_timing, track_tuning_changes,
+};
+use crate::decor::{
+	AmbientPropArgs, AmbientPropKick, DecorationArgs, DecorationKind, DecorationLayer, DecorationScatter,
+	ambient_prop_impacts, kick_ambient_props, move_impact_particles, scatter_decorations,
+};
+use crate::enemies::{
+	AlertIndicator, Enemy, EnemyArchetype, EnemyArchetypeLoader, alert_indicator_system, alert_music_intensity,
+	enemy_attack_system, perception_system,
+};
+use crate::hazards::{PlayerDamagedEvent, TelegraphActivated, draw_telegraphs, hazard_contact_system, telegraph_system};
+use crate::level::{
+	CollectiblePickedUp, CurrentLevel, CurrentRoom, LdtkLevelLoader, LevelAsset, LevelAssetLoader, LevelCompleteEvent,
+	LevelEntered, LevelGroupCommand, LevelGroups, PlayStats, Prefab, PrefabLoader, RestartLevel, RoomChanged,
+	SecretDiscovered, SwitchToggledEvent, TiledLevelLoader, activate_current_room, apply_level_groups,
+	apply_switch_targets, enter_level_exits, finish_level_transition, level_group_commands, pick_up_collectibles,
+	place_players_at_start, press_switches, reset_level_groups, reset_switches, restart_level_system, reveal_secrets,
+	start_level_intro, sync_collectibles_system, sync_level_system, sync_secrets_system, track_current_room,
+	track_play_stats,
+};
+use crate::platforms::{
+	BlockBroken, BrokenFloors, DoorOpened, OneWayPlatformHooks, break_blocks_system, breakable_floor_system,
+	draw_floor_cracks, moving_platform_system, one_way_platform_system, open_locked_doors, player_group,
+	restore_broken_floors, spring_launch_bodies,
+};
+use crate::player::{
+	Character, CharacterRoster, CheckpointReached, DeathReplay, DeathReplayFinished, GamepadSlots, InputMap,
+	InputMapLoader, KnockbackEvent, LandingIndicator, LocalPlayers, Player, PlayerAssetLoader, PlayerControlParams,
+	PlayerCrushedEvent, PlayerDied, PlayerInputMap, PlayerJumped, PlayerSkidded, PlayerSlot, PlayerSpawnPoint,
+	PlayerStomped, ReturnToCheckpoint, SoftlockDetected, SoftlockParams, SpeedFeedbackSounds, SpeedTierChanged,
+	TouchControls, TutorialGhosts, ZoomPunch, activate_checkpoints, afterimage_system, apply_zoom_punch,
+	assign_gamepad_slots, clear_player_input_presses, crush_detection_system, detect_speed_tiers, drive_tutorial_ghosts,
+	gather_player_input, landing_indicator_system, low_health_music_intensity, play_death_replay, player_health_system,
+	player_sprite_size_system, player_system, record_player_history, reset_inventories, respawn_players,
+	setup_softlock_prompt, setup_touch_controls, softlock_prompt_system, softlock_watchdog_system, speed_tier_feedback,
+	start_death_replay, swap_character_system, tutorial_ghost_system, update_touch_controls,
+};
+use crate::save::{AssistOptions, SaveFile, apply_assist_options, autosave, restore_save};
+use crate::settings::Settings;
+use crate::ui::{
+	AttractMode, CinematicCamera, CinematicFinished, FloatingTextStyle, FloatingTextStyleLoader, FocusActivated,
+	FocusCancelled, FocusStyle, HoldAction, HoldActionCompleted, HoldActionKind, LevelResultsDisplay, RoomCamera,
+	SpawnFloatingText, UiFocus, WorldCamera, animate_floating_text, animate_results_screen, attract_mode_system,
+	draw_hold_progress, handle_hold_actions, handle_results_buttons, highlight_focus, layout_system, navigate_focus,
+	play_cinematic_camera, rebuild_minimap, room_camera_system, setup_collectible_counter, setup_floating_text,
+	setup_letterbox_camera, setup_minimap, setup_results_screen, show_results_screen, spawn_floating_text,
+	tick_hold_actions, update_collectible_counter, update_minimap_marker,
+};
+use crate::util::{
+	GameClock, ImpulseBroadcast, Side, TweenFinished, apply_impulse_broadcasts, init_physics_positions,
+	interpolate_render_transforms, lifetime_system, record_physics_positions, restore_physics_positions,
+	sync_game_clock, tick_game_clock, tween_system,
+};
+use crate::zones::{TriggerEntered, TriggerExited, buoyancy_system, trigger_zone_system, wind_force_system};
 use bevy::asset::AssetServer;
+use bevy::input::InputSystem;
 use bevy::prelude::*;
 use bevy::render::camera::ScalingMode;
 use bevy_rapier2d::prelude::*;
+use std::path::Path;
 
 fn main() {
+	// `--check-assets` validates every asset file and exits, without opening a window
+	if std::env::args().any(|arg| arg == "--check-assets") {
+		let problems = check_assets(Path::new("assets"));
+		std::process::exit(if problems == 0 { 0 } else { 1 });
+	}
+
+	// `--benchmark [frames]` times fixed updates on a generated stress level, without a window
+	let benchmark = Benchmark::from_args();
+	// `--record-input <path>`, `--replay-input <path>`, and `--verify-input <path>` record and replay runs
+	let input_recording = InputRecording::from_args();
+	let mut plugins = DefaultPlugins.set(AssetPlugin {
+		// opt in to hot reloading of assets
+		watch_for_changes_override: Some(true),
+		..default()
+	});
+	if benchmark.is_enabled() || input_recording.is_headless() {
+		plugins = Benchmark::headless(plugins);
+	}
+	// recorded runs play without the player's own save and assists, so they replay the same anywhere
+	let (save_file, assist_options) = if input_recording.is_active() {
+		(SaveFile::fresh(), AssistOptions::default())
+	} else {
+		(SaveFile::from_args(), AssistOptions::load())
+	};
+	// demos play when nobody touches anything for a while, but not over a benchmark or a recorded run
+	let attract_mode = if benchmark.is_enabled() || input_recording.is_active() {
+		AttractMode::default()
+	} else {
+		AttractMode::load()
+	};
+
 	App::new()
 		// baseline bevy stuff
-		.add_plugins(DefaultPlugins.set(AssetPlugin {
-			// opt in to hot reloading of assets
-			watch_for_changes_override: Some(true),
-			..default()
-		}))
+		.add_plugins(plugins)
 		.init_asset::<PlayerControlParams>()
 		.init_asset_loader::<PlayerAssetLoader>()
+		.init_asset::<InputMap>()
+		.init_asset_loader::<InputMapLoader>()
 		.insert_resource(Time::<Fixed>::from_hz(60.))
-		.add_systems(Update, watch_player_config)
+		.init_resource::<GameClock>()
+		.add_systems(First, sync_game_clock)
+		.add_systems(FixedFirst, tick_game_clock)
+		// gamepads are handed out to players in the order they're connected
+		.init_resource::<GamepadSlots>()
+		// `--coop` adds a second player, each with their own keys (hot-reloaded from disk) and gamepad
+		.insert_resource(LocalPlayers::from_args())
+		// on-screen buttons for touch screens, shown once the screen is touched (or from the start with `--touch`)
+		.insert_resource(TouchControls::from_args())
+		.add_systems(Startup, setup_touch_controls)
+		// each player's input, gathered every frame and acted on in fixed updates
+		.add_systems(
+			PreUpdate,
+			(assign_gamepad_slots, update_touch_controls, gather_player_input)
+				.chain()
+				.after(InputSystem),
+		)
+		.add_systems(FixedLast, clear_player_input_presses)
+		.add_systems(FixedUpdate, lifetime_system)
+		.add_event::<TweenFinished>()
+		.add_systems(FixedUpdate, tween_system)
+		.init_resource::<Settings>()
+		// hot-reloaded params changes are shown as they happen, and can be exported with F8
+		.init_resource::<TuningHistory>()
+		.add_systems(Update, (track_tuning_changes, export_tuning_history))
+		// F7 shows the lead player's input, buffers, and cooldowns
+		.add_systems(Startup, setup_input_display)
+		.add_systems(Update, input_display_system)
 		//
 		// platformer learning zone
 		//
-		.add_systems(Startup, setup_camera)
+		.add_systems(Startup, (setup_camera, setup_letterbox_camera))
+		.add_systems(Update, layout_system)
+		// keyboard/gamepad navigation between menu entries
+		.init_resource::<UiFocus>()
+		.init_resource::<FocusStyle>()
+		.add_event::<FocusActivated>()
+		.add_event::<FocusCancelled>()
+		.add_systems(Update, (navigate_focus, highlight_focus).chain())
+		// actions that have to be confirmed by holding a key down
+		.add_event::<HoldActionCompleted>()
+		.add_systems(Startup, setup_hold_actions)
+		.add_systems(Update, (tick_hold_actions, draw_hold_progress, handle_hold_actions).chain())
+		.init_asset::<FloatingTextStyle>()
+		.init_asset_loader::<FloatingTextStyleLoader>()
+		.add_event::<SpawnFloatingText>()
+		.add_systems(Startup, setup_floating_text)
+		.add_systems(Update, spawn_floating_text)
+		.add_systems(FixedUpdate, animate_floating_text)
 		.add_systems(Startup, setup_player)
-		.add_systems(Startup, setup_platforms)
-		.add_systems(FixedUpdate, player_system)
+		.add_systems(Startup, setup_scenery)
+		.init_resource::<DecorationScatter>()
+		.add_systems(Update, scatter_decorations.after(sync_level_system))
+		.init_resource::<AmbientPropKick>()
+		.add_event::<ImpulseBroadcast>()
+		.add_systems(
+			FixedUpdate,
+			(kick_ambient_props, apply_impulse_broadcasts)
+				.chain()
+				.after(player_system),
+		)
+		.add_systems(FixedUpdate, move_impact_particles)
+		.add_systems(Update, ambient_prop_impacts)
+		//
+		// level geometry, hot-reloaded from disk
+		//
+		.init_asset::<LevelAsset>()
+		.init_asset_loader::<LevelAssetLoader>()
+		// levels can also be drawn in LDtk or Tiled, and played with `--level <path>.ldtk` or `.tmx`
+		.init_asset_loader::<LdtkLevelLoader>()
+		.init_asset_loader::<TiledLevelLoader>()
+		// platforms that levels place by name, kept in their own files
+		.init_asset::<Prefab>()
+		.init_asset_loader::<PrefabLoader>()
+		.add_systems(Startup, setup_level)
+		.add_systems(Update, (sync_level_system, place_players_at_start))
+		// exits to other levels, which pause the game while the next level loads
+		.add_event::<LevelEntered>()
+		.add_systems(
+			Update,
+			(
+				enter_level_exits.before(sync_level_system),
+				finish_level_transition
+					.after(sync_level_system)
+					.after(place_players_at_start)
+					.before(respawn_players),
+			),
+		)
+		// groups of platforms switched on and off (or moved, or recolored) together
+		.init_resource::<LevelGroups>()
+		.add_event::<LevelGroupCommand>()
+		.add_systems(
+			Update,
+			(reset_level_groups, level_group_commands, apply_level_groups)
+				.chain()
+				.after(sync_level_system)
+				.after(restart_level_system),
+		)
+		// switches, which flip groups on and off
+		.add_event::<SwitchToggledEvent>()
+		.add_systems(FixedUpdate, press_switches.after(player_system))
+		.add_systems(
+			Update,
+			(reset_switches, apply_switch_targets)
+				.chain()
+				.before(level_group_commands),
+		)
+		// secret areas, hidden behind fake walls until the player steps inside
+		.init_resource::<PlayStats>()
+		.add_event::<SecretDiscovered>()
+		.add_systems(Update, (sync_secrets_system, reveal_secrets).chain())
+		.add_systems(Startup, setup_minimap)
+		.add_systems(Update, (rebuild_minimap.after(reveal_secrets), update_minimap_marker))
+		.add_event::<RestartLevel>()
+		.add_systems(Update, restart_level_system.before(respawn_players))
+		// deaths and time taken, tallied up on the results screen at the end of the level
+		.add_systems(Update, track_play_stats.after(handle_results_buttons))
+		// coins and gems, picked up for points
+		.add_event::<CollectiblePickedUp>()
+		.add_systems(
+			Update,
+			(sync_collectibles_system, pick_up_collectibles)
+				.chain()
+				.after(track_play_stats),
+		)
+		.add_systems(Startup, setup_collectible_counter)
+		.add_systems(Update, update_collectible_counter.after(pick_up_collectibles))
+		.init_resource::<LevelResultsDisplay>()
+		.add_event::<LevelCompleteEvent>()
+		.add_systems(Startup, setup_results_screen)
+		.add_systems(
+			Update,
+			(
+				show_results_screen,
+				animate_results_screen,
+				handle_results_buttons.after(navigate_focus),
+			)
+				.chain(),
+		)
+		.add_systems(Update, debug_complete_level)
+		// number keys spawn dynamic bodies at the cursor, for testing how the player interacts with them
+		.add_systems(Update, spawn_palette_system)
+		.insert_resource(benchmark)
+		.add_systems(Startup, setup_benchmark.after(setup_level).after(setup_player))
+		.add_systems(FixedUpdate, drive_benchmark_bots.before(player_system))
+		.add_systems(FixedFirst, start_fixed_update_timing)
+		.add_systems(FixedLast, finish_fixed_update_timing)
+		.insert_resource(input_recording)
+		.add_systems(
+			FixedUpdate,
+			record_or_replay_input.after(drive_benchmark_bots).before(player_system),
+		)
+		.add_systems(FixedLast, check_recorded_positions)
+		.add_systems(Last, save_input_recording)
+		.insert_resource(attract_mode)
+		.add_systems(Update, attract_mode_system.before(restart_level_system))
+		// progress is saved at checkpoints, at the end of each level, and when the game closes
+		.insert_resource(save_file)
+		.add_systems(PostStartup, restore_save.run_if(not_recording_input))
+		.add_systems(Last, autosave.run_if(not_recording_input))
+		// optional assists, kept alongside the save
+		.insert_resource(assist_options)
+		.add_systems(Update, apply_assist_options)
+		.init_resource::<CinematicCamera>()
+		.add_event::<CinematicFinished>()
+		.add_systems(Update, start_level_intro)
+		.add_systems(
+			PostUpdate,
+			play_cinematic_camera.before(TransformSystem::TransformPropagate),
+		)
+		.init_resource::<CurrentRoom>()
+		.add_event::<RoomChanged>()
+		.add_systems(Update, (track_current_room, activate_current_room.after(track_current_room)))
+		// the camera stays within the lead player's room, panning over when they move to another
+		.init_resource::<RoomCamera>()
+		.add_systems(
+			PostUpdate,
+			room_camera_system
+				.after(interpolate_render_transforms)
+				.before(play_cinematic_camera)
+				.before(play_death_replay)
+				.before(TransformSystem::TransformPropagate),
+		)
+		.init_resource::<BrokenFloors>()
+		.add_systems(FixedUpdate, breakable_floor_system.after(player_system))
+		.add_systems(Update, (restore_broken_floors.after(track_current_room), draw_floor_cracks))
+		.add_event::<BlockBroken>()
+		.add_systems(FixedUpdate, break_blocks_system.before(player_system))
+		// doors that open for players carrying their key
+		.add_event::<DoorOpened>()
+		.add_systems(FixedUpdate, open_locked_doors.after(player_system))
+		.add_systems(Update, reset_inventories)
+		.add_systems(FixedPostUpdate, spring_launch_bodies.after(PhysicsSet::Writeback))
+		// platforms need to move before the player, so riders can be carried along
+		.add_systems(
+			FixedUpdate,
+			(
+				moving_platform_system,
+				player_system,
+				crush_detection_system,
+				one_way_platform_system,
+			)
+				.chain(),
+		)
+		.add_event::<PlayerCrushedEvent>()
+		.add_event::<PlayerJumped>()
+		.add_event::<PlayerStomped>()
+		.add_event::<PlayerSkidded>()
+		.add_event::<KnockbackEvent>()
+		// `--export-wall-frames <path>` logs wall interactions to a CSV, for tuning the wall params offline
+		.insert_resource(WallFrameExport::from_args())
+		.add_systems(FixedUpdate, export_wall_frames.after(player_system))
+		// the companion reacts to the player's jumps, so it has to run after them
+		.add_systems(
+			FixedUpdate,
+			(companion_behavior_system, companion_follow_system)
+				.chain()
+				.after(player_system),
+		)
+		.add_systems(Update, draw_companion_pointing)
+		// checkpoints move the spawn point, and are where the player can swap characters
+		.add_event::<CheckpointReached>()
+		.add_systems(
+			FixedUpdate,
+			(
+				activate_checkpoints,
+				swap_character_system.after(record_or_replay_input).before(player_system),
+			),
+		)
+		// dust, speed lines, and a camera punch when the player gets going fast
+		.add_event::<SpeedTierChanged>()
+		.init_resource::<SpeedFeedbackSounds>()
+		.init_resource::<ZoomPunch>()
+		.add_systems(
+			FixedUpdate,
+			(detect_speed_tiers, speed_tier_feedback)
+				.chain()
+				.after(player_system),
+		)
+		.add_systems(
+			PostUpdate,
+			apply_zoom_punch
+				.after(play_cinematic_camera)
+				.after(play_death_replay)
+				.before(TransformSystem::TransformPropagate),
+		)
+		.add_systems(Update, (player_sprite_size_system, landing_indicator_system))
+		.init_resource::<DeathReplay>()
+		.add_event::<PlayerDied>()
+		.add_event::<DeathReplayFinished>()
+		.add_systems(Update, (start_death_replay, respawn_players))
+		// offer a way out when the player can't get anywhere on their own
+		.init_resource::<SoftlockParams>()
+		.add_event::<SoftlockDetected>()
+		.add_event::<ReturnToCheckpoint>()
+		.add_systems(Startup, setup_softlock_prompt)
+		.add_systems(FixedUpdate, softlock_watchdog_system.after(player_system))
+		.add_systems(Update, softlock_prompt_system.before(respawn_players))
+		.add_systems(
+			FixedPostUpdate,
+			(record_player_history, afterimage_system).after(PhysicsSet::Writeback),
+		)
+		.add_systems(
+			PostUpdate,
+			play_death_replay
+				.after(interpolate_render_transforms)
+				.before(TransformSystem::TransformPropagate),
+		)
+		//
+		// enemies
+		//
+		.init_asset::<EnemyArchetype>()
+		.init_asset_loader::<EnemyArchetypeLoader>()
+		.add_systems(Startup, setup_enemies)
+		.add_systems(FixedUpdate, perception_system.after(player_system))
+		.add_systems(
+			FixedUpdate,
+			enemy_attack_system
+				.after(perception_system)
+				.after(telegraph_system)
+				.before(player_health_system),
+		)
+		.add_systems(Update, (alert_indicator_system, alert_music_intensity))
+		//
+		// zones
+		//
+		.add_systems(FixedUpdate, (buoyancy_system, wind_force_system))
+		// areas that other features listen to, to find out when players go in and out of them
+		.add_event::<TriggerEntered>()
+		.add_event::<TriggerExited>()
+		.add_systems(FixedUpdate, trigger_zone_system.after(player_system))
+		// see-through players showing the way through tutorial sections, for players who are stuck on them
+		.insert_resource(TutorialGhosts::load())
+		.add_systems(
+			FixedUpdate,
+			(
+				tutorial_ghost_system
+					.after(trigger_zone_system)
+					.run_if(not_recording_input),
+				drive_tutorial_ghosts.after(record_or_replay_input).before(player_system),
+			),
+		)
+		//
+		// audio
+		//
+		.add_systems(Update, (start_sound_emitters, attenuate_sound_emitters))
+		// music layers that fade in and out with the intensity of the gameplay
+		.init_resource::<MusicIntensity>()
+		.init_resource::<MusicMix>()
+		.add_systems(Update, (start_level_music, low_health_music_intensity, crossfade_music_layers))
+		//
+		// hazards
+		//
+		.add_event::<TelegraphActivated>()
+		.add_systems(FixedUpdate, telegraph_system)
+		.add_event::<PlayerDamagedEvent>()
+		.add_systems(
+			FixedUpdate,
+			(hazard_contact_system, player_health_system)
+				.chain()
+				.after(player_system),
+		)
+		.add_systems(Update, draw_telegraphs)
 		//
 		// rapier physics
 		//
@@ -33,7 +453,21 @@ fn main() {
 			dt: 1. / 60.,
 			substeps: 1,
 		})
-		.add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(10.0).in_fixed_schedule())
+		.add_plugins(RapierPhysicsPlugin::<OneWayPlatformHooks>::pixels_per_meter(10.0).in_fixed_schedule())
+		//
+		// render interpolation between fixed updates
+		//
+		.add_systems(FixedFirst, restore_physics_positions)
+		.add_systems(
+			FixedPostUpdate,
+			(record_physics_positions, init_physics_positions)
+				.chain()
+				.after(PhysicsSet::Writeback),
+		)
+		.add_systems(
+			PostUpdate,
+			interpolate_render_transforms.before(TransformSystem::TransformPropagate),
+		)
 		// .add_plugins(RapierDebugRenderPlugin::default())
 		.run();
 }
@@ -41,6 +475,9 @@ fn main() {
 fn setup_camera(mut commands: Commands) {
 	commands.spawn((
 		Camera2d,
+		WorldCamera,
+		IsDefaultUiCamera,
+		SpatialListener::new(4.0),
 		Transform::from_xyz(50.0, 50.0, 1.0),
 		OrthographicProjection {
 			scaling_mode: ScalingMode::AutoMin {
@@ -61,97 +498,49 @@ struct WallArgs {
 	size: Vec2,
 }
 impl WallArgs {
-	fn spawn(self, commands: &mut Commands) {
+	fn bundle(self) -> impl Bundle {
 		let WallArgs { color, pos, size } = self;
-		commands.spawn((
+		(
 			Platform,
 			RigidBody::Fixed,
 			Sprite::from_color(color, size),
 			Collider::cuboid(size.x * 0.5, size.y * 0.5),
 			Transform::from_xyz(pos.x, pos.y, 0.0),
-		));
+		)
 	}
 }
 
-fn setup_platforms(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup_level(mut commands: Commands, asset_server: Res<AssetServer>) {
+	// `--level <path>` plays a different level than the sandbox
+	let path = std::env::args()
+		.skip_while(|arg| arg != "--level")
+		.nth(1)
+		.unwrap_or_else(|| "levels/sandbox.level.ron".into());
+	commands.insert_resource(CurrentLevel(asset_server.load(path)));
+}
+
+/// Scenery and toys for the sandbox level that can't be described in its level file (yet)
+fn setup_scenery(mut commands: Commands, asset_server: Res<AssetServer>) {
 	// background
 	commands.spawn((
 		Sprite::from_color(Color::srgba(0., 0.5, 0.75, 0.2), Vec2::new(100., 100.)),
 		Transform::from_xyz(50., 50., 0.),
 	));
 
-	// floor
-	WallArgs {
-		color: Color::srgb(0.15, 0.8, 0.25),
-		pos: Vec2::new(50., 3.),
-		size: Vec2::new(98.0, 4.0),
-	}
-	.spawn(&mut commands);
-
-	// platform 1
-	WallArgs {
-		color: Color::srgb(0.15, 0.8, 0.25),
-		pos: Vec2::new(75.0, 18.0),
-		size: Vec2::new(20.0, 4.0),
-	}
-	.spawn(&mut commands);
-
-	// platform 2
-	WallArgs {
-		color: Color::srgb(0.15, 0.8, 0.25),
-		pos: Vec2::new(50.0, 30.0),
-		size: Vec2::new(20.0, 2.0),
-	}
-	.spawn(&mut commands);
-
-	WallArgs {
-		color: Color::srgb(0.15, 0.8, 0.25),
-		pos: Vec2::new(35.0, 50.0),
-		size: Vec2::new(2.0, 20.0),
-	}
-	.spawn(&mut commands);
-
-	WallArgs {
-		color: Color::srgb(0.15, 0.8, 0.25),
-		pos: Vec2::new(50.0, 58.0),
-		size: Vec2::new(2.0, 20.0),
-	}
-	.spawn(&mut commands);
-
-	WallArgs {
-		color: Color::srgb(0.15, 0.8, 0.25),
-		pos: Vec2::new(28.0, 68.0),
-		size: Vec2::new(20.0, 2.0),
-	}
-	.spawn(&mut commands);
-
-	// west wall
-	WallArgs {
-		color: Color::srgb(0.15, 0.5, 0.15),
-		pos: Vec2::new(3., 50.),
-		size: Vec2::new(4.0, 98.0),
-	}
-	.spawn(&mut commands);
-
-	// east wall
-	WallArgs {
-		color: Color::srgb(0.45, 0.5, 0.15),
-		pos: Vec2::new(97., 50.),
-		size: Vec2::new(4.0, 98.0),
+	// some pipes in the background
+	for x in [12.0, 15.0] {
+		DecorationArgs {
+			kind: DecorationKind::Pipe,
+			pos: Vec2::new(x, 5.0),
+			layer: DecorationLayer::Background,
+		}
+		.spawn(&mut commands);
 	}
-	.spawn(&mut commands);
 
-	// ceiling
-	WallArgs {
-		color: Color::srgb(0.45, 0.8, 0.25),
-		pos: Vec2::new(50., 97.),
-		size: Vec2::new(98.0, 4.0),
-	}
-	.spawn(&mut commands);
+	// a chain hanging from the ceiling
+	AmbientPropArgs::spawn_chain(&mut commands, Vec2::new(65.0, 95.0), 8, None);
 
-	// a ball to bounce around
-	// TODO: why does the kinematic character controller seem to get "stuck" on the ball
-	//       when attempting to start pushing it from stationary?
+	// a ball to bounce around (and push around)
 	commands.spawn((
 		RigidBody::Dynamic,
 		Sprite {
@@ -165,48 +554,177 @@ fn setup_platforms(mut commands: Commands, asset_server: Res<AssetServer>) {
 		GravityScale(1.0),
 		Velocity::linear(Vec2::new(200.0, 200.0)),
 		Ccd::enabled(),
+		ExternalImpulse::default(),
+		ExternalForce::default(),
+		ReadMassProperties::default(),
 	));
 }
 
+fn setup_enemies(mut commands: Commands, asset_server: Res<AssetServer>) {
+	// a sentry keeping watch over the floor
+	commands
+		.spawn((
+			Enemy {
+				archetype: asset_server.load("enemies/sentry.enemy.ron"),
+				facing: Side::Right,
+			},
+			Sprite::from_color(Color::srgb(0.6, 0.2, 0.6), Vec2::new(3.0, 3.0)),
+			Transform::from_xyz(8.0, 6.5, 0.0),
+		))
+		.with_child((
+			AlertIndicator,
+			Text2d::default(),
+			TextColor(Color::NONE),
+			Transform::from_xyz(0.0, 3.5, 1.0).with_scale(Vec3::splat(0.1)),
+		));
+}
+
+fn setup_hold_actions(mut commands: Commands) {
+	HoldAction::new(HoldActionKind::RestartLevel, vec![KeyCode::KeyR], vec![], 1.0).spawn(
+		&mut commands,
+		"Restart",
+		Node {
+			position_type: PositionType::Absolute,
+			bottom: Val::Px(20.0),
+			right: Val::Px(20.0),
+			..default()
+		},
+	);
+	HoldAction::new(
+		HoldActionKind::QuitToMenu,
+		vec![KeyCode::Escape],
+		vec![GamepadButton::Select],
+		1.0,
+	)
+	.spawn(
+		&mut commands,
+		"Quit",
+		Node {
+			position_type: PositionType::Absolute,
+			bottom: Val::Px(20.0),
+			right: Val::Px(90.0),
+			..default()
+		},
+	);
+}
+
+/// Debug text showing the state of the given player
 #[derive(Component)]
-struct PlayerStatusText;
+struct PlayerStatusText(Entity);
 
-fn setup_player(mut commands: Commands, asset_server: Res<AssetServer>) {
-	commands.spawn((
-		Player(asset_server.load("player.ron")),
+/// Everything the player in the given `slot` needs to run around as the given `character`, starting at `position`
+fn player_bundle(character: &Character, slot: PlayerSlot, position: Vec2) -> impl Bundle {
+	(
+		Player(character.params.clone()),
+		slot,
+		PlayerSpawnPoint(position),
 		Friction {
 			coefficient: 0.0,
 			combine_rule: CoefficientCombineRule::Multiply,
 		},
-		Sprite::from_color(Color::srgb(1., 0.5, 0.), Vec2::new(3.0, 5.0)),
-		Collider::cuboid(1.5, 2.5),
-		Transform::from_xyz(25., 25., 0.),
+		Sprite::from_color(character.color, character.half_extents * 2.0),
+		Collider::cuboid(character.half_extents.x, character.half_extents.y),
+		Transform::from_translation(position.extend(0.)),
 		RigidBody::KinematicPositionBased,
 		KinematicCharacterController {
-			filter_flags: QueryFilterFlags::EXCLUDE_DYNAMIC,
+			filter_flags: QueryFilterFlags::EXCLUDE_SENSORS,
+			filter_groups: Some(CollisionGroups::new(player_group(slot), Group::ALL)),
+			// pushing dynamic bodies is handled by `player_system` instead
+			apply_impulse_to_dynamic_bodies: false,
 			..default()
 		},
 		KinematicCharacterControllerOutput::default(),
-	));
+	)
+}
 
-	// Debug text for player state
+fn setup_player(mut commands: Commands, asset_server: Res<AssetServer>, local_players: Res<LocalPlayers>) {
+	// in co-op, the second player's characters are colored differently, to tell the players apart
+	let palettes = [
+		[Color::srgb(1., 0.5, 0.), Color::srgb(0.3, 0.8, 0.4)],
+		[Color::srgb(0.3, 0.5, 1.), Color::srgb(0.8, 0.4, 0.9)],
+	];
+	let mut players = Vec::new();
+	for slot in 0..local_players.0 {
+		let [runner_color, scout_color] = palettes[slot % palettes.len()];
+		// a smaller, floatier character with a double jump can be swapped in at checkpoints
+		let roster = CharacterRoster {
+			characters: vec![
+				Character {
+					name: "runner".into(),
+					params: asset_server.load("player.ron"),
+					half_extents: Vec2::new(1.5, 2.5),
+					color: runner_color,
+				},
+				Character {
+					name: "scout".into(),
+					params: asset_server.load("characters/scout.ron"),
+					half_extents: Vec2::new(1.0, 1.5),
+					color: scout_color,
+				},
+			],
+			current: 0,
+			swap_anywhere: false,
+		};
+		let character = roster.current().clone();
+		let position = Vec2::new(25. + 6. * slot as f32, 25.);
+		let player = commands
+			.spawn((
+				player_bundle(&character, PlayerSlot(slot), position),
+				roster,
+				PlayerInputMap(asset_server.load(local_players.input_map_path(slot))),
+			))
+			.id();
+		players.push(player);
+
+		// shadow beneath the player while airborne
+		commands.spawn((
+			LandingIndicator(player),
+			Sprite::from_color(Color::BLACK, Vec2::ONE),
+			Transform::from_translation(position.extend(0.5)),
+			Visibility::Hidden,
+		));
+
+		// Debug text for player state, in the corner on the player's side of the screen
+		let (justify, left, right) = if slot % 2 == 0 {
+			(JustifyText::Right, Val::Auto, Val::Px(10.0))
+		} else {
+			(JustifyText::Left, Val::Px(10.0), Val::Auto)
+		};
+		commands.spawn((
+			PlayerStatusText(player),
+			Text::new("hello world"),
+			TextLayout::new_with_justify(justify),
+			Node {
+				position_type: PositionType::Absolute,
+				top: Val::Px(10.0),
+				left,
+				right,
+				..default()
+			},
+		));
+	}
+
+	// a little critter to keep the first player company
 	commands.spawn((
-		PlayerStatusText,
-		Text::new("hello world"),
-		TextLayout::new_with_justify(JustifyText::Right),
-		Node {
-			position_type: PositionType::Absolute,
-			top: Val::Px(10.0),
-			right: Val::Px(10.0),
+		Companion {
+			leader: players[0],
+			follow_delay: 20,
+			hover_height: 5.0,
+			responsiveness: 6.0,
+			max_speed: 80.0,
+			teleport_distance: 40.0,
+		},
+		Sprite::from_color(Color::srgb(0.6, 0.9, 1.0), Vec2::splat(1.2)),
+		Collider::ball(0.6),
+		// it floats along without blocking (or being blocked by) the player or anything that moves
+		Sensor,
+		Transform::from_xyz(25., 30., 0.1),
+		RigidBody::KinematicPositionBased,
+		KinematicCharacterController {
+			filter_flags: QueryFilterFlags::EXCLUDE_SENSORS
+				| QueryFilterFlags::EXCLUDE_KINEMATIC
+				| QueryFilterFlags::EXCLUDE_DYNAMIC,
 			..default()
 		},
 	));
 }
-
-fn watch_player_config(mut events: EventReader<AssetEvent<PlayerControlParams>>) {
-	for event in events.read() {
-		if let AssetEvent::Modified { id } = event {
-			info!("Asset modified: {:?}", id);
-		}
-	}
-}
@@ -0,0 +1,201 @@
+use crate::physics::{CastHit, PhysicsWorld, QueryScope};
+use bevy::prelude::*;
+
+/// A [PhysicsWorld] made of nothing but axis-aligned boxes, for testing code that queries physics
+/// without spinning up a Rapier world
+#[derive(Default)]
+pub struct MockWorld {
+	bodies: Vec<MockBody>,
+}
+
+struct MockBody {
+	entity: Entity,
+	area: Rect,
+	dynamic: bool,
+}
+
+impl MockWorld {
+	/// Adds a static box with the given `half_extents` at `center`
+	pub fn with_box(self, entity: Entity, center: Vec2, half_extents: Vec2) -> Self {
+		self.with_body(entity, center, half_extents, false)
+	}
+
+	/// Adds a dynamic box, which only counts for queries whose [QueryScope] includes dynamic bodies
+	pub fn with_dynamic_box(self, entity: Entity, center: Vec2, half_extents: Vec2) -> Self {
+		self.with_body(entity, center, half_extents, true)
+	}
+
+	fn with_body(mut self, entity: Entity, center: Vec2, half_extents: Vec2, dynamic: bool) -> Self {
+		self.bodies.push(MockBody {
+			entity,
+			area: Rect::from_center_half_size(center, half_extents),
+			dynamic,
+		});
+		self
+	}
+
+	fn in_scope(scope: QueryScope) -> impl Fn(&&MockBody) -> bool {
+		move |body: &&MockBody| (scope.dynamic || !body.dynamic) && scope.exclude != Some(body.entity)
+	}
+
+	/// The first box (grown by `half_extents`, so a box cast becomes a ray cast) hit by the ray
+	fn cast(
+		&self,
+		origin: Vec2,
+		direction: Vec2,
+		max_distance: f32,
+		half_extents: Vec2,
+		scope: QueryScope,
+	) -> Option<CastHit> {
+		self.bodies
+			.iter()
+			.filter(Self::in_scope(scope))
+			.filter_map(|body| {
+				let area = Rect::from_center_half_size(body.area.center(), body.area.half_size() + half_extents);
+				let (distance, normal) = ray_vs_rect(origin, direction, area)?;
+				(distance <= max_distance).then_some(CastHit {
+					entity: body.entity,
+					distance,
+					normal,
+				})
+			})
+			.min_by(|a, b| a.distance.total_cmp(&b.distance))
+	}
+}
+
+impl PhysicsWorld for MockWorld {
+	fn cast_ray(&self, origin: Vec2, direction: Vec2, max_distance: f32, scope: QueryScope) -> Option<CastHit> {
+		self.cast(origin, direction, max_distance, Vec2::ZERO, scope)
+	}
+
+	fn cast_box(
+		&self,
+		center: Vec2,
+		half_extents: Vec2,
+		velocity: Vec2,
+		max_distance: f32,
+		scope: QueryScope,
+	) -> Option<CastHit> {
+		self.cast(center, velocity, max_distance, half_extents, scope)
+	}
+
+	fn overlapping_box(&self, center: Vec2, half_extents: Vec2, scope: QueryScope) -> Vec<Entity> {
+		let query = Rect::from_center_half_size(center, half_extents);
+		self.bodies
+			.iter()
+			.filter(Self::in_scope(scope))
+			.filter(|body| !body.area.intersect(query).is_empty())
+			.map(|body| body.entity)
+			.collect()
+	}
+}
+
+/// Where a ray enters the rectangle, in multiples of `direction`, and the normal of the side it
+/// enters through. A ray starting inside the rectangle hits it right away, with no normal.
+fn ray_vs_rect(origin: Vec2, direction: Vec2, area: Rect) -> Option<(f32, Vec2)> {
+	let (mut near, mut far) = (f32::NEG_INFINITY, f32::INFINITY);
+	let mut normal = Vec2::ZERO;
+	for axis in [Vec2::X, Vec2::Y] {
+		let (o, d) = (origin.dot(axis), direction.dot(axis));
+		let (min, max) = (area.min.dot(axis), area.max.dot(axis));
+		if d == 0.0 {
+			if o < min || o > max {
+				return None;
+			}
+			continue;
+		}
+		let (t1, t2) = ((min - o) / d, (max - o) / d);
+		let (enter, exit) = (t1.min(t2), t1.max(t2));
+		if enter > near {
+			near = enter;
+			normal = -axis * d.signum();
+		}
+		far = far.min(exit);
+	}
+	if far < near.max(0.0) {
+		return None;
+	}
+	if near < 0.0 {
+		return Some((0.0, Vec2::ZERO));
+	}
+	Some((near, normal))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const WALL: Entity = Entity::from_raw(1);
+	const CRATE: Entity = Entity::from_raw(2);
+
+	/// A wall from x = 4 to 6, and a crate from x = -3 to -1, both between y = -1 and 1
+	fn world() -> MockWorld {
+		MockWorld::default()
+			.with_box(WALL, Vec2::new(5.0, 0.0), Vec2::ONE)
+			.with_dynamic_box(CRATE, Vec2::new(-2.0, 0.0), Vec2::ONE)
+	}
+
+	#[test]
+	fn cast_ray_hits_the_near_side() {
+		let hit = world().cast_ray(Vec2::ZERO, Vec2::X, 10.0, QueryScope::solid());
+		assert_eq!(
+			hit,
+			Some(CastHit {
+				entity: WALL,
+				distance: 4.0,
+				normal: Vec2::NEG_X,
+			})
+		);
+	}
+
+	#[test]
+	fn cast_ray_measures_in_multiples_of_direction() {
+		let hit = world().cast_ray(Vec2::ZERO, Vec2::X * 2.0, 10.0, QueryScope::solid());
+		assert_eq!(hit.map(|hit| hit.distance), Some(2.0));
+	}
+
+	#[test]
+	fn cast_ray_stops_at_max_distance() {
+		assert_eq!(world().cast_ray(Vec2::ZERO, Vec2::X, 3.9, QueryScope::solid()), None);
+		assert_eq!(world().cast_ray(Vec2::ZERO, Vec2::Y, 10.0, QueryScope::solid()), None);
+	}
+
+	#[test]
+	fn cast_ray_from_inside_hits_right_away() {
+		let hit = world().cast_ray(Vec2::new(5.0, 0.0), Vec2::X, 10.0, QueryScope::solid());
+		assert_eq!(hit.map(|hit| (hit.entity, hit.distance)), Some((WALL, 0.0)));
+	}
+
+	#[test]
+	fn cast_ray_respects_scope() {
+		let left = Vec2::NEG_X;
+		assert_eq!(world().cast_ray(Vec2::ZERO, left, 10.0, QueryScope::solid()), None);
+		let hit = world().cast_ray(Vec2::ZERO, left, 10.0, QueryScope::solid_and_dynamic());
+		assert_eq!(hit.map(|hit| hit.entity), Some(CRATE));
+		let excluded = QueryScope::solid_and_dynamic().excluding(CRATE);
+		assert_eq!(world().cast_ray(Vec2::ZERO, left, 10.0, excluded), None);
+	}
+
+	#[test]
+	fn cast_box_hits_with_its_edge() {
+		let hit = world().cast_box(Vec2::ZERO, Vec2::splat(0.5), Vec2::X, 10.0, QueryScope::solid());
+		assert_eq!(hit.map(|hit| (hit.entity, hit.distance)), Some((WALL, 3.5)));
+		// a box passing just above the wall misses it, where its center would have hit
+		let above = Vec2::new(0.0, 1.6);
+		assert_eq!(
+			world().cast_box(above, Vec2::splat(0.5), Vec2::X, 10.0, QueryScope::solid()),
+			None
+		);
+	}
+
+	#[test]
+	fn is_box_clear_checks_overlaps() {
+		let world = world();
+		assert!(world.is_box_clear(Vec2::ZERO, Vec2::ONE, QueryScope::solid_and_dynamic()));
+		assert!(!world.is_box_clear(Vec2::new(3.5, 0.0), Vec2::ONE, QueryScope::solid()));
+		assert!(world.is_box_clear(Vec2::new(-0.5, 0.0), Vec2::ONE, QueryScope::solid()));
+		assert!(!world.is_box_clear(Vec2::new(-0.5, 0.0), Vec2::ONE, QueryScope::solid_and_dynamic()));
+		// touching isn't overlapping
+		assert!(world.is_box_clear(Vec2::new(3.0, 0.0), Vec2::ONE, QueryScope::solid()));
+	}
+}
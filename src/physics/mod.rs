@@ -0,0 +1,9 @@
+#[cfg(test)]
+mod mock;
+mod query;
+mod rapier;
+
+#[cfg(test)]
+pub use mock::*;
+pub use query::*;
+pub use rapier::*;
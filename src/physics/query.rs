@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+
+/// Which colliders a physics query should consider
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct QueryScope {
+	/// Whether dynamic bodies (crates, debris) count, or only static and kinematic ones
+	pub dynamic: bool,
+	/// An entity to ignore, usually the one doing the query
+	pub exclude: Option<Entity>,
+}
+
+impl QueryScope {
+	/// Static and kinematic colliders: level geometry, moving platforms, other characters.
+	/// Sensors never count.
+	pub fn solid() -> Self {
+		QueryScope {
+			dynamic: false,
+			exclude: None,
+		}
+	}
+
+	/// Like [QueryScope::solid], but dynamic bodies count too
+	pub fn solid_and_dynamic() -> Self {
+		QueryScope {
+			dynamic: true,
+			exclude: None,
+		}
+	}
+
+	/// Ignores the given entity's own collider
+	pub fn excluding(self, entity: Entity) -> Self {
+		QueryScope {
+			exclude: Some(entity),
+			..self
+		}
+	}
+}
+
+/// Where a ray or shape cast hit something
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CastHit {
+	pub entity: Entity,
+	/// How far along the cast the hit was, in multiples of the cast's direction (or velocity)
+	pub distance: f32,
+	/// The surface normal at the hit, pointing out of what was hit
+	pub normal: Vec2,
+}
+
+/// The queries that gameplay code asks of the physics engine, so that the player, their sensors,
+/// and enemies don't depend on a particular engine. [RapierWorld](crate::physics::RapierWorld) is the
+/// only implementation for now; swapping engines (or upgrading Rapier) should only mean touching
+/// the implementation, rather than every caller.
+///
+/// Character movement isn't part of this: it's still driven by Rapier's character controller
+/// components, which `player_system` writes to and reads back from.
+pub trait PhysicsWorld {
+	/// Casts a ray from `origin` along `direction`, up to `max_distance` multiples of `direction`,
+	/// and returns the first thing it hits. A ray starting inside a collider hits it right away.
+	fn cast_ray(&self, origin: Vec2, direction: Vec2, max_distance: f32, scope: QueryScope) -> Option<CastHit>;
+
+	/// Sweeps a box with the given `half_extents` from `center` along `velocity`, for up to `max_distance`
+	/// multiples of `velocity`, and returns the first thing it hits
+	fn cast_box(
+		&self,
+		center: Vec2,
+		half_extents: Vec2,
+		velocity: Vec2,
+		max_distance: f32,
+		scope: QueryScope,
+	) -> Option<CastHit>;
+
+	/// Every entity whose collider overlaps a box with the given `half_extents` at `center`
+	fn overlapping_box(&self, center: Vec2, half_extents: Vec2, scope: QueryScope) -> Vec<Entity>;
+
+	/// Whether nothing overlaps a box with the given `half_extents` at `center`
+	fn is_box_clear(&self, center: Vec2, half_extents: Vec2, scope: QueryScope) -> bool {
+		self.overlapping_box(center, half_extents, scope).is_empty()
+	}
+}
@@ -0,0 +1,79 @@
+use crate::physics::{CastHit, PhysicsWorld, QueryScope};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_rapier2d::geometry::{Collider, ShapeCastOptions};
+use bevy_rapier2d::pipeline::{QueryFilter, QueryFilterFlags};
+use bevy_rapier2d::plugin::{RapierContext, ReadRapierContext};
+
+/// The [PhysicsWorld] for systems to query, standing in for Rapier's own context parameter
+#[derive(SystemParam)]
+pub struct Physics<'w, 's> {
+	context: ReadRapierContext<'w, 's>,
+}
+
+impl Physics<'_, '_> {
+	pub fn world(&self) -> RapierWorld<'_> {
+		RapierWorld(self.context.single())
+	}
+}
+
+/// The [PhysicsWorld] implemented with Rapier
+pub struct RapierWorld<'a>(RapierContext<'a>);
+
+impl QueryScope {
+	fn filter(&self) -> QueryFilter<'static> {
+		let mut flags = QueryFilterFlags::EXCLUDE_SENSORS;
+		if !self.dynamic {
+			flags |= QueryFilterFlags::EXCLUDE_DYNAMIC;
+		}
+		QueryFilter {
+			flags,
+			exclude_collider: self.exclude,
+			exclude_rigid_body: self.exclude,
+			..default()
+		}
+	}
+}
+
+impl PhysicsWorld for RapierWorld<'_> {
+	fn cast_ray(&self, origin: Vec2, direction: Vec2, max_distance: f32, scope: QueryScope) -> Option<CastHit> {
+		self.0
+			.cast_ray_and_get_normal(origin, direction, max_distance, true, scope.filter())
+			.map(|(entity, hit)| CastHit {
+				entity,
+				distance: hit.time_of_impact,
+				normal: hit.normal,
+			})
+	}
+
+	fn cast_box(
+		&self,
+		center: Vec2,
+		half_extents: Vec2,
+		velocity: Vec2,
+		max_distance: f32,
+		scope: QueryScope,
+	) -> Option<CastHit> {
+		let shape = Collider::cuboid(half_extents.x, half_extents.y);
+		let options = ShapeCastOptions::with_max_time_of_impact(max_distance);
+		self.0
+			.cast_shape(center, 0.0, velocity, &shape, options, scope.filter())
+			.map(|(entity, hit)| CastHit {
+				entity,
+				distance: hit.time_of_impact,
+				// the normal on the shape that was hit, pointing out of it
+				normal: hit.details.map_or(Vec2::ZERO, |details| details.normal1),
+			})
+	}
+
+	fn overlapping_box(&self, center: Vec2, half_extents: Vec2, scope: QueryScope) -> Vec<Entity> {
+		let shape = Collider::cuboid(half_extents.x, half_extents.y);
+		let mut overlapping = Vec::new();
+		self.0
+			.intersections_with_shape(center, 0.0, &shape, scope.filter(), |entity| {
+				overlapping.push(entity);
+				true
+			});
+		overlapping
+	}
+}
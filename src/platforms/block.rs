@@ -0,0 +1,102 @@
+use crate::decor::ImpactParticle;
+use crate::player::{Player, PlayerControlState, TutorialGhost};
+use crate::util::{FrameCount, Lifetime, SeededRng};
+use bevy::prelude::*;
+use bevy_rapier2d::control::KinematicCharacterControllerOutput;
+use bevy_rapier2d::geometry::ColliderDisabled;
+use serde::Deserialize;
+
+/// How many pieces of debris a block shatters into
+const DEBRIS_COUNT: usize = 8;
+
+/// A platform that shatters when the player hits it the right way. Each way of breaking it has to be
+/// allowed explicitly, so blocks can be made that only give way to a ground pound, for example.
+///
+/// Broken blocks stay broken (collider disabled, sprite hidden) until the level restarts.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BreakableBlock {
+	/// Whether the player can break the block by jumping into it from below
+	#[serde(default)]
+	pub bonk: bool,
+	/// If set, the player can break the block by landing on it at least this fast, like a ground pound
+	#[serde(default)]
+	pub pound_speed: Option<f32>,
+	/// If set, the player can break the block by running into its side at least this fast, like a dash
+	#[serde(default)]
+	pub ram_speed: Option<f32>,
+}
+
+impl BreakableBlock {
+	/// Whether hitting the block with the given velocity, on the side of the player given by the
+	/// contact `normal` (pointing out of the player), breaks it. Both are relative to the player's gravity.
+	fn breaks(&self, normal: Vec2, velocity: Vec2) -> bool {
+		if normal.y > 0.7 {
+			self.bonk && velocity.y > 0.0
+		} else if normal.y < -0.7 {
+			self.pound_speed.is_some_and(|speed| -velocity.y >= speed)
+		} else {
+			self.ram_speed
+				.is_some_and(|speed| velocity.x * normal.x.signum() >= speed)
+		}
+	}
+}
+
+/// Sent when a player breaks a [BreakableBlock]
+#[derive(Event, Debug)]
+pub struct BlockBroken {
+	pub block: Entity,
+	pub player: Entity,
+	pub position: Vec2,
+}
+
+/// Breaks the blocks that the players ran into the right way during their latest move, throwing off
+/// debris in the block's color. Should run before `player_system`, while each player's controller
+/// output and `previous_total_velocity` still describe the same move.
+pub fn break_blocks_system(
+	mut commands: Commands,
+	players: Query<
+		(Entity, &PlayerControlState, &KinematicCharacterControllerOutput),
+		(With<Player>, Without<TutorialGhost>),
+	>,
+	mut blocks: Query<(&BreakableBlock, &Transform, &Sprite, &mut Visibility), Without<ColliderDisabled>>,
+	mut broken_events: EventWriter<BlockBroken>,
+) {
+	for (player, state, output) in &players {
+		let velocity = state.gravity.orient(state.previous_total_velocity);
+		for collision in &output.collisions {
+			let Some(hit) = collision.hit.details else {
+				continue;
+			};
+			let Ok((block, transform, sprite, mut visibility)) = blocks.get_mut(collision.entity) else {
+				continue;
+			};
+			// two players can hit the same block at once, but it only breaks once
+			if *visibility == Visibility::Hidden || !block.breaks(state.gravity.orient(hit.normal1), velocity) {
+				continue;
+			}
+			let position = transform.translation.truncate();
+			debug!("player broke block {:?}", collision.entity);
+			commands.entity(collision.entity).insert(ColliderDisabled);
+			*visibility = Visibility::Hidden;
+			broken_events.send(BlockBroken {
+				block: collision.entity,
+				player,
+				position,
+			});
+
+			let size = sprite.custom_size.unwrap_or(Vec2::ONE);
+			let mut rng = SeededRng::new(collision.entity.to_bits());
+			for _ in 0..DEBRIS_COUNT {
+				let offset = Vec2::new(rng.range(-0.5, 0.5), rng.range(-0.5, 0.5)) * size;
+				let velocity = Vec2::new(rng.range(-10.0, 10.0), rng.range(6.0, 16.0));
+				commands.spawn((
+					ImpactParticle::new(velocity),
+					Lifetime(FrameCount(30)),
+					Sprite::from_color(sprite.color, Vec2::splat(0.5)),
+					Transform::from_translation((position + offset).extend(0.2)),
+				));
+			}
+		}
+	}
+}
@@ -0,0 +1,195 @@
+use crate::decor::ImpactParticle;
+use crate::hazards::collider_area;
+use crate::level::{CurrentLevel, DisabledByGroup, LevelAsset, RoomChanged};
+use crate::player::{Player, PlayerControlState, TutorialGhost};
+use crate::util::{FrameCount, Lifetime};
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use bevy::utils::HashMap;
+use bevy_rapier2d::geometry::{Collider, ColliderDisabled};
+
+/// How far a cracking floor's sprite shakes from side to side, at most
+const SHAKE_DISTANCE: f32 = 0.15;
+
+/// How long the falling remains of a broken floor stay on screen
+const FALL_LIFETIME: FrameCount = FrameCount(45);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum BreakState {
+	#[default]
+	Intact,
+	/// Someone stood on the floor; it gives way once the cracks have spread for `crack_time`
+	Cracking(FrameCount),
+	/// The floor is gone (collider disabled, sprite hidden) until the player leaves the room, or until
+	/// it respawns on its own. Counts how long it's been gone.
+	Broken(FrameCount),
+}
+
+/// A floor that shakes and collapses shortly after the player stands on it
+#[derive(Component, Debug)]
+pub struct BreakableFloor {
+	pub crack_time: FrameCount,
+	/// If set, the floor comes back this long after breaking, rather than waiting for the player
+	/// to leave the room
+	pub respawn_time: Option<FrameCount>,
+	state: BreakState,
+}
+
+impl BreakableFloor {
+	pub fn new(crack_time: FrameCount, respawn_time: Option<FrameCount>) -> Self {
+		BreakableFloor {
+			crack_time,
+			respawn_time,
+			state: BreakState::Intact,
+		}
+	}
+
+	pub fn state(&self) -> BreakState {
+		self.state
+	}
+}
+
+/// Remembers which floors broke in each room, so they can be restored once the player leaves
+#[derive(Resource, Debug, Default)]
+pub struct BrokenFloors {
+	by_room: HashMap<String, Vec<Entity>>,
+}
+
+/// Starts the cracks on floors the player stands on, shakes the floors that are cracking, breaks the
+/// ones whose cracks have finished spreading (dropping their remains), and brings back the broken floors
+/// that respawn on their own once their time is up. Should run after `player_system`, so the ground
+/// sensor is up to date.
+pub fn breakable_floor_system(
+	mut commands: Commands,
+	players: Query<(&PlayerControlState, &Transform, &Collider), (With<Player>, Without<TutorialGhost>)>,
+	mut floors: Query<(
+		Entity,
+		&mut BreakableFloor,
+		&Transform,
+		&Collider,
+		&mut Sprite,
+		&mut Visibility,
+		Has<DisabledByGroup>,
+	)>,
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	mut broken_floors: ResMut<BrokenFloors>,
+) {
+	let level = current_level.and_then(|level| levels.get(level.0.id()));
+
+	for (player, ..) in &players {
+		if let Some(ground) = player.vertical_sensors.ground().filter(|_| player.grounded.is_set()) {
+			if let Ok((_, mut floor, ..)) = floors.get_mut(ground) {
+				if floor.state == BreakState::Intact {
+					floor.state = BreakState::Cracking(FrameCount(0));
+				}
+			}
+		}
+	}
+
+	for (entity, mut floor, transform, collider, mut sprite, mut visibility, disabled_by_group) in &mut floors {
+		let (crack_time, respawn_time) = (floor.crack_time, floor.respawn_time);
+		let size = sprite.custom_size.unwrap_or(Vec2::ONE);
+		match &mut floor.state {
+			BreakState::Intact => {}
+			BreakState::Cracking(elapsed) => {
+				elapsed.increment();
+				// the floor shakes harder the closer it gets to giving way
+				let progress = (elapsed.0 as f32 / crack_time.0.max(1) as f32).min(1.0);
+				let side = if elapsed.0 % 4 < 2 { 1.0 } else { -1.0 };
+				sprite.anchor = Anchor::Custom(Vec2::new(side * SHAKE_DISTANCE * progress / size.x, 0.0));
+				if *elapsed >= crack_time {
+					floor.state = BreakState::Broken(FrameCount(0));
+					sprite.anchor = Anchor::Center;
+					commands.entity(entity).insert(ColliderDisabled);
+					*visibility = Visibility::Hidden;
+					commands.spawn((
+						ImpactParticle::new(Vec2::ZERO),
+						Lifetime(FALL_LIFETIME),
+						Sprite::from_color(sprite.color, size),
+						*transform,
+					));
+
+					// floors that respawn on their own don't wait for the player to leave the room,
+					// and floors outside of any room stay broken
+					let room = level
+						.filter(|_| respawn_time.is_none())
+						.and_then(|level| level.room_at(transform.translation.truncate()));
+					if let Some(room) = room {
+						broken_floors.by_room.entry(room.id.clone()).or_default().push(entity);
+					}
+				}
+			}
+			BreakState::Broken(elapsed) => {
+				elapsed.increment();
+				let Some(respawn_time) = respawn_time.filter(|time| *elapsed >= *time) else {
+					continue;
+				};
+				// wait for the space to clear, rather than respawning around a player,
+				// and leave floors that are switched off with their group to the group
+				let area = collider_area(transform, collider);
+				let blocked = players.iter().any(|(_, player_transform, player_collider)| {
+					area.zip(collider_area(player_transform, player_collider))
+						.is_some_and(|(area, player_area)| !area.intersect(player_area).is_empty())
+				});
+				if blocked || disabled_by_group {
+					continue;
+				}
+				debug!("breakable floor respawned after {:?}", respawn_time);
+				floor.state = BreakState::Intact;
+				*visibility = Visibility::Inherited;
+				commands.entity(entity).remove::<ColliderDisabled>();
+			}
+		}
+	}
+}
+
+/// Restores the floors that broke in a room once the player has left it
+pub fn restore_broken_floors(
+	mut commands: Commands,
+	mut events: EventReader<RoomChanged>,
+	mut floors: Query<(&mut BreakableFloor, &mut Visibility, Has<DisabledByGroup>)>,
+	mut broken_floors: ResMut<BrokenFloors>,
+) {
+	for RoomChanged { left, .. } in events.read() {
+		let Some(restored) = left.as_ref().and_then(|room| broken_floors.by_room.remove(room)) else {
+			continue;
+		};
+		for entity in restored {
+			if let Ok((mut floor, mut visibility, disabled_by_group)) = floors.get_mut(entity) {
+				floor.state = BreakState::Intact;
+				// floors that are switched off with their group come back once `apply_level_groups`
+				// switches them back on
+				if disabled_by_group {
+					continue;
+				}
+				*visibility = Visibility::Inherited;
+				commands.entity(entity).remove::<ColliderDisabled>();
+			}
+		}
+	}
+}
+
+/// Draws spreading cracks across floors that are about to break
+pub fn draw_floor_cracks(floors: Query<(&BreakableFloor, &Transform, &Collider)>, mut gizmos: Gizmos) {
+	for (floor, transform, collider) in &floors {
+		let BreakState::Cracking(elapsed) = floor.state else {
+			continue;
+		};
+		let Some(half_extents) = collider.as_cuboid().map(|c| c.half_extents()) else {
+			continue;
+		};
+		let progress = (elapsed.0 as f32 / floor.crack_time.0.max(1) as f32).min(1.0);
+		let center = transform.translation.truncate();
+
+		// a zig-zag spreading out from the middle, growing with the progress
+		const SEGMENTS: usize = 8;
+		let reach = half_extents.x * progress;
+		let points = (0..=SEGMENTS).map(|i| {
+			let t = i as f32 / SEGMENTS as f32;
+			let zig = if i % 2 == 0 { 0.5 } else { -0.5 };
+			center + Vec2::new(reach * (t * 2.0 - 1.0), half_extents.y * zig)
+		});
+		gizmos.linestrip_2d(points, Color::srgba(0.1, 0.05, 0.0, 0.5 + 0.5 * progress));
+	}
+}
@@ -0,0 +1,71 @@
+use crate::player::{Player, PlayerInventory};
+use crate::util::{FrameCount, Tween, TweenTarget};
+use bevy::prelude::*;
+use bevy_rapier2d::geometry::{Collider, ColliderDisabled};
+
+/// How long an unlocked door takes to fade away
+const OPEN_FADE_DURATION: FrameCount = FrameCount(20);
+
+/// How close the player has to be to a locked door to unlock it, since they can't overlap it
+const UNLOCK_MARGIN: f32 = 0.1;
+
+/// A platform that stands in the player's way until they touch it while carrying the key with the
+/// matching id, after which it fades away and stops blocking anything
+#[derive(Component, Debug)]
+pub struct LockedDoor {
+	/// Id of the key collectible that opens the door
+	pub key: String,
+	open: bool,
+}
+
+impl LockedDoor {
+	pub fn new(key: String) -> Self {
+		LockedDoor { key, open: false }
+	}
+}
+
+/// Sent when a player unlocks a door
+#[derive(Event, Debug)]
+pub struct DoorOpened {
+	pub door: Entity,
+	pub player: Entity,
+}
+
+/// Opens the locked doors that players are touching while carrying their key
+pub fn open_locked_doors(
+	mut commands: Commands,
+	players: Query<(Entity, &PlayerInventory, &Transform, &Collider), With<Player>>,
+	mut doors: Query<(Entity, &mut LockedDoor, &Transform, &Collider, &Sprite), Without<Player>>,
+	mut opened_events: EventWriter<DoorOpened>,
+) {
+	for (door_entity, mut door, door_transform, door_collider, sprite) in &mut doors {
+		if door.open {
+			continue;
+		}
+		let half_extents = door_collider.as_cuboid().map_or(Vec2::ZERO, |c| c.half_extents());
+		let door_area =
+			Rect::from_center_half_size(door_transform.translation.truncate(), half_extents).inflate(UNLOCK_MARGIN);
+		let unlocked_by = players.iter().find(|(_, inventory, transform, collider)| {
+			let half_extents = collider.as_cuboid().map_or(Vec2::ZERO, |c| c.half_extents());
+			let area = Rect::from_center_half_size(transform.translation.truncate(), half_extents);
+			inventory.keys.contains(&door.key) && !area.intersect(door_area).is_empty()
+		});
+		let Some((player, ..)) = unlocked_by else {
+			continue;
+		};
+		info!("opened door locked with key {:?}", door.key);
+		door.open = true;
+		commands.entity(door_entity).insert((
+			ColliderDisabled,
+			Tween::new(
+				TweenTarget::sprite_alpha(sprite.color, 0.0),
+				OPEN_FADE_DURATION,
+				EaseFunction::QuadraticIn,
+			),
+		));
+		opened_events.send(DoorOpened {
+			door: door_entity,
+			player,
+		});
+	}
+}
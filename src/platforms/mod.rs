@@ -0,0 +1,21 @@
+mod block;
+mod breakable;
+mod door;
+mod motion;
+mod moving;
+mod one_way;
+mod spring;
+mod sticky;
+mod surface;
+mod zipline;
+
+pub use block::*;
+pub use breakable::*;
+pub use door::*;
+pub use motion::*;
+pub use moving::*;
+pub use one_way::*;
+pub use spring::*;
+pub use sticky::*;
+pub use surface::*;
+pub use zipline::*;
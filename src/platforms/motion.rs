@@ -0,0 +1,45 @@
+use bevy::math::Vec2;
+use bevy::prelude::{Component, Entity, Query};
+
+/// Tracks how far a kinematic platform has been moved during the current fixed update.
+///
+/// Platform movement systems are expected to run before `player_system`, so that the
+/// player controller can add the platform's delta to its own translation before Rapier
+/// resolves the move. Otherwise, a platform rising into the player would be resolved as
+/// a penetration (or a sideways push) instead of lifting the player.
+#[derive(Component, Default, Debug, Copy, Clone)]
+pub struct PlatformMotion {
+	/// Translation applied to the platform in the current frame
+	pub delta: Vec2,
+	/// The platform's current velocity (per second), for riders to inherit when they leave it
+	pub velocity: Vec2,
+}
+
+impl PlatformMotion {
+	/// Look up the motion of the platform identified by `entity`, if it is a moving platform
+	pub fn delta_of(query: &Query<&PlatformMotion>, entity: Entity) -> Vec2 {
+		query.get(entity).map_or(Vec2::ZERO, |motion| motion.delta)
+	}
+
+	/// Look up the velocity of the platform identified by `entity`, if it is a moving platform
+	pub fn velocity_of(query: &Query<&PlatformMotion>, entity: Entity) -> Vec2 {
+		query.get(entity).map_or(Vec2::ZERO, |motion| motion.velocity)
+	}
+
+	/// How far a rider gets carried this frame: along with the `ground` they're standing on, or only
+	/// sideways by a sticky platform they're still `stuck_to` after leaving it. Dynamic bodies haven't
+	/// moved yet this frame, so standing on one carries the rider along with its current `body_velocity`.
+	pub fn carry_delta(
+		query: &Query<&PlatformMotion>,
+		ground: Option<Entity>,
+		body_velocity: Option<Vec2>,
+		stuck_to: Option<Entity>,
+		delta_secs: f32,
+	) -> Vec2 {
+		match (ground, body_velocity) {
+			(Some(ground), _) if query.contains(ground) => Self::delta_of(query, ground),
+			(Some(_), Some(velocity)) => velocity * delta_secs,
+			_ => stuck_to.map_or(Vec2::ZERO, |platform| Self::delta_of(query, platform) * Vec2::X),
+		}
+	}
+}
@@ -0,0 +1,210 @@
+use crate::level::RoomInactive;
+use crate::platforms::PlatformMotion;
+use crate::util::GameClock;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// Determines what a [MovingPlatform] does after reaching the last waypoint in its path
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+pub enum PathMode {
+	/// Travel back through the waypoints in reverse order
+	PingPong,
+	/// Travel from the last waypoint directly back to the first
+	Loop,
+}
+
+/// A kinematic platform that travels between a list of waypoints.
+///
+/// Each leg of the path is eased independently, so e.g. with `SineInOut` the platform
+/// will slow down as it approaches each waypoint and speed up as it leaves.
+#[derive(Component, Debug)]
+#[require(PlatformMotion)]
+pub struct MovingPlatform {
+	pub waypoints: Vec<Vec2>,
+	/// Average speed along each leg of the path, in units per second
+	pub speed: f32,
+	pub easing: EaseFunction,
+	pub mode: PathMode,
+	/// index of the waypoint the platform is moving away from
+	from: usize,
+	/// index of the waypoint the platform is moving towards
+	to: usize,
+	/// ratio from 0.0 to 1.0 of how far along the current leg the platform has traveled
+	progress: f32,
+}
+
+impl MovingPlatform {
+	/// Create a platform that starts at the first of the given `waypoints`
+	pub fn new(waypoints: Vec<Vec2>, speed: f32, easing: EaseFunction, mode: PathMode) -> Self {
+		assert!(waypoints.len() >= 2, "a moving platform needs at least two waypoints");
+		MovingPlatform {
+			waypoints,
+			speed,
+			easing,
+			mode,
+			from: 0,
+			to: 1,
+			progress: 0.0,
+		}
+	}
+
+	/// Where the platform should be, given its current leg and progress
+	pub fn position(&self) -> Vec2 {
+		let a = self.waypoints[self.from];
+		let b = self.waypoints[self.to];
+		let t = EasingCurve::new(0.0, 1.0, self.easing).sample_unchecked(self.progress);
+		a.lerp(b, t)
+	}
+
+	/// Advance along the path by the given amount of time
+	fn advance(&mut self, delta_secs: f32) {
+		let leg_length = self.waypoints[self.from].distance(self.waypoints[self.to]);
+		if leg_length <= f32::EPSILON {
+			self.progress = 1.0;
+		} else {
+			self.progress += self.speed * delta_secs / leg_length;
+		}
+
+		if self.progress >= 1.0 {
+			// pick the next leg; any leftover progress is dropped so the platform pauses
+			// for a moment on each waypoint, which makes them easier to catch
+			let last = self.waypoints.len() - 1;
+			let next = match self.mode {
+				PathMode::Loop => (self.to + 1) % self.waypoints.len(),
+				PathMode::PingPong if self.to == last => self.to - 1,
+				PathMode::PingPong if self.to == 0 => 1,
+				PathMode::PingPong if self.to > self.from => self.to + 1,
+				PathMode::PingPong => self.to - 1,
+			};
+			self.from = self.to;
+			self.to = next;
+			self.progress = 0.0;
+		}
+	}
+}
+
+/// Moves each [MovingPlatform] along its path, recording the applied translation in its [PlatformMotion].
+///
+/// Platforms in a room the player isn't in stay put until the player gets there.
+///
+/// Must run before `player_system` so riders can be carried by the same delta.
+pub fn moving_platform_system(
+	mut platforms: Query<(
+		&mut MovingPlatform,
+		&mut PlatformMotion,
+		&mut Transform,
+		Has<RoomInactive>,
+	)>,
+	clock: Res<GameClock>,
+) {
+	for (mut platform, mut motion, mut transform, inactive) in &mut platforms {
+		if inactive {
+			*motion = PlatformMotion::default();
+			continue;
+		}
+		let previous = transform.translation.truncate();
+		platform.advance(clock.delta_secs());
+		let next = platform.position();
+
+		motion.delta = next - previous;
+		motion.velocity = if clock.delta_secs() > 0.0 {
+			motion.delta / clock.delta_secs()
+		} else {
+			Vec2::ZERO
+		};
+		transform.translation.x = next.x;
+		transform.translation.y = next.y;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::physics::MockWorld;
+	use crate::util::{VerticalSensors, YSide, tick_game_clock};
+	use std::time::Duration;
+
+	const RIDER: Entity = Entity::from_raw(1000);
+	const ELEVATOR_HALF_EXTENTS: Vec2 = Vec2::new(4.0, 0.5);
+	const RIDER_HALF_EXTENTS: Vec2 = Vec2::new(0.5, 2.0);
+	/// How far beneath their feet the rider looks for the ground, like the player's `detection_length`
+	const DETECTION_LENGTH: f32 = 0.1;
+
+	#[derive(Resource)]
+	struct Rider {
+		center: Vec2,
+		sensors: VerticalSensors,
+	}
+
+	/// Does what `player_system` does for a grounded player after the platforms have moved: finds the
+	/// ground beneath the rider, then gets carried by it
+	fn ride(
+		mut rider: ResMut<Rider>,
+		platforms: Query<(Entity, &Transform), With<MovingPlatform>>,
+		platform_motion: Query<&PlatformMotion>,
+		clock: Res<GameClock>,
+	) {
+		let physics = platforms
+			.iter()
+			.fold(MockWorld::default(), |physics, (entity, transform)| {
+				physics.with_box(entity, transform.translation.truncate(), ELEVATOR_HALF_EXTENTS)
+			});
+		let center = rider.center;
+		rider.sensors.update(
+			YSide::Down,
+			center,
+			RIDER_HALF_EXTENTS,
+			YSide::Up,
+			DETECTION_LENGTH,
+			&physics,
+			RIDER,
+		);
+		let ground = rider.sensors.ground();
+		rider.center += PlatformMotion::carry_delta(&platform_motion, ground, None, None, clock.delta_secs());
+	}
+
+	/// Rides an elevator up and down twice. The elevator eases in and out of each stop, and pauses on it,
+	/// so the rider has to keep up with it speeding up, slowing down, and turning around.
+	#[test]
+	fn rider_stays_grounded_through_a_vertical_elevator_trip() {
+		let bottom = Vec2::new(10.0, 5.0);
+		let top = Vec2::new(10.0, 25.0);
+		let feet_offset = ELEVATOR_HALF_EXTENTS.y + RIDER_HALF_EXTENTS.y;
+		let mut app = App::new();
+		app.init_resource::<GameClock>()
+			.init_resource::<Time<Fixed>>()
+			.insert_resource(Rider {
+				center: bottom + Vec2::Y * feet_offset,
+				sensors: VerticalSensors::default(),
+			})
+			.add_systems(Update, (tick_game_clock, moving_platform_system, ride).chain());
+		let elevator = app
+			.world_mut()
+			.spawn((
+				MovingPlatform::new(vec![bottom, top], 10.0, EaseFunction::SineInOut, PathMode::PingPong),
+				Transform::from_translation(bottom.extend(0.0)),
+			))
+			.id();
+
+		let (mut highest, mut lowest) = (f32::NEG_INFINITY, f32::INFINITY);
+		// two seconds per leg, and a frame of rest at each end
+		for frame in 0..(4 * 2 * 60 + 4) {
+			app.world_mut()
+				.resource_mut::<Time<Fixed>>()
+				.advance_by(Duration::from_secs_f64(1.0 / 60.0));
+			app.update();
+			let rider = app.world().resource::<Rider>();
+			assert_eq!(
+				rider.sensors.ground(),
+				Some(elevator),
+				"lost the elevator on frame {}",
+				frame
+			);
+			highest = highest.max(rider.center.y);
+			lowest = lowest.min(rider.center.y);
+		}
+		// the rider made it all the way up and back down
+		assert!((highest - feet_offset - top.y).abs() < 0.01);
+		assert!((lowest - feet_offset - bottom.y).abs() < 0.01);
+	}
+}
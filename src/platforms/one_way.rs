@@ -0,0 +1,84 @@
+use crate::player::{Player, PlayerControlState, PlayerSlot};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// Collision groups for players' character controllers, one per [PlayerSlot] (wrapping around past
+/// the last one). One-way platforms remove a player's group from their filter when that player
+/// should pass through them, so each player needs their own to stand on a platform that another
+/// player is jumping up through.
+const PLAYER_SLOT_GROUPS: [Group; 4] = [Group::GROUP_1, Group::GROUP_3, Group::GROUP_4, Group::GROUP_5];
+
+/// Every player's collision group, for things that none of the players collide with
+pub const PLAYER_GROUP: Group = Group::GROUP_1
+	.union(Group::GROUP_3)
+	.union(Group::GROUP_4)
+	.union(Group::GROUP_5);
+
+/// The collision group of the given player's character controller
+pub fn player_group(slot: PlayerSlot) -> Group {
+	PLAYER_SLOT_GROUPS[slot.0 % PLAYER_SLOT_GROUPS.len()]
+}
+
+/// A platform that can be jumped through from below, and landed on from above.
+/// Players can also drop down through it by holding Down and pressing jump.
+#[derive(Component, Debug)]
+#[require(CollisionGroups, ActiveHooks(one_way_active_hooks))]
+pub struct OneWayPlatform;
+
+fn one_way_active_hooks() -> ActiveHooks {
+	ActiveHooks::MODIFY_SOLVER_CONTACTS
+}
+
+/// The player's character controller doesn't go through Rapier's physics hooks, since it only
+/// performs scene queries. Instead, each one-way platform's collision groups are updated so that
+/// each player's controller only "sees" the platform when that player's feet are above it (relative
+/// to their gravity), and they aren't trying to drop through it.
+///
+/// Should run after `player_system`, so that a newly-started drop-through takes effect immediately.
+pub fn one_way_platform_system(
+	players: Query<(&PlayerSlot, &PlayerControlState, &Transform, &Collider), With<Player>>,
+	mut platforms: Query<(&Transform, &Collider, &mut CollisionGroups), (With<OneWayPlatform>, Without<Player>)>,
+) {
+	for (platform_transform, platform_collider, mut groups) in &mut platforms {
+		let platform_half_height = platform_collider.as_cuboid().map_or(0.0, |c| c.half_extents().y);
+
+		let mut filters = Group::ALL;
+		for (slot, player, player_transform, player_collider) in &players {
+			let up = player.gravity.up;
+			// the side of the platform facing away from the player's gravity is the one they can stand on
+			let platform_surface = platform_transform.translation.y + platform_half_height * up;
+			let player_feet =
+				player_transform.translation.y - player_collider.as_cuboid().map_or(0.0, |c| c.half_extents().y) * up;
+			// a little tolerance, since the controller keeps a small gap between the player and the ground
+			let above = (player_feet - platform_surface) * up >= -0.1;
+			if !above || !player.drop_through_cooldown.is_ready() {
+				filters -= player_group(*slot);
+			}
+		}
+		if groups.filters != filters {
+			groups.filters = filters;
+		}
+	}
+}
+
+/// Physics hooks that let dynamic bodies pass up through [OneWayPlatform]s, like the player can
+#[derive(SystemParam)]
+pub struct OneWayPlatformHooks<'w, 's> {
+	one_way_platforms: Query<'w, 's, (), With<OneWayPlatform>>,
+}
+
+impl BevyPhysicsHooks for OneWayPlatformHooks<'_, '_> {
+	fn modify_solver_contacts(&self, context: ContactModificationContextView) {
+		// The contact normal is expressed in the local-space of the first collider,
+		// so the "allowed" direction depends on which of the two colliders is the platform
+		let allowed_local_n1 = if self.one_way_platforms.contains(context.collider1()) {
+			Vect::Y
+		} else if self.one_way_platforms.contains(context.collider2()) {
+			-Vect::Y
+		} else {
+			return;
+		};
+		context.raw.update_as_oneway_platform(&allowed_local_n1.into(), 0.1);
+	}
+}
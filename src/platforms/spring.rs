@@ -0,0 +1,43 @@
+use crate::level::RoomInactive;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{CollisionEvent, RigidBody, Velocity};
+use serde::Deserialize;
+
+/// A platform that launches whatever lands on top of it. Players are launched by `player_system`,
+/// without spending any of their jumps; dynamic bodies are launched by [spring_launch_bodies].
+///
+/// Needs `ActiveEvents::COLLISION_EVENTS` on the platform for dynamic bodies to notice it.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Spring {
+	/// Velocity (per second) given to anything that lands on the spring
+	pub velocity: Vec2,
+}
+
+/// Launches dynamic bodies that come down onto a [Spring].
+/// Should run after the physics step, so that the launch velocity isn't overwritten by it.
+pub fn spring_launch_bodies(
+	mut collisions: EventReader<CollisionEvent>,
+	springs: Query<(&Spring, &Transform), Without<RoomInactive>>,
+	mut bodies: Query<(&RigidBody, &Transform, &mut Velocity), Without<Spring>>,
+) {
+	for event in collisions.read() {
+		let CollisionEvent::Started(a, b, _) = event else {
+			continue;
+		};
+		for (spring_entity, body_entity) in [(*a, *b), (*b, *a)] {
+			let Ok((spring, spring_transform)) = springs.get(spring_entity) else {
+				continue;
+			};
+			let Ok((body, body_transform, mut velocity)) = bodies.get_mut(body_entity) else {
+				continue;
+			};
+			// only bodies coming down onto the spring from above, not ones bumping into its side
+			let landed = body_transform.translation.y > spring_transform.translation.y && velocity.linvel.y <= 0.0;
+			if *body == RigidBody::Dynamic && landed {
+				debug!("spring {:?} launched {:?}", spring_entity, body_entity);
+				velocity.linvel = spring.velocity;
+			}
+		}
+	}
+}
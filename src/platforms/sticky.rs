@@ -0,0 +1,16 @@
+use crate::platforms::PlatformMotion;
+use crate::util::FrameCount;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// Makes a moving platform hold on to its riders: after losing contact with it, a player keeps being
+/// carried along sideways for `stick_frames`, as if they were still standing on it.
+///
+/// A fast platform can pull away from under the player for a frame, or the player can hop between
+/// two of them, and the grounded flag alone would leave them behind for that frame.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[require(PlatformMotion)]
+pub struct StickyPlatform {
+	pub stick_frames: FrameCount,
+}
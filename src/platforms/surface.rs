@@ -0,0 +1,77 @@
+use bevy::prelude::Component;
+use serde::Deserialize;
+
+/// Marks a platform's top surface as having special effects on players standing on it.
+/// The actual effects are data-driven, via the [SurfaceMaterialParams] in the player's params.
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+pub enum SurfaceMaterial {
+	Ice,
+	Mud,
+	Bouncy,
+}
+
+/// Marks a platform's sides as restricting how players can hold onto them.
+/// Platforms without this component can be grabbed, climbed, and wall-jumped off of as usual.
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+pub enum WallSurface {
+	/// Too slippery to hold onto (e.g. metal); the player can't grab, climb, or wall jump off of it
+	NoGrab,
+	/// Only the top edge can be grabbed, as a ledge; the face of the wall below it is as slippery as `NoGrab`
+	LedgeOnly,
+}
+
+/// Changes how players hold onto the sides of a platform that can be held onto at all.
+/// Walls that can't be grabbed in the first place are marked with [WallSurface::NoGrab] instead.
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+pub enum WallMaterial {
+	/// The player grabs hold of it without having to press towards it, and sticks to it
+	/// even when they drift into it without any momentum
+	Sticky,
+	/// The player can't grab, climb, or run up it, only slide down it (at the faster
+	/// `slippery_slide_max_speed`) or wall jump off of it
+	Slippery,
+}
+
+/// Multipliers applied to a player's grounded movement while standing on a particular surface
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SurfaceModifier {
+	pub max_speed: f32,
+	pub acceleration: f32,
+	pub deceleration: f32,
+	/// Fraction of the player's downward speed that is reflected back upward when landing
+	#[serde(default)]
+	pub bounce: f32,
+	/// Landings slower than this are absorbed instead of bouncing, so the player comes to rest
+	#[serde(default)]
+	pub bounce_threshold: f32,
+}
+
+impl SurfaceModifier {
+	/// A modifier that has no effect, e.g. for normal ground or when airborne
+	pub const NONE: SurfaceModifier = SurfaceModifier {
+		max_speed: 1.0,
+		acceleration: 1.0,
+		deceleration: 1.0,
+		bounce: 0.0,
+		bounce_threshold: 0.0,
+	};
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SurfaceMaterialParams {
+	pub ice: SurfaceModifier,
+	pub mud: SurfaceModifier,
+	pub bouncy: SurfaceModifier,
+}
+
+impl SurfaceMaterialParams {
+	pub fn get(&self, material: SurfaceMaterial) -> SurfaceModifier {
+		match material {
+			SurfaceMaterial::Ice => self.ice,
+			SurfaceMaterial::Mud => self.mud,
+			SurfaceMaterial::Bouncy => self.bouncy,
+		}
+	}
+}
@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// A line strung between two points, which the player can grab onto by jumping into it,
+/// then slide along under gravity (see [ZiplineRide](crate::player::ZiplineRide)).
+///
+/// Ziplines have no collider; the player finds them by their endpoints alone.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Zipline {
+	pub start: Vec2,
+	pub end: Vec2,
+}
+
+impl Zipline {
+	pub fn length(&self) -> f32 {
+		self.start.distance(self.end)
+	}
+
+	/// Unit vector pointing from `start` to `end`
+	pub fn direction(&self) -> Vec2 {
+		(self.end - self.start).normalize_or_zero()
+	}
+
+	/// The point `distance` along the line from its `start`
+	pub fn point_at(&self, distance: f32) -> Vec2 {
+		self.start + self.direction() * distance
+	}
+
+	/// How far along the line the closest point to `point` is
+	pub fn distance_along(&self, point: Vec2) -> f32 {
+		(point - self.start).dot(self.direction()).clamp(0.0, self.length())
+	}
+
+	/// A transform that lays a sprite of size `(length, thickness)` along the line
+	pub fn transform(&self, z: f32) -> Transform {
+		let delta = self.end - self.start;
+		Transform::from_translation(((self.start + self.end) * 0.5).extend(z))
+			.with_rotation(Quat::from_rotation_z(delta.y.atan2(delta.x)))
+	}
+}
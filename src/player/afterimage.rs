@@ -0,0 +1,109 @@
+use crate::player::{Player, PlayerControlParams, PlayerControlState};
+use crate::settings::Settings;
+use crate::util::{FrameCount, pick_pooled};
+use bevy::prelude::*;
+
+/// What caused an [Afterimage] to be left behind, which decides its tint
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AfterimageSource {
+	/// The player was simply moving fast
+	Speed,
+	/// The player was carried by the force of a wall jump
+	WallJump,
+}
+
+/// A fading copy of the player's sprite. Pooled: expired copies are hidden until reused.
+#[derive(Component, Debug, Default)]
+pub struct Afterimage {
+	/// `None` while the copy is sitting in the pool
+	age: Option<FrameCount>,
+}
+
+/// Counts the frames since the player last left an [Afterimage]
+#[derive(Component, Debug, Default)]
+pub struct AfterimageTimer(FrameCount);
+
+/// Leaves fading copies of the player's sprite behind them at regular intervals while they're
+/// moving fast. Runs after the physics step, so each copy lands where the player actually is.
+pub fn afterimage_system(
+	mut commands: Commands,
+	settings: Res<Settings>,
+	control_params: Res<Assets<PlayerControlParams>>,
+	mut players: Query<(&Player, &PlayerControlState, &mut AfterimageTimer, &Transform, &Sprite)>,
+	mut afterimages: Query<(Entity, &mut Afterimage, &mut Sprite, &mut Transform, &mut Visibility), Without<Player>>,
+) {
+	let style = &settings.afterimage;
+
+	// fade the existing copies, returning expired ones to the pool
+	for (_, mut afterimage, mut sprite, _, mut visibility) in &mut afterimages {
+		let Some(age) = afterimage.age.as_mut() else {
+			continue;
+		};
+		age.increment();
+		if *age >= style.lifetime {
+			afterimage.age = None;
+			*visibility = Visibility::Hidden;
+		} else {
+			let t = age.0 as f32 / style.lifetime.0.max(1) as f32;
+			sprite.color.set_alpha(style.opacity * (1.0 - t));
+		}
+	}
+
+	if !settings.accessibility.afterimages {
+		return;
+	}
+
+	let mut spawned = 0;
+	for (player_component, player, mut timer, player_transform, player_sprite) in &mut players {
+		timer.0.increment();
+		if timer.0 < style.interval {
+			continue;
+		}
+		let wall_jumping = control_params
+			.get(player_component.0.id())
+			.is_some_and(|params| player.wall_jump_force.eval(&params.wall_jump_force_decay) != Vec2::ZERO);
+		let source = if wall_jumping {
+			AfterimageSource::WallJump
+		} else if player.previous_total_velocity.length() >= style.speed_threshold {
+			AfterimageSource::Speed
+		} else {
+			continue;
+		};
+		timer.0.reset();
+
+		let tint = match source {
+			AfterimageSource::Speed => style.speed_tint,
+			AfterimageSource::WallJump => style.wall_jump_tint,
+		};
+		let sprite = Sprite {
+			color: tint.with_alpha(style.opacity),
+			..player_sprite.clone()
+		};
+		// slightly behind the player
+		let transform = player_transform.with_translation(player_transform.translation - Vec3::Z * 0.1);
+
+		let ages = afterimages
+			.iter()
+			.map(|(entity, afterimage, ..)| (entity, afterimage.age));
+		let recycled = pick_pooled(ages, spawned, style.max_active);
+
+		match recycled.and_then(|entity| afterimages.get_mut(entity).ok()) {
+			Some((_, mut afterimage, mut afterimage_sprite, mut afterimage_transform, mut visibility)) => {
+				afterimage.age = Some(FrameCount(0));
+				*afterimage_sprite = sprite;
+				*afterimage_transform = transform;
+				*visibility = Visibility::Visible;
+			}
+			None => {
+				commands.spawn((
+					Afterimage {
+						age: Some(FrameCount(0)),
+					},
+					sprite,
+					transform,
+				));
+				spawned += 1;
+			}
+		}
+	}
+}
@@ -0,0 +1,47 @@
+use crate::level::RoomInactive;
+use crate::player::{Player, PlayerSpawnPoint, TutorialGhost};
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// A rectangular area that becomes the player's spawn point once they pass through it.
+/// Players can also swap characters while standing in one (see [CharacterRoster](crate::player::CharacterRoster)).
+#[derive(Component, Debug)]
+#[require(Sensor)]
+pub struct Checkpoint;
+
+impl Checkpoint {
+	/// The area covered by a checkpoint with the given `transform` and `collider`
+	pub fn area(transform: &Transform, collider: &Collider) -> Rect {
+		let half_extents = collider.as_cuboid().map_or(Vec2::ZERO, |c| c.half_extents());
+		Rect::from_center_half_size(transform.translation.truncate(), half_extents)
+	}
+}
+
+/// Sent when a player reaches a new checkpoint, making it their spawn point
+#[derive(Event, Debug)]
+pub struct CheckpointReached {
+	pub player: Entity,
+	pub position: Vec2,
+}
+
+/// Moves each player's spawn point to the checkpoint their center is in, if any
+pub fn activate_checkpoints(
+	checkpoints: Query<(&Transform, &Collider), (With<Checkpoint>, Without<Player>, Without<RoomInactive>)>,
+	mut players: Query<(Entity, &Transform, &mut PlayerSpawnPoint), (With<Player>, Without<TutorialGhost>)>,
+	mut reached_events: EventWriter<CheckpointReached>,
+) {
+	for (player, transform, mut spawn_point) in &mut players {
+		let center = transform.translation.truncate();
+		let reached = checkpoints
+			.iter()
+			.find(|(checkpoint_transform, collider)| Checkpoint::area(checkpoint_transform, collider).contains(center));
+		if let Some((checkpoint_transform, _)) = reached {
+			let position = checkpoint_transform.translation.truncate();
+			if spawn_point.0 != position {
+				info!("reached checkpoint at {:?}", position);
+				spawn_point.0 = position;
+				reached_events.send(CheckpointReached { player, position });
+			}
+		}
+	}
+}
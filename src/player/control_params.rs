@@ -1,30 +1,197 @@
+use crate::platforms::SurfaceMaterialParams;
+use crate::player::ZiplineParams;
 use crate::util::{FrameCount, PlayerWallControlParams};
 use bevy::prelude::{Asset, Component, EaseFunction, TypePath};
 use serde::Deserialize;
 
-#[derive(Asset, Copy, Clone, Component, Debug, Deserialize, TypePath)]
+#[derive(Asset, Clone, Component, Debug, Deserialize, TypePath)]
+#[serde(deny_unknown_fields)]
 pub struct PlayerControlParams {
 	pub run: HorizontalControlParams,
+	/// Replaces `run` while the sprint input is held on the ground
+	pub sprint: HorizontalControlParams,
 	pub float: HorizontalControlParams,
+	pub crouch: HorizontalControlParams,
+	/// Height of the player's collider while crouching, as a fraction of their standing height
+	pub crouch_height_ratio: f32,
 	pub jump_speed: f32,
 	pub gravity: f32,
+	/// Terminal velocity; gravity stops accelerating the player once they fall this fast
+	pub max_fall_speed: f32,
+	/// If set, holding Down in midair raises the terminal velocity to this speed
+	#[serde(default)]
+	pub fast_fall_speed: Option<f32>,
+	/// Adjustments near the top of a jump, for a floatier apex
+	pub apex_modifier: ApexModifier,
+	/// If set, holding jump while falling with no jumps left lets the player glide
+	#[serde(default)]
+	pub glide: Option<GlideParams>,
+	pub swim: SwimParams,
+	/// Movement while hanging from a [Zipline](crate::platforms::Zipline)
+	pub zipline: ZiplineParams,
+	/// How far the player can be nudged sideways to slip around the corner of a ceiling they jumped into
+	pub corner_correction: f32,
+	/// Fraction of the player's speed that is transferred to a dynamic body each frame they push against it
+	pub push_strength: f32,
+	/// Whether standing on a dynamic body carries the player along with it (and weighs it down)
+	pub ride_dynamic_bodies: bool,
+	/// Mass used for the player's weight when standing on dynamic bodies
+	pub player_mass: f32,
 	pub coyote_time: FrameCount,
 	pub jump_input_buffer: FrameCount,
 	pub max_jumps: u8,
 	pub jump_cooldown: FrameCount,
+	/// Overrides for each of the `max_jumps`, in order (first jump, second jump, and so on).
+	/// Jumps without a profile use `jump_speed` and `jump_cooldown`.
+	#[serde(default)]
+	pub jump_profiles: Vec<JumpProfile>,
+	/// If set, jumping off the ground is charged up by holding jump, and happens on release
+	#[serde(default)]
+	pub charge_jump: Option<ChargeJumpParams>,
+	/// If set, landing on top of a dynamic body or an enemy while falling bounces the player back up
+	#[serde(default)]
+	pub stomp: Option<StompParams>,
+	/// How long a one-way platform stays passable after the player drops down through it
+	pub drop_through_duration: FrameCount,
+	/// Speed away from the wall at the start of a wall jump. This decays along the `wall_jump_force_decay`,
+	/// rather than being subject to the usual run/float control.
+	pub wall_jump_horizontal_speed: f32,
+	/// Upward speed at the start of a wall jump
+	pub wall_jump_vertical_speed: f32,
 	pub wall_jump_force_decay: ForceDecayCurve,
 	pub wall_jump_input_cooldown: FrameCount,
+	/// How the player is thrown around by a [KnockbackEvent](crate::player::KnockbackEvent)
+	pub knockback: KnockbackParams,
+	/// What happens when the player gets hurt, e.g. by a [Hazard](crate::hazards::Hazard)
+	pub damage: DamageParams,
 	pub wall_control_params: PlayerWallControlParams,
+	pub surface_materials: SurfaceMaterialParams,
+}
+
+impl PlayerControlParams {
+	/// How the jump with the given index (0 for the first jump, 1 for the second, etc) should feel
+	pub fn jump_profile(&self, index: usize) -> JumpProfile {
+		self.jump_profiles.get(index).cloned().unwrap_or_else(|| JumpProfile {
+			speed: self.jump_speed,
+			cooldown: self.jump_cooldown,
+			particles: None,
+			sound: None,
+		})
+	}
+}
+
+/// The feel of one particular jump in a sequence of multi-jumps
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JumpProfile {
+	pub speed: f32,
+	/// How long after this jump before the next one is allowed
+	pub cooldown: FrameCount,
+	/// Id of the particle effect to play for this jump
+	#[serde(default)]
+	pub particles: Option<String>,
+	/// Id of the sound effect to play for this jump
+	#[serde(default)]
+	pub sound: Option<String>,
+}
+
+/// Holding jump on the ground fills a charge meter, and letting go jumps with a speed scaled by the charge
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChargeJumpParams {
+	/// How long jump has to be held to fully charge the jump
+	pub charge_time: FrameCount,
+	/// Fraction of the jump speed used for an uncharged jump (a quick tap)
+	pub min_ratio: f32,
+	/// Fraction of the jump speed used for a fully charged jump
+	pub max_ratio: f32,
+}
+
+impl ChargeJumpParams {
+	/// Fraction of the jump speed to use after charging for the given number of frames
+	pub fn speed_ratio(&self, charge: FrameCount) -> f32 {
+		let progress = (charge.0 as f32 / self.charge_time.0.max(1) as f32).min(1.0);
+		self.min_ratio + (self.max_ratio - self.min_ratio) * progress
+	}
 }
 
 #[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct HorizontalControlParams {
 	pub max_speed: f32,
 	pub acceleration: f32,
 	pub deceleration: f32,
+	/// Replaces `deceleration` while the player is moving against their current direction, i.e. skidding
+	pub turn_deceleration: f32,
+}
+
+/// While the player is jumping and their vertical speed is within `threshold` of zero,
+/// gravity is scaled by `gravity_multiplier` and their max horizontal speed gets a `speed_boost`
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApexModifier {
+	pub threshold: f32,
+	pub gravity_multiplier: f32,
+	pub speed_boost: f32,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GlideParams {
+	/// Terminal velocity while gliding
+	pub fall_speed: f32,
+	/// Added to the `float` max speed while gliding
+	pub speed_bonus: f32,
+}
+
+/// Movement while the player is in a [WaterVolume](crate::zones::WaterVolume)
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SwimParams {
+	pub horizontal: HorizontalControlParams,
+	/// Gravity in the water, as a fraction of normal gravity
+	pub gravity_multiplier: f32,
+	/// Fraction of the player's velocity lost each frame
+	pub damping: f32,
+	pub max_sink_speed: f32,
+	/// Upward speed from pressing jump underwater
+	pub stroke_speed: f32,
+	/// Upward speed from pressing jump at the surface, to leap out of the water
+	pub surface_jump_speed: f32,
+}
+
+/// Bouncing off things by landing on them
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StompParams {
+	/// Upward speed the player bounces away with
+	pub bounce_speed: f32,
+	/// The player has to be falling at least this fast for a landing to count as a stomp
+	pub min_fall_speed: f32,
+	/// Number of spent jumps given back by a stomp (up to `max_jumps`)
+	pub jump_refund: u8,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KnockbackParams {
+	/// How the knockback force fades out
+	pub decay: ForceDecayCurve,
+	/// How long the player's directional input is ignored after being knocked back
+	pub control_loss: FrameCount,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DamageParams {
+	/// How much [Health](crate::player::Health) the player starts with
+	pub max_health: u32,
+	/// How long the player can't be hurt again after being hurt
+	pub invulnerability: FrameCount,
 }
 
 #[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ForceDecayCurve {
 	pub easing: EaseFunction,
 	pub duration: FrameCount,
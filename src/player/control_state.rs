@@ -1,13 +1,18 @@
 use bevy::math::Vec2;
-use bevy::prelude::{Component, Curve, EasingCurve};
-use crate::player::ForceDecayCurve;
-use crate::util::{CapacitiveFlag, Cooldown, FrameCount, PlayerWallControlState, Side, WallSensors};
+use bevy::prelude::{Component, Curve, EasingCurve, Entity};
+use crate::player::{ForceDecayCurve, ZiplineRide};
+use crate::util::{
+    CapacitiveFlag, Cooldown, FrameCount, PlayerWallControlState, Side, VerticalSensors, WallFrame, WallSensors, YSide,
+};
 
 #[derive(Component, Default)]
 pub struct PlayerControlState {
     /// tracks whether the player is on the ground, or how recently they were on the ground
     pub grounded: CapacitiveFlag,
 
+    /// tracks whether the player just bounced off a bouncy surface, or how recently they did
+    pub bounced: CapacitiveFlag,
+
     /// tracks whether the player is airborne as the result of a jump (as opposed to falling)
     pub jumping: bool,
 
@@ -17,11 +22,29 @@ pub struct PlayerControlState {
     /// flag used to avoid decrementing `jumps_remaining` every frame while in midair
     pub lost_jump_due_to_falling: bool,
 
+    /// which way gravity pulls the player, and how strongly
+    pub gravity: PlayerGravity,
+
     /// Velocity derived from the directional inputs, and gravity.
     /// Does not exclude "external forces" affecting the X direction, like
-    /// wall jumping or moving platforms
+    /// wall jumping or moving platforms.
+    /// Relative to `gravity`, i.e. positive Y is always away from the ground.
     pub own_velocity: Vec2,
 
+    /// Velocity that was inherited from something else, like momentum carried over from jumping
+    /// off a moving platform. Cleared upon landing or grabbing a wall
+    pub external_velocity: Vec2,
+
+    /// Velocity of the ground the player was most recently standing on
+    pub ground_velocity: Vec2,
+
+    /// the sticky platform the player was most recently standing on, which keeps carrying them
+    /// for a few frames after they lose contact with it
+    pub stuck_to: Option<Entity>,
+
+    /// while not "ready", the player is still carried along by the platform they're `stuck_to`
+    pub stick_cooldown: Cooldown,
+
     /// Input buffer for jumping
     pub jump_requested: CapacitiveFlag,
 
@@ -29,12 +52,56 @@ pub struct PlayerControlState {
     /// from a platform. Resets when landing on the ground
     pub jumps_remaining: u8,
 
+    /// how long jump has been held on the ground, when charge jumps are enabled
+    pub jump_charge: FrameCount,
+
     /// cooldown timer for jumping
     pub jump_cooldown: Cooldown,
 
+    /// while not "ready", one-way platforms let the player fall through them
+    pub drop_through_cooldown: Cooldown,
+
     /// a sensor object used to detect walls, ledges, and steps adjacent to the player
     pub wall_sensors: WallSensors,
 
+    /// a sensor object used to identify the entity the player is standing on,
+    /// and to detect obstructions above the player's head
+    pub vertical_sensors: VerticalSensors,
+
+    /// whether the player is walking into a small step, which they should automatically climb
+    pub stepping_up: bool,
+
+    /// tracks whether the player is walking into a step, or how recently they were
+    pub step_contact: CapacitiveFlag,
+
+    /// the side of the step the player most recently walked into
+    pub step_side: Option<Side>,
+
+    /// the side of the step the player jumped up onto, while they're still rising past it
+    pub step_jump: Option<Side>,
+
+    /// whether the player is crouching, with a shortened collider
+    pub crouching: bool,
+
+    /// whether the player is reversing direction on the ground, skidding to a stop before turning around
+    pub skidding: bool,
+
+    /// whether the player is holding jump to slow their fall, having run out of jumps
+    pub gliding: bool,
+
+    /// the height of the surface of the water the player is swimming in, if any
+    pub water_surface: Option<f32>,
+
+    /// the zipline the player is hanging from, if any
+    pub zipline: Option<ZiplineRide>,
+
+    /// while not "ready", the player can't grab onto a zipline
+    pub zipline_cooldown: Cooldown,
+
+    /// half-extents of the player's collider while standing, remembered from the first update
+    /// so that the collider can be restored after crouching
+    pub standing_half_extents: Option<Vec2>,
+
     /// a decaying force that is added when wall-jumping
     pub wall_jump_force: TemporaryForce,
 
@@ -44,13 +111,58 @@ pub struct PlayerControlState {
     /// tracks the side that the wall was on, when jumping from it
     pub wall_jump_latest_side: Option<Side>,
 
+    /// a decaying force that is added when knocked back by something
+    pub knockback_force: TemporaryForce,
+
+    /// amount of time after being knocked back, where directional input will be ignored
+    pub knockback_control_loss: Cooldown,
+
+    /// while not "ready", the player can't be hurt again
+    pub invulnerability: Cooldown,
+
     /// state that becomes active when the player comes in contact with a wall while airborne
     pub wall_control_state: PlayerWallControlState,
 
+    /// the inputs and outcome of the latest wall interaction update, for analysis tools
+    pub wall_frame: WallFrame,
+
+    /// drains while grabbing or climbing walls, and refills on the ground.
+    /// `None` until the next update, which fills it up to the character's `stamina_max`
+    pub stamina: Option<f32>,
+
     /// remembers the total computed velocity (per-second) from the previous update
     pub previous_total_velocity: Vec2,
 }
 
+/// The direction and strength of gravity, as felt by the player.
+///
+/// Most of the player's state (their `own_velocity`, the wall sensors, what counts as "grounded")
+/// is relative to this, so that walking on the ceiling works the same way as walking on the floor.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PlayerGravity {
+    /// The direction the player's head points, away from the ground
+    pub up: YSide,
+    /// Multiplier on the player's normal gravity
+    pub scale: f32,
+}
+
+impl Default for PlayerGravity {
+    fn default() -> Self {
+        PlayerGravity {
+            up: YSide::Up,
+            scale: 1.0,
+        }
+    }
+}
+
+impl PlayerGravity {
+    /// Converts a vector between world space and the player's gravity-relative space
+    /// (the conversion is the same in either direction)
+    pub fn orient(&self, v: Vec2) -> Vec2 {
+        Vec2::new(v.x, v.y * self.up)
+    }
+}
+
 
 #[derive(Default)]
 pub struct TemporaryForce {
@@ -0,0 +1,31 @@
+use bevy::prelude::*;
+
+/// How many players are playing together on this machine.
+///
+/// Enabled by running the game with `--coop`, which adds a second player. The players split the
+/// keyboard between them (WASD for the first, the arrow keys for the second), and each can use
+/// the gamepad in their [PlayerSlot](crate::player::PlayerSlot) as well.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct LocalPlayers(pub usize);
+
+impl LocalPlayers {
+	/// Reads whether to play co-op from the command line
+	pub fn from_args() -> Self {
+		if std::env::args().any(|arg| arg == "--coop") {
+			info!("starting local co-op");
+			LocalPlayers(2)
+		} else {
+			LocalPlayers(1)
+		}
+	}
+
+	/// The asset path of the [InputMap](crate::player::InputMap) for the player in the given slot.
+	/// A single player gets the whole keyboard to themselves.
+	pub fn input_map_path(&self, slot: usize) -> String {
+		if self.0 == 1 {
+			"controls.input.ron".into()
+		} else {
+			format!("controls.p{}.input.ron", slot + 1)
+		}
+	}
+}
@@ -0,0 +1,132 @@
+use crate::Platform;
+use crate::level::{CurrentLevel, LevelAsset};
+use crate::physics::{Physics, PhysicsWorld, QueryScope};
+use crate::platforms::OneWayPlatform;
+use crate::player::{Player, PlayerControlState, PlayerDied, TutorialGhost};
+use crate::util::{Side, YSide};
+use bevy::prelude::*;
+use bevy_rapier2d::geometry::Collider;
+use serde::Deserialize;
+
+/// How far an obstacle has to intrude into the player's collider before it counts as crushing them.
+/// The character controller keeps a small gap from everything it touches, so anything inside
+/// that gap was pushed there by something moving.
+const CRUSH_TOLERANCE: f32 = 0.2;
+
+/// What happens to a player who gets crushed, configured per level
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum CrushResponse {
+	/// The player dies (and respawns after the death replay)
+	Kill,
+	/// The player is squeezed out to whichever side of the crushing obstacle is closest
+	#[default]
+	PushOut,
+}
+
+/// The direction along which a player is being squeezed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CrushAxis {
+	/// Between the ground and something overhead
+	Vertical,
+	/// Between walls on the left and right
+	Horizontal,
+}
+
+/// Sent when a player gets squeezed between two obstacles, e.g. a moving platform and a wall
+#[derive(Event, Debug)]
+pub struct PlayerCrushedEvent {
+	pub player: Entity,
+	/// The obstacle that intruded into the player's collider
+	pub crusher: Entity,
+	pub axis: CrushAxis,
+	pub response: CrushResponse,
+}
+
+/// Detects players whose sensors report obstacles on opposing sides while something is
+/// overlapping their collider, and responds according to the current level's [CrushResponse].
+///
+/// Should run after `player_system`, so that the sensors are up to date.
+pub fn crush_detection_system(
+	mut players: Query<
+		(Entity, &mut PlayerControlState, &mut Transform, &Collider),
+		(With<Player>, Without<TutorialGhost>),
+	>,
+	crushers: Query<(&Transform, &Collider), (With<Platform>, Without<OneWayPlatform>, Without<Player>)>,
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	mut crushed_events: EventWriter<PlayerCrushedEvent>,
+	mut died_events: EventWriter<PlayerDied>,
+	physics: Physics,
+) {
+	let physics = physics.world();
+	let response = current_level
+		.and_then(|level| levels.get(level.0.id()))
+		.map_or(CrushResponse::default(), |level| level.crush_response);
+
+	for (player_entity, mut player, mut transform, collider) in &mut players {
+		let Some(half_extents) = collider.as_cuboid().map(|c| c.half_extents()) else {
+			continue;
+		};
+
+		let axis = if player.vertical_sensors.ground().is_some() && player.vertical_sensors.is_blocked(YSide::Up) {
+			CrushAxis::Vertical
+		} else if Side::BOTH.into_iter().all(|side| player.wall_sensors.any_hit(side)) {
+			CrushAxis::Horizontal
+		} else {
+			continue;
+		};
+
+		// Opposing sensor hits alone just mean the player is in a tight spot.
+		// They're only crushed if something has actually pushed into them.
+		let center = transform.translation.truncate();
+		let inner_half_extents = (half_extents - CRUSH_TOLERANCE).max(Vec2::ZERO);
+		let overlapping =
+			physics.overlapping_box(center, inner_half_extents, QueryScope::solid().excluding(player_entity));
+
+		// of everything intruding into the player, squeeze out of whichever is easiest to escape
+		let escape = overlapping
+			.into_iter()
+			.filter_map(|entity| {
+				let (crusher_transform, crusher_collider) = crushers.get(entity).ok()?;
+				let crusher_half_extents = crusher_collider.as_cuboid()?.half_extents();
+				let offset = center - crusher_transform.translation.truncate();
+				let clearance = half_extents + crusher_half_extents;
+				let escape = match axis {
+					// squeezed from above and below, so escape sideways
+					CrushAxis::Vertical => Vec2::X * (clearance.x.copysign(offset.x) - offset.x),
+					// squeezed from the sides, so escape over or under
+					CrushAxis::Horizontal => Vec2::Y * (clearance.y.copysign(offset.y) - offset.y),
+				};
+				Some((entity, escape))
+			})
+			.min_by(|(_, a), (_, b)| a.length_squared().total_cmp(&b.length_squared()));
+		let Some((crusher, escape)) = escape else {
+			continue;
+		};
+
+		info!("player crushed along {:?} axis by {:?}", axis, crusher);
+		crushed_events.send(PlayerCrushedEvent {
+			player: player_entity,
+			crusher,
+			axis,
+			response,
+		});
+
+		match response {
+			CrushResponse::Kill => {
+				died_events.send(PlayerDied {
+					player: player_entity,
+					impact_point: center,
+				});
+			}
+			CrushResponse::PushOut => {
+				transform.translation += escape.extend(0.0);
+				// don't let the player's momentum carry them straight back in
+				match axis {
+					CrushAxis::Vertical => player.own_velocity.y = 0.0,
+					CrushAxis::Horizontal => player.own_velocity.x = 0.0,
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,171 @@
+use crate::player::Player;
+use crate::settings::Settings;
+use crate::ui::WorldCamera;
+use crate::util::{GameClock, PauseReason};
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+/// A ring buffer of the player's most recent positions, one per fixed update
+#[derive(Component, Debug)]
+pub struct PlayerHistory {
+	frames: VecDeque<Vec3>,
+	capacity: usize,
+}
+
+impl Default for PlayerHistory {
+	fn default() -> Self {
+		// about 2 seconds at 60 updates per second
+		PlayerHistory::with_capacity(120)
+	}
+}
+
+impl PlayerHistory {
+	pub fn with_capacity(capacity: usize) -> Self {
+		PlayerHistory {
+			frames: VecDeque::with_capacity(capacity),
+			capacity,
+		}
+	}
+
+	pub fn record(&mut self, translation: Vec3) {
+		if self.frames.len() == self.capacity {
+			self.frames.pop_front();
+		}
+		self.frames.push_back(translation);
+	}
+
+	/// Copy out the most recent `count` frames, oldest first
+	pub fn latest(&self, count: usize) -> Vec<Vec3> {
+		let skip = self.frames.len().saturating_sub(count);
+		self.frames.iter().skip(skip).copied().collect()
+	}
+
+	/// The position from `count` frames ago, or the oldest one recorded if the history doesn't go back that far.
+	/// `None` only if nothing has been recorded yet.
+	pub fn frames_ago(&self, count: usize) -> Option<Vec3> {
+		let index = self.frames.len().saturating_sub(count + 1);
+		self.frames.get(index).copied()
+	}
+}
+
+/// Sent when the player dies, which starts a [DeathReplay]
+#[derive(Event, Debug)]
+pub struct PlayerDied {
+	pub player: Entity,
+	/// Where the fatal blow landed, which the camera will zoom towards during the replay
+	pub impact_point: Vec2,
+}
+
+/// Sent when a death replay finishes (or immediately, if replays are disabled),
+/// signalling that it is time to respawn the player
+#[derive(Event, Debug)]
+pub struct DeathReplayFinished(pub Entity);
+
+struct ActiveReplay {
+	player: Entity,
+	frames: Vec<Vec3>,
+	/// fractional index into `frames`
+	cursor: f32,
+	impact_point: Vec2,
+	camera_origin: Vec3,
+}
+
+/// Freezes the game on the player's death, then re-plays their last moments in slow motion
+/// with the camera zoomed in on the impact point. The replay only reads from the recorded
+/// [PlayerHistory]; the [GameClock] is paused throughout, so nothing in the world actually changes.
+#[derive(Resource, Default)]
+pub struct DeathReplay(Option<ActiveReplay>);
+
+impl DeathReplay {
+	pub fn is_playing(&self) -> bool {
+		self.0.is_some()
+	}
+}
+
+/// Runs after the physics step, recording where each player ended up
+pub fn record_player_history(mut players: Query<(&mut PlayerHistory, &Transform), With<Player>>) {
+	for (mut history, transform) in &mut players {
+		history.record(transform.translation);
+	}
+}
+
+pub fn start_death_replay(
+	mut died_events: EventReader<PlayerDied>,
+	mut finished_events: EventWriter<DeathReplayFinished>,
+	mut replay: ResMut<DeathReplay>,
+	mut clock: ResMut<GameClock>,
+	settings: Res<Settings>,
+	histories: Query<&PlayerHistory>,
+	camera: Query<&Transform, With<WorldCamera>>,
+) {
+	for PlayerDied { player, impact_point } in died_events.read() {
+		let style = &settings.death_replay;
+		let frames = histories
+			.get(*player)
+			.map(|h| h.latest(style.frames))
+			.unwrap_or_default();
+		if !style.enabled || frames.is_empty() || replay.is_playing() {
+			finished_events.send(DeathReplayFinished(*player));
+			continue;
+		}
+
+		info!(
+			"starting death replay of {} frames, at frame {:?}",
+			frames.len(),
+			clock.frame()
+		);
+		clock.pause(PauseReason::DeathReplay);
+		replay.0 = Some(ActiveReplay {
+			player: *player,
+			frames,
+			cursor: 0.0,
+			impact_point: *impact_point,
+			camera_origin: camera.get_single().map_or(Vec3::ZERO, |t| t.translation),
+		});
+	}
+}
+
+/// Drives the player's sprite and the camera during a death replay.
+/// Runs after render interpolation, so the replayed position wins for the frame.
+pub fn play_death_replay(
+	mut replay: ResMut<DeathReplay>,
+	mut finished_events: EventWriter<DeathReplayFinished>,
+	mut clock: ResMut<GameClock>,
+	real_time: Res<Time<Real>>,
+	settings: Res<Settings>,
+	mut players: Query<&mut Transform, (With<Player>, Without<WorldCamera>)>,
+	mut camera: Query<(&mut Transform, &mut OrthographicProjection), With<WorldCamera>>,
+) {
+	let Some(active) = replay.0.as_mut() else {
+		return;
+	};
+	let style = &settings.death_replay;
+
+	// the recording has one frame per fixed update, at 60 updates per second
+	active.cursor += real_time.delta_secs() * 60.0 * style.slow_motion;
+	let last_index = active.frames.len() - 1;
+	let progress = (active.cursor / last_index.max(1) as f32).min(1.0);
+
+	if let Ok(mut transform) = players.get_mut(active.player) {
+		transform.translation = active.frames[(active.cursor as usize).min(last_index)];
+	}
+
+	if let Ok((mut camera_transform, mut projection)) = camera.get_single_mut() {
+		// ease in towards the impact point over the first quarter of the replay
+		let zoom_t = EasingCurve::new(0.0, 1.0, EaseFunction::SineOut).sample_clamped(progress * 4.0);
+		let target = active.impact_point.extend(active.camera_origin.z);
+		camera_transform.translation = active.camera_origin.lerp(target, zoom_t);
+		projection.scale = 1.0 + (style.zoom - 1.0) * zoom_t;
+
+		if progress >= 1.0 {
+			camera_transform.translation = active.camera_origin;
+			projection.scale = 1.0;
+		}
+	}
+
+	if progress >= 1.0 {
+		finished_events.send(DeathReplayFinished(active.player));
+		replay.0 = None;
+		clock.unpause(PauseReason::DeathReplay);
+	}
+}
@@ -0,0 +1,87 @@
+use crate::audio::MusicIntensity;
+use crate::hazards::PlayerDamagedEvent;
+use crate::player::{Player, PlayerControlParams, PlayerControlState, PlayerDied};
+use bevy::prelude::*;
+
+/// How many more hits the player can take. Running out kills them, which sends them back to their
+/// spawn point with full health once the death replay is over.
+#[derive(Component, Debug, Default)]
+pub struct Health {
+	/// From the player's [DamageParams](crate::player::DamageParams)
+	pub max: u32,
+	pub current: u32,
+}
+
+impl Health {
+	pub fn is_dead(&self) -> bool {
+		self.current == 0
+	}
+
+	pub fn refill(&mut self) {
+		self.current = self.max;
+	}
+}
+
+/// Keeps each player's max health in line with their params (filling up the health of players that
+/// were just spawned), and applies [PlayerDamagedEvent]s: taking away health, making the player briefly
+/// invulnerable, and sending a [PlayerDied] once their health runs out.
+///
+/// Should run after the systems that send the damage events, so that the player is invulnerable to
+/// anything else that would hurt them in the same update.
+pub fn player_health_system(
+	mut damaged_events: EventReader<PlayerDamagedEvent>,
+	mut died_events: EventWriter<PlayerDied>,
+	control_params: Res<Assets<PlayerControlParams>>,
+	mut players: Query<(&Player, &mut Health, &mut PlayerControlState, &Transform)>,
+) {
+	for (player, mut health, mut state, _) in &mut players {
+		state.invulnerability.tick();
+		let Some(params) = control_params.get(player.0.id()) else {
+			continue;
+		};
+		if health.max != params.damage.max_health {
+			let spawned = health.max == 0;
+			health.max = params.damage.max_health;
+			health.current = if spawned {
+				health.max
+			} else {
+				health.current.min(health.max)
+			};
+		}
+	}
+
+	for event in damaged_events.read() {
+		let Ok((player, mut health, mut state, transform)) = players.get_mut(event.player) else {
+			continue;
+		};
+		// the dead can't be hurt, and neither can the recently hurt
+		if health.is_dead() || !state.invulnerability.is_ready() {
+			continue;
+		}
+		let Some(params) = control_params.get(player.0.id()) else {
+			continue;
+		};
+		health.current = health.current.saturating_sub(event.damage);
+		state.invulnerability.reset(params.damage.invulnerability);
+		info!(
+			"took {} damage, {}/{} health left",
+			event.damage, health.current, health.max
+		);
+		if health.is_dead() {
+			died_events.send(PlayerDied {
+				player: event.player,
+				impact_point: transform.translation.truncate(),
+			});
+		}
+	}
+}
+
+/// Raises the music's intensity as the player closest to death loses health,
+/// up to full intensity when they're down to their last hit point
+pub fn low_health_music_intensity(players: Query<&Health, With<Player>>, mut intensity: ResMut<MusicIntensity>) {
+	intensity.low_health = players
+		.iter()
+		.filter(|health| health.max > 1 && !health.is_dead())
+		.map(|health| (health.max - health.current) as f32 / (health.max - 1) as f32)
+		.fold(0.0, f32::max);
+}
@@ -0,0 +1,188 @@
+use crate::player::{InputAction, InputMap, PlayerInputMap, TouchControls};
+use crate::settings::Settings;
+use crate::util::{GameClock, YSide};
+use bevy::input::gamepad::GamepadConnectionEvent;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How far the stick has to be pushed up or down before it counts as pressing up or down,
+/// so that running with the stick held slightly off horizontal doesn't crouch or climb
+const VERTICAL_THRESHOLD: f32 = 0.5;
+
+/// Which player this is, when there's more than one. Each slot is controlled by the gamepad
+/// in the same slot of the [GamepadSlots], and by whichever keys the player's [PlayerInputMap] binds.
+#[derive(Component, Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PlayerSlot(pub usize);
+
+impl PlayerSlot {
+	/// The first player, who things that only follow one player (like the minimap) keep track of
+	pub const LEAD: PlayerSlot = PlayerSlot(0);
+}
+
+/// The connected gamepads, indexed by the [PlayerSlot] they control.
+///
+/// A newly connected gamepad takes the lowest free slot, and keeps it until it's disconnected,
+/// so which gamepad controls which player only depends on the order they were connected in,
+/// rather than on entity ids or query order.
+#[derive(Resource, Debug, Default)]
+pub struct GamepadSlots(Vec<Option<Entity>>);
+
+impl GamepadSlots {
+	/// The gamepad controlling the given slot, if any
+	pub fn gamepad(&self, slot: usize) -> Option<Entity> {
+		self.0.get(slot).copied().flatten()
+	}
+}
+
+/// Hands out [GamepadSlots] as gamepads are connected, and frees them up as they're disconnected
+pub fn assign_gamepad_slots(mut events: EventReader<GamepadConnectionEvent>, mut slots: ResMut<GamepadSlots>) {
+	for event in events.read() {
+		let gamepad = Some(event.gamepad);
+		if event.connected() && !slots.0.contains(&gamepad) {
+			let slot = match slots.0.iter().position(Option::is_none) {
+				Some(free) => {
+					slots.0[free] = gamepad;
+					free
+				}
+				None => {
+					slots.0.push(gamepad);
+					slots.0.len() - 1
+				}
+			};
+			info!("gamepad {:?} connected to player slot {}", event.gamepad, slot);
+		} else if event.disconnected() {
+			for slot in slots.0.iter_mut().filter(|slot| **slot == gamepad) {
+				*slot = None;
+			}
+			info!("gamepad {:?} disconnected", event.gamepad);
+		}
+	}
+}
+
+/// What a player is asking their character to do, gathered from the keyboard, their gamepad, and
+/// (for the lead player) the [TouchControls] through the [InputMap] by [gather_player_input], and acted on by `player_system`.
+///
+/// Input is gathered every frame, but the player moves in fixed updates, which don't line up with
+/// frames: a press can come and go during a frame where no fixed update runs, or several fixed updates
+/// can run in the same frame. So presses are latched until a fixed update has seen them, and then
+/// cleared by [clear_player_input_presses], making sure each press is acted on exactly once.
+#[derive(Component, Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerInput {
+	/// From -1 (full speed to the left) to 1 (full speed to the right). Keys and buttons are
+	/// all-or-nothing, while the stick asks for a speed proportional to how far it's pushed.
+	pub horizontal: f32,
+	/// From -1 (towards the ground) to 1 (away from it), relative to the player's gravity
+	pub vertical: f32,
+	/// Whether jump is held, or was pressed since the previous fixed update
+	pub jump: bool,
+	/// Whether jump was pressed since the previous fixed update
+	pub jump_pressed: bool,
+	/// Whether sprint is held
+	pub sprint: bool,
+	/// Whether swapping characters was pressed since the previous fixed update
+	#[serde(default)]
+	pub swap_character_pressed: bool,
+}
+
+impl PlayerInput {
+	/// Whether the player is pressing up or down, ignoring small vertical deflections of the stick
+	pub fn vertical_direction(&self) -> Option<YSide> {
+		match self.vertical {
+			v if v >= VERTICAL_THRESHOLD => Some(YSide::Up),
+			v if v <= -VERTICAL_THRESHOLD => Some(YSide::Down),
+			_ => None,
+		}
+	}
+
+	/// Updates the input with the current state of the keyboard, gamepad, and touch controls. Held inputs
+	/// are replaced, while presses are added to any that haven't been seen by a fixed update yet.
+	///
+	/// While the game is `paused`, presses are dropped rather than latched: they belong to whatever
+	/// paused it (e.g. confirming the results screen, or skipping a cinematic), and would otherwise
+	/// be acted on by the first fixed update after it unpauses.
+	fn gather(
+		&mut self,
+		map: &InputMap,
+		keyboard: Option<&ButtonInput<KeyCode>>,
+		gamepad: Option<&Gamepad>,
+		touch: Option<&TouchControls>,
+		dead_zone: f32,
+		paused: bool,
+	) {
+		let buttons = gamepad.map(Gamepad::digital);
+		let held = |action: InputAction| {
+			map.pressed(action, keyboard, buttons) || touch.is_some_and(|touch| touch.pressed(action))
+		};
+		let stick = gamepad.map_or(Vec2::ZERO, Gamepad::left_stick);
+		// keys and buttons win over the stick
+		let axis = |negative: InputAction, positive: InputAction, stick: f32| match (held(negative), held(positive)) {
+			(true, false) => -1.0,
+			(false, true) => 1.0,
+			_ => analog_axis(stick, dead_zone),
+		};
+
+		self.horizontal = axis(InputAction::MoveLeft, InputAction::MoveRight, stick.x);
+		self.vertical = axis(InputAction::MoveDown, InputAction::MoveUp, stick.y);
+		let just_pressed = |action: InputAction| {
+			!paused
+				&& (map.just_pressed(action, keyboard, buttons)
+					|| touch.is_some_and(|touch| touch.just_pressed(action)))
+		};
+		if paused {
+			self.jump_pressed = false;
+			self.swap_character_pressed = false;
+		}
+		self.jump_pressed |= just_pressed(InputAction::Jump);
+		self.jump = held(InputAction::Jump) || self.jump_pressed;
+		self.sprint = held(InputAction::Sprint);
+		self.swap_character_pressed |= just_pressed(InputAction::SwapCharacter);
+	}
+}
+
+/// Maps a raw stick axis value to [-1, 1], with the `dead_zone` around the center mapping to 0
+/// and the rest of the range rescaled so that the smallest deflection outside of it starts from 0
+fn analog_axis(value: f32, dead_zone: f32) -> f32 {
+	let magnitude = ((value.abs() - dead_zone) / (1.0 - dead_zone)).clamp(0.0, 1.0);
+	magnitude.copysign(value)
+}
+
+/// Fills in each player's [PlayerInput] from the keys their [PlayerInputMap] binds, and the gamepad in
+/// their [PlayerSlot]. The [TouchControls] control the lead player. Runs every frame in `PreUpdate` after input handling, so it's ready before the
+/// frame's fixed updates.
+pub fn gather_player_input(
+	kb: Res<ButtonInput<KeyCode>>,
+	input_maps: Res<Assets<InputMap>>,
+	slots: Res<GamepadSlots>,
+	gamepads: Query<&Gamepad>,
+	touch_controls: Res<TouchControls>,
+	settings: Res<Settings>,
+	clock: Res<GameClock>,
+	mut players: Query<(&PlayerSlot, Option<&PlayerInputMap>, &mut PlayerInput)>,
+) {
+	let default_map = InputMap::default();
+	for (slot, input_map, mut input) in &mut players {
+		let keyboard = input_map.is_some().then_some(&*kb);
+		let map = input_map
+			.and_then(|map| input_maps.get(map.0.id()))
+			.unwrap_or(&default_map);
+		let gamepad = slots.gamepad(slot.0).and_then(|entity| gamepads.get(entity).ok());
+		let touch = (*slot == PlayerSlot::LEAD).then_some(&*touch_controls);
+		input.gather(
+			map,
+			keyboard,
+			gamepad,
+			touch,
+			settings.controls.stick_dead_zone,
+			clock.is_paused(),
+		);
+	}
+}
+
+/// Forgets the presses that the just-finished fixed update has seen.
+/// Runs in `FixedLast`, after every fixed update.
+pub fn clear_player_input_presses(mut players: Query<&mut PlayerInput>) {
+	for mut input in &mut players {
+		input.jump_pressed = false;
+		input.swap_character_pressed = false;
+	}
+}
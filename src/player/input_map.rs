@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::Deserialize;
+
+/// Something the player can do, independent of which key or button does it
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum InputAction {
+	MoveLeft,
+	MoveRight,
+	/// Away from the ground, whichever way gravity points
+	MoveUp,
+	/// Towards the ground: crouching, dropping through one-way platforms, fast-falling
+	MoveDown,
+	Jump,
+	Sprint,
+	/// Reserved for holding onto walls and ledges on purpose, rather than by pushing into them
+	Grab,
+	/// Reserved for a quick burst of speed
+	Dash,
+	/// Switches to the next character in the player's roster
+	SwapCharacter,
+}
+
+impl InputAction {
+	/// The actions every input map needs to bind for the game to be playable
+	pub const REQUIRED: [InputAction; 3] = [InputAction::MoveLeft, InputAction::MoveRight, InputAction::Jump];
+}
+
+/// The keys and gamepad buttons that trigger an [InputAction]. Any one of them will do.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ActionBinding {
+	#[serde(default)]
+	pub keys: Vec<KeyCode>,
+	#[serde(default)]
+	pub buttons: Vec<GamepadButton>,
+}
+
+/// Which keys and gamepad buttons trigger each [InputAction], loaded from an `.input.ron` file.
+/// Hot-reloaded like the player's params, so bindings can be changed while playing.
+///
+/// The gamepad's left stick always moves the player, in addition to whatever the map binds.
+#[derive(Asset, TypePath, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InputMap {
+	pub bindings: HashMap<InputAction, ActionBinding>,
+}
+
+impl Default for InputMap {
+	/// The bindings used until the input map file has loaded
+	fn default() -> Self {
+		let binding = |keys: &[KeyCode], buttons: &[GamepadButton]| ActionBinding {
+			keys: keys.to_vec(),
+			buttons: buttons.to_vec(),
+		};
+		InputMap {
+			bindings: HashMap::from_iter([
+				(
+					InputAction::MoveLeft,
+					binding(&[KeyCode::KeyA, KeyCode::ArrowLeft], &[GamepadButton::DPadLeft]),
+				),
+				(
+					InputAction::MoveRight,
+					binding(&[KeyCode::KeyD, KeyCode::ArrowRight], &[GamepadButton::DPadRight]),
+				),
+				(InputAction::MoveUp, binding(&[KeyCode::KeyW], &[GamepadButton::DPadUp])),
+				(
+					InputAction::MoveDown,
+					binding(&[KeyCode::KeyS], &[GamepadButton::DPadDown]),
+				),
+				(InputAction::Jump, binding(&[KeyCode::Space], &[GamepadButton::South])),
+				(
+					InputAction::Sprint,
+					binding(&[KeyCode::ShiftLeft, KeyCode::ShiftRight], &[GamepadButton::West]),
+				),
+				(
+					InputAction::Grab,
+					binding(&[KeyCode::KeyJ], &[GamepadButton::RightTrigger]),
+				),
+				(InputAction::Dash, binding(&[KeyCode::KeyK], &[GamepadButton::East])),
+				(
+					InputAction::SwapCharacter,
+					binding(&[KeyCode::Tab], &[GamepadButton::North]),
+				),
+			]),
+		}
+	}
+}
+
+impl InputMap {
+	/// Whether any of the action's keys or buttons is held
+	pub fn pressed(
+		&self,
+		action: InputAction,
+		keyboard: Option<&ButtonInput<KeyCode>>,
+		buttons: Option<&ButtonInput<GamepadButton>>,
+	) -> bool {
+		self.check(action, keyboard, buttons, ButtonInput::pressed, ButtonInput::pressed)
+	}
+
+	/// Whether any of the action's keys or buttons was pressed this frame
+	pub fn just_pressed(
+		&self,
+		action: InputAction,
+		keyboard: Option<&ButtonInput<KeyCode>>,
+		buttons: Option<&ButtonInput<GamepadButton>>,
+	) -> bool {
+		self.check(
+			action,
+			keyboard,
+			buttons,
+			ButtonInput::just_pressed,
+			ButtonInput::just_pressed,
+		)
+	}
+
+	fn check(
+		&self,
+		action: InputAction,
+		keyboard: Option<&ButtonInput<KeyCode>>,
+		buttons: Option<&ButtonInput<GamepadButton>>,
+		key_check: fn(&ButtonInput<KeyCode>, KeyCode) -> bool,
+		button_check: fn(&ButtonInput<GamepadButton>, GamepadButton) -> bool,
+	) -> bool {
+		let Some(binding) = self.bindings.get(&action) else {
+			return false;
+		};
+		keyboard.is_some_and(|kb| binding.keys.iter().any(|&key| key_check(kb, key)))
+			|| buttons.is_some_and(|buttons| binding.buttons.iter().any(|&button| button_check(buttons, button)))
+	}
+}
+
+/// The [InputMap] that a player's input is read through. In co-op, each player has their own map,
+/// binding their own share of the keyboard.
+///
+/// Players without one (like the benchmark's bots) can only be controlled with a gamepad.
+#[derive(Component, Debug)]
+pub struct PlayerInputMap(pub Handle<InputMap>);
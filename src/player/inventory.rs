@@ -0,0 +1,25 @@
+use crate::level::RestartLevel;
+use crate::player::Player;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+/// What a player is carrying around the level
+#[derive(Component, Debug, Default)]
+pub struct PlayerInventory {
+	/// Ids of the keys the player has picked up, each opening the
+	/// [LockedDoor](crate::platforms::LockedDoor)s locked with that id
+	pub keys: HashSet<String>,
+}
+
+/// Empties every player's pockets when the level restarts, since the keys go back where they were found
+pub fn reset_inventories(
+	mut restart_events: EventReader<RestartLevel>,
+	mut inventories: Query<&mut PlayerInventory, With<Player>>,
+) {
+	if restart_events.read().count() == 0 {
+		return;
+	}
+	for mut inventory in &mut inventories {
+		inventory.keys.clear();
+	}
+}
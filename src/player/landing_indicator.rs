@@ -0,0 +1,62 @@
+use crate::physics::{Physics, PhysicsWorld, QueryScope};
+use crate::player::{Player, PlayerControlState};
+use crate::settings::Settings;
+use bevy::prelude::*;
+use bevy_rapier2d::geometry::Collider;
+
+/// The shadow sprite drawn on the ground beneath an airborne player,
+/// to help judge where they will land. Holds the player it belongs to.
+#[derive(Component)]
+pub struct LandingIndicator(pub Entity);
+
+pub fn landing_indicator_system(
+	settings: Res<Settings>,
+	player_query: Query<(Entity, &PlayerControlState, &Transform, &Collider), With<Player>>,
+	mut indicator_query: Query<(&LandingIndicator, &mut Transform, &mut Sprite, &mut Visibility), Without<Player>>,
+	physics: Physics,
+) {
+	let style = &settings.landing_indicator;
+	let physics = physics.world();
+
+	for (indicator, mut indicator_transform, mut sprite, mut visibility) in &mut indicator_query {
+		let Ok((player_entity, player, player_transform, player_collider)) = player_query.get(indicator.0) else {
+			*visibility = Visibility::Hidden;
+			continue;
+		};
+
+		let hit = if style.enabled && !player.grounded.is_set() {
+			let half_height = player_collider.as_cuboid().map_or(0.0, |c| c.half_extents().y);
+			let feet = player_transform.translation.truncate() - Vec2::new(0.0, half_height);
+			physics
+				.cast_ray(
+					feet,
+					Vec2::NEG_Y,
+					style.max_distance,
+					QueryScope::solid().excluding(player_entity),
+				)
+				.map(|hit| (feet, hit.distance))
+		} else {
+			None
+		};
+
+		match hit {
+			Some((feet, distance)) => {
+				// shrink (and optionally fade) the shadow as the player gets further from the ground
+				let closeness = 1.0 - (distance / style.max_distance).clamp(0.0, 1.0);
+				let alpha = if style.fade_with_distance {
+					style.color.alpha() * closeness
+				} else {
+					style.color.alpha()
+				};
+				sprite.color = style.color.with_alpha(alpha);
+				sprite.custom_size = Some(Vec2::new(style.width * (0.5 + 0.5 * closeness), style.thickness));
+				indicator_transform.translation.x = feet.x;
+				indicator_transform.translation.y = feet.y - distance + style.thickness * 0.5;
+				*visibility = Visibility::Visible;
+			}
+			None => {
+				*visibility = Visibility::Hidden;
+			}
+		}
+	}
+}
@@ -1,4 +1,4 @@
-use crate::player::PlayerControlParams;
+use crate::player::{InputMap, PlayerControlParams};
 use bevy::asset::io::Reader;
 use bevy::asset::{AssetLoader, LoadContext};
 use thiserror::Error;
@@ -35,3 +35,29 @@ impl AssetLoader for PlayerAssetLoader {
 		&["ron"]
 	}
 }
+
+#[derive(Default)]
+pub struct InputMapLoader;
+
+impl AssetLoader for InputMapLoader {
+	type Asset = InputMap;
+	type Settings = ();
+	type Error = PlayerAssetLoaderError;
+
+	async fn load(
+		&self,
+		reader: &mut dyn Reader,
+		_settings: &Self::Settings,
+		_load_context: &mut LoadContext<'_>,
+	) -> Result<Self::Asset, Self::Error> {
+		let mut bytes = Vec::new();
+		reader.read_to_end(&mut bytes).await?;
+		let input_map = ron::de::from_bytes::<InputMap>(&bytes)?;
+		Ok(input_map)
+	}
+
+	fn extensions(&self) -> &[&str] {
+		// more specific than the "ron" claimed by the player loader
+		&["input.ron"]
+	}
+}
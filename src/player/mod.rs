@@ -1,15 +1,61 @@
+mod afterimage;
+mod checkpoint;
 mod control_params;
 mod control_state;
+mod coop;
+mod crush;
+mod death_replay;
+mod health;
+mod input;
+mod inventory;
+mod input_map;
+mod landing_indicator;
 mod loader;
+mod respawn;
+mod roster;
+mod softlock;
+mod speed_feedback;
 mod system;
+mod touch_controls;
+mod tutorial_ghost;
+mod wall_jump_assist;
+mod zipline;
 
 use bevy::asset::Handle;
 use bevy::prelude::Component;
+pub use afterimage::*;
+pub use checkpoint::*;
 pub use control_params::*;
 pub use control_state::*;
+pub use coop::*;
+pub use crush::*;
+pub use death_replay::*;
+pub use health::*;
+pub use input::*;
+pub use inventory::*;
+pub use input_map::*;
+pub use landing_indicator::*;
 pub use loader::*;
+pub use respawn::*;
+pub use roster::*;
+pub use softlock::*;
+pub use speed_feedback::*;
 pub use system::*;
+pub use touch_controls::*;
+pub use tutorial_ghost::*;
+pub use wall_jump_assist::*;
+pub use zipline::*;
 
 #[derive(Component, Debug)]
-#[require(PlayerControlState)]
+#[require(
+	PlayerControlState,
+	PlayerInput,
+	PlayerSlot,
+	PlayerHistory,
+	AfterimageTimer,
+	SoftlockWatchdog,
+	SpeedTracker,
+	Health,
+	PlayerInventory
+)]
 pub struct Player(pub Handle<PlayerControlParams>);
@@ -0,0 +1,52 @@
+use crate::player::{DeathReplayFinished, Health, Player, PlayerControlState};
+use crate::util::PhysicsPosition;
+use bevy::prelude::*;
+
+/// Where a player goes back to after dying
+#[derive(Component, Debug)]
+pub struct PlayerSpawnPoint(pub Vec2);
+
+/// Sent to put a player back at their spawn point without dying, e.g. when they accept the
+/// prompt offered after getting softlocked
+#[derive(Event, Debug)]
+pub struct ReturnToCheckpoint(pub Entity);
+
+/// Moves players back to their spawn point once their death replay has finished (with their health
+/// refilled), or when asked to by a [ReturnToCheckpoint]
+pub fn respawn_players(
+	mut events: EventReader<DeathReplayFinished>,
+	mut returns: EventReader<ReturnToCheckpoint>,
+	mut players: Query<
+		(
+			&PlayerSpawnPoint,
+			&mut PlayerControlState,
+			&mut Transform,
+			&mut Health,
+			Option<&mut PhysicsPosition>,
+		),
+		With<Player>,
+	>,
+) {
+	let entities = events
+		.read()
+		.map(|e| (e.0, true))
+		.chain(returns.read().map(|e| (e.0, false)));
+	for (entity, died) in entities {
+		let Ok((spawn_point, mut player, mut transform, mut health, position)) = players.get_mut(entity) else {
+			continue;
+		};
+		info!("respawning player at {:?}", spawn_point.0);
+		transform.translation = spawn_point.0.extend(transform.translation.z);
+		if let Some(mut position) = position {
+			position.snap(&transform);
+		}
+		player.own_velocity = Vec2::ZERO;
+		player.external_velocity = Vec2::ZERO;
+		player.ground_velocity = Vec2::ZERO;
+		// filled back up on the next update
+		player.stamina = None;
+		if died {
+			health.refill();
+		}
+	}
+}
@@ -0,0 +1,104 @@
+use crate::level::RoomInactive;
+use crate::physics::{Physics, PhysicsWorld, QueryScope};
+use crate::player::{Checkpoint, Player, PlayerControlParams, PlayerControlState, PlayerInput};
+use crate::util::PhysicsPosition;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// Collider shapes closer than this to the surrounding geometry still count as fitting,
+/// so that a character standing on the ground can swap into one of the same height
+const SWAP_CLEARANCE_TOLERANCE: f32 = 0.05;
+
+/// One of the characters a player can swap between, each with their own movement profile
+#[derive(Debug, Clone)]
+pub struct Character {
+	pub name: String,
+	pub params: Handle<PlayerControlParams>,
+	/// Half-extents of the character's collider (and sprite) while standing
+	pub half_extents: Vec2,
+	pub color: Color,
+}
+
+/// The characters available to a player. Swapping between them keeps the player where they are,
+/// but starts the new character off with a fresh [PlayerControlState].
+#[derive(Component, Debug)]
+pub struct CharacterRoster {
+	pub characters: Vec<Character>,
+	/// Index of the character currently being played
+	pub current: usize,
+	/// Whether the player can swap anywhere, rather than only while standing in a [Checkpoint]
+	pub swap_anywhere: bool,
+}
+
+impl CharacterRoster {
+	pub fn current(&self) -> &Character {
+		&self.characters[self.current]
+	}
+}
+
+/// Swaps the player to the next character in their [CharacterRoster] when they press the swap button.
+/// Should run before `player_system`, so the new character's params are used right away.
+///
+/// The new character's feet are kept where the old character's were. If the new character's collider
+/// wouldn't fit there (e.g. a tall character in a low tunnel), the swap doesn't happen.
+pub fn swap_character_system(
+	physics: Physics,
+	checkpoints: Query<(&Transform, &Collider), (With<Checkpoint>, Without<Player>, Without<RoomInactive>)>,
+	mut players: Query<(
+		Entity,
+		&PlayerInput,
+		&mut Player,
+		&mut CharacterRoster,
+		&mut PlayerControlState,
+		&mut Transform,
+		&mut Collider,
+		&mut Sprite,
+		Option<&mut PhysicsPosition>,
+	)>,
+) {
+	let physics = physics.world();
+	for (entity, input, mut player, mut roster, mut state, mut transform, mut collider, mut sprite, position) in
+		&mut players
+	{
+		if !input.swap_character_pressed || roster.characters.len() < 2 {
+			continue;
+		}
+		let center = transform.translation.truncate();
+		let at_checkpoint = checkpoints
+			.iter()
+			.any(|(checkpoint_transform, collider)| Checkpoint::area(checkpoint_transform, collider).contains(center));
+		if !roster.swap_anywhere && !at_checkpoint {
+			debug!("can only swap characters at a checkpoint");
+			continue;
+		}
+
+		let next = (roster.current + 1) % roster.characters.len();
+		let character = roster.characters[next].clone();
+		let up = state.gravity.up;
+		let current_half_height = collider.as_cuboid().map_or(0.0, |c| c.half_extents().y);
+		let feet_y = center.y - current_half_height * up;
+		let next_center = Vec2::new(center.x, feet_y + character.half_extents.y * up);
+
+		let inset = (character.half_extents - SWAP_CLEARANCE_TOLERANCE).max(Vec2::ZERO);
+		if !physics.is_box_clear(next_center, inset, QueryScope::solid_and_dynamic().excluding(entity)) {
+			info!("no room to swap to {:?} here", character.name);
+			continue;
+		}
+
+		info!("swapping from {:?} to {:?}", roster.current().name, character.name);
+		roster.current = next;
+		player.0 = character.params;
+		*collider = Collider::cuboid(character.half_extents.x, character.half_extents.y);
+		sprite.color = character.color;
+		transform.translation = next_center.extend(transform.translation.z);
+		if let Some(mut position) = position {
+			position.snap(&transform);
+		}
+		// velocities, timers, and sensor readings all belong to the old character; only the gravity
+		// is kept, since that comes from the player's surroundings rather than the character
+		*state = PlayerControlState {
+			gravity: state.gravity,
+			..default()
+		};
+	}
+}
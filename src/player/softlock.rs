@@ -0,0 +1,239 @@
+use crate::physics::{Physics, PhysicsWorld, QueryScope};
+use crate::player::{Player, PlayerControlParams, PlayerControlState, PlayerInput, ReturnToCheckpoint, TutorialGhost};
+use crate::util::{FrameCount, GameClock, YSide};
+use bevy::prelude::*;
+use bevy_rapier2d::geometry::Collider;
+
+/// The directions (left, right, and up) the player is trying to move in, as bits
+fn movement_directions(input: &PlayerInput) -> u8 {
+	let up = input.jump || input.vertical_direction() == Some(YSide::Up);
+	[input.horizontal < 0.0, input.horizontal > 0.0, up]
+		.into_iter()
+		.enumerate()
+		.filter(|(_, trying)| *trying)
+		.fold(0u8, |bits, (i, _)| bits | 1 << i)
+}
+
+/// Thresholds for deciding that the player can't get anywhere on their own
+#[derive(Resource, Debug)]
+pub struct SoftlockParams {
+	/// The player counts as stuck after trying to move for this long without getting anywhere.
+	/// Only counts once they've tried at least two different directions, so that simply running
+	/// into a wall doesn't count.
+	pub stuck_time: FrameCount,
+	/// Moving less than this far from where the player got stuck doesn't count as getting anywhere
+	pub min_displacement: f32,
+	/// The player counts as trapped after standing in a pit with no reachable exit for this long
+	pub trapped_time: FrameCount,
+	/// An opening at least this wide (to either side, within reach) counts as a way out of a pit
+	pub exit_width: f32,
+	/// Vertical spacing between the probes looking for a way out
+	pub probe_spacing: f32,
+}
+
+impl Default for SoftlockParams {
+	fn default() -> Self {
+		SoftlockParams {
+			stuck_time: FrameCount(300),
+			min_displacement: 1.0,
+			trapped_time: FrameCount(180),
+			exit_width: 6.0,
+			probe_spacing: 1.0,
+		}
+	}
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SoftlockReason {
+	/// Trying to move, but not going anywhere (e.g. wedged in some geometry)
+	Stuck,
+	/// Standing in a pit that the player can't jump or climb out of
+	Trapped,
+}
+
+/// Sent once when a player is found to be softlocked, as the return prompt is offered
+#[derive(Event, Debug)]
+pub struct SoftlockDetected {
+	pub player: Entity,
+	pub reason: SoftlockReason,
+}
+
+/// Per-player state of the anti-softlock watchdog
+#[derive(Component, Debug, Default)]
+pub struct SoftlockWatchdog {
+	/// Where the player was when they last made any real progress
+	anchor: Option<Vec2>,
+	/// The `movement_directions` tried since the `anchor`, as bits
+	directions_tried: u8,
+	stuck_for: FrameCount,
+	trapped_for: FrameCount,
+	detected: Option<SoftlockReason>,
+}
+
+impl SoftlockWatchdog {
+	/// The reason the return prompt is being offered, if it is
+	pub fn detected(&self) -> Option<SoftlockReason> {
+		self.detected
+	}
+
+	pub fn reset(&mut self) {
+		*self = SoftlockWatchdog::default();
+	}
+}
+
+/// Highest the player can get above the ground under their own power: every jump at full height,
+/// plus climbing a wall for as long as their stamina lasts
+pub fn reachable_height(params: &PlayerControlParams, delta_secs: f32) -> f32 {
+	let mut jump_height = 0.0;
+	for index in 0..params.max_jumps as usize {
+		let mut vy = params.jump_profile(index).speed;
+		while vy > 0.0 && params.gravity < 0.0 {
+			jump_height += vy * delta_secs;
+			vy += params.gravity;
+		}
+	}
+	let wall = &params.wall_control_params;
+	let climb_frames = if wall.stamina_climb_drain > 0.0 {
+		wall.stamina_max / wall.stamina_climb_drain
+	} else {
+		0.0
+	};
+	jump_height + climb_frames * wall.climb_max_speed * delta_secs
+}
+
+/// Watches each player for being stuck in geometry, or trapped somewhere they can't get out of,
+/// and sends a [SoftlockDetected] once either lasts long enough. Should run after `player_system`.
+pub fn softlock_watchdog_system(
+	softlock_params: Res<SoftlockParams>,
+	mut players: Query<
+		(
+			Entity,
+			&Player,
+			&PlayerInput,
+			&PlayerControlState,
+			&mut SoftlockWatchdog,
+			&Transform,
+			&Collider,
+		),
+		Without<TutorialGhost>,
+	>,
+	control_params: Res<Assets<PlayerControlParams>>,
+	clock: Res<GameClock>,
+	physics: Physics,
+	mut detections: EventWriter<SoftlockDetected>,
+) {
+	if clock.is_paused() {
+		return;
+	}
+	let physics = physics.world();
+
+	for (player_entity, player_component, input, player, mut watchdog, transform, collider) in &mut players {
+		let directions = movement_directions(input);
+		let Some(params) = control_params.get(player_component.0.id()) else {
+			continue;
+		};
+		let position = transform.translation.truncate();
+
+		// stuck: trying to move, but staying in the same spot
+		let anchor = *watchdog.anchor.get_or_insert(position);
+		if anchor.distance(position) >= softlock_params.min_displacement {
+			watchdog.anchor = Some(position);
+			watchdog.directions_tried = 0;
+			watchdog.stuck_for.reset();
+		} else if directions != 0 {
+			watchdog.directions_tried |= directions;
+			if watchdog.directions_tried.count_ones() >= 2 {
+				watchdog.stuck_for.increment();
+			}
+		}
+
+		// trapped: on the ground, with walls (or a ceiling) everywhere within reach
+		let trapped = player.grounded.is_set() && {
+			let half_extents = collider.as_cuboid().map_or(Vec2::ZERO, |c| c.half_extents());
+			let feet = position - Vec2::new(0.0, half_extents.y);
+			let scope = QueryScope::solid().excluding(player_entity);
+			// a low ceiling limits how high the player can get, no matter how well they jump
+			let reach = reachable_height(params, clock.delta_secs());
+			let reach = physics
+				.cast_ray(position, Vec2::Y, reach, scope)
+				.map_or(reach, |hit| hit.distance + half_extents.y);
+			let probe_length = half_extents.x + softlock_params.exit_width;
+			let probes = (reach / softlock_params.probe_spacing.max(0.1)) as usize;
+			let has_exit = (0..=probes).any(|i| {
+				let origin = feet + Vec2::Y * (half_extents.y + i as f32 * softlock_params.probe_spacing).min(reach);
+				[Vec2::X, Vec2::NEG_X]
+					.into_iter()
+					.any(|direction| physics.cast_ray(origin, direction, probe_length, scope).is_none())
+			});
+			!has_exit
+		};
+		if trapped {
+			watchdog.trapped_for.increment();
+		} else {
+			watchdog.trapped_for.reset();
+		}
+
+		let reason = if watchdog.stuck_for >= softlock_params.stuck_time {
+			Some(SoftlockReason::Stuck)
+		} else if watchdog.trapped_for >= softlock_params.trapped_time {
+			Some(SoftlockReason::Trapped)
+		} else {
+			None
+		};
+		if let (Some(reason), None) = (reason, watchdog.detected) {
+			info!("player {:?} seems softlocked ({:?})", player_entity, reason);
+			detections.send(SoftlockDetected {
+				player: player_entity,
+				reason,
+			});
+		}
+		watchdog.detected = reason;
+	}
+}
+
+/// Marker for the text offering to return the player to their last checkpoint
+#[derive(Component)]
+pub struct SoftlockPrompt;
+
+pub fn setup_softlock_prompt(mut commands: Commands) {
+	commands.spawn((
+		SoftlockPrompt,
+		Text::new("Stuck? Press C to return to the last checkpoint"),
+		Node {
+			position_type: PositionType::Absolute,
+			bottom: Val::Px(20.0),
+			left: Val::Px(20.0),
+			..default()
+		},
+		Visibility::Hidden,
+	));
+}
+
+/// Shows the return prompt while any player is softlocked, and sends them back when they accept it.
+/// Accepted with C, since holding R restarts the whole level.
+pub fn softlock_prompt_system(
+	kb: Res<ButtonInput<KeyCode>>,
+	mut players: Query<(Entity, &mut SoftlockWatchdog), (With<Player>, Without<TutorialGhost>)>,
+	mut prompts: Query<&mut Visibility, With<SoftlockPrompt>>,
+	mut returns: EventWriter<ReturnToCheckpoint>,
+) {
+	let mut prompting = false;
+	for (player_entity, mut watchdog) in &mut players {
+		if watchdog.detected().is_none() {
+			continue;
+		}
+		if kb.just_pressed(KeyCode::KeyC) {
+			returns.send(ReturnToCheckpoint(player_entity));
+			watchdog.reset();
+		} else {
+			prompting = true;
+		}
+	}
+	for mut visibility in &mut prompts {
+		*visibility = if prompting {
+			Visibility::Inherited
+		} else {
+			Visibility::Hidden
+		};
+	}
+}
@@ -0,0 +1,211 @@
+use crate::decor::ImpactParticle;
+use crate::player::{Player, PlayerControlParams, PlayerControlState};
+use crate::settings::{Settings, SpeedFeedbackSettings};
+use crate::ui::WorldCamera;
+use crate::util::{FrameCount, GameClock, Lifetime, SeededRng, Tween, TweenTarget};
+use bevy::prelude::*;
+use bevy_rapier2d::geometry::Collider;
+
+/// How fast the player is moving horizontally, relative to their top running speed
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd)]
+pub enum SpeedTier {
+	#[default]
+	Normal,
+	/// Running flat out
+	TopSpeed,
+	/// Faster than running alone could get them, thanks to momentum from elsewhere
+	Overdrive,
+}
+
+impl SpeedTier {
+	/// The horizontal speed at which the player enters this tier
+	fn threshold(self, run_speed: f32, settings: &SpeedFeedbackSettings) -> f32 {
+		match self {
+			SpeedTier::Normal => 0.0,
+			SpeedTier::TopSpeed => run_speed * settings.top_speed_ratio,
+			SpeedTier::Overdrive => run_speed * settings.overdrive_ratio,
+		}
+	}
+
+	/// The tier for the given `speed`, given that the player was previously in the `current` tier.
+	/// Speeding up changes tiers right at the thresholds, but slowing down only does once the
+	/// speed is clearly below the threshold, so the player doesn't flicker between tiers.
+	fn classify(speed: f32, current: SpeedTier, run_speed: f32, settings: &SpeedFeedbackSettings) -> SpeedTier {
+		let reached = [SpeedTier::Overdrive, SpeedTier::TopSpeed]
+			.into_iter()
+			.find(|tier| speed >= tier.threshold(run_speed, settings))
+			.unwrap_or(SpeedTier::Normal);
+		let holding_on = speed >= current.threshold(run_speed, settings) * (1.0 - settings.hysteresis);
+		if reached < current && holding_on {
+			current
+		} else {
+			reached
+		}
+	}
+}
+
+/// Remembers which [SpeedTier] the player was in as of the latest fixed update
+#[derive(Component, Debug, Default)]
+pub struct SpeedTracker(SpeedTier);
+
+/// Sent when the player crosses from one [SpeedTier] into another
+#[derive(Event, Debug)]
+pub struct SpeedTierChanged {
+	pub player: Entity,
+	pub from: SpeedTier,
+	pub to: SpeedTier,
+}
+
+/// Optional sounds played when reaching a faster [SpeedTier]
+#[derive(Resource, Debug, Default)]
+pub struct SpeedFeedbackSounds {
+	pub top_speed: Option<Handle<AudioSource>>,
+	pub overdrive: Option<Handle<AudioSource>>,
+}
+
+/// A streak left behind the player when they enter overdrive
+#[derive(Component, Debug)]
+pub struct SpeedLine;
+
+/// A brief zoom of the world camera, which eases back out over its duration.
+/// Applied on top of whatever scale the camera otherwise has.
+#[derive(Resource, Debug)]
+pub struct ZoomPunch {
+	strength: f32,
+	duration: f32,
+	remaining: f32,
+	/// The scale factor currently applied to the camera, so it can be taken back out
+	applied: f32,
+}
+
+impl Default for ZoomPunch {
+	fn default() -> Self {
+		ZoomPunch {
+			strength: 0.0,
+			duration: 0.0,
+			remaining: 0.0,
+			applied: 1.0,
+		}
+	}
+}
+
+impl ZoomPunch {
+	pub fn start(&mut self, strength: f32, duration: f32) {
+		self.strength = strength;
+		self.duration = duration;
+		self.remaining = duration;
+	}
+}
+
+/// Watches the player's horizontal speed for [SpeedTier] changes. Should run after `player_system`.
+pub fn detect_speed_tiers(
+	settings: Res<Settings>,
+	control_params: Res<Assets<PlayerControlParams>>,
+	mut players: Query<(Entity, &Player, &PlayerControlState, &mut SpeedTracker)>,
+	mut tier_events: EventWriter<SpeedTierChanged>,
+) {
+	for (entity, player_component, player, mut tracker) in &mut players {
+		let Some(params) = control_params.get(player_component.0.id()) else {
+			continue;
+		};
+		let speed = player.previous_total_velocity.x.abs();
+		let tier = SpeedTier::classify(speed, tracker.0, params.run.max_speed, &settings.speed_feedback);
+		if tier != tracker.0 {
+			debug!("speed tier changed from {:?} to {:?} at {:?}", tracker.0, tier, speed);
+			tier_events.send(SpeedTierChanged {
+				player: entity,
+				from: tracker.0,
+				to: tier,
+			});
+			tracker.0 = tier;
+		}
+	}
+}
+
+/// Kicks up dust, draws speed lines, punches the camera in, and plays a sound when the player
+/// reaches a faster [SpeedTier]. Slowing down passes without comment.
+pub fn speed_tier_feedback(
+	mut commands: Commands,
+	mut tier_events: EventReader<SpeedTierChanged>,
+	settings: Res<Settings>,
+	sounds: Res<SpeedFeedbackSounds>,
+	clock: Res<GameClock>,
+	mut zoom_punch: ResMut<ZoomPunch>,
+	players: Query<(&PlayerControlState, &Transform, &Collider)>,
+) {
+	let style = &settings.speed_feedback;
+	for event in tier_events.read().filter(|e| e.to > e.from) {
+		let Ok((player, transform, collider)) = players.get(event.player) else {
+			continue;
+		};
+		let half_extents = collider.as_cuboid().map_or(Vec2::ZERO, |c| c.half_extents());
+		let center = transform.translation.truncate();
+		let feet = center - Vec2::new(0.0, half_extents.y);
+		// everything trails off behind the player
+		let behind = -player.previous_total_velocity.x.signum();
+
+		let mut rng = SeededRng::new(event.player.to_bits() ^ clock.frame().0 as u64);
+		for _ in 0..style.dust_particles {
+			let velocity = Vec2::new(behind * rng.range(4.0, 12.0), rng.range(2.0, 8.0));
+			commands.spawn((
+				ImpactParticle::new(velocity),
+				Lifetime(FrameCount(20)),
+				Sprite::from_color(Color::srgba(0.8, 0.75, 0.6, 0.8), Vec2::splat(0.3)),
+				Transform::from_translation(feet.extend(0.2)),
+			));
+		}
+
+		if event.to == SpeedTier::Overdrive {
+			for _ in 0..style.speed_lines {
+				let offset = Vec2::new(
+					behind * (half_extents.x + rng.range(1.0, 3.0)),
+					rng.range(-half_extents.y, half_extents.y),
+				);
+				let color = Color::srgba(1.0, 1.0, 1.0, 0.6);
+				commands.spawn((
+					SpeedLine,
+					Lifetime(style.speed_line_lifetime),
+					Tween::new(
+						TweenTarget::sprite_alpha(color, 0.0),
+						style.speed_line_lifetime,
+						EaseFunction::Linear,
+					),
+					Sprite::from_color(color, Vec2::new(rng.range(2.0, 4.0), 0.15)),
+					Transform::from_translation((center + offset).extend(0.2)),
+				));
+			}
+			if style.zoom_punch > 0.0 {
+				zoom_punch.start(style.zoom_punch, style.zoom_punch_duration);
+			}
+		}
+
+		let sound = match event.to {
+			SpeedTier::Normal => None,
+			SpeedTier::TopSpeed => sounds.top_speed.clone(),
+			SpeedTier::Overdrive => sounds.overdrive.clone(),
+		};
+		if let Some(sound) = sound {
+			commands.spawn((AudioPlayer::<AudioSource>(sound), PlaybackSettings::DESPAWN));
+		}
+	}
+}
+
+/// Applies the [ZoomPunch] to the world camera. Runs in real time, like the other camera effects.
+pub fn apply_zoom_punch(
+	mut zoom_punch: ResMut<ZoomPunch>,
+	real_time: Res<Time<Real>>,
+	mut camera: Query<&mut OrthographicProjection, With<WorldCamera>>,
+) {
+	let Ok(mut projection) = camera.get_single_mut() else {
+		return;
+	};
+	if zoom_punch.remaining <= 0.0 && zoom_punch.applied == 1.0 {
+		return;
+	}
+	zoom_punch.remaining = (zoom_punch.remaining - real_time.delta_secs()).max(0.0);
+	let t = zoom_punch.remaining / zoom_punch.duration.max(f32::EPSILON);
+	let factor = 1.0 - zoom_punch.strength * t * t;
+	// take out the previous frame's factor before putting in the new one
+	projection.scale *= factor / zoom_punch.applied;
+	zoom_punch.applied = factor;
+}
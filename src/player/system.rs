@@ -1,92 +1,407 @@
-use crate::player::{HorizontalControlParams, Player, PlayerControlParams, PlayerControlState};
-use crate::util::{PlayerWallState, Side, SideMap, YSide};
+use crate::enemies::Enemy;
+use crate::level::RoomInactive;
+use crate::physics::Physics;
+use crate::platforms::{
+    OneWayPlatform, PlatformMotion, Spring, StickyPlatform, SurfaceMaterial, SurfaceModifier, WallMaterial, WallSurface, Zipline,
+};
+use crate::player::{
+    HorizontalControlParams, Player, PlayerControlParams, PlayerControlState, PlayerGravity, PlayerInput, ZiplineRide,
+    aim_wall_jump,
+};
+use crate::save::AssistOptions;
+use crate::util::{
+    FrameCount, GameClock, PlayerWallState, Side, SideMap, VerticalSensors, WallFrame, WallSensorResult, YSide,
+};
+use crate::zones::{GravityZone, WaterVolume, WindZone};
 use crate::{Platform, PlayerStatusText};
-use bevy::input::ButtonInput;
 use bevy::log::{debug, info};
 use bevy::math::Vec2;
-use bevy::prelude::{Entity, Gizmos, KeyCode, Query, Res, Text, Time, Transform, With};
-use bevy_rapier2d::control::{KinematicCharacterController, KinematicCharacterControllerOutput};
+use bevy::prelude::{
+    Changed, Entity, Event, EventReader, EventWriter, Gizmos, Query, Res, Sprite, Text, Transform, With, Without,
+};
+use bevy_rapier2d::control::{
+    CharacterAutostep, CharacterLength, KinematicCharacterController, KinematicCharacterControllerOutput,
+};
+use bevy_rapier2d::dynamics::{ExternalImpulse, ReadMassProperties, Velocity};
 use bevy_rapier2d::geometry::Collider;
-use bevy_rapier2d::plugin::ReadRapierContext;
-use std::f32;
 use bevy::asset::Assets;
+use bevy::ecs::system::SystemParam;
+
+/// Sent whenever the player does a normal (ground or multi-) jump,
+/// so presentation systems can play the effects from its [JumpProfile](crate::player::JumpProfile)
+#[derive(Event, Debug)]
+pub struct PlayerJumped {
+    pub player: Entity,
+    /// 0 for the first jump, 1 for the second, etc
+    pub index: usize,
+    pub particles: Option<String>,
+    pub sound: Option<String>,
+}
+
+/// Sent to throw the player in some `direction`, taking away their directional control for a moment.
+/// The force decays according to the player's [KnockbackParams](crate::player::KnockbackParams).
+#[derive(Event, Debug, Copy, Clone)]
+pub struct KnockbackEvent {
+    pub player: Entity,
+    pub direction: Vec2,
+    /// Initial speed of the knockback
+    pub strength: f32,
+}
+
+/// Sent when the player bounces off something by landing on top of it
+#[derive(Event, Debug)]
+pub struct PlayerStomped {
+    pub player: Entity,
+    /// The dynamic body or enemy that was stomped on
+    pub target: Entity,
+}
+
+/// Sent when the player starts skidding to turn around on the ground, for animation and particles
+#[derive(Event, Debug)]
+pub struct PlayerSkidded {
+    pub player: Entity,
+    /// The direction the player was moving in, which they're skidding against
+    pub side: Side,
+}
+
+/// Which part of the player touched something, relative to their gravity
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum ContactSide {
+    Feet,
+    Head,
+    Side,
+}
+
+impl ContactSide {
+    /// Classifies a contact by the normal on the player's collider at the contact point
+    /// (pointing out of the player, towards what they touched), in gravity-relative space
+    fn from_normal(normal: Vec2) -> Self {
+        if normal.y < -0.7 {
+            ContactSide::Feet
+        } else if normal.y > 0.7 {
+            ContactSide::Head
+        } else {
+            ContactSide::Side
+        }
+    }
+}
+
+/// The parts of the world that [player_system] reacts to, grouped together to keep
+/// the system within Bevy's limit on the number of system parameters
+#[derive(SystemParam)]
+pub struct PlayerSurroundings<'w, 's> {
+    obstacles: Query<'w, 's, (), With<Platform>>,
+    platform_motion: Query<'w, 's, &'static PlatformMotion>,
+    sticky_platforms: Query<'w, 's, &'static StickyPlatform>,
+    one_way_platforms: Query<'w, 's, (), With<OneWayPlatform>>,
+    surface_materials: Query<'w, 's, &'static SurfaceMaterial>,
+    wall_surfaces: Query<'w, 's, &'static WallSurface>,
+    wall_materials: Query<'w, 's, &'static WallMaterial>,
+    springs: Query<'w, 's, &'static Spring, Without<RoomInactive>>,
+    dynamic_velocities: Query<'w, 's, &'static Velocity, Without<Player>>,
+    water_volumes: Query<
+        'w,
+        's,
+        (&'static Transform, &'static Collider),
+        (With<WaterVolume>, Without<Player>, Without<RoomInactive>),
+    >,
+    wind_zones: Query<
+        'w,
+        's,
+        (&'static WindZone, &'static Transform, &'static Collider),
+        (Without<Player>, Without<RoomInactive>),
+    >,
+    gravity_zones: Query<
+        'w,
+        's,
+        (&'static GravityZone, &'static Transform, &'static Collider),
+        (Without<Player>, Without<RoomInactive>),
+    >,
+    ziplines: Query<'w, 's, (Entity, &'static Zipline), Without<RoomInactive>>,
+    enemies: Query<'w, 's, (), With<Enemy>>,
+}
 
 pub fn player_system(
-    kb: Res<ButtonInput<KeyCode>>,
     mut player_query: Query<(
         Entity,
         &Player,
+        &PlayerInput,
         &mut PlayerControlState,
         &mut KinematicCharacterController,
         &KinematicCharacterControllerOutput,
-        &Transform,
-        &Collider,
+        &mut Transform,
+        &mut Collider,
     )>,
     control_params: Res<Assets<PlayerControlParams>>,
-    mut status_text_query: Query<&mut Text, With<PlayerStatusText>>,
-    obstacles: Query<(), With<Platform>>,
-    time: Res<Time>,
-    rapier_context: ReadRapierContext,
+    mut status_text_query: Query<(&PlayerStatusText, &mut Text)>,
+    surroundings: PlayerSurroundings,
+    mut pushables: Query<(&Transform, &mut ExternalImpulse, &ReadMassProperties), Without<Player>>,
+    clock: Res<GameClock>,
+    assist: Res<AssistOptions>,
+    physics: Physics,
     mut gizmos: Gizmos,
+    mut jump_events: EventWriter<PlayerJumped>,
+    mut stomp_events: EventWriter<PlayerStomped>,
+    mut skid_events: EventWriter<PlayerSkidded>,
+    mut knockback_events: EventReader<KnockbackEvent>,
 ) {
-    let rapier_context = rapier_context.single();
-
-    let mut status_text = status_text_query.single_mut();
+    let physics = physics.world();
+    let PlayerSurroundings {
+        obstacles,
+        platform_motion,
+        sticky_platforms,
+        one_way_platforms,
+        surface_materials,
+        wall_surfaces,
+        wall_materials,
+        springs,
+        dynamic_velocities,
+        water_volumes,
+        wind_zones,
+        gravity_zones,
+        ziplines,
+        enemies,
+    } = surroundings;
+    let knockbacks: Vec<KnockbackEvent> = knockback_events.read().copied().collect();
 
     for (
         player_entity,
         player_component,
+        input,
         mut player,
         mut controller,
         last_controller_out,
-        player_transform,
-        player_collider,
+        mut player_transform,
+        mut player_collider,
     ) in &mut player_query
     {
         if let Some(player_params) = control_params.get(player_component.0.id()) {
 
-            // Check if the player wants to jump
-            let wants_to_jump = {
-                player.jump_requested.tick(kb.just_pressed(KeyCode::Space));
-                player.jump_requested.was_set_within(player_params.jump_input_buffer)
+            // gravity zones can flip or scale the player's gravity, for as long as they're inside one.
+            // Everything below works relative to the player's gravity, so "up" is away from the ground
+            let next_gravity = {
+                let player_center = player_transform.translation.truncate();
+                gravity_zones
+                    .iter()
+                    .find(|(_, transform, collider)| GravityZone::area(transform, collider).contains(player_center))
+                    .map_or_else(PlayerGravity::default, |(zone, ..)| zone.gravity())
             };
+            if next_gravity.up != player.gravity.up {
+                debug!("gravity flipped to {:?}", next_gravity.up);
+                // keep moving the same way in the world, which is the opposite way relative to the new gravity
+                player.own_velocity.y = -player.own_velocity.y;
+                player.wall_control_state.release();
+            }
+            player.gravity = next_gravity;
+            let up = player.gravity.up;
+            controller.up = Vec2::Y * up;
 
             // manage jump cooldown (more important when double-jump is enabled)
             player.jump_cooldown.tick();
+            player.drop_through_cooldown.tick();
 
             // sync Rapier controller state back to player
             player.grounded.tick(last_controller_out.grounded);
 
+            // with charge jumps, holding jump on the ground builds up a charge instead of jumping right away,
+            // and letting go jumps with a speed scaled by how long it was held
+            let charged_jump_ratio = match player_params.charge_jump {
+                Some(charge) if input.jump => {
+                    if player.grounded.is_set() && player.jump_charge < charge.charge_time {
+                        player.jump_charge.increment();
+                    }
+                    None
+                }
+                Some(charge) if player.jump_charge > FrameCount(0) => {
+                    let ratio = charge.speed_ratio(player.jump_charge);
+                    player.jump_charge.reset();
+                    Some(ratio)
+                }
+                _ => None,
+            };
+
+            // Check if the player wants to jump
+            let wants_to_jump = {
+                // with jump repeat on, holding jump on the ground counts as pressing it again
+                let repeated = assist.jump_repeat && input.jump && player.grounded.is_set();
+                player.jump_requested.tick(input.jump_pressed || repeated);
+                let buffered = player.jump_requested.was_set_within(player_params.jump_input_buffer);
+                let charging = player_params.charge_jump.is_some() && player.grounded.is_set();
+                charged_jump_ratio.is_some() || (buffered && !charging)
+            };
+
             // update timers related to wall-jumping
             player.wall_jump_force.tick();
             player.wall_jump_input_cooldown.tick();
 
+            // getting knocked back replaces whatever momentum the player had,
+            // and takes away their directional control for a moment
+            player.knockback_force.tick();
+            player.knockback_control_loss.tick();
+            for knockback in knockbacks.iter().filter(|k| k.player == player_entity) {
+                debug!("knocked back towards {:?}", knockback.direction);
+                player.knockback_force.reset(knockback.direction.normalize_or_zero() * knockback.strength);
+                player.knockback_control_loss.reset(player_params.knockback.control_loss);
+                player.own_velocity = Vec2::ZERO;
+                player.wall_control_state.release();
+                player.zipline = None;
+            }
+            let lost_control = !player.knockback_control_loss.is_ready();
+
+            // `horizontal_input` is just the direction; `horizontal_amount` is how far the stick is pushed
+            let horizontal_axis = input.horizontal;
+            let horizontal_amount = horizontal_axis.abs();
             // if the player wall-jumped the last several frames,
             // stop them from trying to move back towards that wall
             let horizontal_input = {
-                let desired = match (kb.pressed(KeyCode::KeyA) || kb.pressed(KeyCode::ArrowLeft), kb.pressed(KeyCode::KeyD) || kb.pressed(KeyCode::ArrowRight)) {
-                    (true, false) => Some(Side::Left),
-                    (false, true) => Some(Side::Right),
+                let desired = match horizontal_axis {
+                    x if x < 0.0 => Some(Side::Left),
+                    x if x > 0.0 => Some(Side::Right),
                     _ => None,
                 };
-                if !player.wall_jump_input_cooldown.is_ready() && desired == player.wall_jump_latest_side {
+                if lost_control {
+                    None
+                } else if !player.wall_jump_input_cooldown.is_ready() && desired == player.wall_jump_latest_side {
                     None
                 } else {
                     desired
                 }
             };
-            let vertical_input = match (kb.pressed(KeyCode::KeyW), kb.pressed(KeyCode::KeyS)) {
-                (true, false) => Some(YSide::Up),
-                (false, true) => Some(YSide::Down),
-                _ => None,
+            // like velocity, vertical input is relative to gravity; "down" is towards the ground
+            let vertical_input = match input.vertical_direction() {
+                _ if lost_control => None,
+                Some(YSide::Up) => Some(up),
+                Some(YSide::Down) => Some(-up),
+                None => None,
+            };
+
+            // grab onto a zipline by jumping into it. While hanging from one, the line takes over the
+            // player's movement until they jump or drop off, or slide off one of its ends
+            player.zipline_cooldown.tick();
+            let hang_offset = {
+                let half_height = player_collider.as_cuboid().map_or(0.0, |c| c.half_extents().y);
+                Vec2::Y * up * half_height
             };
+            if player.zipline.is_none() && !player.grounded.is_set() && player.zipline_cooldown.is_ready() {
+                let hands = player_transform.translation.truncate() + hang_offset;
+                player.zipline = ZiplineRide::grab(
+                    hands,
+                    player.previous_total_velocity,
+                    player_params.zipline.grab_distance,
+                    ziplines.iter(),
+                );
+                if let Some(ride) = player.zipline {
+                    debug!("grabbed zipline {:?}", ride.line);
+                    // like landing, grabbing a line gives the player their jumps back
+                    player.jumps_remaining = player_params.max_jumps;
+                    player.wall_control_state.release();
+                }
+            }
+            let zipline = player
+                .zipline
+                .and_then(|ride| ziplines.get(ride.line).ok().map(|(_, line)| (ride, *line)));
+            if let Some((mut ride, line)) = zipline {
+                let gravity = Vec2::Y * up * player_params.gravity * player.gravity.scale;
+                let on_line = ride.slide(&line, &player_params.zipline, gravity, clock.delta_secs());
+                let jumped = wants_to_jump && player.jump_cooldown.is_ready();
+                let dropped = vertical_input == Some(YSide::Down);
+                if on_line && !jumped && !dropped {
+                    player.zipline = Some(ride);
+                    player.previous_total_velocity = ride.velocity(&line);
+                    let hang_position = ride.hands(&line) - hang_offset;
+                    controller.translation = Some(hang_position - player_transform.translation.truncate());
+                    continue;
+                }
+
+                // let go, keeping the momentum from the line
+                debug!("let go of zipline {:?}", ride.line);
+                let velocity = ride.velocity(&line);
+                player.zipline = None;
+                player.zipline_cooldown.reset(player_params.zipline.regrab_cooldown);
+                player.own_velocity = Vec2::new(0.0, player.gravity.orient(velocity).y);
+                player.external_velocity = Vec2::new(velocity.x, 0.0);
+                if jumped {
+                    player.own_velocity.y = player.own_velocity.y.max(0.0) + player_params.zipline.jump_speed;
+                    player.jumping = true;
+                    player.jump_cooldown.reset(player_params.jump_cooldown);
+                    player.jump_requested.clear();
+                }
+            } else {
+                // the line was removed out from under the player
+                player.zipline = None;
+            }
+
+            // set when landing on a bouncy surface, so that being grounded doesn't cancel the bounce
+            let mut bounced = false;
+            // set when landing on a spring, to the velocity it launches the player with
+            let mut launch = None;
+            // set when stomping on something, to the number of jumps the player had beforehand
+            let mut stomp_jumps = None;
 
             // if player ran into a platform, reset the portion of their velocity that caused that collision.
             // e.g. bonk your head when you jump into the ceiling, or stop when you run into a wall
             for collision in &last_controller_out.collisions {
+                // coming down on top of a dynamic body or an enemy bounces the player off of it
+                if let (Some(stomp), Some(hit)) = (player_params.stomp, collision.hit.details) {
+                    let contact = ContactSide::from_normal(player.gravity.orient(hit.normal1));
+                    let is_stompable = pushables.contains(collision.entity) || enemies.contains(collision.entity);
+                    let falling_fast = -player.own_velocity.y >= stomp.min_fall_speed;
+                    if is_stompable && contact == ContactSide::Feet && falling_fast && stomp_jumps.is_none() {
+                        debug!("stomped on {:?}", collision.entity);
+                        stomp_jumps = Some(player.jumps_remaining);
+                        stomp_events.send(PlayerStomped {
+                            player: player_entity,
+                            target: collision.entity,
+                        });
+                    }
+                }
+
+                // push dynamic bodies (like the ball) in the direction the player ran into them,
+                // proportional to how fast the player was moving towards them
+                if let Ok((body_transform, mut impulse, mass)) = pushables.get_mut(collision.entity) {
+                    let push_dir = (body_transform.translation - player_transform.translation)
+                        .truncate()
+                        .normalize_or_zero();
+                    let speed_towards_body = player.previous_total_velocity.dot(push_dir);
+                    if speed_towards_body > 0.0 {
+                        impulse.impulse += push_dir * speed_towards_body * player_params.push_strength * mass.get().mass;
+                    }
+                }
+
                 if let Ok(_) = obstacles.get(collision.entity) {
                     if let Some(hit) = collision.hit.details {
-                        let normal = hit.normal1;
+                        // the player's own velocity is relative to their gravity, so the normal has to be too
+                        let world_normal = hit.normal1;
+                        let normal = player.gravity.orient(world_normal);
+
+                        // when stepping (or jumping) up onto a small obstacle, running into its side shouldn't stop the player
+                        if (player.stepping_up || player.step_jump.is_some()) && normal.y.abs() < 0.7 {
+                            continue;
+                        }
+
+                        // if only a corner of the player clipped the ceiling, push them around the corner
+                        // and let them keep their upward momentum
+                        let is_head_bonk = normal.y.abs() > 0.7 && player.own_velocity.y > 0.0;
+                        if is_head_bonk {
+                            let half_extents = player_collider
+                                .as_cuboid()
+                                .unwrap_or_else(|| panic!("player collider isn't a cuboid"))
+                                .half_extents();
+                            let correction = VerticalSensors::corner_correction(
+                                player_transform.translation.truncate(),
+                                half_extents,
+                                up,
+                                player.own_velocity.y * clock.delta_secs() + player_params.wall_control_params.detection_length,
+                                player_params.corner_correction,
+                                &physics,
+                                player_entity,
+                            );
+                            if let Some(dx) = correction {
+                                debug!("corner correction: nudging player by {:?}", dx);
+                                player_transform.translation.x += dx;
+                                continue;
+                            }
+                        }
 
                         let prev_player_vel = player.own_velocity;
                         let arrested_velocity = -prev_player_vel.dot(normal) * normal;
@@ -96,12 +411,77 @@ pub fn player_system(
                             normal, arrested_velocity
                         );
                         player.own_velocity += arrested_velocity;
+                        let external_velocity = player.external_velocity;
+                        player.external_velocity -= external_velocity.dot(world_normal) * world_normal;
 
-                        // TODO: if only a corner of the player actually clipped the wall/ceiling, push them around the corner
+                        // landing on a bouncy surface reflects some of the downward speed back upward
+                        let is_landing = normal.y.abs() > 0.7 && prev_player_vel.y < 0.0;
+                        // (unless the landing was too gentle, in which case the impact is absorbed)
+                        if let Ok(&material) = surface_materials.get(collision.entity) {
+                            let surface = player_params.surface_materials.get(material);
+                            let impact_speed = -prev_player_vel.y;
+                            if is_landing && surface.bounce > 0.0 && impact_speed >= surface.bounce_threshold {
+                                player.own_velocity.y = impact_speed * surface.bounce;
+                                bounced = true;
+                            }
+                        }
+                        if let Ok(spring) = springs.get(collision.entity) {
+                            if is_landing {
+                                launch = Some(player.gravity.orient(spring.velocity));
+                            }
+                        }
                     }
                 }
             }
 
+            player.bounced.tick(bounced);
+
+            // crouch while holding Down on the ground, and stay crouched for as long as
+            // something overhead would prevent the player from standing back up
+            {
+                let current_half_extents = player_collider
+                    .as_cuboid()
+                    .unwrap_or_else(|| panic!("player collider isn't a cuboid"))
+                    .half_extents();
+                let standing_half_extents = *player.standing_half_extents.get_or_insert(current_half_extents);
+
+                // only need to look as far up as the player would grow by standing
+                let head_ray_length = 2.0 * (standing_half_extents.y - current_half_extents.y)
+                    + player_params.wall_control_params.detection_length;
+                let player_center = player_transform.translation.truncate();
+                player.vertical_sensors.update(
+                    YSide::Up,
+                    player_center,
+                    current_half_extents,
+                    up,
+                    head_ray_length,
+                    &physics,
+                    player_entity,
+                );
+                player.vertical_sensors.draw(
+                    YSide::Up,
+                    player_center,
+                    current_half_extents,
+                    up,
+                    head_ray_length,
+                    &mut gizmos,
+                );
+
+                let wants_to_crouch = player.grounded.is_set() && vertical_input == Some(YSide::Down);
+                let crouching = wants_to_crouch || (player.crouching && player.vertical_sensors.is_blocked(YSide::Up));
+                if crouching != player.crouching {
+                    player.crouching = crouching;
+                    let next_half_height = if crouching {
+                        standing_half_extents.y * player_params.crouch_height_ratio
+                    } else {
+                        standing_half_extents.y
+                    };
+                    *player_collider = Collider::cuboid(standing_half_extents.x, next_half_height);
+                    // keep the player's feet in place while resizing around the center
+                    player_transform.translation.y += (next_half_height - current_half_extents.y) * up;
+                }
+            }
+
             // update wall sensors
             let wall_sensor_state = {
                 let player_center = player_transform.translation.truncate();
@@ -113,22 +493,72 @@ pub fn player_system(
                 player.wall_sensors.update(
                     player_center,
                     player_half_extents,
+                    up,
                     player_params.wall_control_params.detection_length,
-                    &rapier_context,
+                    &physics,
                     player_entity,
                 );
                 player
                     .wall_sensors
-                    .draw(player_center, player_half_extents, &mut gizmos);
+                    .draw(player_center, player_half_extents, up, &mut gizmos);
 
-                SideMap {
-                    left: player.wall_sensors.interpret(Side::Left),
-                    right: player.wall_sensors.interpret(Side::Right),
-                }
+                player.vertical_sensors.update(
+                    YSide::Down,
+                    player_center,
+                    player_half_extents,
+                    up,
+                    player_params.wall_control_params.detection_length,
+                    &physics,
+                    player_entity,
+                );
+                player.vertical_sensors.draw(
+                    YSide::Down,
+                    player_center,
+                    player_half_extents,
+                    up,
+                    player_params.wall_control_params.detection_length,
+                    &mut gizmos,
+                );
+
+                let surface_of = |entity| wall_surfaces.get(entity).ok().copied();
+                SideMap::from_fn(|side| player.wall_sensors.interpret(side, surface_of))
             };
 
-            // refund jump ability when reaching the ground
+            // remember how fast the ground is moving, so the player can keep that momentum after leaving it
             if player.grounded.is_set() {
+                player.ground_velocity = match player.vertical_sensors.ground() {
+                    Some(ground) if player_params.ride_dynamic_bodies && dynamic_velocities.contains(ground) => {
+                        dynamic_velocities.get(ground).map_or(Vec2::ZERO, |v| v.linvel)
+                    }
+                    Some(ground) => PlatformMotion::velocity_of(&platform_motion, ground),
+                    None => Vec2::ZERO,
+                };
+
+                // standing on a dynamic body (a crate, the ball) weighs it down
+                if let Some(ground) = player.vertical_sensors.ground().filter(|_| player_params.ride_dynamic_bodies) {
+                    if let Ok((_, mut impulse, _)) = pushables.get_mut(ground) {
+                        impulse.impulse += Vec2::Y * up * player_params.gravity * player_params.player_mass;
+                    }
+                }
+                player.external_velocity = Vec2::ZERO;
+            } else if player.grounded.was_set_within(FrameCount(0)) {
+                // just walked (or got carried) off the edge of the ground
+                player.external_velocity.x = player.ground_velocity.x;
+            }
+
+            // swim while the player's center is underwater
+            player.water_surface = {
+                let player_center = player_transform.translation.truncate();
+                water_volumes
+                    .iter()
+                    .map(|(transform, collider)| WaterVolume::area(transform, collider))
+                    .find(|area| area.contains(player_center))
+                    .map(|area| if up == YSide::Up { area.max.y } else { area.min.y })
+            };
+            let swimming = player.water_surface.is_some();
+
+            // refund jump ability when reaching the ground (or the water)
+            if player.grounded.is_set() || swimming {
                 player.jumps_remaining = player_params.max_jumps;
                 player.jumping = false;
                 player.lost_jump_due_to_falling = false;
@@ -138,7 +568,7 @@ pub fn player_system(
                     info!("Jumped from {:?} to {:?} (distance: {:?})!", x_when_jumped, x_when_landed, x_when_landed - x_when_jumped);
                 }
 
-            } else if !player.grounded.was_set_within(player_params.coyote_time) {
+            } else if !player.grounded.was_set_within(assist.coyote_time(player_params.coyote_time)) {
                 // If player walks off a platform without jumping, then they lose a jump.
                 // For a player with at most 1 jump, that just means they start falling normally.
                 // We use "Coyote Time" per Looney Tunes logic, so this doesn't happen until
@@ -151,6 +581,51 @@ pub fn player_system(
                 }
             }
 
+            // a spring's launch overrides whatever the player was doing, and since it counts as a jump
+            // rather than falling off the edge, the player keeps all of their jumps for afterwards
+            if let Some(velocity) = launch {
+                debug!("launched by spring at {:?}", velocity);
+                player.own_velocity.y = velocity.y;
+                player.external_velocity.x = velocity.x;
+                player.jumping = true;
+            }
+
+            // a stomp is a bounce that only gives back some of the player's jumps, rather than all of them
+            if let (Some(stomp), Some(jumps_before)) = (player_params.stomp, stomp_jumps) {
+                player.own_velocity.y = stomp.bounce_speed;
+                player.jumps_remaining = jumps_before.saturating_add(stomp.jump_refund).min(player_params.max_jumps);
+                player.jumping = true;
+                bounced = true;
+            }
+
+            // step up onto small obstacles (as classified by the wall sensors) instead of stopping dead against them.
+            // Rapier's autostep does the actual work, but only while the player is walking into a step
+            let step_side = horizontal_input.filter(|&side| wall_sensor_state[side] == WallSensorResult::Step);
+            player.stepping_up = player.grounded.is_set() && step_side.is_some();
+            player.step_contact.tick(player.stepping_up);
+            if player.stepping_up {
+                player.step_side = step_side;
+            }
+            // a step jump lasts until the player has risen past the step, or starts coming back down
+            if let Some(side) = player.step_jump {
+                let past_step = wall_sensor_state[side] != WallSensorResult::Step;
+                if past_step || player.own_velocity.y <= 0.0 {
+                    player.step_jump = None;
+                }
+            }
+            controller.autostep = if player.stepping_up {
+                Some(CharacterAutostep {
+                    max_height: CharacterLength::Absolute(player_params.wall_control_params.max_step_height),
+                    min_width: CharacterLength::Absolute(0.5),
+                    include_dynamic_bodies: false,
+                })
+            } else {
+                None
+            };
+
+            // fresh characters, and players who just respawned, start out with full stamina
+            let stamina = *player.stamina.get_or_insert(player_params.wall_control_params.stamina_max);
+
             let player_wall_state = {
                 let is_airborne = !player.grounded.is_set();
                 let horizontal_momentum = match player.previous_total_velocity.x {
@@ -163,54 +638,158 @@ pub fn player_system(
                         }
                     }
                 };
-                player.wall_control_state.tick(
+                let stamina_exhausted = stamina <= 0.0;
+                let material_of = |side| {
+                    let hit = player.wall_sensors.nearest_hit(side)?;
+                    wall_materials.get(hit.entity).ok().copied()
+                };
+                let sensed_wall_materials = SideMap {
+                    left: material_of(Side::Left),
+                    right: material_of(Side::Right),
+                };
+                let state = player.wall_control_state.tick(
                     &wall_sensor_state,
+                    &sensed_wall_materials,
                     is_airborne,
                     &player_params.wall_control_params,
                     horizontal_input,
                     horizontal_momentum,
+                    player.previous_total_velocity.x,
                     vertical_input,
-                )
+                    stamina_exhausted,
+                );
+                player.wall_frame = WallFrame {
+                    hit_patterns: SideMap {
+                        left: player.wall_sensors.hit_pattern(Side::Left),
+                        right: player.wall_sensors.hit_pattern(Side::Right),
+                    },
+                    horizontal_input,
+                    vertical_input,
+                    airborne: is_airborne,
+                    state,
+                };
+                state
             };
 
-            // update player's "run/float" based on horizontal inputs
+            // holding on to walls is tiring
+            let wall_params = &player_params.wall_control_params;
+            player.stamina = Some(match player_wall_state {
+                _ if player.grounded.is_set() => (stamina + wall_params.stamina_refill).min(wall_params.stamina_max),
+                Some(PlayerWallState::Grabbed(_)) => (stamina - wall_params.stamina_grab_drain).max(0.0),
+                Some(PlayerWallState::Climbing(_) | PlayerWallState::Running(_)) => {
+                    (stamina - wall_params.stamina_climb_drain).max(0.0)
+                }
+                _ => stamina,
+            });
+
+            // grabbing a wall stops any momentum inherited from platforms
+            if player_wall_state.is_some() {
+                player.external_velocity = Vec2::ZERO;
+            }
+
+            // near the top of a jump, the player hangs in the air a little longer and can drift a little faster
+            let at_jump_apex = player.jumping
+                && !player.grounded.is_set()
+                && player_wall_state.is_none()
+                && player.own_velocity.y.abs() < player_params.apex_modifier.threshold;
+
+            // holding jump while falling, with no jumps left to spend, slows the player's fall
+            player.gliding = player_params.glide.is_some()
+                && input.jump
+                && player.jumps_remaining == 0
+                && !player.grounded.is_set()
+                && player_wall_state.is_none()
+                && !swimming
+                && player.own_velocity.y <= 0.0;
+
+            // holding sprint swaps running for sprinting; momentum from a sprint carries into the air
+            let sprinting = !lost_control && input.sprint;
+
+            // update player's "run/sprint/float" based on horizontal inputs
             player.own_velocity.x = {
                 let filtered_horizontal_input = if player_wall_state.is_some() {
                     None
                 } else {
                     horizontal_input
                 };
-                compute_next_horizontal_velocity(
-                    player.own_velocity.x,
-                    filtered_horizontal_input,
-                    if player.grounded.is_set() {
-                        player_params.run
-                    } else {
-                        player_params.float
-                    },
-                )
+                // the surface the player is standing on can make them slippery or sluggish
+                let surface = match player.vertical_sensors.ground() {
+                    Some(ground) if player.grounded.is_set() => surface_materials
+                        .get(ground)
+                        .map_or(SurfaceModifier::NONE, |&m| player_params.surface_materials.get(m)),
+                    _ => SurfaceModifier::NONE,
+                };
+                let horizontal_params = if player.grounded.is_set() && player.crouching {
+                    player_params.crouch
+                } else if swimming {
+                    player_params.swim.horizontal
+                } else if player.grounded.is_set() && sprinting {
+                    player_params.sprint
+                } else if player.grounded.is_set() {
+                    player_params.run
+                } else if let Some(glide) = player_params.glide.filter(|_| player.gliding) {
+                    HorizontalControlParams {
+                        max_speed: player_params.float.max_speed + glide.speed_bonus,
+                        ..player_params.float
+                    }
+                } else if at_jump_apex {
+                    HorizontalControlParams {
+                        max_speed: player_params.float.max_speed + player_params.apex_modifier.speed_boost,
+                        ..player_params.float
+                    }
+                } else {
+                    player_params.float
+                };
+                let input = filtered_horizontal_input.map_or(0.0, |side| horizontal_amount * side);
+
+                // reversing direction on the ground at speed skids to a stop before turning around,
+                // unless the turn deceleration is enough to reverse within a single frame
+                let was_skidding = player.skidding;
+                player.skidding = player.grounded.is_set()
+                    && input != 0.0
+                    && input.signum() != player.own_velocity.x.signum()
+                    && player.own_velocity.x.abs() > horizontal_params.turn_deceleration * surface.deceleration;
+                if player.skidding && !was_skidding {
+                    skid_events.send(PlayerSkidded {
+                        player: player_entity,
+                        side: if player.own_velocity.x > 0.0 { Side::Right } else { Side::Left },
+                    });
+                }
+
+                compute_next_horizontal_velocity(player.own_velocity.x, input, horizontal_params, surface)
             };
 
             // apply gravity (when not already on the ground or stuck to a wall)
-            if player.grounded.is_set() {
+            let gravity = player_params.gravity * player.gravity.scale;
+            if bounced || launch.is_some() {
+                // leave the bounce or launch velocity alone for this frame, even though the player touched the ground
+            } else if player.grounded.is_set() {
                 player.own_velocity.y = 0.0;
+            } else if swimming {
+                // the water slows everything down, and mostly cancels out gravity
+                let swim = &player_params.swim;
+                let vy = player.own_velocity.y + gravity * swim.gravity_multiplier;
+                player.own_velocity.y = (vy * (1.0 - swim.damping)).max(-swim.max_sink_speed);
+                player.external_velocity *= 1.0 - swim.damping;
             } else if let Some(wall_state) = player_wall_state {
                 let vy = player.own_velocity.y;
                 match wall_state {
                     PlayerWallState::Grabbed(_) => {
                         // apply gravity to arrest upward momentum, but don't let the player slide down
-                        player.own_velocity.y = (vy + player_params.gravity).max(0.0);
+                        player.own_velocity.y = (vy + gravity).max(0.0);
                     }
                     PlayerWallState::Sliding(_) => {
                         // apply normal gravity to arrest upward momentum,
                         // but downward force should be gentle
-                        if vy >= -player_params.gravity {
-                            player.own_velocity.y += player_params.gravity;
+                        if vy >= -gravity {
+                            player.own_velocity.y += gravity;
                         } else if vy > 0.0 {
                             player.own_velocity.y = 0.0
                         } else {
-                            player.own_velocity.y = (vy - player_params.wall_control_params.slide_acceleration)
-                                .max(-player_params.wall_control_params.slide_max_speed);
+                            let wall_params = &player_params.wall_control_params;
+                            let material = player.wall_control_state.material();
+                            let slide_max_speed = wall_params.slide_max_speed(material);
+                            player.own_velocity.y = (vy - wall_params.slide_acceleration).max(-slide_max_speed);
                         }
                     }
                     PlayerWallState::Climbing(_) => {
@@ -223,34 +802,115 @@ pub fn player_system(
                         } else {
                             // if they are already moving upwards quickly, let gravity apply
                             // until they reach the normal climbing speed
-                            player.own_velocity.y = (vy + player_params.gravity).min(climb_max);
+                            player.own_velocity.y = (vy + gravity).min(climb_max);
+                        }
+                    }
+                    PlayerWallState::Running(_) => {
+                        // horizontal momentum has been converted into a burst of upward speed,
+                        // but don't slow down a player who was already moving up faster
+                        let run_speed = player
+                            .wall_control_state
+                            .wall_run_speed(&player_params.wall_control_params)
+                            .unwrap_or(0.0);
+                        player.own_velocity.y = run_speed.max(vy + gravity);
+                    }
+                    PlayerWallState::Mantling(side) => {
+                        // scripted motion: rise until the player's feet clear the top of the ledge,
+                        // then move forward onto it and let gravity bring them down onto the surface
+                        let wall_params = &player_params.wall_control_params;
+                        if wall_sensor_state[side] == WallSensorResult::NotAWall {
+                            player.own_velocity.x = wall_params.mantle_forward_speed * side;
+                            player.own_velocity.y = vy.min(0.0) + gravity;
+                        } else {
+                            player.own_velocity.y = wall_params.mantle_rise_speed;
                         }
                     }
                 }
             } else {
-                // apply normal gravity
-                player.own_velocity.y += player_params.gravity;
+                // apply normal gravity, up to terminal velocity (which is higher while fast-falling)
+                let max_fall_speed = match (player_params.glide, player_params.fast_fall_speed) {
+                    (Some(glide), _) if player.gliding => glide.fall_speed,
+                    (_, Some(fast_fall_speed)) if vertical_input == Some(YSide::Down) => fast_fall_speed,
+                    _ => player_params.max_fall_speed,
+                };
+                let gravity = if at_jump_apex {
+                    gravity * player_params.apex_modifier.gravity_multiplier
+                } else {
+                    gravity
+                };
+                player.own_velocity.y = (player.own_velocity.y + gravity).max(-max_fall_speed);
                 if player.own_velocity.y <= 0.0 {
                     if let Some(y_when_jumped) = player.y_when_jumped.take() {
                         let apex = player_transform.translation.y;
-                        info!("Jumped apex {:?} to {:?} (distance: {:?})!", y_when_jumped, apex, apex - y_when_jumped);
+                        info!("Jumped apex {:?} to {:?} (distance: {:?})!", y_when_jumped, apex, (apex - y_when_jumped) * up);
                     }
                 }
             }
 
-            // jump
-            if wants_to_jump && player.jump_cooldown.is_ready() {
-                if let Some(wall_state) = player_wall_state.as_ref() {
+            // holding Down while jumping on a one-way platform drops through it instead
+            let is_on_one_way_platform = player.grounded.is_set()
+                && player.vertical_sensors.ground().is_some_and(|e| one_way_platforms.contains(e));
+            if wants_to_jump && vertical_input == Some(YSide::Down) && is_on_one_way_platform {
+                debug!("dropping through one-way platform");
+                player.drop_through_cooldown.reset(player_params.drop_through_duration);
+                player.jump_requested.clear();
+            }
+
+            // jump (but not in the middle of a mantle, which is committed once started)
+            let is_mantling = matches!(player_wall_state, Some(PlayerWallState::Mantling(_)));
+            let is_dropping = !player.drop_through_cooldown.is_ready();
+            // the player can still wall jump for a few frames after letting go of a wall
+            let wall_jump_side = player_wall_state.map(|s| s.side()).or_else(|| {
+                let coyote_time = assist.coyote_time(player_params.wall_control_params.wall_jump_coyote_time);
+                (!player.grounded.is_set())
+                    .then(|| player.wall_control_state.recent_wall_side(coyote_time))
+                    .flatten()
+            });
+            if wants_to_jump && player.jump_cooldown.is_ready() && !is_mantling && !is_dropping {
+                if let Some(surface) = player.water_surface {
+                    // swim stroke, or leap out of the water if the player's head is above the surface
+                    let half_height = player_collider.as_cuboid().map_or(0.0, |c| c.half_extents().y);
+                    let at_surface = (player_transform.translation.y + half_height * up - surface) * up >= 0.0;
+                    debug!("swim stroke (at surface: {:?})", at_surface);
+                    player.own_velocity.y = if at_surface {
+                        player_params.swim.surface_jump_speed
+                    } else {
+                        player_params.swim.stroke_speed
+                    };
+                    if at_surface {
+                        // leaping out spends a jump, just like jumping off the ground
+                        player.jumping = true;
+                        player.jumps_remaining = player.jumps_remaining.saturating_sub(1);
+                    }
+                    player.jump_cooldown.reset(player_params.jump_cooldown);
+                } else if let Some(wall_side) = wall_jump_side {
                     // wall jump
-                    debug!("wall jumping from {:?} wall!", wall_state.side());
+                    debug!("wall jumping from {:?} wall!", wall_side);
                     // although effectively a vector, the X and Y components will be split;
                     // the Y trajectory will be applied normally, but the X trajectory
                     // will be applied as an "external force" so the player's run/float
                     // control logic doesn't completely overwrite the force too soon
-                    let jump_vx = player_params.run.max_speed * f32::consts::FRAC_1_SQRT_2 * -wall_state.side();
-                    let jump_vy = player_params.jump_speed * f32::consts::FRAC_1_SQRT_2;
-                    player.wall_jump_force.reset(Vec2::new(jump_vx, 0.0));
-                    player.own_velocity.y = jump_vy;
+                    let mut jump_velocity = Vec2::new(
+                        player_params.wall_jump_horizontal_speed * -wall_side,
+                        player_params.wall_jump_vertical_speed,
+                    );
+                    // (the aim assist predicts the jump arc with normal gravity, so it sits out while inverted)
+                    if assist.wall_jump_aim && up == YSide::Up {
+                        // nudge the jump toward somewhere the player can actually land
+                        let half_height = player_collider.as_cuboid().map_or(0.0, |c| c.half_extents().y);
+                        let feet = player_transform.translation.truncate() - Vec2::new(0.0, half_height);
+                        jump_velocity = aim_wall_jump(
+                            &physics,
+                            player_entity,
+                            feet,
+                            jump_velocity,
+                            player_params,
+                            &assist,
+                            clock.delta_secs(),
+                        );
+                    }
+                    player.wall_jump_force.reset(Vec2::new(jump_velocity.x, 0.0));
+                    player.own_velocity.y = jump_velocity.y;
                     player.x_when_jumped = Some(player_transform.translation.x);
                     player.y_when_jumped = Some(player_transform.translation.y);
                     player.jumping = true;
@@ -258,74 +918,189 @@ pub fn player_system(
                     player
                         .wall_jump_input_cooldown
                         .reset(player_params.wall_jump_input_cooldown);
-                    player.wall_jump_latest_side = Some(wall_state.side());
+                    player.wall_jump_latest_side = Some(wall_side);
                     player.wall_control_state.release();
-                } else if player.jumps_remaining > 0 {
+                } else if player.jumps_remaining > 0 || assist.infinite_jumps {
                     // normal jump
                     debug!("jumping with coyote time {:?}", player.grounded);
-                    player.own_velocity.y = player_params.jump_speed;
-                    player.jumps_remaining -= 1;
+                    // keep the momentum of a moving platform; horizontal momentum is kept separately
+                    // so that the run/float logic doesn't immediately cancel it out
+                    // jumping during (or just after) a bounce chains into a higher jump
+                    let bounce_vy = if player.bounced.was_set_within(player_params.coyote_time) {
+                        player.own_velocity.y.max(0.0)
+                    } else {
+                        0.0
+                    };
+                    // later jumps in a multi-jump can have a different feel than the first
+                    let jump_index = player_params.max_jumps.saturating_sub(player.jumps_remaining) as usize;
+                    let profile = player_params.jump_profile(jump_index);
+                    let speed = profile.speed * charged_jump_ratio.unwrap_or(1.0);
+                    // the ground's velocity is only kept up to date while standing on it, so a midair jump
+                    // long after leaving the ground doesn't get a boost from wherever the player used to be
+                    let ground_vy = if player.grounded.was_set_within(assist.coyote_time(player_params.coyote_time)) {
+                        player.external_velocity.x = player.ground_velocity.x;
+                        player.gravity.orient(player.ground_velocity).y
+                    } else {
+                        0.0
+                    };
+                    player.own_velocity.y = speed + ground_vy.max(0.0) + bounce_vy;
+                    player.jumps_remaining = player.jumps_remaining.saturating_sub(1);
                     player.x_when_jumped = Some(player_transform.translation.x);
                     player.y_when_jumped = Some(player_transform.translation.y);
                     player.jumping = true;
-                    player.jump_cooldown.reset(player_params.jump_cooldown);
+                    player.jump_cooldown.reset(profile.cooldown);
+                    // jumping just after walking into a step hops up onto it, rather than stopping dead against
+                    // its side, even if the jump came a little too late for the step to still be underfoot
+                    let step_forgiveness = player_params.wall_control_params.step_jump_forgiveness;
+                    if player.step_contact.was_set_within(step_forgiveness)
+                        && horizontal_input.is_some()
+                        && horizontal_input == player.step_side
+                    {
+                        debug!("jumping up onto {:?} step", player.step_side);
+                        player.step_jump = player.step_side;
+                    }
+                    jump_events.send(PlayerJumped {
+                        player: player_entity,
+                        index: jump_index,
+                        particles: profile.particles,
+                        sound: profile.sound,
+                    });
                 }
             }
 
             // finish velocity computation
             let wall_jump_force = player.wall_jump_force.eval(&player_params.wall_jump_force_decay);
-            let player_velocity_per_sec = player.own_velocity + wall_jump_force;
+            let knockback_force = player.knockback_force.eval(&player_params.knockback.decay);
+            // wind carries the player along for as long as they're in it
+            let wind_velocity: Vec2 = {
+                let player_center = player_transform.translation.truncate();
+                wind_zones
+                    .iter()
+                    .filter(|(_, transform, collider)| WindZone::area(transform, collider).contains(player_center))
+                    .map(|(zone, ..)| zone.velocity())
+                    .sum()
+            };
+            let own_velocity = player.gravity.orient(player.own_velocity);
+            let player_velocity_per_sec =
+                own_velocity + wall_jump_force + knockback_force + wind_velocity + player.external_velocity;
             player.previous_total_velocity = player_velocity_per_sec;
 
-            // debug text for velocity
-            status_text.0 = format!(
-                "vx: {}\nvy: {}\ngrounded: {}\njumps: {}\nwall_jump: {:?}\nwall_state: {:?}",
-                player_velocity_per_sec.x,
-                player_velocity_per_sec.y,
-                player.grounded.is_set(),
-                player.jumps_remaining,
-                wall_jump_force,
-                player_wall_state,
+            // debug text for velocity, for each player that has some
+            let status_text = status_text_query.iter_mut().find(|(owner, _)| owner.0 == player_entity);
+            if let Some((_, mut status_text)) = status_text {
+                status_text.0 = format!(
+                    "vx: {}\nvy: {}\ngrounded: {}\ncrouching: {}\nskidding: {}\ngliding: {}\njumps: {}\ncharge: {}\nstamina: {:.0}\nwall_jump: {:?}\nwall_state: {:?}",
+                    player_velocity_per_sec.x,
+                    player_velocity_per_sec.y,
+                    player.grounded.is_set(),
+                    player.crouching,
+                    player.skidding,
+                    player.gliding,
+                    player.jumps_remaining,
+                    player.jump_charge.0,
+                    player.stamina.unwrap_or_default(),
+                    wall_jump_force,
+                    player_wall_state,
+                );
+            }
+
+            // if standing on a moving platform, get carried along by however far it moved this frame.
+            // Since platforms move before this system runs, but Rapier resolves the controller against
+            // their previous positions, this is what keeps a rising platform from swallowing the player.
+            // Dynamic bodies haven't moved yet this frame, so riders move along with their current velocity.
+            // A sticky platform keeps carrying the player sideways for a few frames after they lose contact with it,
+            // unless they jumped off of it, which carries its momentum anyway.
+            player.stick_cooldown.tick();
+            let sticky_ground = player
+                .vertical_sensors
+                .ground()
+                .filter(|_| player.grounded.is_set())
+                .and_then(|ground| sticky_platforms.get(ground).ok().map(|sticky| (ground, sticky.stick_frames)));
+            if let Some((ground, stick_frames)) = sticky_ground {
+                player.stuck_to = Some(ground);
+                player.stick_cooldown.reset(stick_frames);
+            } else if player.grounded.is_set() || player.jumping || player.stick_cooldown.is_ready() {
+                player.stuck_to = None;
+            }
+            let ground = player.vertical_sensors.ground().filter(|_| player.grounded.is_set());
+            let body_velocity = ground
+                .filter(|_| player_params.ride_dynamic_bodies)
+                .map(|ground| dynamic_velocities.get(ground).map_or(Vec2::ZERO, |v| v.linvel));
+            let platform_delta = PlatformMotion::carry_delta(
+                &platform_motion,
+                ground,
+                body_velocity,
+                player.stuck_to,
+                clock.delta_secs(),
             );
 
+            // close any small gap between the player and the wall they're holding onto,
+            // so they don't appear to hover next to it (or flicker in and out of the wall state)
+            let wall_snap = match player_wall_state {
+                Some(PlayerWallState::Grabbed(side) | PlayerWallState::Sliding(side)) => player
+                    .wall_sensors
+                    .nearest_hit(side)
+                    .filter(|hit| hit.distance <= player_params.wall_control_params.wall_snap_distance)
+                    .map_or(Vec2::ZERO, |hit| Vec2::X * side * hit.distance),
+                _ => Vec2::ZERO,
+            };
+
             // send computed translation to controller for resolution in the physics world
-            controller.translation = Some(player_velocity_per_sec * time.delta_secs());
+            controller.translation = Some(player_velocity_per_sec * clock.delta_secs() + platform_delta + wall_snap);
         } else {
             info!("player params not loaded yet");
         }
     }
 }
 
+/// Keeps the player's sprite the same size as their collider, e.g. while crouching
+pub fn player_sprite_size_system(mut player_query: Query<(&Collider, &mut Sprite), (With<Player>, Changed<Collider>)>) {
+    for (collider, mut sprite) in &mut player_query {
+        if let Some(cuboid) = collider.as_cuboid() {
+            sprite.custom_size = Some(cuboid.half_extents() * 2.0);
+        }
+    }
+}
+
 /// Solve for a player's new horizontal velocity by accelerating or decelerating
-/// their current velocity towards their desired velocity
+/// their current velocity towards their desired velocity.
+/// The `input` ranges from -1 (full speed to the left) to 1 (full speed to the right),
+/// with anything in between (from an analog stick) asking for a proportionally slower speed.
+/// The `surface` modifier scales the control params, e.g. for slippery ice.
 fn compute_next_horizontal_velocity(
     current_vel: f32,
-    input_direction: Option<Side>,
+    input: f32,
     HorizontalControlParams {
         max_speed,
         acceleration,
         deceleration,
+        turn_deceleration,
     }: HorizontalControlParams,
+    surface: SurfaceModifier,
 ) -> f32 {
-    let target_vel = match input_direction {
-        None => 0.0,
-        Some(Side::Left) => -max_speed,
-        Some(Side::Right) => max_speed,
-    };
+    let max_speed = max_speed * surface.max_speed;
+    let acceleration = acceleration * surface.acceleration;
+    let deceleration = deceleration * surface.deceleration;
+    let turn_deceleration = turn_deceleration * surface.deceleration;
+
+    let target_vel = input.clamp(-1.0, 1.0) * max_speed;
 
     let accel_base = if current_vel == 0.0 {
         // anything is faster than 0, regardless of direction
         acceleration
-    } else if input_direction.is_none() {
+    } else if target_vel == 0.0 {
         // if the goal is to stop, that's always deceleration
         deceleration
     } else if target_vel.signum() != current_vel.signum() {
-        // if the goal is in the opposite direction, decelerate to 0 first
-        deceleration
-    } else if max_speed > current_vel.abs() {
+        // if the goal is in the opposite direction, skid to 0 first
+        turn_deceleration
+    } else if target_vel.abs() > current_vel.abs() {
         // previous conditions ensure `target_vel` and `current_vel` have the same sign,
-        // so this means we need to speed up (in whichever direction) to reach max speed
+        // so this means we need to speed up (in whichever direction) to reach the target speed
         acceleration
+    } else if max_speed >= current_vel.abs() {
+        // the stick was eased back from a bigger deflection, so slow down to match it
+        deceleration
     } else {
         // player is already moving faster than `max_speed`, which could happen if they
         // had previously accelerated in a different mode (e.g. running vs floating).
@@ -0,0 +1,150 @@
+use crate::player::InputAction;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy::window::PrimaryWindow;
+
+/// Width and height of each on-screen button, in logical pixels
+const BUTTON_SIZE: f32 = 72.0;
+
+/// Space between the buttons, and between the buttons and the edges of the screen
+const BUTTON_MARGIN: f32 = 16.0;
+
+const IDLE_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.15);
+const PRESSED_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.4);
+
+/// On-screen buttons for playing on a touch screen (e.g. the web build on a phone), which press
+/// [InputAction]s for the lead player the same way their keys and gamepad buttons would.
+///
+/// Hidden until the screen is first touched, or shown from the start when running the game with `--touch`.
+#[derive(Resource, Debug, Default)]
+pub struct TouchControls {
+	shown: bool,
+	held: HashSet<InputAction>,
+	/// Actions that are held now but weren't as of the previous frame
+	just_pressed: HashSet<InputAction>,
+}
+
+impl TouchControls {
+	/// Reads whether to show the touch controls from the start from the command line
+	pub fn from_args() -> Self {
+		let shown = std::env::args().any(|arg| arg == "--touch");
+		if shown {
+			info!("showing touch controls");
+		}
+		TouchControls { shown, ..default() }
+	}
+
+	/// Whether the action's button is being touched
+	pub fn pressed(&self, action: InputAction) -> bool {
+		self.held.contains(&action)
+	}
+
+	/// Whether the action's button started being touched this frame
+	pub fn just_pressed(&self, action: InputAction) -> bool {
+		self.just_pressed.contains(&action)
+	}
+}
+
+/// Holds all of the [TouchButton]s, so they can be shown and hidden together
+#[derive(Component, Debug)]
+pub struct TouchControlsRoot;
+
+/// An on-screen button that presses an [InputAction] while touched
+#[derive(Component, Debug)]
+pub struct TouchButton(pub InputAction);
+
+/// Spawns the touch controls: movement in the bottom left corner, jump and grab in the bottom right
+pub fn setup_touch_controls(mut commands: Commands, touch_controls: Res<TouchControls>) {
+	let button = |parent: &mut ChildBuilder, action: InputAction, label: &str| {
+		parent
+			.spawn((
+				TouchButton(action),
+				Node {
+					width: Val::Px(BUTTON_SIZE),
+					height: Val::Px(BUTTON_SIZE),
+					justify_content: JustifyContent::Center,
+					align_items: AlignItems::Center,
+					..default()
+				},
+				BorderRadius::MAX,
+				BackgroundColor(IDLE_COLOR),
+			))
+			.with_children(|parent| {
+				parent.spawn((Text::new(label), TextFont::from_font_size(16.0)));
+			});
+	};
+	let group = Node {
+		column_gap: Val::Px(BUTTON_MARGIN),
+		..default()
+	};
+
+	commands
+		.spawn((
+			TouchControlsRoot,
+			Node {
+				position_type: PositionType::Absolute,
+				left: Val::Px(0.0),
+				right: Val::Px(0.0),
+				bottom: Val::Px(0.0),
+				padding: UiRect::all(Val::Px(BUTTON_MARGIN)),
+				justify_content: JustifyContent::SpaceBetween,
+				..default()
+			},
+			if touch_controls.shown {
+				Visibility::Inherited
+			} else {
+				Visibility::Hidden
+			},
+		))
+		.with_children(|parent| {
+			parent.spawn(group.clone()).with_children(|parent| {
+				button(parent, InputAction::MoveLeft, "<");
+				button(parent, InputAction::MoveRight, ">");
+			});
+			parent.spawn(group).with_children(|parent| {
+				button(parent, InputAction::Grab, "GRAB");
+				button(parent, InputAction::Jump, "JUMP");
+			});
+		});
+}
+
+/// Works out which of the [TouchButton]s are being touched, showing the touch controls the first
+/// time the screen is touched. Runs every frame in `PreUpdate`, before `gather_player_input`.
+///
+/// Every touch is checked against every button (rather than going by each button's `Interaction`,
+/// which only follows a single pointer), so the player can hold a direction and jump at the same time,
+/// and can slide a finger from one button to another.
+pub fn update_touch_controls(
+	touches: Res<Touches>,
+	windows: Query<&Window, With<PrimaryWindow>>,
+	mut touch_controls: ResMut<TouchControls>,
+	mut roots: Query<&mut Visibility, With<TouchControlsRoot>>,
+	mut buttons: Query<(&TouchButton, &ComputedNode, &GlobalTransform, &mut BackgroundColor)>,
+) {
+	if !touch_controls.shown {
+		if !touches.any_just_pressed() {
+			return;
+		}
+		info!("screen touched, showing touch controls");
+		touch_controls.shown = true;
+		for mut visibility in &mut roots {
+			*visibility = Visibility::Inherited;
+		}
+	}
+
+	// touches are in logical pixels, while UI nodes are laid out in physical pixels
+	let scale_factor = windows.get_single().map_or(1.0, Window::scale_factor);
+	let mut held = HashSet::new();
+	for (button, node, transform, mut color) in &mut buttons {
+		let rect = Rect::from_center_size(transform.translation().truncate(), node.size());
+		let touched = touches
+			.iter()
+			.any(|touch| rect.contains(touch.position() * scale_factor));
+		color.set_if_neq(BackgroundColor(if touched { PRESSED_COLOR } else { IDLE_COLOR }));
+		if touched {
+			held.insert(button.0);
+		}
+	}
+	touch_controls.just_pressed = held.difference(&touch_controls.held).copied().collect();
+	touch_controls.held = held;
+}
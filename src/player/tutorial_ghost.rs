@@ -0,0 +1,194 @@
+use crate::debug::InputScript;
+use crate::player::{Character, CharacterRoster, Player, PlayerDied, PlayerInput, PlayerSlot};
+use crate::player_bundle;
+use crate::util::GameClock;
+use crate::zones::{TriggerOccupants, TriggerZone};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier2d::prelude::Sensor;
+
+/// Where the ghost runs are bundled
+const TUTORIALS_DIR: &str = "assets/tutorials";
+
+/// Seconds (of game time) that the players have to stand around in a tutorial section before its ghost shows up
+const IDLE_SECS: f32 = 10.0;
+
+/// How many times the players have to die in a tutorial section before its ghost shows up
+const FAILURES: usize = 3;
+
+/// How see-through the ghost is
+const GHOST_ALPHA: f32 = 0.4;
+
+/// The ghost's slot is past the keyboard and any connected gamepads, and its one-way platform collision group
+/// is the last one, which only a fourth local player would share
+const GHOST_SLOT: PlayerSlot = PlayerSlot(1003);
+
+/// A see-through extra player showing the way through a tutorial section, by playing back a recording of
+/// the section being played. Since it replays input rather than positions, it goes through the same
+/// `player_system` as everyone else, and stays honest when the player params change.
+///
+/// The ghost is a [Sensor], so nothing bumps into it, and the systems that react to players (collectibles,
+/// checkpoints, hazards, triggers, the camera, ...) leave it out. It disappears once its recording is over.
+#[derive(Component, Debug)]
+pub struct TutorialGhost {
+	script: InputScript,
+	/// Index of the recorded tick to play next
+	tick: usize,
+}
+
+/// Shows a [TutorialGhost] in a tutorial section when the players stand around in it for a while,
+/// or keep dying in it.
+///
+/// A tutorial section is a [TriggerZone] with a ghost run bundled for it, as `assets/tutorials/<id>.ghost.ron`
+/// where `<id>` is the trigger's id. The runs are `--record-input` recordings of the lead player going through
+/// the section (see [InputRecording](crate::debug::InputRecording)); the ghost starts out where the recording's
+/// first tick left them, and plays the rest.
+#[derive(Resource, Debug, Default)]
+pub struct TutorialGhosts {
+	/// The bundled ghost runs, by the id of the tutorial section's trigger
+	scripts: HashMap<String, InputScript>,
+	/// The id of the tutorial section that any of the players are in
+	section: Option<String>,
+	/// Seconds since the players last touched anything in the current section
+	idle_secs: f32,
+	/// How many times the players have died in each section since its ghost last showed up
+	failures: HashMap<String, usize>,
+}
+
+impl TutorialGhosts {
+	/// Reads the ghost runs bundled with the game. Without any, no ghosts ever show up.
+	pub fn load() -> Self {
+		let entries = match std::fs::read_dir(TUTORIALS_DIR) {
+			Ok(entries) => entries,
+			Err(err) => {
+				warn!("failed to find tutorial ghosts in {}: {}", TUTORIALS_DIR, err);
+				return TutorialGhosts::default();
+			}
+		};
+		let mut scripts = HashMap::new();
+		for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+			let name = path.file_name().map(|name| name.to_string_lossy().into_owned());
+			let Some(id) = name.as_deref().and_then(|name| name.strip_suffix(".ghost.ron")) else {
+				continue;
+			};
+			match InputScript::read(&path) {
+				Ok(script) => {
+					scripts.insert(id.to_string(), script);
+				}
+				Err(err) => warn!("failed to read tutorial ghost {:?}: {}", path, err),
+			}
+		}
+		info!("found {} tutorial ghosts", scripts.len());
+		TutorialGhosts { scripts, ..default() }
+	}
+}
+
+/// The lead player's recorded input on the given tick of a ghost run
+fn lead_input(script: &InputScript, tick: usize) -> Option<PlayerInput> {
+	let (_, input) = script
+		.ticks
+		.get(tick)?
+		.inputs
+		.iter()
+		.find(|(slot, _)| *slot == PlayerSlot::LEAD.0)?;
+	Some(input.clone())
+}
+
+/// Where the lead player was at the end of the given tick of a ghost run
+fn lead_position(script: &InputScript, tick: usize) -> Option<Vec2> {
+	let (_, position) = script
+		.ticks
+		.get(tick)?
+		.positions
+		.iter()
+		.find(|(slot, _)| *slot == PlayerSlot::LEAD.0)?;
+	Some(*position)
+}
+
+/// Keeps track of how long the players have stood around in a tutorial section, and how many times they've
+/// died in it, and spawns its [TutorialGhost] once either is enough. Only one ghost shows at a time.
+///
+/// Should run after `trigger_zone_system`, and not while a run is being recorded or replayed.
+pub fn tutorial_ghost_system(
+	mut commands: Commands,
+	mut tutorials: ResMut<TutorialGhosts>,
+	mut died_events: EventReader<PlayerDied>,
+	zones: Query<(&TriggerZone, &TriggerOccupants)>,
+	players: Query<(&PlayerSlot, &PlayerInput, Option<&CharacterRoster>), (With<Player>, Without<TutorialGhost>)>,
+	ghosts: Query<(), With<TutorialGhost>>,
+	clock: Res<GameClock>,
+) {
+	let tutorials = &mut *tutorials;
+	let scripts = &tutorials.scripts;
+	let sections = || zones.iter().filter(move |(zone, _)| scripts.contains_key(&zone.id));
+
+	for died in died_events.read() {
+		for (zone, _) in sections().filter(|(_, occupants)| occupants.contains(died.player)) {
+			*tutorials.failures.entry(zone.id.clone()).or_default() += 1;
+		}
+	}
+
+	let section = sections()
+		.find(|(_, occupants)| !occupants.is_empty())
+		.map(|(zone, _)| zone.id.clone());
+	let idle = players.iter().all(|(_, input, _)| *input == PlayerInput::default());
+	if idle && section == tutorials.section {
+		tutorials.idle_secs += clock.delta_secs();
+	} else {
+		tutorials.idle_secs = 0.0;
+	}
+	tutorials.section = section;
+
+	let Some(id) = &tutorials.section else {
+		return;
+	};
+	let failures = tutorials.failures.get(id).copied().unwrap_or_default();
+	if !ghosts.is_empty() || (tutorials.idle_secs < IDLE_SECS && failures < FAILURES) {
+		return;
+	}
+	// the runs are recorded with the lead player's first character
+	let Some(character) = players
+		.iter()
+		.find(|(slot, _, _)| **slot == PlayerSlot::LEAD)
+		.and_then(|(_, _, roster)| roster?.characters.first())
+	else {
+		return;
+	};
+	let script = &tutorials.scripts[id];
+	let Some(start) = lead_position(script, 0) else {
+		warn!("tutorial ghost {:?} doesn't record the lead player", id);
+		return;
+	};
+
+	info!("showing the tutorial ghost for {:?}", id);
+	let character = Character {
+		color: character.color.with_alpha(GHOST_ALPHA),
+		..character.clone()
+	};
+	commands.spawn((
+		player_bundle(&character, GHOST_SLOT, start),
+		Sensor,
+		TutorialGhost {
+			script: script.clone(),
+			tick: 1,
+		},
+	));
+	tutorials.idle_secs = 0.0;
+	tutorials.failures.remove(id);
+}
+
+/// Plays each [TutorialGhost]'s recording in place of its input, and takes it away once the recording is over.
+/// Should run after `record_or_replay_input`, before `player_system`.
+pub fn drive_tutorial_ghosts(
+	mut commands: Commands,
+	mut ghosts: Query<(Entity, &mut TutorialGhost, &mut PlayerInput)>,
+) {
+	for (entity, mut ghost, mut input) in &mut ghosts {
+		if ghost.tick >= ghost.script.ticks.len() {
+			commands.entity(entity).despawn();
+			continue;
+		}
+		*input = lead_input(&ghost.script, ghost.tick).unwrap_or_default();
+		ghost.tick += 1;
+	}
+}
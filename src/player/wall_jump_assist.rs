@@ -0,0 +1,62 @@
+use crate::physics::{PhysicsWorld, QueryScope};
+use crate::player::{PlayerControlParams, TemporaryForce};
+use crate::save::AssistOptions;
+use bevy::prelude::*;
+
+/// Surfaces whose normal points at least this far upward count as somewhere to land
+const MIN_LANDING_NORMAL_Y: f32 = 0.7;
+
+/// Nudges a wall jump's launch velocity (within the assist's cone) toward the nearest platform it could
+/// land on. Each candidate trajectory is followed frame by frame from the player's `feet`, the same way
+/// `player_system` would move them if they let go of the controls: the horizontal part decays along the
+/// `wall_jump_force_decay` curve, while the vertical part is pulled down by gravity.
+///
+/// Returns the original `velocity` if no candidate lands anywhere.
+pub fn aim_wall_jump(
+	physics: &impl PhysicsWorld,
+	player_entity: Entity,
+	feet: Vec2,
+	velocity: Vec2,
+	params: &PlayerControlParams,
+	assist: &AssistOptions,
+	delta_secs: f32,
+) -> Vec2 {
+	let cone = assist.wall_jump_aim_cone.to_radians();
+	let scope = QueryScope::solid().excluding(player_entity);
+
+	// frames until landing for a given launch velocity, or None if it hits a wall or lands nowhere in time
+	let frames_to_land = |launch: Vec2| -> Option<usize> {
+		let mut force = TemporaryForce::default();
+		force.reset(Vec2::new(launch.x, 0.0));
+		let mut position = feet;
+		let mut vy = launch.y;
+		for frame in 0..assist.wall_jump_aim_frames {
+			let step = Vec2::new(force.eval(&params.wall_jump_force_decay).x, vy) * delta_secs;
+			if let Some(hit) = physics.cast_ray(position, step, 1.0, scope) {
+				return (step.y < 0.0 && hit.normal.y >= MIN_LANDING_NORMAL_Y).then_some(frame);
+			}
+			position += step;
+			force.tick();
+			vy = (vy + params.gravity).max(-params.max_fall_speed);
+		}
+		None
+	};
+
+	// prefer the soonest landing, then the smallest nudge
+	let samples = assist.wall_jump_aim_samples.max(1);
+	(0..samples)
+		.filter_map(|i| {
+			let t = if samples > 1 {
+				i as f32 / (samples - 1) as f32
+			} else {
+				0.5
+			};
+			let offset = (t * 2.0 - 1.0) * cone;
+			let launch = Vec2::from_angle(offset).rotate(velocity);
+			frames_to_land(launch).map(|frames| (frames, offset.abs(), launch))
+		})
+		.min_by(|(a_frames, a_offset, _), (b_frames, b_offset, _)| {
+			a_frames.cmp(b_frames).then(a_offset.total_cmp(b_offset))
+		})
+		.map_or(velocity, |(_, _, launch)| launch)
+}
@@ -0,0 +1,75 @@
+use crate::platforms::Zipline;
+use crate::util::FrameCount;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// Movement while hanging from a [Zipline]
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ZiplineParams {
+	/// How close a zipline has to pass to the top of the player's collider for them to grab it
+	pub grab_distance: f32,
+	/// Fraction of gravity that pulls the player along the line (the rest is held up by the line)
+	pub gravity_multiplier: f32,
+	/// Fraction of the player's speed along the line lost each frame
+	pub drag: f32,
+	pub max_speed: f32,
+	/// Upward speed from jumping off the line
+	pub jump_speed: f32,
+	/// How long after letting go before the player can grab a line again,
+	/// so that jumping off doesn't immediately re-attach them
+	pub regrab_cooldown: FrameCount,
+}
+
+/// The player's hold on a [Zipline]
+#[derive(Copy, Clone, Debug)]
+pub struct ZiplineRide {
+	pub line: Entity,
+	/// How far along the line (from its `start`) the player's hands are
+	pub distance: f32,
+	/// Speed along the line, per second. Positive is towards the line's `end`.
+	pub speed: f32,
+}
+
+impl ZiplineRide {
+	/// Grabs the closest of the `lines` that passes within `reach` of the player's `hands`, if any.
+	/// The player keeps whatever part of their `velocity` is along the line.
+	pub fn grab<'a>(
+		hands: Vec2,
+		velocity: Vec2,
+		reach: f32,
+		lines: impl Iterator<Item = (Entity, &'a Zipline)>,
+	) -> Option<ZiplineRide> {
+		lines
+			.map(|(entity, line)| {
+				let distance = line.distance_along(hands);
+				(entity, line, distance, line.point_at(distance).distance(hands))
+			})
+			.filter(|(.., gap)| *gap <= reach)
+			.min_by(|(.., a), (.., b)| a.total_cmp(b))
+			.map(|(line_entity, line, distance, _)| ZiplineRide {
+				line: line_entity,
+				distance,
+				speed: velocity.dot(line.direction()),
+			})
+	}
+
+	/// Advances the player along the `line` for one frame, with gravity (in world space, as a change in
+	/// velocity per frame) pulling them along it. Returns `false` once they've slid off either end.
+	pub fn slide(&mut self, line: &Zipline, params: &ZiplineParams, gravity: Vec2, delta_secs: f32) -> bool {
+		let pull = gravity.dot(line.direction()) * params.gravity_multiplier;
+		self.speed = ((self.speed + pull) * (1.0 - params.drag)).clamp(-params.max_speed, params.max_speed);
+		self.distance += self.speed * delta_secs;
+		(0.0..=line.length()).contains(&self.distance)
+	}
+
+	/// Where the player's hands are on the `line`
+	pub fn hands(&self, line: &Zipline) -> Vec2 {
+		line.point_at(self.distance.clamp(0.0, line.length()))
+	}
+
+	/// The player's velocity (per second) in world space
+	pub fn velocity(&self, line: &Zipline) -> Vec2 {
+		line.direction() * self.speed
+	}
+}
@@ -0,0 +1,94 @@
+use crate::save::{SaveError, read_ron, write_ron};
+use crate::util::{FrameCount, GameClock};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Where the assist options are kept, next to the save
+const ASSIST_OPTIONS_PATH: &str = "assist.ron";
+
+/// Help for players who find the game too demanding, applied on top of the player's params rather
+/// than by changing them, so the tuned values in `player.ron` stay the same for everyone.
+///
+/// Read from `assist.ron` when the game starts, and written back whenever the options change.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AssistOptions {
+	/// Whether the player can always jump again in midair, no matter how many jumps they've used
+	pub infinite_jumps: bool,
+	/// How fast the game runs, from `0.25` (quarter speed) to `1.0` (full speed). Slowing down runs fixed
+	/// updates less often rather than making them shorter, so the game plays exactly the same, just slower.
+	pub game_speed: f32,
+	/// Multiplier on how long the player can still jump after walking off a ledge or letting go of a wall
+	pub coyote_time_multiplier: f32,
+	/// Whether holding jump on the ground jumps again as soon as the player lands, without pressing it again
+	pub jump_repeat: bool,
+	/// Whether wall jumps get nudged toward the nearest platform they could land on
+	pub wall_jump_aim: bool,
+	/// How far (in degrees, either way) a wall jump may be nudged
+	pub wall_jump_aim_cone: f32,
+	/// Number of trajectories tried across the cone
+	pub wall_jump_aim_samples: usize,
+	/// How many fixed updates ahead each trajectory is followed when looking for a landing
+	pub wall_jump_aim_frames: usize,
+}
+
+impl Default for AssistOptions {
+	fn default() -> Self {
+		AssistOptions {
+			infinite_jumps: false,
+			game_speed: 1.0,
+			coyote_time_multiplier: 1.0,
+			jump_repeat: false,
+			wall_jump_aim: false,
+			wall_jump_aim_cone: 15.0,
+			wall_jump_aim_samples: 7,
+			wall_jump_aim_frames: 60,
+		}
+	}
+}
+
+impl AssistOptions {
+	/// The slowest the `game_speed` can go
+	pub const MIN_GAME_SPEED: f32 = 0.25;
+
+	/// Reads the assist options from disk, falling back to the defaults if they can't be read
+	pub fn load() -> Self {
+		match read_ron(Path::new(ASSIST_OPTIONS_PATH)) {
+			Ok(Some(options)) => {
+				info!("loaded assist options from {}", ASSIST_OPTIONS_PATH);
+				options
+			}
+			Ok(None) => AssistOptions::default(),
+			Err(err) => {
+				warn!("failed to load assist options from {}: {}", ASSIST_OPTIONS_PATH, err);
+				AssistOptions::default()
+			}
+		}
+	}
+
+	pub fn write(&self) -> Result<(), SaveError> {
+		write_ron(Path::new(ASSIST_OPTIONS_PATH), self)
+	}
+
+	/// The given coyote time (for jumping off of the ground or a wall), lengthened by the multiplier
+	pub fn coyote_time(&self, base: FrameCount) -> FrameCount {
+		FrameCount((base.0 as f32 * self.coyote_time_multiplier.max(1.0)).round() as usize)
+	}
+}
+
+/// Slows the [GameClock] down to the assisted game speed, and saves the options whenever they change
+pub fn apply_assist_options(options: Res<AssistOptions>, mut clock: ResMut<GameClock>) {
+	if !options.is_changed() {
+		return;
+	}
+	clock.set_speed(options.game_speed.clamp(AssistOptions::MIN_GAME_SPEED, 1.0));
+	// the options were just loaded, rather than changed
+	if options.is_added() {
+		return;
+	}
+	match options.write() {
+		Ok(()) => info!("saved assist options"),
+		Err(err) => warn!("failed to save assist options: {}", err),
+	}
+}
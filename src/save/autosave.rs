@@ -0,0 +1,103 @@
+use crate::level::{CurrentLevel, LevelCompleteEvent, PlayStats};
+use crate::player::{CharacterRoster, CheckpointReached, Player, PlayerSlot, PlayerSpawnPoint};
+use crate::save::SaveFile;
+use bevy::prelude::*;
+use bevy::window::WindowCloseRequested;
+use bevy_rapier2d::prelude::Collider;
+
+/// The asset path of the level being played, as it's recorded in the save
+fn current_level_path(current_level: Option<&CurrentLevel>, asset_server: &AssetServer) -> Option<String> {
+	current_level
+		.and_then(|level| asset_server.get_path(level.0.id()))
+		.map(|path| path.to_string())
+}
+
+/// Picks up where the save left off: if it's for the level being played, the player starts from its
+/// latest checkpoint, as the same character, with the secrets they'd found. Runs once the player and
+/// the level have been set up.
+pub fn restore_save(
+	save: Res<SaveFile>,
+	current_level: Option<Res<CurrentLevel>>,
+	asset_server: Res<AssetServer>,
+	mut stats: ResMut<PlayStats>,
+	mut players: Query<(
+		&mut Player,
+		&mut CharacterRoster,
+		&mut PlayerSpawnPoint,
+		&mut Transform,
+		&mut Collider,
+		&mut Sprite,
+	)>,
+) {
+	let data = &save.data;
+	if data.level.is_none() || data.level != current_level_path(current_level.as_deref(), &asset_server) {
+		return;
+	}
+	info!("restoring save for level {:?}", data.level);
+	stats.secrets_found.extend(data.secrets_found.iter().cloned());
+	for (mut player, mut roster, mut spawn_point, mut transform, mut collider, mut sprite) in &mut players {
+		if let Some(character) = roster.characters.get(data.character).cloned() {
+			roster.current = data.character;
+			player.0 = character.params;
+			*collider = Collider::cuboid(character.half_extents.x, character.half_extents.y);
+			sprite.color = character.color;
+			sprite.custom_size = Some(character.half_extents * 2.0);
+		}
+		if let Some(checkpoint) = data.checkpoint {
+			spawn_point.0 = checkpoint;
+			transform.translation = checkpoint.extend(transform.translation.z);
+		}
+	}
+}
+
+/// Saves the player's progress whenever they reach a checkpoint or complete a level, and one last time
+/// when the game is closing, so that quitting (or crashing) loses as little as possible.
+/// Runs in `Last`, so that it sees the `AppExit` sent when the window is closed before the app stops.
+pub fn autosave(
+	mut checkpoint_events: EventReader<CheckpointReached>,
+	mut complete_events: EventReader<LevelCompleteEvent>,
+	mut close_events: EventReader<WindowCloseRequested>,
+	mut exit_events: EventReader<AppExit>,
+	mut save: ResMut<SaveFile>,
+	current_level: Option<Res<CurrentLevel>>,
+	asset_server: Res<AssetServer>,
+	stats: Res<PlayStats>,
+	players: Query<(&PlayerSlot, &PlayerSpawnPoint, &CharacterRoster), With<Player>>,
+) {
+	let reached_checkpoint = checkpoint_events.read().count() > 0;
+	let completed_level = complete_events.read().count() > 0;
+	let closing = close_events.read().count() > 0 || exit_events.read().count() > 0;
+	if !reached_checkpoint && !completed_level && !closing {
+		return;
+	}
+
+	// levels generated in code (like the benchmark's stress level) have no path to come back to
+	let Some(level) = current_level_path(current_level.as_deref(), &asset_server) else {
+		return;
+	};
+	let data = &mut save.data;
+	if data.level.as_ref() != Some(&level) {
+		// the checkpoint from another level doesn't mean anything in this one
+		data.checkpoint = None;
+	}
+	// in co-op, the lead player's checkpoint and character are the ones saved
+	if let Some((_, spawn_point, roster)) = players.iter().find(|(slot, ..)| **slot == PlayerSlot::LEAD) {
+		// only a checkpoint actually reached is worth saving, rather than the level's default spawn point
+		if reached_checkpoint {
+			data.checkpoint = Some(spawn_point.0);
+		}
+		data.character = roster.current;
+	}
+	if completed_level && !data.completed_levels.contains(&level) {
+		data.completed_levels.push(level.clone());
+	}
+	data.level = Some(level);
+	data.secrets_found = stats.secrets_found.iter().cloned().collect();
+	// keep the save stable from one write to the next, since the secrets come out of a set in any order
+	data.secrets_found.sort();
+
+	match save.write() {
+		Ok(()) => debug!("autosaved"),
+		Err(err) => warn!("failed to autosave: {}", err),
+	}
+}
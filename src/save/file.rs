@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Where the save goes, unless overridden with `--save <path>`
+const DEFAULT_SAVE_PATH: &str = "save.ron";
+
+/// The player's progress, as written to the save file
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SaveData {
+	/// Asset path of the level being played
+	pub level: Option<String>,
+	/// Position of the latest checkpoint reached in that level, where the player will spawn
+	pub checkpoint: Option<Vec2>,
+	/// Index of the character being played, in the player's [CharacterRoster](crate::player::CharacterRoster)
+	pub character: usize,
+	/// Ids of the secrets found in that level
+	pub secrets_found: Vec<String>,
+	/// Asset paths of every level that's been completed
+	pub completed_levels: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum SaveError {
+	#[error("Could not write save: {0}")]
+	Io(#[from] std::io::Error),
+
+	#[error("Could not serialize save: {0}")]
+	Ron(#[from] ron::Error),
+
+	#[error("Could not parse save: {0}")]
+	Parse(#[from] ron::de::SpannedError),
+}
+
+/// The save file, and the progress last read from or written to it
+#[derive(Resource, Debug)]
+pub struct SaveFile {
+	path: PathBuf,
+	pub data: SaveData,
+}
+
+impl SaveFile {
+	/// Reads the save path from the command line (if given), and the progress saved there (if any).
+	/// If the save can't be read, play starts from scratch and the next autosave replaces it.
+	pub fn from_args() -> Self {
+		let path = std::env::args()
+			.skip_while(|arg| arg != "--save")
+			.nth(1)
+			.map_or_else(|| PathBuf::from(DEFAULT_SAVE_PATH), PathBuf::from);
+		let data = match read_ron(&path) {
+			Ok(Some(data)) => {
+				info!("loaded save from {:?}", path);
+				data
+			}
+			Ok(None) => SaveData::default(),
+			Err(err) => {
+				warn!("failed to load save from {:?}: {}", path, err);
+				SaveData::default()
+			}
+		};
+		SaveFile { path, data }
+	}
+
+	/// A save with no progress in it, which doesn't read what's already saved on disk
+	pub fn fresh() -> Self {
+		SaveFile {
+			path: PathBuf::from(DEFAULT_SAVE_PATH),
+			data: SaveData::default(),
+		}
+	}
+
+	pub fn write(&self) -> Result<(), SaveError> {
+		write_ron(&self.path, &self.data)
+	}
+}
+
+/// Writes a value out to `path` atomically: the new file is written and synced to a temporary file
+/// next to the real one, which is then renamed over it. A crash partway through leaves
+/// the previous file intact, instead of a half-written one.
+pub fn write_ron<T: Serialize>(path: &Path, value: &T) -> Result<(), SaveError> {
+	let text = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())?;
+	let temp_path = path.with_extension("ron.tmp");
+	let mut file = File::create(&temp_path)?;
+	file.write_all(text.as_bytes())?;
+	file.sync_all()?;
+	std::fs::rename(&temp_path, path)?;
+	Ok(())
+}
+
+/// Reads the value saved at `path`, or `None` if there isn't one yet
+pub fn read_ron<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, SaveError> {
+	let text = match std::fs::read_to_string(path) {
+		Ok(text) => text,
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+		Err(err) => return Err(err.into()),
+	};
+	Ok(Some(ron::de::from_str(&text)?))
+}
@@ -0,0 +1,7 @@
+mod assist;
+mod autosave;
+mod file;
+
+pub use assist::*;
+pub use autosave::*;
+pub use file::*;
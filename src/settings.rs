@@ -0,0 +1,184 @@
+use crate::util::FrameCount;
+use bevy::color::Color;
+use bevy::prelude::Resource;
+
+/// Player-facing options that don't affect the game's rules
+#[derive(Resource, Debug, Default)]
+pub struct Settings {
+	pub landing_indicator: LandingIndicatorSettings,
+	pub death_replay: DeathReplaySettings,
+	pub display: DisplaySettings,
+	pub afterimage: AfterimageSettings,
+	pub speed_feedback: SpeedFeedbackSettings,
+	pub accessibility: AccessibilitySettings,
+	pub controls: ControlSettings,
+}
+
+/// How the player's controllers are read
+#[derive(Debug)]
+pub struct ControlSettings {
+	/// Stick deflection smaller than this (from 0 to 1) is ignored, so a worn stick resting
+	/// slightly off-center doesn't walk the player around
+	pub stick_dead_zone: f32,
+}
+
+impl Default for ControlSettings {
+	fn default() -> Self {
+		ControlSettings { stick_dead_zone: 0.15 }
+	}
+}
+
+/// Options for players who are sensitive to (or distracted by) certain visual effects
+#[derive(Debug)]
+pub struct AccessibilitySettings {
+	/// Whether fading copies of the player are left behind while they move quickly
+	pub afterimages: bool,
+}
+
+impl Default for AccessibilitySettings {
+	fn default() -> Self {
+		AccessibilitySettings { afterimages: true }
+	}
+}
+
+/// Styling for the fading copies of the player's sprite left behind while moving quickly
+#[derive(Debug)]
+pub struct AfterimageSettings {
+	/// Fixed updates between each copy
+	pub interval: FrameCount,
+	/// How long each copy takes to fade away
+	pub lifetime: FrameCount,
+	/// Opacity of a freshly-spawned copy
+	pub opacity: f32,
+	/// Copies are left behind whenever the player moves at least this fast
+	pub speed_threshold: f32,
+	/// Tint for copies left while merely moving fast
+	pub speed_tint: Color,
+	/// Tint for copies left during the burst of speed from a wall jump
+	pub wall_jump_tint: Color,
+	/// Maximum number of copies at once
+	pub max_active: usize,
+}
+
+impl Default for AfterimageSettings {
+	fn default() -> Self {
+		AfterimageSettings {
+			interval: FrameCount(3),
+			lifetime: FrameCount(15),
+			opacity: 0.5,
+			speed_threshold: 60.0,
+			speed_tint: Color::srgb(1.0, 0.8, 0.5),
+			wall_jump_tint: Color::srgb(0.5, 0.8, 1.0),
+			max_active: 12,
+		}
+	}
+}
+
+/// When (and how) to make a fuss over the player going fast. Speeds are relative to the
+/// player's top running speed, so the effects keep up with changes to `player.ron`.
+#[derive(Debug)]
+pub struct SpeedFeedbackSettings {
+	/// Fraction of the top running speed at which the player counts as going flat out
+	pub top_speed_ratio: f32,
+	/// Fraction of the top running speed at which the player counts as going faster than they could
+	/// by running alone, thanks to momentum from slopes, springs, wall jumps, and the like
+	pub overdrive_ratio: f32,
+	/// How far (as a fraction of a threshold) the speed has to drop back below it before the player
+	/// leaves that tier, so that hovering around a threshold doesn't set off the effects over and over
+	pub hysteresis: f32,
+	/// Specks of dust kicked up when reaching a faster tier
+	pub dust_particles: usize,
+	/// Streaks left behind when entering overdrive
+	pub speed_lines: usize,
+	pub speed_line_lifetime: FrameCount,
+	/// How far the camera briefly zooms in when entering overdrive, as a fraction of its scale.
+	/// Zero disables the zoom.
+	pub zoom_punch: f32,
+	/// Seconds (of real time) for the zoom to settle back
+	pub zoom_punch_duration: f32,
+}
+
+impl Default for SpeedFeedbackSettings {
+	fn default() -> Self {
+		SpeedFeedbackSettings {
+			top_speed_ratio: 0.95,
+			overdrive_ratio: 1.2,
+			hysteresis: 0.1,
+			dust_particles: 6,
+			speed_lines: 4,
+			speed_line_lifetime: FrameCount(12),
+			zoom_punch: 0.04,
+			zoom_punch_duration: 0.3,
+		}
+	}
+}
+
+/// Options for how the world and HUD are fit into the window
+#[derive(Debug)]
+pub struct DisplaySettings {
+	/// Multiplier for the size of all UI, on top of the automatic scaling by window size
+	pub ui_scale: f32,
+	/// Whether to restrict the world view to a square in the middle of the window,
+	/// rather than revealing extra space beyond the 100x100 world on wide windows
+	pub letterbox: bool,
+}
+
+impl Default for DisplaySettings {
+	fn default() -> Self {
+		DisplaySettings {
+			ui_scale: 1.0,
+			letterbox: true,
+		}
+	}
+}
+
+/// Styling for the shadow drawn on the ground beneath the airborne player
+#[derive(Debug)]
+pub struct LandingIndicatorSettings {
+	pub enabled: bool,
+	pub color: Color,
+	/// Width of the shadow when the player is right above the ground.
+	/// The shadow shrinks as the player gets further away.
+	pub width: f32,
+	pub thickness: f32,
+	/// How far below the player to look for ground
+	pub max_distance: f32,
+	/// Whether the shadow should fade out as the player gets further from the ground
+	pub fade_with_distance: bool,
+}
+
+impl Default for LandingIndicatorSettings {
+	fn default() -> Self {
+		LandingIndicatorSettings {
+			enabled: true,
+			color: Color::srgba(0.0, 0.0, 0.0, 0.4),
+			width: 3.0,
+			thickness: 0.5,
+			max_distance: 60.0,
+			fade_with_distance: true,
+		}
+	}
+}
+
+/// Presentation of the slow-motion replay shown when the player dies
+#[derive(Debug)]
+pub struct DeathReplaySettings {
+	pub enabled: bool,
+	/// How many fixed updates (from the end of the player's history) to replay
+	pub frames: usize,
+	/// Playback speed of the replay, relative to normal speed
+	pub slow_motion: f32,
+	/// Camera projection scale to zoom to; smaller values zoom in further
+	pub zoom: f32,
+}
+
+impl Default for DeathReplaySettings {
+	fn default() -> Self {
+		DeathReplaySettings {
+			enabled: true,
+			frames: 120,
+			slow_motion: 0.25,
+			zoom: 0.5,
+		}
+	}
+}
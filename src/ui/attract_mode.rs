@@ -0,0 +1,156 @@
+use crate::debug::{InputRecording, InputScript};
+use crate::level::{CurrentLevel, LevelAsset, PLAYER_START_SPACING, RestartLevel};
+use crate::player::{Player, PlayerSlot, PlayerSpawnPoint};
+use bevy::prelude::*;
+use std::path::PathBuf;
+
+/// Where the demo runs are bundled
+const DEMOS_DIR: &str = "assets/demos";
+
+/// Seconds (of real time) that nobody has to touch anything for before the demos start
+const IDLE_SECS: f32 = 60.0;
+
+/// How far a gamepad stick has to be pushed to count as touching it, rather than drifting
+const STICK_THRESHOLD: f32 = 0.5;
+
+/// Plays recorded demo runs when nobody has touched anything for a while, one after another,
+/// until someone does. Then the level restarts, with the players back where they were.
+///
+/// The demos are `--record-input` recordings (see [InputRecording]) bundled as `.demo.ron` files
+/// in `assets/demos`, and are played back from the start of the level. Since they're replays,
+/// each one also checks that it still ends up where it was recorded, and warns if it doesn't.
+#[derive(Resource, Debug, Default)]
+pub struct AttractMode {
+	/// The bundled demos, in the order they're shown
+	demos: Vec<PathBuf>,
+	/// Index of the demo to show next
+	next: usize,
+	/// Seconds since anyone last touched anything
+	idle_secs: f32,
+	/// Each player's spawn point from before the demos started, to put back once they're over.
+	/// Only set while the demos are playing.
+	spawn_points: Option<Vec<(Entity, Vec2)>>,
+}
+
+impl AttractMode {
+	/// Finds the demos bundled with the game. Without any, the attract mode never starts.
+	pub fn load() -> Self {
+		let mut demos: Vec<PathBuf> = match std::fs::read_dir(DEMOS_DIR) {
+			Ok(entries) => entries
+				.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+				.filter(|path| path.to_string_lossy().ends_with(".demo.ron"))
+				.collect(),
+			Err(err) => {
+				warn!("failed to find demos in {}: {}", DEMOS_DIR, err);
+				Vec::new()
+			}
+		};
+		demos.sort();
+		info!("found {} demos for the attract mode", demos.len());
+		AttractMode { demos, ..default() }
+	}
+
+	pub fn is_playing(&self) -> bool {
+		self.spawn_points.is_some()
+	}
+
+	/// Starts replaying the next demo that can be read, restarting the level for it.
+	/// Returns `false` if none of them can be.
+	fn play_next_demo(&mut self, recording: &mut InputRecording, restarts: &mut EventWriter<RestartLevel>) -> bool {
+		for _ in 0..self.demos.len() {
+			let path = &self.demos[self.next];
+			self.next = (self.next + 1) % self.demos.len();
+			match InputScript::read(path) {
+				Ok(script) => {
+					info!("playing demo {:?}", path);
+					recording.replay(script);
+					restarts.send(RestartLevel);
+					return true;
+				}
+				Err(err) => warn!("failed to read demo {:?}: {}", path, err),
+			}
+		}
+		false
+	}
+}
+
+/// Whether anyone is pressing anything on the keyboard, mouse, or a gamepad
+fn anything_pressed(
+	keyboard: &ButtonInput<KeyCode>,
+	mouse: &ButtonInput<MouseButton>,
+	gamepads: &Query<&Gamepad>,
+) -> bool {
+	keyboard.get_pressed().next().is_some()
+		|| mouse.get_pressed().next().is_some()
+		|| gamepads.iter().any(|gamepad| {
+			gamepad.digital().get_pressed().next().is_some() || gamepad.left_stick().length() >= STICK_THRESHOLD
+		})
+}
+
+/// Starts the demos once nobody has touched anything for long enough, moves on to the next demo
+/// whenever one finishes, and stops them as soon as anyone touches anything.
+/// Leaves the players' input alone while a run is being recorded or replayed from the command line.
+///
+/// Should run before `restart_level_system`, so the level restarts in the same frame.
+pub fn attract_mode_system(
+	mut attract: ResMut<AttractMode>,
+	mut recording: ResMut<InputRecording>,
+	keyboard: Res<ButtonInput<KeyCode>>,
+	mouse: Res<ButtonInput<MouseButton>>,
+	gamepads: Query<&Gamepad>,
+	real_time: Res<Time<Real>>,
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	mut players: Query<(Entity, &PlayerSlot, &mut PlayerSpawnPoint), With<Player>>,
+	mut restarts: EventWriter<RestartLevel>,
+) {
+	let touched = anything_pressed(&keyboard, &mouse, &gamepads);
+
+	if attract.is_playing() {
+		let over = touched || (recording.is_replay_over() && !attract.play_next_demo(&mut recording, &mut restarts));
+		if !over {
+			return;
+		}
+		info!("stopping the demos");
+		let spawn_points = attract.spawn_points.take().unwrap_or_default();
+		for (entity, _, mut spawn_point) in &mut players {
+			if let Some((_, position)) = spawn_points.iter().find(|(player, _)| *player == entity) {
+				spawn_point.0 = *position;
+			}
+		}
+		recording.stop_replay();
+		restarts.send(RestartLevel);
+		attract.idle_secs = 0.0;
+		return;
+	}
+
+	if touched || recording.is_active() || attract.demos.is_empty() {
+		attract.idle_secs = 0.0;
+		return;
+	}
+	attract.idle_secs += real_time.delta_secs();
+	if attract.idle_secs < IDLE_SECS {
+		return;
+	}
+	let Some(level) = current_level.and_then(|level| levels.get(level.0.id())) else {
+		return;
+	};
+	if !attract.play_next_demo(&mut recording, &mut restarts) {
+		// none of the demos can be played, so stop trying
+		attract.demos.clear();
+		return;
+	}
+
+	// the demos were recorded from the start of the level, rather than from a checkpoint
+	attract.spawn_points = Some(
+		players
+			.iter()
+			.map(|(entity, _, spawn_point)| (entity, spawn_point.0))
+			.collect(),
+	);
+	if let Some(start) = level.player_start {
+		for (_, slot, mut spawn_point) in &mut players {
+			spawn_point.0 = start + Vec2::X * PLAYER_START_SPACING * slot.0 as f32;
+		}
+	}
+}
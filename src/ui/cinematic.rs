@@ -0,0 +1,117 @@
+use crate::ui::WorldCamera;
+use crate::util::{GameClock, PauseReason};
+use bevy::prelude::*;
+
+/// A path for the world camera to travel along, at a constant speed (before easing)
+#[derive(Clone, Debug)]
+pub struct CameraPath {
+	pub points: Vec<Vec2>,
+	/// Seconds (of real time) to travel the whole path
+	pub duration: f32,
+	/// Easing applied to the overall progress along the path
+	pub easing: EaseFunction,
+	/// Camera projection scale to use while travelling; smaller values zoom in further
+	pub zoom: f32,
+	/// Whether any key or mouse button ends the path early
+	pub skippable: bool,
+}
+
+impl CameraPath {
+	/// The point at `distance` along the path, measured along its segments
+	fn point_at(&self, mut distance: f32) -> Vec2 {
+		for segment in self.points.windows(2) {
+			let length = segment[0].distance(segment[1]);
+			if distance <= length {
+				return segment[0].lerp(segment[1], distance / length.max(f32::EPSILON));
+			}
+			distance -= length;
+		}
+		self.points.last().copied().unwrap_or_default()
+	}
+
+	fn length(&self) -> f32 {
+		self.points
+			.windows(2)
+			.map(|segment| segment[0].distance(segment[1]))
+			.sum()
+	}
+}
+
+/// Sent when a [CameraPath] finishes playing (or is skipped),
+/// and the camera has been handed back to its usual position
+#[derive(Event, Debug)]
+pub struct CinematicFinished {
+	pub skipped: bool,
+}
+
+struct ActiveCinematic {
+	path: CameraPath,
+	elapsed: f32,
+	/// Where the camera was (and its projection scale) before the path took over.
+	/// Captured on the first frame of playback.
+	camera_origin: Option<(Vec3, f32)>,
+}
+
+/// Takes control of the world camera to play a [CameraPath], e.g. a level's intro pan.
+/// The [GameClock] is paused throughout, so nothing in the world moves while the camera does.
+#[derive(Resource, Default)]
+pub struct CinematicCamera(Option<ActiveCinematic>);
+
+impl CinematicCamera {
+	pub fn is_playing(&self) -> bool {
+		self.0.is_some()
+	}
+
+	/// Starts playing the path, replacing any path that was already playing
+	pub fn play(&mut self, path: CameraPath) {
+		let camera_origin = self.0.take().and_then(|active| active.camera_origin);
+		self.0 = Some(ActiveCinematic {
+			path,
+			elapsed: 0.0,
+			camera_origin,
+		});
+	}
+}
+
+/// Moves the camera along the active [CameraPath], then puts it back where it was.
+/// Runs in real time, since the game clock is paused while a path plays.
+pub fn play_cinematic_camera(
+	mut cinematic: ResMut<CinematicCamera>,
+	mut finished_events: EventWriter<CinematicFinished>,
+	mut clock: ResMut<GameClock>,
+	real_time: Res<Time<Real>>,
+	keys: Res<ButtonInput<KeyCode>>,
+	mouse: Res<ButtonInput<MouseButton>>,
+	mut camera: Query<(&mut Transform, &mut OrthographicProjection), With<WorldCamera>>,
+) {
+	let Some(active) = cinematic.0.as_mut() else {
+		return;
+	};
+	let Ok((mut camera_transform, mut projection)) = camera.get_single_mut() else {
+		return;
+	};
+
+	let (origin, origin_scale) = *active.camera_origin.get_or_insert_with(|| {
+		clock.pause(PauseReason::Cinematic);
+		(camera_transform.translation, projection.scale)
+	});
+	active.elapsed += real_time.delta_secs();
+
+	let skipped = active.path.skippable
+		&& (keys.get_just_pressed().next().is_some() || mouse.get_just_pressed().next().is_some());
+	let progress = (active.elapsed / active.path.duration.max(f32::EPSILON)).min(1.0);
+
+	if skipped || progress >= 1.0 {
+		camera_transform.translation = origin;
+		projection.scale = origin_scale;
+		finished_events.send(CinematicFinished { skipped });
+		cinematic.0 = None;
+		clock.unpause(PauseReason::Cinematic);
+		return;
+	}
+
+	let eased = EasingCurve::new(0.0, 1.0, active.path.easing).sample_clamped(progress);
+	let point = active.path.point_at(eased * active.path.length());
+	camera_transform.translation = point.extend(origin.z);
+	projection.scale = active.path.zoom;
+}
@@ -0,0 +1,53 @@
+use crate::level::{CollectibleKind, CurrentLevel, LevelAsset, PlayStats};
+use bevy::prelude::*;
+
+/// A HUD counter in the top right corner, showing how many collectibles have been picked up during
+/// the current attempt (out of how many the level has) and what they're worth. Hidden in levels
+/// without any collectibles.
+#[derive(Component, Debug)]
+pub struct CollectibleCounter;
+
+pub fn setup_collectible_counter(mut commands: Commands) {
+	commands.spawn((
+		CollectibleCounter,
+		Text::default(),
+		TextFont::from_font_size(20.0),
+		TextColor(Color::srgb(1.0, 0.8, 0.2)),
+		Node {
+			position_type: PositionType::Absolute,
+			top: Val::Px(10.0),
+			right: Val::Px(20.0),
+			..default()
+		},
+		Visibility::Hidden,
+	));
+}
+
+/// Keeps the [CollectibleCounter] in line with the [PlayStats] and the current level
+pub fn update_collectible_counter(
+	stats: Res<PlayStats>,
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	mut counters: Query<(&mut Text, &mut Visibility), With<CollectibleCounter>>,
+) {
+	let total = current_level
+		.and_then(|current_level| levels.get(current_level.0.id()))
+		.map_or(0, |level| {
+			level
+				.collectibles
+				.iter()
+				.filter(|def| def.kind != CollectibleKind::Key)
+				.count()
+		});
+	for (mut text, mut visibility) in &mut counters {
+		visibility.set_if_neq(if total == 0 {
+			Visibility::Hidden
+		} else {
+			Visibility::Inherited
+		});
+		let counter = format!("{}/{}  \u{2605} {}", stats.collectibles, total, stats.score);
+		if text.0 != counter {
+			text.0 = counter;
+		}
+	}
+}
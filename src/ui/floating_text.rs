@@ -0,0 +1,190 @@
+use crate::util::{FrameCount, pick_pooled};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Styling for [FloatingText], loaded from a `.style.ron` file
+#[derive(Asset, Clone, Debug, Deserialize, TypePath)]
+#[serde(deny_unknown_fields)]
+pub struct FloatingTextStyle {
+	/// How long each text stays on screen
+	pub lifetime: FrameCount,
+	/// How far the text rises over its lifetime
+	pub rise_distance: f32,
+	/// Easing applied to the rise; the text fades out linearly regardless
+	pub rise_easing: EaseFunction,
+	pub font_size: f32,
+	/// Text is laid out in pixels, but the world is only 100 units tall, so it needs scaling down
+	pub world_scale: f32,
+	/// sRGB color components for each kind of text
+	pub damage_color: (f32, f32, f32),
+	pub score_color: (f32, f32, f32),
+	/// Maximum number of texts on screen at once
+	pub max_active: usize,
+}
+
+impl FloatingTextStyle {
+	fn color(&self, kind: FloatingTextKind) -> Color {
+		let (r, g, b) = match kind {
+			FloatingTextKind::Damage => self.damage_color,
+			FloatingTextKind::Score => self.score_color,
+		};
+		Color::srgb(r, g, b)
+	}
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FloatingTextKind {
+	Damage,
+	Score,
+}
+
+/// Send this to show a value rising up from a point in the world, e.g. where a hit landed
+#[derive(Event, Debug)]
+pub struct SpawnFloatingText {
+	pub kind: FloatingTextKind,
+	pub value: i32,
+	pub position: Vec2,
+}
+
+/// A pooled text entity. Inactive ones are hidden until the pool hands them out again.
+#[derive(Component, Debug, Default)]
+pub struct FloatingText {
+	/// `None` while the text is sitting in the pool
+	age: Option<FrameCount>,
+	origin: Vec2,
+}
+
+/// The style used by all [FloatingText]
+#[derive(Resource, Debug)]
+pub struct FloatingTextStyleHandle(pub Handle<FloatingTextStyle>);
+
+pub fn setup_floating_text(mut commands: Commands, asset_server: Res<AssetServer>) {
+	commands.insert_resource(FloatingTextStyleHandle(asset_server.load("ui/floating_text.style.ron")));
+}
+
+/// Shows a [FloatingText] for each [SpawnFloatingText] event, reusing inactive entities where
+/// possible, so that a flurry of hits doesn't mean a flurry of spawns and despawns
+pub fn spawn_floating_text(
+	mut commands: Commands,
+	mut events: EventReader<SpawnFloatingText>,
+	style: Res<FloatingTextStyleHandle>,
+	styles: Res<Assets<FloatingTextStyle>>,
+	mut texts: Query<(
+		Entity,
+		&mut FloatingText,
+		&mut Text2d,
+		&mut TextFont,
+		&mut TextColor,
+		&mut Transform,
+		&mut Visibility,
+	)>,
+) {
+	let Some(style) = styles.get(style.0.id()) else {
+		events.clear();
+		return;
+	};
+	let mut spawned = 0;
+	for event in events.read() {
+		let text = format!("{}", event.value);
+		let color = style.color(event.kind);
+
+		let ages = texts.iter().map(|(entity, floating, ..)| (entity, floating.age));
+		let recycled = pick_pooled(ages, spawned, style.max_active);
+
+		match recycled.and_then(|entity| texts.get_mut(entity).ok()) {
+			Some((_, mut floating, mut text2d, mut font, mut text_color, mut transform, mut visibility)) => {
+				*floating = FloatingText {
+					age: Some(FrameCount(0)),
+					origin: event.position,
+				};
+				text2d.0 = text;
+				font.font_size = style.font_size;
+				text_color.0 = color;
+				transform.translation = event.position.extend(2.0);
+				*visibility = Visibility::Visible;
+			}
+			None => {
+				commands.spawn((
+					FloatingText {
+						age: Some(FrameCount(0)),
+						origin: event.position,
+					},
+					Text2d::new(text),
+					TextFont {
+						font_size: style.font_size,
+						..default()
+					},
+					TextColor(color),
+					Transform::from_translation(event.position.extend(2.0)).with_scale(Vec3::splat(style.world_scale)),
+				));
+				spawned += 1;
+			}
+		}
+	}
+}
+
+/// Rises and fades each active [FloatingText], returning it to the pool at the end of its lifetime
+pub fn animate_floating_text(
+	style: Res<FloatingTextStyleHandle>,
+	styles: Res<Assets<FloatingTextStyle>>,
+	mut texts: Query<(&mut FloatingText, &mut Transform, &mut TextColor, &mut Visibility)>,
+) {
+	let Some(style) = styles.get(style.0.id()) else {
+		return;
+	};
+	for (mut floating, mut transform, mut color, mut visibility) in &mut texts {
+		let Some(age) = floating.age.as_mut() else {
+			continue;
+		};
+		age.increment();
+		if *age >= style.lifetime {
+			floating.age = None;
+			*visibility = Visibility::Hidden;
+			continue;
+		}
+
+		let t = age.0 as f32 / style.lifetime.0.max(1) as f32;
+		let rise = EasingCurve::new(0.0, style.rise_distance, style.rise_easing).sample_clamped(t);
+		transform.translation.x = floating.origin.x;
+		transform.translation.y = floating.origin.y + rise;
+		transform.scale = Vec3::splat(style.world_scale);
+		color.0.set_alpha(1.0 - t);
+	}
+}
+
+#[derive(Default)]
+pub struct FloatingTextStyleLoader;
+
+#[derive(Debug, Error)]
+pub enum FloatingTextStyleLoaderError {
+	#[error("Could not load asset: {0}")]
+	Io(#[from] std::io::Error),
+
+	#[error("Could not parse RON: {0}")]
+	Ron(#[from] ron::de::SpannedError),
+}
+impl AssetLoader for FloatingTextStyleLoader {
+	type Asset = FloatingTextStyle;
+	type Settings = ();
+	type Error = FloatingTextStyleLoaderError;
+
+	async fn load(
+		&self,
+		reader: &mut dyn Reader,
+		_settings: &Self::Settings,
+		_load_context: &mut LoadContext<'_>,
+	) -> Result<Self::Asset, Self::Error> {
+		let mut bytes = Vec::new();
+		reader.read_to_end(&mut bytes).await?;
+		let style = ron::de::from_bytes::<FloatingTextStyle>(&bytes)?;
+		Ok(style)
+	}
+
+	fn extensions(&self) -> &[&str] {
+		// more specific than the "ron" claimed by the player loader
+		&["style.ron"]
+	}
+}
@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+
+/// Marks a UI node (a button, a slider, a level tile) that can receive focus from keyboard or gamepad
+/// navigation. Only visible nodes can be focused, so showing or hiding a menu is all it takes to bring
+/// its entries into (or out of) the navigation.
+#[derive(Component, Debug, Default)]
+#[require(Interaction, BorderColor)]
+pub struct Focusable;
+
+/// The [Focusable] node that currently has focus, if any
+#[derive(Resource, Debug, Default)]
+pub struct UiFocus(pub Option<Entity>);
+
+/// Sent when the focused node is activated (Enter, the gamepad's South button, or a click)
+#[derive(Event, Debug)]
+pub struct FocusActivated(pub Entity);
+
+/// Sent when the player backs out (Escape, or the gamepad's East button) while a node has focus,
+/// so the menu containing it can close or return to its parent
+#[derive(Event, Debug)]
+pub struct FocusCancelled(pub Entity);
+
+/// How the focused node stands out from the rest
+#[derive(Resource, Debug)]
+pub struct FocusStyle {
+	pub focused_border: Color,
+	pub unfocused_border: Color,
+}
+
+impl Default for FocusStyle {
+	fn default() -> Self {
+		FocusStyle {
+			focused_border: Color::srgb(1.0, 0.85, 0.3),
+			unfocused_border: Color::NONE,
+		}
+	}
+}
+
+/// Reads this frame's navigation direction from the arrow keys and every gamepad's d-pad
+fn navigation_input(keys: &ButtonInput<KeyCode>, gamepads: &Query<&Gamepad>) -> Option<Vec2> {
+	let pressed = |key: KeyCode, button: GamepadButton| {
+		keys.just_pressed(key) || gamepads.iter().any(|gamepad| gamepad.just_pressed(button))
+	};
+	// UI coordinates grow downward
+	[
+		(KeyCode::ArrowUp, GamepadButton::DPadUp, Vec2::NEG_Y),
+		(KeyCode::ArrowDown, GamepadButton::DPadDown, Vec2::Y),
+		(KeyCode::ArrowLeft, GamepadButton::DPadLeft, Vec2::NEG_X),
+		(KeyCode::ArrowRight, GamepadButton::DPadRight, Vec2::X),
+	]
+	.into_iter()
+	.find(|(key, button, _)| pressed(*key, *button))
+	.map(|(.., direction)| direction)
+}
+
+/// The best node to move focus to from `from` in the given `direction`: the nearest node that lies
+/// in that direction (favoring nodes that are lined up), or if there are none, the furthest node the
+/// other way, so navigation wraps around.
+fn next_focus(from: Vec2, direction: Vec2, candidates: &[(Entity, Vec2)]) -> Option<Entity> {
+	let ahead = candidates
+		.iter()
+		.filter_map(|&(entity, position)| {
+			let offset = position - from;
+			let along = offset.dot(direction);
+			let across = offset.perp_dot(direction).abs();
+			(along > 0.5).then_some((entity, along + across * 2.0))
+		})
+		.min_by(|(_, a), (_, b)| a.total_cmp(b));
+	let wrapped = || {
+		candidates
+			.iter()
+			.map(|&(entity, position)| {
+				let offset = position - from;
+				(entity, offset.dot(direction) + offset.perp_dot(direction).abs() * 2.0)
+			})
+			.min_by(|(_, a), (_, b)| a.total_cmp(b))
+	};
+	ahead.or_else(wrapped).map(|(entity, _)| entity)
+}
+
+/// Moves the [UiFocus] between visible [Focusable] nodes with the arrow keys or d-pad, and sends
+/// [FocusActivated] and [FocusCancelled] events. The mouse participates too: hovering a node focuses it.
+pub fn navigate_focus(
+	keys: Res<ButtonInput<KeyCode>>,
+	gamepads: Query<&Gamepad>,
+	nodes: Query<(Entity, &GlobalTransform, &InheritedVisibility), With<Focusable>>,
+	interactions: Query<(Entity, &Interaction), (Changed<Interaction>, With<Focusable>)>,
+	mut focus: ResMut<UiFocus>,
+	mut activated: EventWriter<FocusActivated>,
+	mut cancelled: EventWriter<FocusCancelled>,
+) {
+	let candidates: Vec<(Entity, Vec2)> = nodes
+		.iter()
+		.filter(|(_, _, visibility)| visibility.get())
+		.map(|(entity, transform, _)| (entity, transform.translation().truncate()))
+		.collect();
+
+	// forget the focus once its node is hidden or gone
+	let focused_position = |focus: Option<Entity>| {
+		let focused = focus?;
+		candidates
+			.iter()
+			.find(|(entity, _)| *entity == focused)
+			.map(|(_, position)| *position)
+	};
+	if focused_position(focus.0).is_none() {
+		focus.0 = None;
+	}
+
+	for (entity, interaction) in &interactions {
+		match interaction {
+			Interaction::Hovered if focus.0 != Some(entity) => focus.0 = Some(entity),
+			Interaction::Pressed => {
+				focus.0 = Some(entity);
+				activated.send(FocusActivated(entity));
+			}
+			_ => (),
+		}
+	}
+
+	if let Some(direction) = navigation_input(&keys, &gamepads) {
+		// with nothing focused yet, the first press focuses the top-left-most node
+		focus.0 = match focused_position(focus.0) {
+			Some(from) => next_focus(from, direction, &candidates).or(focus.0),
+			None => candidates
+				.iter()
+				.min_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)))
+				.map(|(entity, _)| *entity),
+		};
+	}
+
+	let Some(focused) = focus.0 else {
+		return;
+	};
+	let gamepad_pressed = |button: GamepadButton| gamepads.iter().any(|gamepad| gamepad.just_pressed(button));
+	if keys.just_pressed(KeyCode::Enter) || gamepad_pressed(GamepadButton::South) {
+		activated.send(FocusActivated(focused));
+	}
+	if keys.just_pressed(KeyCode::Escape) || gamepad_pressed(GamepadButton::East) {
+		cancelled.send(FocusCancelled(focused));
+	}
+}
+
+/// Outlines the focused node
+pub fn highlight_focus(
+	focus: Res<UiFocus>,
+	style: Res<FocusStyle>,
+	mut nodes: Query<(Entity, &mut BorderColor), With<Focusable>>,
+) {
+	for (entity, mut border) in &mut nodes {
+		let color = if focus.0 == Some(entity) {
+			style.focused_border
+		} else {
+			style.unfocused_border
+		};
+		// avoid triggering change detection every frame
+		if border.0 != color {
+			border.0 = color;
+		}
+	}
+}
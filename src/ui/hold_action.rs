@@ -0,0 +1,164 @@
+use crate::level::RestartLevel;
+use bevy::prelude::*;
+
+/// Number of segments in the ring drawn around a [HoldAction]'s label
+const RING_SEGMENTS: usize = 16;
+
+/// What happens once a [HoldAction] has been held for long enough
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum HoldActionKind {
+	RestartLevel,
+	QuitToMenu,
+}
+
+/// A UI node for an action that has to be confirmed by holding a key or button down for a while,
+/// so that it can't be triggered by accident. Shows a ring that fills up while the input is held.
+#[derive(Component, Debug)]
+pub struct HoldAction {
+	pub kind: HoldActionKind,
+	pub keys: Vec<KeyCode>,
+	pub buttons: Vec<GamepadButton>,
+	/// Seconds (of real time) the input has to be held
+	pub duration: f32,
+	held_for: f32,
+	/// Set once the action fires, so that it doesn't fire again until the input is released
+	fired: bool,
+}
+
+impl HoldAction {
+	pub fn new(kind: HoldActionKind, keys: Vec<KeyCode>, buttons: Vec<GamepadButton>, duration: f32) -> Self {
+		HoldAction {
+			kind,
+			keys,
+			buttons,
+			duration,
+			held_for: 0.0,
+			fired: false,
+		}
+	}
+
+	/// How far along the hold is, from 0 to 1
+	pub fn progress(&self) -> f32 {
+		(self.held_for / self.duration.max(f32::EPSILON)).min(1.0)
+	}
+
+	/// Spawns the action's node, with a `label` in the middle of its progress ring
+	pub fn spawn(self, commands: &mut Commands, label: &str, node: Node) {
+		const RADIUS: f32 = 24.0;
+		const DOT: f32 = 6.0;
+		commands
+			.spawn((
+				self,
+				Node {
+					width: Val::Px(RADIUS * 2.0 + DOT),
+					height: Val::Px(RADIUS * 2.0 + DOT),
+					justify_content: JustifyContent::Center,
+					align_items: AlignItems::Center,
+					..node
+				},
+				Visibility::Hidden,
+			))
+			.with_children(|parent| {
+				parent.spawn((Text::new(label), TextFont::from_font_size(12.0)));
+				// starting from the top, going clockwise
+				for i in 0..RING_SEGMENTS {
+					let angle = i as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+					let offset = Vec2::new(angle.sin(), -angle.cos()) * RADIUS;
+					parent.spawn((
+						HoldRingSegment(i),
+						Node {
+							position_type: PositionType::Absolute,
+							left: Val::Px(RADIUS + offset.x),
+							top: Val::Px(RADIUS + offset.y),
+							width: Val::Px(DOT),
+							height: Val::Px(DOT),
+							..default()
+						},
+						BorderRadius::MAX,
+						BackgroundColor(Color::WHITE),
+					));
+				}
+			});
+	}
+}
+
+/// One dot of the ring around a [HoldAction], lit once the hold's progress passes it
+#[derive(Component, Debug)]
+pub struct HoldRingSegment(usize);
+
+/// Sent when a [HoldAction] has been held for its full duration
+#[derive(Event, Debug)]
+pub struct HoldActionCompleted(pub HoldActionKind);
+
+/// Counts how long each [HoldAction]'s input has been held, and fires it once held for long enough.
+/// Only shown while held, so the ring doesn't clutter the screen.
+pub fn tick_hold_actions(
+	keys: Res<ButtonInput<KeyCode>>,
+	gamepads: Query<&Gamepad>,
+	real_time: Res<Time<Real>>,
+	mut actions: Query<(&mut HoldAction, &mut Visibility)>,
+	mut completed: EventWriter<HoldActionCompleted>,
+) {
+	for (mut action, mut visibility) in &mut actions {
+		let held = action.keys.iter().any(|key| keys.pressed(*key))
+			|| action
+				.buttons
+				.iter()
+				.any(|button| gamepads.iter().any(|gamepad| gamepad.pressed(*button)));
+		if !held {
+			if action.held_for > 0.0 || action.fired {
+				action.held_for = 0.0;
+				action.fired = false;
+			}
+		} else if !action.fired {
+			action.held_for += real_time.delta_secs();
+			if action.held_for >= action.duration {
+				info!("hold action {:?} confirmed", action.kind);
+				action.fired = true;
+				completed.send(HoldActionCompleted(action.kind));
+			}
+		}
+		visibility.set_if_neq(if held && !action.fired {
+			Visibility::Inherited
+		} else {
+			Visibility::Hidden
+		});
+	}
+}
+
+/// Lights up each [HoldAction]'s ring according to its progress
+pub fn draw_hold_progress(
+	actions: Query<(&HoldAction, &Children), Changed<HoldAction>>,
+	mut segments: Query<(&HoldRingSegment, &mut BackgroundColor)>,
+) {
+	for (action, children) in &actions {
+		let lit = (action.progress() * RING_SEGMENTS as f32) as usize;
+		let mut children = segments.iter_many_mut(children);
+		while let Some((segment, mut color)) = children.fetch_next() {
+			color.0 = if segment.0 < lit {
+				Color::WHITE
+			} else {
+				Color::srgba(1.0, 1.0, 1.0, 0.2)
+			};
+		}
+	}
+}
+
+/// Carries out completed [HoldAction]s
+pub fn handle_hold_actions(
+	mut completed: EventReader<HoldActionCompleted>,
+	mut restarts: EventWriter<RestartLevel>,
+	mut exits: EventWriter<AppExit>,
+) {
+	for HoldActionCompleted(kind) in completed.read() {
+		match kind {
+			HoldActionKind::RestartLevel => {
+				restarts.send(RestartLevel);
+			}
+			// there's no menu to go back to yet, so quitting to it means quitting the game
+			HoldActionKind::QuitToMenu => {
+				exits.send(AppExit::Success);
+			}
+		}
+	}
+}
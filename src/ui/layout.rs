@@ -0,0 +1,73 @@
+use crate::settings::Settings;
+use bevy::prelude::*;
+use bevy::render::camera::Viewport;
+use bevy::render::view::RenderLayers;
+use bevy::window::{PrimaryWindow, WindowResized};
+
+/// UI sizes (e.g. `Val::Px` offsets and font sizes) are authored against a window
+/// of this height, and scaled proportionally for other window sizes
+const REFERENCE_HEIGHT: f32 = 720.0;
+
+/// Render layer that no gameplay entity uses, so the letterbox camera draws nothing but its clear color
+const LETTERBOX_LAYER: usize = 31;
+
+/// Marker for the camera that renders the game world
+#[derive(Component)]
+pub struct WorldCamera;
+
+/// Spawns a camera behind the world camera, whose only job is to clear the whole window
+/// (so the bars around a letterboxed viewport are a solid color instead of garbage)
+pub fn setup_letterbox_camera(mut commands: Commands) {
+	commands.spawn((
+		Camera2d,
+		Camera {
+			order: -1,
+			clear_color: ClearColorConfig::Custom(Color::BLACK),
+			..default()
+		},
+		RenderLayers::layer(LETTERBOX_LAYER),
+	));
+}
+
+/// Keeps the world camera's viewport and the global [UiScale] in sync with the window size
+/// and the display settings. The world is a fixed 100x100 area, so when letterboxing is enabled
+/// the viewport is restricted to the largest centered square that fits in the window.
+pub fn layout_system(
+	settings: Res<Settings>,
+	mut resized_events: EventReader<WindowResized>,
+	windows: Query<&Window, With<PrimaryWindow>>,
+	mut cameras: Query<&mut Camera, With<WorldCamera>>,
+	mut ui_scale: ResMut<UiScale>,
+) {
+	let resized = resized_events.read().count() > 0;
+	if !resized && !settings.is_changed() {
+		return;
+	}
+	let Ok(window) = windows.get_single() else {
+		return;
+	};
+	let display = &settings.display;
+
+	let physical_size = window.physical_size();
+	let viewport = if display.letterbox {
+		let side = physical_size.x.min(physical_size.y);
+		Some(Viewport {
+			physical_position: (physical_size - UVec2::splat(side)) / 2,
+			physical_size: UVec2::splat(side),
+			..default()
+		})
+	} else {
+		None
+	};
+	for mut camera in &mut cameras {
+		camera.viewport = viewport.clone();
+	}
+
+	// UI is laid out within the camera's viewport, so scale it to fit whichever is in use
+	let visible_height = if display.letterbox {
+		window.width().min(window.height())
+	} else {
+		window.height()
+	};
+	ui_scale.0 = display.ui_scale * visible_height / REFERENCE_HEIGHT;
+}
@@ -0,0 +1,136 @@
+use crate::level::{CurrentLevel, LevelAsset, PlayStats, SecretDiscovered};
+use crate::player::{Player, PlayerSlot, TutorialGhost};
+use bevy::prelude::*;
+
+/// Width and height of the minimap, in (reference) pixels
+const MINIMAP_SIZE: f32 = 120.0;
+
+/// Size of the dot marking the player's position on the minimap
+const MARKER_SIZE: f32 = 5.0;
+
+/// A small overview of the current level in the corner of the screen, showing its platforms,
+/// the secrets found so far, and where the player is
+#[derive(Component, Debug)]
+pub struct Minimap;
+
+/// One of the rectangles drawn on the [Minimap], rebuilt whenever the level or the player's
+/// discoveries change
+#[derive(Component, Debug)]
+pub struct MinimapTile;
+
+/// The dot marking the player's position on the [Minimap]
+#[derive(Component, Debug)]
+pub struct MinimapPlayerMarker;
+
+pub fn setup_minimap(mut commands: Commands) {
+	commands
+		.spawn((
+			Minimap,
+			Node {
+				position_type: PositionType::Absolute,
+				top: Val::Px(10.0),
+				left: Val::Px(10.0),
+				width: Val::Px(MINIMAP_SIZE),
+				height: Val::Px(MINIMAP_SIZE),
+				..default()
+			},
+			BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+		))
+		.with_child((
+			MinimapPlayerMarker,
+			Node {
+				position_type: PositionType::Absolute,
+				width: Val::Px(MARKER_SIZE),
+				height: Val::Px(MARKER_SIZE),
+				..default()
+			},
+			BorderRadius::MAX,
+			BackgroundColor(Color::srgb(1.0, 0.5, 0.0)),
+			// drawn over the tiles, which are spawned after it
+			ZIndex(1),
+		));
+}
+
+/// Where on the minimap (in pixels from its top-left corner) a point in the level goes,
+/// given the level's `bounds`
+fn to_minimap(bounds: Rect, point: Vec2) -> Vec2 {
+	let t = (point - bounds.min) / bounds.size().max(Vec2::splat(f32::EPSILON));
+	Vec2::new(t.x, 1.0 - t.y) * MINIMAP_SIZE
+}
+
+fn tile(bounds: Rect, rect: Rect, color: Color) -> impl Bundle {
+	let top_left = to_minimap(bounds, Vec2::new(rect.min.x, rect.max.y));
+	let size = rect.size() / bounds.size().max(Vec2::splat(f32::EPSILON)) * MINIMAP_SIZE;
+	(
+		MinimapTile,
+		Node {
+			position_type: PositionType::Absolute,
+			left: Val::Px(top_left.x),
+			top: Val::Px(top_left.y),
+			width: Val::Px(size.x),
+			height: Val::Px(size.y),
+			..default()
+		},
+		BackgroundColor(color),
+	)
+}
+
+/// Redraws the minimap's tiles when the level loads or changes, or when a secret is found
+pub fn rebuild_minimap(
+	mut commands: Commands,
+	mut level_events: EventReader<AssetEvent<LevelAsset>>,
+	mut secret_events: EventReader<SecretDiscovered>,
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	stats: Res<PlayStats>,
+	minimaps: Query<Entity, With<Minimap>>,
+	tiles: Query<Entity, With<MinimapTile>>,
+) {
+	let level_changed = level_events.read().count() > 0;
+	let secret_found = secret_events.read().count() > 0;
+	if !level_changed && !secret_found {
+		return;
+	}
+	let Some(level) = current_level.and_then(|level| levels.get(level.0.id())) else {
+		return;
+	};
+	let (Ok(minimap), Some(bounds)) = (minimaps.get_single(), level.bounds()) else {
+		return;
+	};
+
+	for entity in &tiles {
+		commands.entity(entity).despawn();
+	}
+	commands.entity(minimap).with_children(|parent| {
+		for def in &level.platforms {
+			let rect = Rect::from_center_size(def.pos, def.size);
+			parent.spawn(tile(bounds, rect, Color::srgba(1.0, 1.0, 1.0, 0.6)));
+		}
+		for secret in level.secrets.iter().filter(|s| stats.secrets_found.contains(&s.id)) {
+			let rect = Rect::from_corners(secret.min, secret.max);
+			parent.spawn(tile(bounds, rect, Color::srgba(1.0, 0.85, 0.2, 0.7)));
+		}
+	});
+}
+
+/// Moves the [MinimapPlayerMarker] to the lead player's position
+pub fn update_minimap_marker(
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	players: Query<(&PlayerSlot, &Transform), (With<Player>, Without<TutorialGhost>)>,
+	mut markers: Query<&mut Node, With<MinimapPlayerMarker>>,
+) {
+	let Some(bounds) = current_level
+		.and_then(|level| levels.get(level.0.id()))
+		.and_then(|level| level.bounds())
+	else {
+		return;
+	};
+	let lead_player = players.iter().find(|(slot, _)| **slot == PlayerSlot::LEAD);
+	let (Some((_, player)), Ok(mut marker)) = (lead_player, markers.get_single_mut()) else {
+		return;
+	};
+	let position = to_minimap(bounds, player.translation.truncate()) - MARKER_SIZE * 0.5;
+	marker.left = Val::Px(position.x);
+	marker.top = Val::Px(position.y);
+}
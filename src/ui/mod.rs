@@ -0,0 +1,21 @@
+mod attract_mode;
+mod cinematic;
+mod collectible_counter;
+mod floating_text;
+mod focus;
+mod hold_action;
+mod layout;
+mod minimap;
+mod results_screen;
+mod room_camera;
+
+pub use attract_mode::*;
+pub use cinematic::*;
+pub use collectible_counter::*;
+pub use floating_text::*;
+pub use focus::*;
+pub use hold_action::*;
+pub use layout::*;
+pub use minimap::*;
+pub use results_screen::*;
+pub use room_camera::*;
@@ -0,0 +1,241 @@
+use crate::level::{CurrentLevel, LevelAsset, LevelCompleteEvent, LevelResults, PlayStats, Rank, RestartLevel};
+use crate::ui::{FocusActivated, Focusable, UiFocus};
+use crate::util::{GameClock, PauseReason};
+use bevy::prelude::*;
+
+/// Seconds each line of the results takes to count up
+const TALLY_DURATION: f32 = 0.6;
+
+/// Pause between the tally finishing and the rank appearing, in seconds
+const RANK_DELAY: f32 = 0.4;
+
+/// The overlay shown once a level is complete, tallying up how the run went and grading it
+#[derive(Component, Debug)]
+pub struct ResultsScreen;
+
+/// One of the tallied lines of the [ResultsScreen]
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+pub enum ResultsLine {
+	Time,
+	Deaths,
+	Collectibles,
+}
+
+impl ResultsLine {
+	const ALL: [ResultsLine; 3] = [ResultsLine::Time, ResultsLine::Deaths, ResultsLine::Collectibles];
+
+	/// The line's text, with its value counted up to `progress` (from 0 to 1) of the final result
+	fn text(self, results: &LevelResults, progress: f32) -> String {
+		let tally = |count: u32| (count as f32 * progress).round() as u32;
+		match self {
+			ResultsLine::Time => {
+				let secs = results.time * progress;
+				format!("Time  {}:{:05.2}", (secs / 60.0) as u32, secs % 60.0)
+			}
+			ResultsLine::Deaths => format!("Deaths  {}", tally(results.deaths)),
+			ResultsLine::Collectibles => format!("Collectibles  {}", tally(results.collectibles)),
+		}
+	}
+}
+
+/// The text showing the [Rank] on the [ResultsScreen]
+#[derive(Component, Debug)]
+pub struct ResultsRankText;
+
+/// The buttons on the [ResultsScreen]
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+pub enum ResultsButton {
+	/// Carries on from the end of the level
+	Continue,
+	/// Restarts the level for another attempt
+	Retry,
+}
+
+struct ShownResults {
+	results: LevelResults,
+	/// `None` for levels without rank thresholds
+	rank: Option<Rank>,
+	/// Real seconds since the screen was shown
+	elapsed: f32,
+}
+
+impl ShownResults {
+	/// Real seconds until every line is tallied and the rank is showing
+	fn duration() -> f32 {
+		TALLY_DURATION * ResultsLine::ALL.len() as f32 + RANK_DELAY
+	}
+
+	fn is_finished(&self) -> bool {
+		self.elapsed >= Self::duration()
+	}
+}
+
+/// The results currently shown on the [ResultsScreen], if any.
+/// The [GameClock] is paused while they're shown, so the level waits for the player to decide what's next.
+#[derive(Resource, Default)]
+pub struct LevelResultsDisplay(Option<ShownResults>);
+
+impl LevelResultsDisplay {
+	pub fn is_showing(&self) -> bool {
+		self.0.is_some()
+	}
+}
+
+pub fn setup_results_screen(mut commands: Commands) {
+	let button = |parent: &mut ChildBuilder, kind: ResultsButton, label: &str| {
+		parent
+			.spawn((
+				kind,
+				Focusable,
+				Node {
+					padding: UiRect::axes(Val::Px(16.0), Val::Px(6.0)),
+					border: UiRect::all(Val::Px(2.0)),
+					..default()
+				},
+				BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.1)),
+			))
+			.with_child((Text::new(label), TextFont::from_font_size(20.0)));
+	};
+	commands
+		.spawn((
+			ResultsScreen,
+			Node {
+				position_type: PositionType::Absolute,
+				width: Val::Percent(100.0),
+				height: Val::Percent(100.0),
+				flex_direction: FlexDirection::Column,
+				justify_content: JustifyContent::Center,
+				align_items: AlignItems::Center,
+				row_gap: Val::Px(12.0),
+				..default()
+			},
+			BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+			// over the rest of the UI
+			ZIndex(10),
+			Visibility::Hidden,
+		))
+		.with_children(|parent| {
+			parent.spawn((Text::new("Level Complete"), TextFont::from_font_size(36.0)));
+			for line in ResultsLine::ALL {
+				parent.spawn((line, Text::default(), TextFont::from_font_size(24.0)));
+			}
+			parent.spawn((
+				ResultsRankText,
+				Text::default(),
+				TextFont::from_font_size(64.0),
+				TextColor(Color::srgb(1.0, 0.85, 0.3)),
+			));
+			parent
+				.spawn(Node {
+					column_gap: Val::Px(20.0),
+					..default()
+				})
+				.with_children(|row| {
+					button(row, ResultsButton::Continue, "Continue");
+					button(row, ResultsButton::Retry, "Retry");
+				});
+		});
+}
+
+/// Brings up the [ResultsScreen] when the level is complete, grading the run against the level's
+/// rank thresholds
+pub fn show_results_screen(
+	mut events: EventReader<LevelCompleteEvent>,
+	mut display: ResMut<LevelResultsDisplay>,
+	mut clock: ResMut<GameClock>,
+	mut focus: ResMut<UiFocus>,
+	stats: Res<PlayStats>,
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	mut screens: Query<&mut Visibility, With<ResultsScreen>>,
+	buttons: Query<(Entity, &ResultsButton)>,
+) {
+	if events.read().count() == 0 || display.is_showing() {
+		return;
+	}
+	let results = LevelResults::from_stats(&stats, &clock);
+	let rank = current_level
+		.and_then(|level| levels.get(level.0.id()))
+		.and_then(|level| level.ranks.as_ref())
+		.map(|ranks| ranks.grade(&results));
+	info!("level complete: {:?}, rank {:?}", results, rank);
+
+	clock.pause(PauseReason::ResultsScreen);
+	display.0 = Some(ShownResults {
+		results,
+		rank,
+		elapsed: 0.0,
+	});
+	for mut visibility in &mut screens {
+		*visibility = Visibility::Inherited;
+	}
+	focus.0 = buttons
+		.iter()
+		.find(|(_, kind)| **kind == ResultsButton::Continue)
+		.map(|(entity, _)| entity);
+}
+
+/// Counts up each line of the [ResultsScreen] in turn, then reveals the rank.
+/// Runs in real time, since the game clock is paused while the results are shown.
+pub fn animate_results_screen(
+	mut display: ResMut<LevelResultsDisplay>,
+	real_time: Res<Time<Real>>,
+	mut lines: Query<(&ResultsLine, &mut Text), Without<ResultsRankText>>,
+	mut rank_texts: Query<&mut Text, With<ResultsRankText>>,
+) {
+	let Some(shown) = display.0.as_mut() else {
+		return;
+	};
+	shown.elapsed += real_time.delta_secs();
+
+	for (line, mut text) in &mut lines {
+		let index = ResultsLine::ALL.iter().position(|l| l == line).unwrap_or(0);
+		let progress = ((shown.elapsed - index as f32 * TALLY_DURATION) / TALLY_DURATION).clamp(0.0, 1.0);
+		text.0 = line.text(&shown.results, progress);
+	}
+	let rank = match shown.rank {
+		Some(rank) if shown.is_finished() => format!("Rank {}", rank),
+		_ => String::new(),
+	};
+	for mut text in &mut rank_texts {
+		text.0.clone_from(&rank);
+	}
+}
+
+/// Handles the [ResultsScreen]'s buttons. Pressing either one while the results are still being tallied
+/// skips to the end of the tally instead, so an impatient press doesn't skip past the rank.
+pub fn handle_results_buttons(
+	mut activations: EventReader<FocusActivated>,
+	mut display: ResMut<LevelResultsDisplay>,
+	mut clock: ResMut<GameClock>,
+	mut stats: ResMut<PlayStats>,
+	mut restarts: EventWriter<RestartLevel>,
+	buttons: Query<&ResultsButton>,
+	mut screens: Query<&mut Visibility, With<ResultsScreen>>,
+) {
+	for FocusActivated(entity) in activations.read() {
+		let Ok(button) = buttons.get(*entity) else {
+			continue;
+		};
+		let Some(shown) = display.0.as_mut() else {
+			continue;
+		};
+		if !shown.is_finished() {
+			shown.elapsed = ShownResults::duration();
+			continue;
+		}
+
+		match button {
+			ResultsButton::Continue => stats.start_attempt(&clock),
+			// restarting starts a new attempt too
+			ResultsButton::Retry => {
+				restarts.send(RestartLevel);
+			}
+		}
+		display.0 = None;
+		clock.unpause(PauseReason::ResultsScreen);
+		for mut visibility in &mut screens {
+			*visibility = Visibility::Hidden;
+		}
+	}
+}
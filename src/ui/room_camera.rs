@@ -0,0 +1,95 @@
+use crate::level::{CurrentLevel, CurrentRoom, LevelAsset, RoomChanged, RoomTransition};
+use crate::player::{DeathReplay, Player, PlayerSlot, TutorialGhost};
+use crate::ui::{CinematicCamera, WorldCamera};
+use crate::util::{GameClock, PauseReason};
+use bevy::prelude::*;
+
+/// Seconds (of real time) the camera takes to pan from one room to the next
+const PAN_DURATION: f32 = 0.5;
+
+/// Where the camera sits while the lead player isn't in any room: the middle of the 100x100 world
+const WORLD_CENTER: Vec2 = Vec2::splat(50.0);
+
+struct ActivePan {
+	from: Vec2,
+	elapsed: f32,
+}
+
+/// Keeps the world camera inside the room the lead player is in, following the player around rooms
+/// that are bigger than the view. Crossing into another room pans the camera over (or snaps it,
+/// depending on the room's [RoomTransition]).
+#[derive(Resource, Default)]
+pub struct RoomCamera(Option<ActivePan>);
+
+/// Moves the world camera to follow the lead player within the [CurrentRoom]. Pans run in real
+/// time, with the [GameClock] paused so nothing moves until the camera arrives in the new room.
+/// Leaves the camera alone while a cinematic or death replay is using it.
+pub fn room_camera_system(
+	mut room_camera: ResMut<RoomCamera>,
+	mut room_events: EventReader<RoomChanged>,
+	current_room: Res<CurrentRoom>,
+	current_level: Option<Res<CurrentLevel>>,
+	levels: Res<Assets<LevelAsset>>,
+	cinematic: Res<CinematicCamera>,
+	replay: Res<DeathReplay>,
+	mut clock: ResMut<GameClock>,
+	real_time: Res<Time<Real>>,
+	players: Query<(&PlayerSlot, &Transform), (With<Player>, Without<WorldCamera>, Without<TutorialGhost>)>,
+	mut camera: Query<(&mut Transform, &OrthographicProjection), With<WorldCamera>>,
+) {
+	// the level loading (or the player leaving every room) isn't a move between rooms, so it cuts
+	let crossed = room_events
+		.read()
+		.last()
+		.map(|event| event.left.is_some() && event.entered.is_some());
+	if cinematic.is_playing() || replay.is_playing() {
+		return;
+	}
+	let Ok((mut camera_transform, projection)) = camera.get_single_mut() else {
+		return;
+	};
+	let Some(level) = current_level.and_then(|level| levels.get(level.0.id())) else {
+		return;
+	};
+	let Some((_, player_transform)) = players.iter().find(|(slot, _)| **slot == PlayerSlot::LEAD) else {
+		return;
+	};
+
+	let room = current_room
+		.0
+		.as_ref()
+		.and_then(|id| level.rooms.iter().find(|room| room.id == *id));
+	let target = room.map_or(WORLD_CENTER, |room| {
+		room.camera_center(player_transform.translation.truncate(), projection.area.size())
+	});
+	let camera_position = camera_transform.translation.truncate();
+
+	if let Some(crossed) = crossed {
+		let transition = room
+			.filter(|_| crossed)
+			.map_or(RoomTransition::Snap, |room| room.transition);
+		room_camera.0 = None;
+		if transition == RoomTransition::Pan {
+			clock.pause(PauseReason::RoomPan);
+			room_camera.0 = Some(ActivePan {
+				from: camera_position,
+				elapsed: 0.0,
+			});
+		} else {
+			clock.unpause(PauseReason::RoomPan);
+		}
+	}
+
+	let Some(pan) = room_camera.0.as_mut() else {
+		camera_transform.translation = target.extend(camera_transform.translation.z);
+		return;
+	};
+	pan.elapsed += real_time.delta_secs();
+	let progress = (pan.elapsed / PAN_DURATION).min(1.0);
+	let eased = EasingCurve::new(0.0, 1.0, EaseFunction::SineInOut).sample_clamped(progress);
+	camera_transform.translation = pan.from.lerp(target, eased).extend(camera_transform.translation.z);
+	if progress >= 1.0 {
+		clock.unpause(PauseReason::RoomPan);
+		room_camera.0 = None;
+	}
+}
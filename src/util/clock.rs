@@ -0,0 +1,108 @@
+use crate::util::FrameCount;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+/// Something that pauses the [GameClock]. Each one pauses and unpauses independently, and the clock
+/// only runs while none of them have it paused, so e.g. a cinematic ending doesn't unpause the game
+/// underneath the results screen.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PauseReason {
+	ResultsScreen,
+	Cinematic,
+	RoomPan,
+	LevelTransition,
+	DeathReplay,
+}
+
+/// The game's own notion of time, shared by gameplay systems, replays, and metrics.
+///
+/// Gameplay runs one step per fixed update, so the clock counts fixed frames rather than
+/// accumulating wall-clock time. Pausing or slowing down the clock changes how often fixed
+/// updates happen, but never how long each one is, so frame-based timers ([FrameCount],
+/// [Cooldown](crate::util::Cooldown), etc.) and displayed times always agree with each other.
+#[derive(Resource, Debug)]
+pub struct GameClock {
+	frame: FrameCount,
+	delta_secs: f32,
+	elapsed_secs: f32,
+	paused_by: HashSet<PauseReason>,
+	speed: f32,
+}
+
+impl Default for GameClock {
+	fn default() -> Self {
+		GameClock {
+			frame: FrameCount(0),
+			delta_secs: 0.0,
+			elapsed_secs: 0.0,
+			paused_by: HashSet::new(),
+			speed: 1.0,
+		}
+	}
+}
+
+impl GameClock {
+	/// Number of fixed frames that have elapsed while the game was running
+	pub fn frame(&self) -> FrameCount {
+		self.frame
+	}
+
+	/// Gameplay seconds covered by the current frame
+	pub fn delta_secs(&self) -> f32 {
+		self.delta_secs
+	}
+
+	/// Gameplay seconds elapsed in total, i.e. not counting time spent paused,
+	/// and counting slowed-down time at its slowed-down rate
+	pub fn elapsed_secs(&self) -> f32 {
+		self.elapsed_secs
+	}
+
+	pub fn is_paused(&self) -> bool {
+		!self.paused_by.is_empty()
+	}
+
+	pub fn pause(&mut self, reason: PauseReason) {
+		self.paused_by.insert(reason);
+	}
+
+	/// Lifts the given reason's pause, leaving the clock paused if anything else has it paused too
+	pub fn unpause(&mut self, reason: PauseReason) {
+		self.paused_by.remove(&reason);
+	}
+
+	/// Playback speed relative to real time, e.g. `0.5` for half-speed slow motion
+	pub fn speed(&self) -> f32 {
+		self.speed
+	}
+
+	pub fn set_speed(&mut self, speed: f32) {
+		self.speed = speed.max(0.0);
+	}
+}
+
+/// Advances the [GameClock] by one frame.
+/// Runs in `FixedFirst`, which doesn't run at all while the clock is paused.
+pub fn tick_game_clock(mut clock: ResMut<GameClock>, time: Res<Time<Fixed>>) {
+	clock.frame.increment();
+	clock.delta_secs = time.delta_secs();
+	clock.elapsed_secs += time.delta_secs();
+}
+
+/// Applies the [GameClock]'s pause state and speed to Bevy's virtual time, which is what
+/// actually decides how many fixed updates run each frame
+pub fn sync_game_clock(clock: Res<GameClock>, mut virtual_time: ResMut<Time<Virtual>>) {
+	if !clock.is_changed() {
+		return;
+	}
+	if clock.is_paused() != virtual_time.is_paused() {
+		if clock.is_paused() {
+			virtual_time.pause();
+		} else {
+			virtual_time.unpause();
+		}
+	}
+	if clock.speed != virtual_time.relative_speed() {
+		virtual_time.set_relative_speed(clock.speed);
+	}
+}
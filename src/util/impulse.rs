@@ -0,0 +1,37 @@
+use bevy::prelude::*;
+use bevy_rapier2d::dynamics::{ExternalImpulse, ReadMassProperties};
+
+/// Opts a dynamic body in to being shoved by [ImpulseBroadcast]s
+#[derive(Component, Debug, Default)]
+pub struct Kickable;
+
+/// Sent to shove every [Kickable] body within `radius` of `origin` away from it, e.g. when the
+/// player runs past some loose props. The impulse falls off linearly to zero at the `radius`.
+#[derive(Event, Debug, Copy, Clone)]
+pub struct ImpulseBroadcast {
+	pub origin: Vec2,
+	pub radius: f32,
+	/// Change in velocity (not momentum) given to a body right at the `origin`,
+	/// so that light and heavy bodies react the same way
+	pub strength: f32,
+	/// Added to the upward component of the direction, so that things hop instead of sliding
+	pub upward_bias: f32,
+}
+
+pub fn apply_impulse_broadcasts(
+	mut events: EventReader<ImpulseBroadcast>,
+	mut bodies: Query<(&Transform, &mut ExternalImpulse, &ReadMassProperties), With<Kickable>>,
+) {
+	for broadcast in events.read() {
+		for (transform, mut impulse, mass) in &mut bodies {
+			let offset = transform.translation.truncate() - broadcast.origin;
+			let distance = offset.length();
+			if distance >= broadcast.radius {
+				continue;
+			}
+			let falloff = 1.0 - distance / broadcast.radius;
+			let direction = (offset.normalize_or_zero() + Vec2::Y * broadcast.upward_bias).normalize_or_zero();
+			impulse.impulse += direction * broadcast.strength * falloff * mass.get().mass;
+		}
+	}
+}
@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+use bevy_rapier2d::dynamics::RigidBody;
+
+/// The authoritative position of a physics-driven entity, as of the two most recent fixed updates.
+///
+/// Physics and gameplay run in the fixed schedule, but frames are rendered at whatever rate the
+/// display allows. Between fixed updates, the entity's `Transform` is treated as a *render*
+/// transform, interpolated from `previous` to `current` by how far the clock has progressed
+/// towards the next fixed update. Before each fixed update, the `Transform` is restored to
+/// `current`, so that gameplay systems and Rapier only ever see the authoritative position.
+///
+/// Kinematic and dynamic rigid bodies get this component automatically.
+#[derive(Component, Debug, Copy, Clone)]
+pub struct PhysicsPosition {
+	pub previous: Isometry,
+	pub current: Isometry,
+}
+
+/// The parts of a `Transform` that are driven by physics
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Isometry {
+	pub translation: Vec3,
+	pub rotation: Quat,
+}
+
+impl From<&Transform> for Isometry {
+	fn from(transform: &Transform) -> Self {
+		Isometry {
+			translation: transform.translation,
+			rotation: transform.rotation,
+		}
+	}
+}
+
+impl PhysicsPosition {
+	pub fn new(transform: &Transform) -> Self {
+		let iso = Isometry::from(transform);
+		PhysicsPosition {
+			previous: iso,
+			current: iso,
+		}
+	}
+
+	/// Move to a new position without interpolating from the old one, e.g. when respawning
+	pub fn snap(&mut self, transform: &Transform) {
+		*self = PhysicsPosition::new(transform);
+	}
+
+	fn interpolate(&self, t: f32) -> Isometry {
+		Isometry {
+			translation: self.previous.translation.lerp(self.current.translation, t),
+			rotation: self.previous.rotation.slerp(self.current.rotation, t),
+		}
+	}
+}
+
+/// Attaches a [PhysicsPosition] to any non-fixed rigid body that doesn't have one yet
+pub fn init_physics_positions(
+	mut commands: Commands,
+	bodies: Query<(Entity, &RigidBody, &Transform), Without<PhysicsPosition>>,
+) {
+	for (entity, body, transform) in &bodies {
+		if *body != RigidBody::Fixed {
+			commands.entity(entity).insert(PhysicsPosition::new(transform));
+		}
+	}
+}
+
+/// Runs at the start of each fixed update, putting entities back at their authoritative positions
+pub fn restore_physics_positions(mut query: Query<(&PhysicsPosition, &mut Transform)>) {
+	for (position, mut transform) in &mut query {
+		let Isometry { translation, rotation } = position.current;
+		// avoid triggering change detection (and thus a Rapier teleport) when nothing moved
+		if transform.translation != translation || transform.rotation != rotation {
+			transform.translation = translation;
+			transform.rotation = rotation;
+		}
+	}
+}
+
+/// Runs after the physics step, recording the authoritative positions it produced
+pub fn record_physics_positions(mut query: Query<(&mut PhysicsPosition, &Transform)>) {
+	for (mut position, transform) in &mut query {
+		position.previous = position.current;
+		position.current = Isometry::from(transform);
+	}
+}
+
+/// Runs every rendered frame, placing entities between their last two authoritative positions
+pub fn interpolate_render_transforms(
+	mut query: Query<(&PhysicsPosition, &mut Transform)>,
+	fixed_time: Res<Time<Fixed>>,
+) {
+	let t = fixed_time.overstep_fraction();
+	for (position, mut transform) in &mut query {
+		let Isometry { translation, rotation } = position.interpolate(t);
+		transform.translation = translation;
+		transform.rotation = rotation;
+	}
+}
@@ -0,0 +1,19 @@
+use crate::util::FrameCount;
+use bevy::prelude::*;
+
+/// Despawns its entity (and the entity's children) once the given number of fixed frames
+/// has elapsed. Meant for anything temporary, like projectiles, particles, or markers,
+/// so that each of those doesn't need its own countdown-and-despawn loop.
+#[derive(Component, Debug)]
+pub struct Lifetime(pub FrameCount);
+
+/// Counts down each [Lifetime] by one frame, despawning the ones that run out
+pub fn lifetime_system(mut commands: Commands, mut lifetimes: Query<(Entity, &mut Lifetime)>) {
+	for (entity, mut lifetime) in &mut lifetimes {
+		if lifetime.0 == FrameCount(0) {
+			commands.entity(entity).despawn_recursive();
+		} else {
+			lifetime.0.decrement();
+		}
+	}
+}
@@ -1,7 +1,23 @@
+mod clock;
+mod impulse;
+mod interpolation;
+mod lifetime;
+mod pool;
+mod rng;
 mod side;
 mod timers;
+mod tween;
+mod vertical;
 mod walls;
 
+pub use clock::*;
+pub use impulse::*;
+pub use interpolation::*;
+pub use lifetime::*;
+pub use pool::*;
+pub use rng::*;
 pub use side::*;
 pub use timers::*;
+pub use tween::*;
+pub use vertical::*;
 pub use walls::*;
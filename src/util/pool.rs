@@ -0,0 +1,49 @@
+use crate::util::FrameCount;
+use bevy::prelude::*;
+
+/// Picks which entity of a pool to hand out next, given each entity's age (`None` for the ones sitting
+/// unused in the pool). A free entity is reused first; otherwise, once `max_active` entities are in use,
+/// the oldest is taken over. Returns `None` when a new entity should be spawned instead.
+///
+/// `spawned` counts the entities spawned earlier in the same system, which the query can't see yet.
+pub fn pick_pooled(
+	ages: impl IntoIterator<Item = (Entity, Option<FrameCount>)>,
+	spawned: usize,
+	max_active: usize,
+) -> Option<Entity> {
+	let mut active = spawned;
+	let mut oldest: Option<(Entity, FrameCount)> = None;
+	for (entity, age) in ages {
+		let Some(age) = age else {
+			return Some(entity);
+		};
+		active += 1;
+		if oldest.is_none_or(|(_, oldest_age)| age > oldest_age) {
+			oldest = Some((entity, age));
+		}
+	}
+	oldest.filter(|_| active >= max_active).map(|(entity, _)| entity)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const A: Entity = Entity::from_raw(1);
+	const B: Entity = Entity::from_raw(2);
+
+	#[test]
+	fn reuses_a_free_entity_first() {
+		let ages = [(A, Some(FrameCount(5))), (B, None)];
+		assert_eq!(pick_pooled(ages, 0, 1), Some(B));
+	}
+
+	#[test]
+	fn takes_over_the_oldest_only_at_capacity() {
+		let ages = [(A, Some(FrameCount(5))), (B, Some(FrameCount(9)))];
+		assert_eq!(pick_pooled(ages, 0, 3), None);
+		assert_eq!(pick_pooled(ages, 0, 2), Some(B));
+		// entities spawned this frame count towards the capacity
+		assert_eq!(pick_pooled(ages, 1, 3), Some(B));
+	}
+}
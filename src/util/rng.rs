@@ -0,0 +1,35 @@
+/// A small deterministic pseudo-random number generator (xorshift64*), for procedural content
+/// that should come out the same every time for a given seed
+#[derive(Debug, Clone)]
+pub struct SeededRng(u64);
+
+impl SeededRng {
+	pub fn new(seed: u64) -> Self {
+		// xorshift gets stuck at 0, so nudge the seed away from it
+		SeededRng(seed ^ 0x9E37_79B9_7F4A_7C15)
+	}
+
+	pub fn next_u64(&mut self) -> u64 {
+		let mut x = self.0;
+		x ^= x >> 12;
+		x ^= x << 25;
+		x ^= x >> 27;
+		self.0 = x;
+		x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+	}
+
+	/// A value in the range `[0, 1)`
+	pub fn next_f32(&mut self) -> f32 {
+		(self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+	}
+
+	/// A value in the range `[min, max)`
+	pub fn range(&mut self, min: f32, max: f32) -> f32 {
+		min + (max - min) * self.next_f32()
+	}
+
+	/// Pick one of the given `items`, which must not be empty
+	pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+		&items[(self.next_u64() % items.len() as u64) as usize]
+	}
+}
@@ -11,7 +11,7 @@ macro_rules! impl_sidemap_index {
 			$Neg,
 		}
 		
-		#[derive(Default, Debug)]
+		#[derive(Default, Debug, Copy, Clone, PartialEq)]
 		pub struct $Map<A> {
 			pub $pos: A,
 			pub $neg: A,
@@ -22,6 +22,30 @@ macro_rules! impl_sidemap_index {
 			pub const BOTH: [$Self; 2] = [<$Self>::$Pos, <$Self>::$Neg];
 		}
 		
+		#[allow(unused)]
+		impl <A> $Map<A> {
+			/// Builds a map by calling `f` for each side
+			pub fn from_fn(mut f: impl FnMut($Self) -> A) -> Self {
+				$Map {
+					$pos: f(<$Self>::$Pos),
+					$neg: f(<$Self>::$Neg),
+				}
+			}
+			
+			/// Transforms the value on each side, keeping track of which side it's on
+			pub fn map<B>(self, mut f: impl FnMut($Self, A) -> B) -> $Map<B> {
+				$Map {
+					$pos: f(<$Self>::$Pos, self.$pos),
+					$neg: f(<$Self>::$Neg, self.$neg),
+				}
+			}
+			
+			/// Each side, along with its value
+			pub fn iter(&self) -> impl Iterator<Item = ($Self, &A)> {
+				[(<$Self>::$Pos, &self.$pos), (<$Self>::$Neg, &self.$neg)].into_iter()
+			}
+		}
+		
 		impl <A> Index<$Self> for $Map<A> {
 			type Output = A;
 			fn index(&self, side: $Self) -> &Self::Output {
@@ -50,6 +74,19 @@ macro_rules! impl_sidemap_index {
 			}
 		}
 		
+		/// Combines two sides like signs, e.g. to convert a side that's relative to the player's
+		/// orientation into one in world space: the same side as itself, or the opposite side when
+		/// `rhs` is the negative one
+		impl Mul<$Self> for $Self {
+			type Output = $Self;
+			fn mul(self, rhs: $Self) -> Self::Output {
+				match rhs {
+					<$Self>::$Pos => self,
+					<$Self>::$Neg => -self,
+				}
+			}
+		}
+		
 		macro_rules! impl_extra_traits {
 			($T:ty, $one:expr) => {
 				impl From<$Self> for $T {
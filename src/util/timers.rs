@@ -44,6 +44,11 @@ impl Cooldown {
 	pub fn is_ready(&self) -> bool {
 		self.0 == FrameCount(0)
 	}
+
+	/// How many more ticks until the associated action is ready
+	pub fn remaining(&self) -> FrameCount {
+		self.0
+	}
 }
 
 /// A boolean flag that remembers how long it has been un-set.
@@ -81,6 +86,11 @@ impl CapacitiveFlag {
 		}
 	}
 
+	/// Forget that the flag was ever set, e.g. to consume a buffered input
+	pub fn clear(&mut self) {
+		*self = Self::default();
+	}
+
 	/// Check if the flag is *currently* set
 	pub fn is_set(&self) -> bool {
 		self.value
@@ -91,4 +101,10 @@ impl CapacitiveFlag {
 	pub fn was_set_within(&self, duration: FrameCount) -> bool {
 		self.time_since_released <= duration
 	}
+
+	/// How many frames ago the flag was un-set, or 0 while it's set.
+	/// Saturates at [usize::MAX] if the flag was never set.
+	pub fn time_since_released(&self) -> FrameCount {
+		self.time_since_released
+	}
 }
@@ -0,0 +1,150 @@
+use crate::util::FrameCount;
+use bevy::color::Mix;
+use bevy::prelude::*;
+
+/// The value a [Tween] animates, and the range it animates it over
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TweenTarget {
+	/// The entity's `Transform` translation. Not meant for physics bodies, whose transforms belong
+	/// to the physics step (and the render interpolation).
+	Translation { from: Vec3, to: Vec3 },
+	/// The entity's `Transform` scale, e.g. for squash and stretch
+	Scale { from: Vec3, to: Vec3 },
+	/// The entity's `Sprite` color
+	SpriteColor { from: Color, to: Color },
+	/// The entity's `BackgroundColor`, for UI nodes
+	BackgroundColor { from: Color, to: Color },
+	/// The entity's `TextColor`, for UI text
+	TextColor { from: Color, to: Color },
+}
+
+impl TweenTarget {
+	/// Fades a sprite's color from its current alpha to the given `alpha`
+	pub fn sprite_alpha(color: Color, alpha: f32) -> Self {
+		TweenTarget::SpriteColor {
+			from: color,
+			to: color.with_alpha(alpha),
+		}
+	}
+}
+
+/// What a [Tween] does once it reaches the end
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TweenRepeat {
+	/// Stays at the end value; the [Tween] is removed and a [TweenFinished] is sent
+	#[default]
+	Once,
+	/// Jumps back to the start and plays again, forever
+	Loop,
+	/// Plays backwards to the start, then forwards again, forever (e.g. for bobbing up and down)
+	PingPong,
+}
+
+/// Animates one of its entity's values over a number of fixed frames, along an [EaseFunction].
+///
+/// Tweens advance once per fixed update, so they follow the [GameClock](crate::util::GameClock):
+/// they freeze while the game is paused and slow down along with it, instead of every feature
+/// that fades or slides something keeping its own timer.
+#[derive(Component, Debug)]
+pub struct Tween {
+	pub target: TweenTarget,
+	pub duration: FrameCount,
+	pub easing: EaseFunction,
+	pub repeat: TweenRepeat,
+	elapsed: FrameCount,
+	/// Whether a `PingPong` tween is currently playing backwards
+	reversed: bool,
+}
+
+impl Tween {
+	pub fn new(target: TweenTarget, duration: FrameCount, easing: EaseFunction) -> Self {
+		Tween {
+			target,
+			duration,
+			easing,
+			repeat: TweenRepeat::Once,
+			elapsed: FrameCount(0),
+			reversed: false,
+		}
+	}
+
+	pub fn with_repeat(self, repeat: TweenRepeat) -> Self {
+		Tween { repeat, ..self }
+	}
+
+	/// How far along the tween is, from 0 (the `from` value) to 1 (the `to` value), before easing
+	pub fn progress(&self) -> f32 {
+		let t = (self.elapsed.0 as f32 / self.duration.0.max(1) as f32).min(1.0);
+		if self.reversed { 1.0 - t } else { t }
+	}
+
+	/// Advances the tween by one frame. Returns `true` once a `Once` tween has reached its end.
+	fn tick(&mut self) -> bool {
+		self.elapsed.increment();
+		if self.elapsed < self.duration {
+			return false;
+		}
+		match self.repeat {
+			TweenRepeat::Once => return true,
+			TweenRepeat::Loop => (),
+			TweenRepeat::PingPong => self.reversed = !self.reversed,
+		}
+		self.elapsed.reset();
+		false
+	}
+}
+
+/// Sent when a [Tween] that plays [TweenRepeat::Once] finishes
+#[derive(Event, Debug)]
+pub struct TweenFinished(pub Entity);
+
+/// Advances every [Tween] by one frame and applies its value,
+/// removing the ones that have finished
+pub fn tween_system(
+	mut commands: Commands,
+	mut tweens: Query<(
+		Entity,
+		&mut Tween,
+		Option<&mut Transform>,
+		Option<&mut Sprite>,
+		Option<&mut BackgroundColor>,
+		Option<&mut TextColor>,
+	)>,
+	mut finished_events: EventWriter<TweenFinished>,
+) {
+	for (entity, mut tween, transform, sprite, background, text) in &mut tweens {
+		let finished = tween.tick();
+		let t = EasingCurve::new(0.0, 1.0, tween.easing).sample_clamped(tween.progress());
+		match tween.target {
+			TweenTarget::Translation { from, to } => {
+				if let Some(mut transform) = transform {
+					transform.translation = from.lerp(to, t);
+				}
+			}
+			TweenTarget::Scale { from, to } => {
+				if let Some(mut transform) = transform {
+					transform.scale = from.lerp(to, t);
+				}
+			}
+			TweenTarget::SpriteColor { from, to } => {
+				if let Some(mut sprite) = sprite {
+					sprite.color = from.mix(&to, t);
+				}
+			}
+			TweenTarget::BackgroundColor { from, to } => {
+				if let Some(mut background) = background {
+					background.0 = from.mix(&to, t);
+				}
+			}
+			TweenTarget::TextColor { from, to } => {
+				if let Some(mut text) = text {
+					text.0 = from.mix(&to, t);
+				}
+			}
+		}
+		if finished {
+			commands.entity(entity).remove::<Tween>();
+			finished_events.send(TweenFinished(entity));
+		}
+	}
+}
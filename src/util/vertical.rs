@@ -0,0 +1,146 @@
+use crate::physics::{CastHit, PhysicsWorld, QueryScope};
+use crate::util::{Side, YSide, YSideMap};
+use bevy::color::Color;
+use bevy::math::Vec2;
+use bevy::prelude::*;
+
+/// Projects rays away from the top and bottom edges of the player's collider, to find what's directly
+/// above and beneath them; the vertical counterpart to the [WallSensors](crate::util::WallSensors).
+///
+/// Sides are relative to the player's gravity, like the rest of their state: `down` is the floor under
+/// their feet and `up` is the ceiling over their head, whichever way up the player is.
+///
+/// The floor identifies the entity the player is standing on. The character controller's output only
+/// tells us *whether* the player is grounded, not *what* they are standing on, which matters for
+/// moving platforms. The ceiling decides whether a crouching player has enough room to stand back up.
+#[derive(Default, Debug)]
+pub struct VerticalSensors {
+	/// The closest thing each side's ray-casts hit during its latest `update`, if anything
+	pub hits: YSideMap<Option<CastHit>>,
+}
+
+impl VerticalSensors {
+	/// Horizontal positions of the rays, as a fraction of the collider's half-width.
+	/// The outer rays are inset slightly so they don't graze adjacent walls.
+	const RAY_OFFSETS: [f32; 3] = [-0.9, 0.0, 0.9];
+
+	/// The entity beneath the player's feet, which is what they're standing on while grounded
+	pub fn ground(&self) -> Option<Entity> {
+		self.hits.down.map(|hit| hit.entity)
+	}
+
+	/// Whether something is in the way on the given side
+	pub fn is_blocked(&self, side: YSide) -> bool {
+		self.hits[side].is_some()
+	}
+
+	/// What counts as being in the way on each side: the player can stand on dynamic bodies,
+	/// but only solid obstacles keep them from standing up
+	fn scope(side: YSide) -> QueryScope {
+		match side {
+			YSide::Down => QueryScope::solid_and_dynamic(),
+			YSide::Up => QueryScope::solid(),
+		}
+	}
+
+	/// The height of the given side's edge of the rectangle defined by `center` and `half_extents`,
+	/// and the direction pointing away from it, in world space
+	fn edge(side: YSide, center: Vec2, half_extents: Vec2, up: YSide) -> (f32, Vec2) {
+		let world_side = side * up;
+		(center.y + half_extents.y * world_side, Vec2::Y * world_side)
+	}
+
+	/// Updates the `hits` on one `side` by performing ray-casts in the given `physics` world, from that
+	/// side's edge of the rectangle defined by `center` and `half_extents`.
+	///
+	/// The sides are updated separately, since they're needed at different points in the player's update,
+	/// and the ceiling only has to be checked as far as the player would grow by standing up.
+	pub fn update(
+		&mut self,
+		side: YSide,
+		center: Vec2,
+		half_extents: Vec2,
+		up: YSide,
+		ray_length: f32,
+		physics: &impl PhysicsWorld,
+		excluded_entity: Entity,
+	) {
+		let (edge_y, direction) = Self::edge(side, center, half_extents, up);
+		self.hits[side] = Self::RAY_OFFSETS
+			.into_iter()
+			.filter_map(|offset| {
+				physics.cast_ray(
+					/* origin */ Vec2::new(center.x + half_extents.x * offset, edge_y),
+					direction,
+					/* max_distance */ ray_length,
+					Self::scope(side).excluding(excluded_entity),
+				)
+			})
+			.min_by(|a, b| a.distance.total_cmp(&b.distance));
+	}
+
+	/// When the player bonks their head, checks whether the obstacle only overlaps one of the
+	/// top corners of their collider by at most `max_correction` units. If so, returns the
+	/// horizontal shift that would let the player slip past the obstacle's edge.
+	pub fn corner_correction(
+		center: Vec2,
+		half_extents: Vec2,
+		up: YSide,
+		ray_length: f32,
+		max_correction: f32,
+		physics: &impl PhysicsWorld,
+		excluded_entity: Entity,
+	) -> Option<f32> {
+		const STEP: f32 = 0.25;
+		let (top_y, direction) = Self::edge(YSide::Up, center, half_extents, up);
+		let hits_at = |x: f32| {
+			physics
+				.cast_ray(
+					Vec2::new(x, top_y),
+					direction,
+					ray_length,
+					Self::scope(YSide::Up).excluding(excluded_entity),
+				)
+				.is_some()
+		};
+
+		// the middle of the player's head has to be clear, otherwise it's a real bonk
+		if hits_at(center.x) {
+			return None;
+		}
+
+		for side in Side::BOTH {
+			// inset slightly, so the corner ray doesn't graze a wall the player is touching
+			let edge_x = center.x + (half_extents.x - 0.01) * side;
+			if !hits_at(edge_x) || hits_at(center.x - (half_extents.x - 0.01) * side) {
+				continue;
+			}
+			// walk inward from the corner until the obstacle ends
+			let mut overlap = STEP;
+			while overlap <= max_correction {
+				if !hits_at(edge_x - overlap * side) {
+					return Some(-overlap * side);
+				}
+				overlap += STEP;
+			}
+		}
+		None
+	}
+
+	/// Uses the given `gizmos` to draw each of the rays that would be cast during the given side's `update`
+	pub fn draw(&self, side: YSide, center: Vec2, half_extents: Vec2, up: YSide, ray_length: f32, gizmos: &mut Gizmos) {
+		let (edge_y, direction) = Self::edge(side, center, half_extents, up);
+		let color = if self.is_blocked(side) {
+			Color::srgb(0.8, 0.5, 0.0)
+		} else {
+			Color::srgb(0., 0., 1.)
+		};
+		for offset in Self::RAY_OFFSETS {
+			gizmos.ray_2d(
+				Vec2::new(center.x + half_extents.x * offset, edge_y),
+				direction * ray_length,
+				color,
+			);
+		}
+	}
+}
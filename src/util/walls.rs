@@ -1,9 +1,9 @@
-use crate::util::{FrameCount, Side, SideMap, YSide};
+use crate::physics::{PhysicsWorld, QueryScope};
+use crate::platforms::{WallMaterial, WallSurface};
+use crate::util::{CapacitiveFlag, FrameCount, Side, SideMap, YSide};
 use bevy::color::Color;
 use bevy::math::Vec2;
 use bevy::prelude::*;
-use bevy_rapier2d::pipeline::{QueryFilter, QueryFilterFlags};
-use bevy_rapier2d::plugin::RapierContext;
 use serde::Deserialize;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -11,6 +11,12 @@ pub enum PlayerWallState {
 	Grabbed(Side),
 	Sliding(Side),
 	Climbing(Side),
+	/// Pulling up and over the top of a ledge after climbing it.
+	/// The player's movement is scripted until they land on top.
+	Mantling(Side),
+	/// Running up the wall for a moment after hitting it at speed,
+	/// see [PlayerWallControlState::wall_run_speed]
+	Running(Side),
 }
 
 impl PlayerWallState {
@@ -19,6 +25,8 @@ impl PlayerWallState {
 			PlayerWallState::Grabbed(side) => side,
 			PlayerWallState::Sliding(side) => side,
 			PlayerWallState::Climbing(side) => side,
+			PlayerWallState::Mantling(side) => side,
+			PlayerWallState::Running(side) => side,
 		}
 	}
 }
@@ -32,11 +40,33 @@ struct PlayerWallControlStateInner {
 
 	/// Remembers the type of wall (i.e. Wall vs Ledge) being interacted with
 	wall_type: WallSensorResult,
+
+	/// Whether the player was climbing the ledge as of the previous frame
+	was_climbing: bool,
+
+	/// Duration of the current mantle, if the player has started pulling themselves over the ledge
+	mantle_timer: Option<FrameCount>,
+
+	/// Set if the player hit the wall fast enough to run up it
+	wall_run: Option<WallRun>,
+
+	/// Material of the wall as of the latest tick, if it has one
+	material: Option<WallMaterial>,
+}
+
+struct WallRun {
+	/// Upward speed at the start of the run, which tapers off over its duration
+	speed: f32,
+	timer: FrameCount,
 }
 
 #[derive(Default)]
 pub struct PlayerWallControlState {
 	wall_state: Option<PlayerWallControlStateInner>,
+
+	/// Tracks how recently the player was interacting with a wall on each side,
+	/// so they can still wall jump for a few frames after letting go
+	recently_touched: SideMap<CapacitiveFlag>,
 }
 
 impl PlayerWallControlState {
@@ -44,33 +74,89 @@ impl PlayerWallControlState {
 	/// (expected usage is with wall-jumps, which are not handled by this struct)
 	pub fn release(&mut self) {
 		self.wall_state = None;
+		for side in Side::BOTH {
+			self.recently_touched[side].clear();
+		}
+	}
+
+	/// The side of the wall the player was interacting with at any time in the last `within` frames,
+	/// even if they have since let go of it
+	pub fn recent_wall_side(&self, within: FrameCount) -> Option<Side> {
+		Side::BOTH
+			.into_iter()
+			.find(|&side| self.recently_touched[side].was_set_within(within))
+	}
+
+	/// The [WallMaterial] of the wall the player is interacting with, if any
+	pub fn material(&self) -> Option<WallMaterial> {
+		self.wall_state.as_ref()?.material
+	}
+
+	/// Upward speed for the current frame of a wall run, if the player is running up a wall.
+	/// Starts out at the speed converted from the player's horizontal momentum,
+	/// and tapers off to nothing over the `wall_run_duration`.
+	pub fn wall_run_speed(&self, control_params: &PlayerWallControlParams) -> Option<f32> {
+		let run = self.wall_state.as_ref()?.wall_run.as_ref()?;
+		let remaining = 1.0 - run.timer.0 as f32 / control_params.wall_run_duration.0.max(1) as f32;
+		Some(run.speed * remaining.max(0.0))
 	}
 
 	/// Advance the control state by one frame, taking into consideration the player's
 	/// directional inputs and proximity to walls, and determining how (if at all) the
-	/// player is interacting with a wall.
+	/// player is interacting with a wall. The `wall_materials` are the [WallMaterial]s (if any)
+	/// of the walls sensed on each side.
 	pub fn tick(
 		&mut self,
 		wall_sensor_results: &SideMap<WallSensorResult>,
+		wall_materials: &SideMap<Option<WallMaterial>>,
 		player_is_airborne: bool,
 		control_params: &PlayerWallControlParams,
 		horizontal_input: Option<Side>,
 		horizontal_momentum: Option<Side>,
+		horizontal_speed: f32,
 		vertical_input: Option<YSide>,
+		stamina_exhausted: bool,
 	) -> Option<PlayerWallState> {
 		// Possibly enter the wall state:
 		//   If player gets in contact with a wall while facing it, or gets thrown into
 		//   it regardless of the direction they are facing, they should "attach" to the wall.
+		//   Sticky walls catch the player even if they're just drifting alongside them.
 		if self.wall_state.is_none() && player_is_airborne {
-			if let Some(player_side) = horizontal_momentum.or(horizontal_input) {
+			let sticky_side = || {
+				Side::BOTH.into_iter().find(|&side| {
+					let is_wall = matches!(
+						wall_sensor_results[side],
+						WallSensorResult::Wall | WallSensorResult::Ledge
+					);
+					is_wall && wall_materials[side] == Some(WallMaterial::Sticky)
+				})
+			};
+			if let Some(player_side) = horizontal_momentum.or(horizontal_input).or_else(sticky_side) {
 				match wall_sensor_results[player_side] {
 					t @ (WallSensorResult::Wall | WallSensorResult::Ledge) => {
 						info!("started interacting with wall on {:?}", player_side);
+						// hitting the wall fast enough turns the horizontal momentum into a run up the wall,
+						// unless it's too slippery to get a footing on
+						let is_slippery = wall_materials[player_side] == Some(WallMaterial::Slippery);
+						let wall_run = (horizontal_speed.abs() >= control_params.wall_run_min_speed
+							&& !stamina_exhausted && !is_slippery)
+							.then(|| WallRun {
+								speed: (horizontal_speed.abs() * control_params.wall_run_speed_ratio)
+									.min(control_params.wall_run_max_speed),
+								timer: FrameCount(0),
+							});
+						if wall_run.is_some() {
+							info!("started wall running at {:?}", horizontal_speed);
+						}
 						// enter the wall state
 						self.wall_state = Some(PlayerWallControlStateInner {
 							side: player_side,
 							push_away_timer: FrameCount(0),
 							wall_type: t,
+							was_climbing: false,
+							mantle_timer: None,
+							wall_run,
+							material: wall_materials[player_side],
 						});
 					}
 					_ => (),
@@ -78,10 +164,13 @@ impl PlayerWallControlState {
 			}
 		}
 
+		// A mantle is a scripted motion, so the player's inputs can't cause them to let go of the ledge
+		let is_mantling = self.wall_state.as_ref().is_some_and(|s| s.mantle_timer.is_some());
+
 		// Possibly exit the wall state:
 		//   If the player tries to push away from the wall for long enough,
 		//   they'll "release" the wall.
-		if let Some(wall_state) = &mut self.wall_state {
+		if let Some(wall_state) = self.wall_state.as_mut().filter(|_| !is_mantling) {
 			let is_pushing_away = horizontal_input.map_or(false, |pushing_side| pushing_side != wall_state.side);
 
 			if is_pushing_away {
@@ -102,16 +191,40 @@ impl PlayerWallControlState {
 
 		// Possibly exit the wall state:
 		//   If the player pushes the Down button, they should let go of the wall
-		if vertical_input == Some(YSide::Down) {
+		if vertical_input == Some(YSide::Down) && !is_mantling {
 			info!("Released wall due to pressing Down");
 			self.wall_state = None;
 		}
 
+		// Possibly start a mantle:
+		//   If the player was climbing a ledge and its top edge drops below the upper sensors,
+		//   they should pull themselves up and over it instead of letting go
+		if let Some(wall_state) = self.wall_state.as_mut() {
+			let ledge_cleared = matches!(
+				wall_sensor_results[wall_state.side],
+				WallSensorResult::Step | WallSensorResult::NotAWall
+			);
+			if ledge_cleared && wall_state.was_climbing && wall_state.mantle_timer.is_none() {
+				info!("started mantling the ledge on {:?}", wall_state.side);
+				wall_state.mantle_timer = Some(FrameCount(0));
+			}
+		}
+
+		// Possibly exit the wall state:
+		//   If the mantle has gone on for too long without the player landing on the ledge
+		if let Some(mantle_timer) = self.wall_state.as_mut().and_then(|s| s.mantle_timer.as_mut()) {
+			mantle_timer.increment();
+			if *mantle_timer > control_params.mantle_max_duration {
+				info!("Gave up mantling after {:?}", control_params.mantle_max_duration);
+				self.wall_state = None;
+			}
+		}
+
 		// Possibly exit the wall state:
-		//   If the sensor no longer detects a wall
+		//   If the sensor no longer detects a wall (unless mantling, where that's expected)
 		if let Some(wall_state) = self.wall_state.as_ref() {
 			match wall_sensor_results[wall_state.side] {
-				WallSensorResult::Step | WallSensorResult::NotAWall => {
+				WallSensorResult::Step | WallSensorResult::NotAWall if wall_state.mantle_timer.is_none() => {
 					self.wall_state = None;
 				}
 				_ => (),
@@ -124,41 +237,143 @@ impl PlayerWallControlState {
 			self.wall_state = None;
 		}
 
+		// Possibly end a wall run:
+		//   The run only lasts a moment, after which the player falls back on the usual
+		//   grab/slide/climb states
+		if let Some(wall_state) = self.wall_state.as_mut() {
+			if let Some(run) = wall_state.wall_run.as_mut() {
+				run.timer.increment();
+				if run.timer >= control_params.wall_run_duration || stamina_exhausted {
+					wall_state.wall_run = None;
+				}
+			}
+		}
+
 		// Interpret the state and the player's directional inputs
 		// to determine what the character is actually doing
-		self.wall_state.as_ref().map(|wall_state| {
+		let state = self.wall_state.as_mut().map(|wall_state| {
 			let is_ledge = match wall_state.wall_type {
 				WallSensorResult::Ledge => true,
 				_ => false,
 			};
+			wall_state.material = wall_materials[wall_state.side];
+			let is_slippery = wall_state.material == Some(WallMaterial::Slippery);
+			let is_sticky = wall_state.material == Some(WallMaterial::Sticky);
+
+			let is_running = wall_state.wall_run.is_some();
+			let is_climbing = is_ledge
+				&& !stamina_exhausted
+				&& !is_slippery
+				&& (vertical_input == Some(YSide::Up) || horizontal_input == Some(wall_state.side));
+			// running up a ledge carries straight on into a mantle, just like climbing it
+			wall_state.was_climbing = is_climbing || (is_ledge && is_running);
 
-			if is_ledge && (vertical_input == Some(YSide::Up) || horizontal_input == Some(wall_state.side)) {
+			if wall_state.mantle_timer.is_some() {
+				PlayerWallState::Mantling(wall_state.side)
+			} else if is_running {
+				PlayerWallState::Running(wall_state.side)
+			} else if stamina_exhausted || is_slippery {
+				// too tired to hold on, or nothing to hold on to
+				PlayerWallState::Sliding(wall_state.side)
+			} else if is_climbing {
 				// allow the player to climb up a ledge by holding either Up or towards the ledge
 				PlayerWallState::Climbing(wall_state.side)
-			} else if horizontal_input == Some(wall_state.side) {
-				// on a normal wall, pressing towards the wall counts as grabbing it
+			} else if horizontal_input == Some(wall_state.side) || (is_sticky && horizontal_input.is_none()) {
+				// on a normal wall, pressing towards the wall counts as grabbing it,
+				// while a sticky wall holds on to the player unless they press away from it
 				PlayerWallState::Grabbed(wall_state.side)
 			} else {
 				// pressing away from the wall, or in no direction at all, should result
 				// in the player slowly sliding down the wall
 				PlayerWallState::Sliding(wall_state.side)
 			}
-		})
+		});
+
+		// a mantle is committed, so it doesn't count towards wall-jump coyote time
+		for side in Side::BOTH {
+			let touching = state.is_some_and(|s| s.side() == side && !matches!(s, PlayerWallState::Mantling(_)));
+			self.recently_touched[side].tick(touching);
+		}
+
+		state
 	}
 }
 
+/// Everything that went into (and came out of) one frame of [PlayerWallControlState::tick],
+/// kept around for analysis tools like the wall frame-data export
+#[derive(Default, Debug)]
+pub struct WallFrame {
+	/// See [WallSensors::hit_pattern]
+	pub hit_patterns: SideMap<u8>,
+	pub horizontal_input: Option<Side>,
+	pub vertical_input: Option<YSide>,
+	pub airborne: bool,
+	pub state: Option<PlayerWallState>,
+}
+
 #[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct PlayerWallControlParams {
 	/// Duration that player needs to hold the directional input away from the wall
 	/// before they actually let go and start falling
 	pub push_away_duration: FrameCount,
 	pub slide_max_speed: f32,
+	/// Replaces `slide_max_speed` on [WallMaterial::Slippery] walls
+	pub slippery_slide_max_speed: f32,
 	pub slide_acceleration: f32,
 	pub climb_max_speed: f32,
 	pub climb_acceleration: f32,
 
+	/// Upward speed while pulling up over a ledge, until the player's feet clear its top edge
+	pub mantle_rise_speed: f32,
+	/// Speed towards the ledge once the player's feet have cleared its top edge
+	pub mantle_forward_speed: f32,
+	/// Safety limit on how long a mantle can last before the player lets go of the ledge
+	pub mantle_max_duration: FrameCount,
+
+	/// How long after letting go of a wall the player can still wall jump off of it
+	pub wall_jump_coyote_time: FrameCount,
+
+	/// Stamina the player has after landing. Grabbing and climbing walls uses it up,
+	/// and once it runs out, the player can only slide
+	pub stamina_max: f32,
+	/// Stamina used per frame while grabbing a wall
+	pub stamina_grab_drain: f32,
+	/// Stamina used per frame while climbing a ledge or running up a wall
+	pub stamina_climb_drain: f32,
+	/// Stamina recovered per frame while on the ground
+	pub stamina_refill: f32,
+
+	/// Minimum horizontal speed the player must hit a wall at while airborne to run up it
+	pub wall_run_min_speed: f32,
+	/// Upward speed gained per unit of horizontal speed when starting a wall run
+	pub wall_run_speed_ratio: f32,
+	/// Upper limit on the upward speed at the start of a wall run
+	pub wall_run_max_speed: f32,
+	/// How long a wall run lasts before the player grabs or slides down the wall as usual
+	pub wall_run_duration: FrameCount,
+
+	/// Tallest obstacle the player will automatically step up onto while walking into a `Step`
+	pub max_step_height: f32,
+	/// How long after walking into a `Step` pressing jump still hops up onto it, keeping the player's
+	/// momentum, rather than jumping straight up against its side
+	pub step_jump_forgiveness: FrameCount,
+
 	/// Length of ray-casts used to detect walls adjacent to the player
 	pub detection_length: f32,
+	/// While grabbing or sliding down a wall, gaps up to this wide between the player and the wall
+	/// are closed by pulling the player against it
+	pub wall_snap_distance: f32,
+}
+
+impl PlayerWallControlParams {
+	/// Fastest the player slides down a wall made of the given `material`
+	pub fn slide_max_speed(&self, material: Option<WallMaterial>) -> f32 {
+		match material {
+			Some(WallMaterial::Slippery) => self.slippery_slide_max_speed,
+			_ => self.slide_max_speed,
+		}
+	}
 }
 
 /// Describes a sensor that exists at the sides of a player's collider,
@@ -169,8 +384,8 @@ pub struct WallSensor {
 	/// player's collider this sensor exists
 	local_offset: f32,
 
-	/// Tracks whether the ray-casts on each side of the player have hit something
-	pub hits: SideMap<bool>,
+	/// What the ray-casts on each side of the player have hit, if anything
+	pub hits: SideMap<Option<WallHit>>,
 }
 impl WallSensor {
 	pub fn at_offset(local_offset: f32) -> Self {
@@ -181,6 +396,14 @@ impl WallSensor {
 	}
 }
 
+/// Something hit by one of a [WallSensor]'s ray-casts
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WallHit {
+	pub entity: Entity,
+	/// Distance from the edge of the player's collider to the hit
+	pub distance: f32,
+}
+
 /// A set of four [WallSensor]s.
 ///
 /// As a collective, the sensors can be used not only to detect obstacles adjacent
@@ -207,18 +430,20 @@ impl Default for WallSensors {
 }
 impl WallSensors {
 	/// Updates the `hits` state of each sensor in this group by performing ray-casts in the given
-	/// `rapier_context`, with edges of the rectangular "player" defined in terms of its `center`
-	/// and `half_extents` values.
+	/// `physics` world, with edges of the rectangular "player" defined in terms of its `center`
+	/// and `half_extents` values. The sensors' offsets are measured from the player's feet,
+	/// which are at the bottom of the rectangle unless `up` says the player is upside-down.
 	pub fn update(
 		&mut self,
 		center: Vec2,
 		half_extents: Vec2,
+		up: YSide,
 		ray_length: f32,
-		rapier_context: &RapierContext,
+		physics: &impl PhysicsWorld,
 		excluded_entity: Entity,
 	) {
-		let bottom_y = center.y - half_extents.y;
-		let height = half_extents.y * 2.0;
+		let bottom_y = center.y - half_extents.y * up;
+		let height = half_extents.y * 2.0 * up;
 		for sensor in &mut self.0 {
 			let sensor_y = bottom_y + height * sensor.local_offset;
 
@@ -226,36 +451,32 @@ impl WallSensors {
 				let x_offset = half_extents.x * side;
 				let direction = Vec2::X * side;
 				let raycast_start = Vec2::new(center.x + x_offset, sensor_y);
-				sensor.hits[side] = rapier_context
+				sensor.hits[side] = physics
 					.cast_ray(
 						/* origin */ raycast_start,
-						/* ray_dir */ direction,
-						/* max_toi */ ray_length,
-						/* solid */ true, // IDK what this means
-						/* filter */
-						QueryFilter {
-							flags: QueryFilterFlags::EXCLUDE_DYNAMIC | QueryFilterFlags::EXCLUDE_SENSORS,
-							exclude_collider: Some(excluded_entity),
-							exclude_rigid_body: Some(excluded_entity),
-							..default()
-						},
+						/* direction */ direction,
+						/* max_distance */ ray_length,
+						QueryScope::solid().excluding(excluded_entity),
 					)
-					.is_some();
+					.map(|hit| WallHit {
+						entity: hit.entity,
+						distance: hit.distance,
+					});
 			}
 		}
 	}
 
 	/// Uses the given `gizmos` do draw each of the rays that would be cast during `update`
-	pub fn draw(&mut self, center: Vec2, half_extents: Vec2, gizmos: &mut Gizmos) {
-		let bottom_y = center.y - half_extents.y;
-		let height = half_extents.y * 2.0;
+	pub fn draw(&mut self, center: Vec2, half_extents: Vec2, up: YSide, gizmos: &mut Gizmos) {
+		let bottom_y = center.y - half_extents.y * up;
+		let height = half_extents.y * 2.0 * up;
 		for sensor in &self.0 {
 			let sensor_y = bottom_y + height * sensor.local_offset;
 			for side in Side::BOTH {
 				let x_offset = half_extents.x * side;
 				let direction = Vec2::X * side * 0.25;
 				let raycast_start = Vec2::new(center.x + x_offset, sensor_y);
-				let color = if sensor.hits[side] {
+				let color = if sensor.hits[side].is_some() {
 					Color::srgb(0.8, 0.5, 0.0)
 				} else {
 					Color::srgb(0., 0., 1.)
@@ -265,24 +486,59 @@ impl WallSensors {
 		}
 	}
 
-	/// Interprets the current `hits` state of the sensor group, to determine whether there is
-	/// a wall (or something else) on the requested `side`.
-	pub fn interpret(&self, side: Side) -> WallSensorResult {
-		// make a 4-bit number to represent the wall sensors, where the least-significant bit
-		// represents the bottom sensor, and the bit is 1 when its respective sensor was "hit"
+	/// Whether any of the sensors hit something on the given `side` during the latest `update`
+	pub fn any_hit(&self, side: Side) -> bool {
+		self.0.iter().any(|s| s.hits[side].is_some())
+	}
+
+	/// The closest thing any of the sensors hit on the given `side` during the latest `update`
+	pub fn nearest_hit(&self, side: Side) -> Option<WallHit> {
+		self.0
+			.iter()
+			.filter_map(|s| s.hits[side])
+			.min_by(|a, b| a.distance.total_cmp(&b.distance))
+	}
+
+	/// Makes a 4-bit number to represent the wall sensors on the given `side`, where the
+	/// least-significant bit represents the bottom sensor, and the bit is 1 when its respective
+	/// sensor was "hit" during the latest `update`
+	pub fn hit_pattern(&self, side: Side) -> u8 {
 		let mut hit_flags = 0u8;
 		for (i, hit) in self.0.iter().map(|s| s.hits[side]).enumerate() {
-			if hit {
+			if hit.is_some() {
 				hit_flags |= 1 << i;
 			}
 		}
-		match hit_flags {
+		hit_flags
+	}
+
+	/// Interprets the current `hits` state of the sensor group, to determine whether there is
+	/// a wall (or something else) on the requested `side`.
+	///
+	/// The `surface_of` function looks up the [WallSurface] (if any) of each entity that was hit.
+	/// Walls and ledges with a restrictive [WallSurface] are reported as `NotAWall`,
+	/// so the player never gets to hold onto them.
+	pub fn interpret(&self, side: Side, surface_of: impl Fn(Entity) -> Option<WallSurface>) -> WallSensorResult {
+		let result = match self.hit_pattern(side) {
 			0b0001 => WallSensorResult::Step,
 			0b0011 => WallSensorResult::Ledge,
 			0b0111 => WallSensorResult::Wall,
 			0b1111 => WallSensorResult::Wall,
 			0b1110 => WallSensorResult::Wall,
 			_ => WallSensorResult::NotAWall,
+		};
+		let has_surface = |surface: WallSurface| {
+			self.0
+				.iter()
+				.filter_map(|s| s.hits[side])
+				.any(|hit| surface_of(hit.entity) == Some(surface))
+		};
+		match result {
+			WallSensorResult::Wall | WallSensorResult::Ledge if has_surface(WallSurface::NoGrab) => {
+				WallSensorResult::NotAWall
+			}
+			WallSensorResult::Wall if has_surface(WallSurface::LedgeOnly) => WallSensorResult::NotAWall,
+			result => result,
 		}
 	}
 }
@@ -305,3 +561,97 @@ pub enum WallSensorResult {
 	/// which could be grabbed or climbed.
 	Wall,
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::physics::MockWorld;
+
+	const PLAYER: Entity = Entity::from_raw(1);
+	const OBSTACLE: Entity = Entity::from_raw(2);
+
+	/// A player standing at the origin, 1 unit wide and 4 tall, so the sensors are at y = 0.5, 1.5, 2.5 and 3.5
+	const CENTER: Vec2 = Vec2::new(0.0, 2.0);
+	const HALF_EXTENTS: Vec2 = Vec2::new(0.5, 2.0);
+
+	/// Updates a set of sensors for the player, in a world with an obstacle of the given `height`
+	/// (standing on the ground) just to the player's right
+	fn sensors_beside(height: f32) -> WallSensors {
+		let obstacle_center = Vec2::new(1.75, height * 0.5);
+		let physics = MockWorld::default().with_box(PLAYER, CENTER, HALF_EXTENTS).with_box(
+			OBSTACLE,
+			obstacle_center,
+			Vec2::new(1.0, height * 0.5),
+		);
+		let mut sensors = WallSensors::default();
+		sensors.update(CENTER, HALF_EXTENTS, YSide::Up, 0.5, &physics, PLAYER);
+		sensors
+	}
+
+	/// How the sensors interpret an obstacle of the given `height` and surface to the player's right
+	fn interpret_beside(height: f32, surface: Option<WallSurface>) -> WallSensorResult {
+		sensors_beside(height).interpret(Side::Right, |_| surface)
+	}
+
+	#[test]
+	fn update_reports_hits_on_each_side() {
+		let sensors = sensors_beside(10.0);
+		assert_eq!(sensors.hit_pattern(Side::Right), 0b1111);
+		assert_eq!(sensors.hit_pattern(Side::Left), 0b0000);
+		assert_eq!(
+			sensors.nearest_hit(Side::Right),
+			Some(WallHit {
+				entity: OBSTACLE,
+				distance: 0.25,
+			})
+		);
+	}
+
+	#[test]
+	fn update_ignores_obstacles_out_of_reach() {
+		let physics = MockWorld::default().with_box(OBSTACLE, Vec2::new(2.5, 5.0), Vec2::new(1.0, 5.0));
+		let mut sensors = WallSensors::default();
+		sensors.update(CENTER, HALF_EXTENTS, YSide::Up, 0.5, &physics, PLAYER);
+		assert!(!sensors.any_hit(Side::Right));
+	}
+
+	#[test]
+	fn interpret_tells_steps_ledges_and_walls_apart() {
+		assert_eq!(interpret_beside(1.0, None), WallSensorResult::Step);
+		assert_eq!(interpret_beside(2.0, None), WallSensorResult::Ledge);
+		assert_eq!(interpret_beside(3.0, None), WallSensorResult::Wall);
+		assert_eq!(interpret_beside(10.0, None), WallSensorResult::Wall);
+		let nothing_on_the_left = sensors_beside(10.0).interpret(Side::Left, |_| None);
+		assert_eq!(nothing_on_the_left, WallSensorResult::NotAWall);
+	}
+
+	#[test]
+	fn interpret_respects_wall_surfaces() {
+		assert_eq!(
+			interpret_beside(10.0, Some(WallSurface::NoGrab)),
+			WallSensorResult::NotAWall
+		);
+		assert_eq!(
+			interpret_beside(2.0, Some(WallSurface::NoGrab)),
+			WallSensorResult::NotAWall
+		);
+		assert_eq!(
+			interpret_beside(10.0, Some(WallSurface::LedgeOnly)),
+			WallSensorResult::NotAWall
+		);
+		assert_eq!(
+			interpret_beside(2.0, Some(WallSurface::LedgeOnly)),
+			WallSensorResult::Ledge
+		);
+	}
+
+	#[test]
+	fn update_measures_from_the_feet_when_upside_down() {
+		// hanging from a ceiling at y = 4, the player's feet are at the top, so a 1-unit obstacle
+		// hanging beside them from the ceiling is a step
+		let physics = MockWorld::default().with_box(OBSTACLE, Vec2::new(1.75, 3.5), Vec2::new(1.0, 0.5));
+		let mut sensors = WallSensors::default();
+		sensors.update(CENTER, HALF_EXTENTS, YSide::Down, 0.5, &physics, PLAYER);
+		assert_eq!(sensors.interpret(Side::Right, |_| None), WallSensorResult::Step);
+	}
+}
@@ -0,0 +1,35 @@
+use crate::player::PlayerGravity;
+use crate::util::YSide;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
+
+/// A rectangular area that changes the player's gravity while their center is inside it.
+/// Leaving the zone puts gravity back to normal.
+///
+/// Like other zones, it's a sensor, so it never blocks anything or shows up in the player's ray-casts.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[require(Sensor)]
+pub struct GravityZone {
+	/// Whether gravity pulls the player upward instead of downward
+	pub inverted: bool,
+	/// Multiplier on the player's normal gravity
+	pub scale: f32,
+}
+
+impl GravityZone {
+	/// The gravity felt by a player inside this zone
+	pub fn gravity(&self) -> PlayerGravity {
+		PlayerGravity {
+			up: if self.inverted { YSide::Down } else { YSide::Up },
+			scale: self.scale,
+		}
+	}
+
+	/// The area covered by a gravity zone with the given `transform` and `collider`
+	pub fn area(transform: &Transform, collider: &Collider) -> Rect {
+		let half_extents = collider.as_cuboid().map_or(Vec2::ZERO, |c| c.half_extents());
+		Rect::from_center_half_size(transform.translation.truncate(), half_extents)
+	}
+}
@@ -0,0 +1,9 @@
+mod gravity;
+mod trigger;
+mod water;
+mod wind;
+
+pub use gravity::*;
+pub use trigger::*;
+pub use water::*;
+pub use wind::*;
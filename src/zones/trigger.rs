@@ -0,0 +1,89 @@
+use crate::hazards::collider_area;
+use crate::level::RoomInactive;
+use crate::player::{Player, TutorialGhost};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
+
+/// An area that reports players going in and out of it, for things like starting a cutscene,
+/// showing a tutorial popup, or changing the music. It does nothing by itself; whatever listens for
+/// [TriggerEntered] and [TriggerExited] picks out the triggers it cares about by `id`.
+///
+/// A player counts as inside the trigger while their center is.
+#[derive(Component, Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[require(Sensor, TriggerOccupants)]
+pub struct TriggerZone {
+	pub id: String,
+}
+
+/// The players inside a [TriggerZone] as of the latest fixed update
+#[derive(Component, Debug, Default)]
+pub struct TriggerOccupants(HashSet<Entity>);
+
+impl TriggerOccupants {
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	pub fn contains(&self, player: Entity) -> bool {
+		self.0.contains(&player)
+	}
+}
+
+/// Sent when a player goes into a [TriggerZone]
+#[derive(Event, Debug)]
+pub struct TriggerEntered {
+	pub zone: Entity,
+	pub id: String,
+	pub player: Entity,
+}
+
+/// Sent when a player leaves a [TriggerZone], including by despawning
+#[derive(Event, Debug)]
+pub struct TriggerExited {
+	pub zone: Entity,
+	pub id: String,
+	pub player: Entity,
+}
+
+/// Keeps track of which players are inside each [TriggerZone], sending an event whenever one goes in
+/// or out. Should run after `player_system`, so the players have moved.
+pub fn trigger_zone_system(
+	mut zones: Query<(Entity, &TriggerZone, &mut TriggerOccupants, &Transform, &Collider), Without<RoomInactive>>,
+	players: Query<(Entity, &Transform), (With<Player>, Without<TutorialGhost>)>,
+	mut entered_events: EventWriter<TriggerEntered>,
+	mut exited_events: EventWriter<TriggerExited>,
+) {
+	for (zone, trigger, mut occupants, transform, collider) in &mut zones {
+		let Some(area) = collider_area(transform, collider) else {
+			continue;
+		};
+		let inside: HashSet<Entity> = players
+			.iter()
+			.filter(|(_, player_transform)| area.contains(player_transform.translation.truncate()))
+			.map(|(player, _)| player)
+			.collect();
+		if inside == occupants.0 {
+			continue;
+		}
+		for &player in inside.difference(&occupants.0) {
+			debug!("player {:?} entered trigger {:?}", player, trigger.id);
+			entered_events.send(TriggerEntered {
+				zone,
+				id: trigger.id.clone(),
+				player,
+			});
+		}
+		for &player in occupants.0.difference(&inside) {
+			debug!("player {:?} left trigger {:?}", player, trigger.id);
+			exited_events.send(TriggerExited {
+				zone,
+				id: trigger.id.clone(),
+				player,
+			});
+		}
+		occupants.0 = inside;
+	}
+}
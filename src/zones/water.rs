@@ -0,0 +1,74 @@
+use crate::level::RoomInactive;
+use crate::util::GameClock;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
+
+/// A rectangular body of water. The player swims while their center is inside it,
+/// and dynamic bodies inside it float (or sink, depending on `buoyancy`).
+///
+/// Water is a sensor, so it never blocks anything or shows up in the player's ray-casts.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[require(Sensor)]
+pub struct WaterVolume {
+	/// Upward force on a fully-submerged body, as a multiple of gravity.
+	/// Values above 1 make bodies float, values below 1 make them sink slowly.
+	pub buoyancy: f32,
+	/// Fraction of a dynamic body's velocity lost per second while fully submerged
+	pub drag: f32,
+}
+
+impl WaterVolume {
+	/// The area covered by a water volume with the given `transform` and `collider`
+	pub fn area(transform: &Transform, collider: &Collider) -> Rect {
+		let half_extents = collider.as_cuboid().map_or(Vec2::ZERO, |c| c.half_extents());
+		Rect::from_center_half_size(transform.translation.truncate(), half_extents)
+	}
+}
+
+/// Pushes dynamic bodies up out of water, proportional to how much of each body is submerged,
+/// and slows them down as they move through it
+pub fn buoyancy_system(
+	waters: Query<(&WaterVolume, &Transform, &Collider), Without<RoomInactive>>,
+	mut bodies: Query<
+		(
+			&Transform,
+			&Collider,
+			&RigidBody,
+			&mut ExternalImpulse,
+			&ReadMassProperties,
+			Option<&Velocity>,
+		),
+		Without<WaterVolume>,
+	>,
+	rapier_config: Query<&RapierConfiguration>,
+	clock: Res<GameClock>,
+) {
+	let Ok(rapier_config) = rapier_config.get_single() else {
+		return;
+	};
+	let dt = clock.delta_secs();
+	for (transform, collider, body, mut impulse, mass, velocity) in &mut bodies {
+		if *body != RigidBody::Dynamic {
+			continue;
+		}
+		let aabb = collider.raw.compute_local_aabb();
+		let body_area = Rect::from_center_half_size(
+			transform.translation.truncate(),
+			Vec2::new(aabb.half_extents().x, aabb.half_extents().y),
+		);
+		for (water, water_transform, water_collider) in &waters {
+			let submerged = body_area.intersect(WaterVolume::area(water_transform, water_collider));
+			if submerged.is_empty() {
+				continue;
+			}
+			let fraction = (submerged.height() / body_area.height().max(f32::EPSILON)).min(1.0);
+			let mass = mass.get().mass;
+			impulse.impulse -= rapier_config.gravity * water.buoyancy * fraction * mass * dt;
+			if let Some(velocity) = velocity {
+				impulse.impulse -= velocity.linvel * (water.drag * fraction * dt).min(1.0) * mass;
+			}
+		}
+	}
+}
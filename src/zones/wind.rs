@@ -0,0 +1,55 @@
+use crate::level::RoomInactive;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
+
+/// A rectangular area where the wind blows. The player is carried along at the wind's speed
+/// while their center is inside it, and dynamic bodies inside it are pushed along.
+///
+/// Wind is a sensor, so it never blocks anything or shows up in the player's ray-casts.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[require(Sensor)]
+pub struct WindZone {
+	/// Which way the wind blows (doesn't need to be normalized)
+	pub direction: Vec2,
+	/// Speed (per second) that the wind adds to the player's velocity.
+	/// Dynamic bodies are accelerated by this much per second instead.
+	pub strength: f32,
+}
+
+impl WindZone {
+	/// The wind's velocity
+	pub fn velocity(&self) -> Vec2 {
+		self.direction.normalize_or_zero() * self.strength
+	}
+
+	/// The area covered by a wind zone with the given `transform` and `collider`
+	pub fn area(transform: &Transform, collider: &Collider) -> Rect {
+		let half_extents = collider.as_cuboid().map_or(Vec2::ZERO, |c| c.half_extents());
+		Rect::from_center_half_size(transform.translation.truncate(), half_extents)
+	}
+}
+
+/// Sets the [ExternalForce] on each dynamic body according to the wind zones its center is in
+pub fn wind_force_system(
+	zones: Query<(&WindZone, &Transform, &Collider), Without<RoomInactive>>,
+	mut bodies: Query<(&Transform, &RigidBody, &mut ExternalForce, &ReadMassProperties), Without<WindZone>>,
+) {
+	for (transform, body, mut force, mass) in &mut bodies {
+		if *body != RigidBody::Dynamic {
+			continue;
+		}
+		let center = transform.translation.truncate();
+		let acceleration: Vec2 = zones
+			.iter()
+			.filter(|(_, zone_transform, zone_collider)| WindZone::area(zone_transform, zone_collider).contains(center))
+			.map(|(zone, ..)| zone.velocity())
+			.sum();
+		let wind_force = acceleration * mass.get().mass;
+		// avoid triggering change detection every frame for bodies that aren't in the wind
+		if force.force != wind_force {
+			force.force = wind_force;
+		}
+	}
+}